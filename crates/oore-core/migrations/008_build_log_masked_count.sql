@@ -0,0 +1,2 @@
+-- Track how many secrets were masked in each build log
+ALTER TABLE build_logs ADD COLUMN masked_count INTEGER NOT NULL DEFAULT 0;
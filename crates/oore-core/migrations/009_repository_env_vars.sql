@@ -0,0 +1,21 @@
+-- Migration: Repository Environment Variables
+-- Description: Per-repository environment variables and secret groups injected into builds
+
+CREATE TABLE repository_env_vars (
+    id TEXT PRIMARY KEY,
+    repository_id TEXT NOT NULL REFERENCES repositories(id) ON DELETE CASCADE,
+    -- Optional group name (Codemagic `environment.groups`); NULL applies to every build
+    group_name TEXT,
+    name TEXT NOT NULL,
+    -- Encrypted value (AES-256-GCM with AAD), stored encrypted even for plain variables
+    value_encrypted BLOB NOT NULL,
+    value_nonce BLOB NOT NULL,
+    -- Secret values are never returned by the API and are masked in build logs
+    is_secret INTEGER NOT NULL DEFAULT 0,
+    created_at TEXT NOT NULL,
+    updated_at TEXT NOT NULL
+);
+
+CREATE INDEX idx_repository_env_vars_repository ON repository_env_vars(repository_id);
+CREATE UNIQUE INDEX idx_repository_env_vars_name
+    ON repository_env_vars(repository_id, COALESCE(group_name, ''), name);
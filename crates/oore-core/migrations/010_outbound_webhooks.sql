@@ -0,0 +1,40 @@
+-- Migration: Outbound Webhooks
+-- Description: Per-repository build notification webhooks and their delivery queue
+
+CREATE TABLE outbound_webhooks (
+    id TEXT PRIMARY KEY,
+    repository_id TEXT NOT NULL REFERENCES repositories(id) ON DELETE CASCADE,
+    url TEXT NOT NULL,
+    -- Comma-separated event filter (e.g. 'build.started,build.failed')
+    events TEXT NOT NULL,
+    -- Signing secret (AES-256-GCM with AAD)
+    secret_encrypted BLOB NOT NULL,
+    secret_nonce BLOB NOT NULL,
+    is_active INTEGER NOT NULL DEFAULT 1,
+    created_at TEXT NOT NULL,
+    updated_at TEXT NOT NULL
+);
+
+CREATE INDEX idx_outbound_webhooks_repository ON outbound_webhooks(repository_id);
+
+-- Durable delivery queue; rows are kept as delivery history
+CREATE TABLE outbound_webhook_deliveries (
+    id TEXT PRIMARY KEY,
+    webhook_id TEXT NOT NULL REFERENCES outbound_webhooks(id) ON DELETE CASCADE,
+    build_id TEXT REFERENCES builds(id) ON DELETE SET NULL,
+    event TEXT NOT NULL,
+    -- Exact JSON body sent (and re-sent on retry)
+    payload TEXT NOT NULL,
+    status TEXT NOT NULL DEFAULT 'pending' CHECK (status IN ('pending', 'success', 'failed')),
+    attempts INTEGER NOT NULL DEFAULT 0,
+    next_attempt_at TEXT,
+    response_status INTEGER,
+    error_message TEXT,
+    created_at TEXT NOT NULL,
+    delivered_at TEXT
+);
+
+CREATE INDEX idx_outbound_webhook_deliveries_webhook
+    ON outbound_webhook_deliveries(webhook_id, created_at);
+CREATE INDEX idx_outbound_webhook_deliveries_due
+    ON outbound_webhook_deliveries(status, next_attempt_at);
@@ -0,0 +1,15 @@
+-- Migration: Slack Notifications
+-- Description: Per-repository Slack incoming-webhook configuration
+
+CREATE TABLE slack_notification_configs (
+    id TEXT PRIMARY KEY,
+    repository_id TEXT NOT NULL UNIQUE REFERENCES repositories(id) ON DELETE CASCADE,
+    -- Incoming-webhook URL (AES-256-GCM with AAD); the URL itself is the credential
+    webhook_url_encrypted BLOB NOT NULL,
+    webhook_url_nonce BLOB NOT NULL,
+    -- Comma-separated event filter (e.g. 'build.failed,build.succeeded')
+    events TEXT NOT NULL,
+    is_active INTEGER NOT NULL DEFAULT 1,
+    created_at TEXT NOT NULL,
+    updated_at TEXT NOT NULL
+);
@@ -0,0 +1,36 @@
+-- Migration: Email Notifications
+-- Description: SMTP settings and per-repository email recipients
+
+-- Server-wide SMTP settings (single row). Overrides OORE_SMTP_* when present.
+CREATE TABLE smtp_settings (
+    id INTEGER PRIMARY KEY CHECK (id = 1),
+    host TEXT NOT NULL,
+    port INTEGER NOT NULL,
+    tls_mode TEXT NOT NULL CHECK (tls_mode IN ('none', 'starttls', 'tls')),
+    username TEXT,
+    -- Password (AES-256-GCM with AAD), NULL when the server needs no auth
+    password_encrypted BLOB,
+    password_nonce BLOB,
+    from_address TEXT NOT NULL,
+    -- Comma-separated recipients of the daily digest; empty disables it
+    digest_recipients TEXT NOT NULL DEFAULT '',
+    created_at TEXT NOT NULL,
+    updated_at TEXT NOT NULL
+);
+
+CREATE TABLE email_notification_configs (
+    id TEXT PRIMARY KEY,
+    repository_id TEXT NOT NULL UNIQUE REFERENCES repositories(id) ON DELETE CASCADE,
+    -- Comma-separated email addresses
+    recipients TEXT NOT NULL,
+    on_failure INTEGER NOT NULL DEFAULT 1,
+    -- First successful build after a failure on the same branch
+    on_recovery INTEGER NOT NULL DEFAULT 1,
+    is_active INTEGER NOT NULL DEFAULT 1,
+    created_at TEXT NOT NULL,
+    updated_at TEXT NOT NULL
+);
+
+-- Digest and recovery lookups filter builds by finish time and branch
+CREATE INDEX idx_builds_finished_at ON builds(finished_at);
+CREATE INDEX idx_builds_repo_branch ON builds(repository_id, branch, created_at);
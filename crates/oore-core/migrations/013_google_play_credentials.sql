@@ -0,0 +1,20 @@
+-- Migration: Google Play Credentials
+-- Description: Per-repository service accounts used to publish to Google Play
+
+CREATE TABLE google_play_credentials (
+    id TEXT PRIMARY KEY,
+    repository_id TEXT NOT NULL REFERENCES repositories(id) ON DELETE CASCADE,
+    -- Name referenced by publishing.google_play.credentials
+    name TEXT NOT NULL,
+    -- Service account email, kept in clear for display
+    client_email TEXT NOT NULL,
+    -- Service account JSON key (AES-256-GCM with AAD)
+    service_account_encrypted BLOB NOT NULL,
+    service_account_nonce BLOB NOT NULL,
+    is_active INTEGER NOT NULL DEFAULT 1,
+    created_at TEXT NOT NULL,
+    updated_at TEXT NOT NULL,
+    UNIQUE(repository_id, name)
+);
+
+CREATE INDEX idx_google_play_credentials_repo ON google_play_credentials(repository_id);
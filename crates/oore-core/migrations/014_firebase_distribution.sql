@@ -0,0 +1,33 @@
+-- Migration: Firebase App Distribution
+-- Description: Per-repository Firebase service account and per-build publications
+
+CREATE TABLE firebase_credentials (
+    id TEXT PRIMARY KEY,
+    repository_id TEXT NOT NULL UNIQUE REFERENCES repositories(id) ON DELETE CASCADE,
+    -- Service account email, kept in clear for display
+    client_email TEXT NOT NULL,
+    -- Service account JSON key (AES-256-GCM with AAD)
+    service_account_encrypted BLOB NOT NULL,
+    service_account_nonce BLOB NOT NULL,
+    created_at TEXT NOT NULL,
+    updated_at TEXT NOT NULL
+);
+
+-- One row per publishing target run for a build
+CREATE TABLE build_publications (
+    id TEXT PRIMARY KEY,
+    build_id TEXT NOT NULL REFERENCES builds(id) ON DELETE CASCADE,
+    -- Publishing target key (e.g. 'firebase', 'google_play')
+    publisher TEXT NOT NULL,
+    -- Build step holding the publication's logs
+    step_index INTEGER NOT NULL,
+    -- 'running', 'success' or 'failure'
+    status TEXT NOT NULL,
+    -- Short outcome summary shown on success (e.g. release name)
+    summary TEXT,
+    error TEXT,
+    created_at TEXT NOT NULL,
+    finished_at TEXT
+);
+
+CREATE INDEX idx_build_publications_build ON build_publications(build_id);
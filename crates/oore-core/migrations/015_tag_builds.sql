@@ -0,0 +1,40 @@
+-- Migration: Tag builds
+-- Description: Allow the 'tag' trigger type on builds
+
+-- SQLite can't alter a CHECK constraint, so the table is rebuilt. Migrations
+-- run with foreign keys off (see `run_migrations`), so dropping the old table
+-- leaves build steps, logs and artifacts in place.
+CREATE TABLE builds_new (
+    id TEXT PRIMARY KEY,
+    repository_id TEXT NOT NULL REFERENCES repositories(id) ON DELETE CASCADE,
+    webhook_event_id TEXT REFERENCES webhook_events(id) ON DELETE SET NULL,
+    commit_sha TEXT NOT NULL,
+    branch TEXT NOT NULL,
+    trigger_type TEXT NOT NULL CHECK (trigger_type IN ('push', 'tag', 'pull_request', 'merge_request', 'manual')),
+    status TEXT NOT NULL DEFAULT 'pending' CHECK (status IN ('pending', 'running', 'success', 'failure', 'cancelled')),
+    started_at TEXT,
+    finished_at TEXT,
+    created_at TEXT NOT NULL,
+    workflow_name TEXT,
+    config_source TEXT CHECK (config_source IN ('repository', 'stored')),
+    error_message TEXT
+);
+
+INSERT INTO builds_new (
+    id, repository_id, webhook_event_id, commit_sha, branch, trigger_type, status,
+    started_at, finished_at, created_at, workflow_name, config_source, error_message
+)
+SELECT
+    id, repository_id, webhook_event_id, commit_sha, branch, trigger_type, status,
+    started_at, finished_at, created_at, workflow_name, config_source, error_message
+FROM builds;
+
+DROP TABLE builds;
+
+ALTER TABLE builds_new RENAME TO builds;
+
+CREATE INDEX idx_builds_repository ON builds(repository_id);
+CREATE INDEX idx_builds_status ON builds(status);
+CREATE INDEX idx_builds_webhook_event ON builds(webhook_event_id);
+CREATE INDEX idx_builds_finished_at ON builds(finished_at);
+CREATE INDEX idx_builds_repo_branch ON builds(repository_id, branch, created_at);
@@ -0,0 +1,5 @@
+-- Migration: Build filters
+-- Description: Per-repository filters applied before a webhook creates a build
+
+ALTER TABLE repositories ADD COLUMN skip_draft_pull_requests INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE repositories ADD COLUMN required_pull_request_label TEXT;
@@ -0,0 +1,7 @@
+-- Migration: Cancel previous builds
+-- Description: Cancel older pending/running builds when a newer commit arrives for the same branch
+
+ALTER TABLE repositories ADD COLUMN cancel_previous_builds INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE builds ADD COLUMN superseded_by TEXT REFERENCES builds(id) ON DELETE SET NULL;
+
+CREATE INDEX IF NOT EXISTS idx_builds_superseded_by ON builds(superseded_by);
@@ -0,0 +1,65 @@
+-- Migration: Build schedules
+-- Description: Cron schedules per repository and the 'scheduled' trigger type
+
+CREATE TABLE build_schedules (
+    id TEXT PRIMARY KEY,
+    repository_id TEXT NOT NULL REFERENCES repositories(id) ON DELETE CASCADE,
+    cron_expression TEXT NOT NULL,
+    branch TEXT NOT NULL,
+    -- NULL runs the pipeline's default workflow
+    workflow_name TEXT,
+    -- 'UTC', a fixed offset such as '+02:00', or an IANA name
+    timezone TEXT NOT NULL DEFAULT 'UTC',
+    -- Skip the run when the branch head already built successfully
+    skip_if_built INTEGER NOT NULL DEFAULT 0,
+    is_active INTEGER NOT NULL DEFAULT 1,
+    next_run_at TEXT,
+    last_run_at TEXT,
+    last_build_id TEXT REFERENCES builds(id) ON DELETE SET NULL,
+    last_error TEXT,
+    created_at TEXT NOT NULL,
+    updated_at TEXT NOT NULL
+);
+
+CREATE INDEX idx_build_schedules_repository ON build_schedules(repository_id);
+CREATE INDEX idx_build_schedules_next_run ON build_schedules(is_active, next_run_at);
+
+-- Same rebuild as 015_tag_builds.sql, adding 'scheduled' to the trigger types
+CREATE TABLE builds_new (
+    id TEXT PRIMARY KEY,
+    repository_id TEXT NOT NULL REFERENCES repositories(id) ON DELETE CASCADE,
+    webhook_event_id TEXT REFERENCES webhook_events(id) ON DELETE SET NULL,
+    commit_sha TEXT NOT NULL,
+    branch TEXT NOT NULL,
+    trigger_type TEXT NOT NULL CHECK (trigger_type IN ('push', 'tag', 'pull_request', 'merge_request', 'manual', 'scheduled')),
+    status TEXT NOT NULL DEFAULT 'pending' CHECK (status IN ('pending', 'running', 'success', 'failure', 'cancelled')),
+    started_at TEXT,
+    finished_at TEXT,
+    created_at TEXT NOT NULL,
+    workflow_name TEXT,
+    config_source TEXT CHECK (config_source IN ('repository', 'stored')),
+    error_message TEXT,
+    superseded_by TEXT REFERENCES builds(id) ON DELETE SET NULL
+);
+
+INSERT INTO builds_new (
+    id, repository_id, webhook_event_id, commit_sha, branch, trigger_type, status,
+    started_at, finished_at, created_at, workflow_name, config_source, error_message,
+    superseded_by
+)
+SELECT
+    id, repository_id, webhook_event_id, commit_sha, branch, trigger_type, status,
+    started_at, finished_at, created_at, workflow_name, config_source, error_message,
+    superseded_by
+FROM builds;
+
+DROP TABLE builds;
+
+ALTER TABLE builds_new RENAME TO builds;
+
+CREATE INDEX idx_builds_repository ON builds(repository_id);
+CREATE INDEX idx_builds_status ON builds(status);
+CREATE INDEX idx_builds_webhook_event ON builds(webhook_event_id);
+CREATE INDEX idx_builds_finished_at ON builds(finished_at);
+CREATE INDEX idx_builds_repo_branch ON builds(repository_id, branch, created_at);
+CREATE INDEX idx_builds_superseded_by ON builds(superseded_by);
@@ -0,0 +1,4 @@
+-- Migration: Repository executor
+-- Description: Per-repository choice of build executor (shell or container); NULL uses the server default
+
+ALTER TABLE repositories ADD COLUMN executor TEXT;
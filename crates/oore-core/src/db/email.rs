@@ -0,0 +1,239 @@
+//! Database operations for SMTP settings and email notification configs.
+
+use chrono::{DateTime, Utc};
+use sqlx::Row;
+
+use super::DbPool;
+use crate::error::{OoreError, Result};
+use crate::models::{
+    parse_email_recipients, EmailConfigId, EmailNotificationConfig, RepositoryId, SmtpSettings,
+};
+use crate::oauth::{decrypt_with_aad, encrypt_with_aad, EncryptionKey};
+
+/// AAD table name used when encrypting the SMTP password.
+pub const SMTP_SETTINGS_AAD_TABLE: &str = "smtp_settings";
+
+/// The settings table holds a single row with this ID.
+const SMTP_SETTINGS_ROW_ID: i64 = 1;
+
+/// Encrypts the SMTP password.
+pub fn encrypt_smtp_password(key: &EncryptionKey, password: &str) -> Result<(Vec<u8>, Vec<u8>)> {
+    encrypt_with_aad(
+        key,
+        password.as_bytes(),
+        SMTP_SETTINGS_AAD_TABLE,
+        &SMTP_SETTINGS_ROW_ID.to_string(),
+    )
+}
+
+/// Decrypts the SMTP password, if one is stored.
+pub fn decrypt_smtp_password(
+    key: &EncryptionKey,
+    settings: &SmtpSettings,
+) -> Result<Option<String>> {
+    let (Some(ciphertext), Some(nonce)) = (&settings.password_encrypted, &settings.password_nonce)
+    else {
+        return Ok(None);
+    };
+    let bytes = decrypt_with_aad(
+        key,
+        ciphertext,
+        nonce,
+        SMTP_SETTINGS_AAD_TABLE,
+        &SMTP_SETTINGS_ROW_ID.to_string(),
+    )?;
+    String::from_utf8(bytes)
+        .map(Some)
+        .map_err(|_| OoreError::Encryption("SMTP password is not valid UTF-8".to_string()))
+}
+
+/// SMTP settings repository.
+pub struct SmtpSettingsRepo;
+
+impl SmtpSettingsRepo {
+    /// Gets the stored SMTP settings.
+    pub async fn get(pool: &DbPool) -> Result<Option<SmtpSettings>> {
+        let row = sqlx::query(
+            r#"
+            SELECT host, port, tls_mode, username, password_encrypted, password_nonce,
+                   from_address, digest_recipients, created_at, updated_at
+            FROM smtp_settings
+            WHERE id = ?
+            "#,
+        )
+        .bind(SMTP_SETTINGS_ROW_ID)
+        .fetch_optional(pool)
+        .await?;
+
+        row.map(|r| Self::row_to_settings(&r)).transpose()
+    }
+
+    /// Creates or replaces the SMTP settings.
+    pub async fn upsert(pool: &DbPool, settings: &SmtpSettings) -> Result<()> {
+        let now = Utc::now().to_rfc3339();
+        sqlx::query(
+            r#"
+            INSERT INTO smtp_settings (
+                id, host, port, tls_mode, username, password_encrypted, password_nonce,
+                from_address, digest_recipients, created_at, updated_at
+            ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+            ON CONFLICT(id) DO UPDATE SET
+                host = excluded.host,
+                port = excluded.port,
+                tls_mode = excluded.tls_mode,
+                username = excluded.username,
+                password_encrypted = excluded.password_encrypted,
+                password_nonce = excluded.password_nonce,
+                from_address = excluded.from_address,
+                digest_recipients = excluded.digest_recipients,
+                updated_at = excluded.updated_at
+            "#,
+        )
+        .bind(SMTP_SETTINGS_ROW_ID)
+        .bind(&settings.host)
+        .bind(settings.port as i64)
+        .bind(settings.tls.as_str())
+        .bind(&settings.username)
+        .bind(&settings.password_encrypted)
+        .bind(&settings.password_nonce)
+        .bind(&settings.from_address)
+        .bind(settings.digest_recipients.join(","))
+        .bind(settings.created_at.to_rfc3339())
+        .bind(&now)
+        .execute(pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Deletes the stored SMTP settings, falling back to the environment.
+    pub async fn delete(pool: &DbPool) -> Result<bool> {
+        let result = sqlx::query("DELETE FROM smtp_settings WHERE id = ?")
+            .bind(SMTP_SETTINGS_ROW_ID)
+            .execute(pool)
+            .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    fn row_to_settings(row: &sqlx::sqlite::SqliteRow) -> Result<SmtpSettings> {
+        let port: i64 = row.get("port");
+        let tls_str: String = row.get("tls_mode");
+        let recipients_str: String = row.get("digest_recipients");
+        let created_at_str: String = row.get("created_at");
+        let updated_at_str: String = row.get("updated_at");
+
+        Ok(SmtpSettings {
+            host: row.get("host"),
+            port: u16::try_from(port).map_err(|e| decode_error(e.to_string()))?,
+            tls: tls_str.parse().map_err(decode_error)?,
+            username: row.get("username"),
+            password_encrypted: row.get("password_encrypted"),
+            password_nonce: row.get("password_nonce"),
+            from_address: row.get("from_address"),
+            digest_recipients: parse_email_recipients(&recipients_str),
+            created_at: parse_datetime(&created_at_str)?,
+            updated_at: parse_datetime(&updated_at_str)?,
+        })
+    }
+}
+
+/// Email notification config repository.
+pub struct EmailConfigRepo;
+
+impl EmailConfigRepo {
+    /// Gets the email config for a repository.
+    pub async fn get_for_repo(
+        pool: &DbPool,
+        repository_id: &RepositoryId,
+    ) -> Result<Option<EmailNotificationConfig>> {
+        let row = sqlx::query(
+            r#"
+            SELECT id, repository_id, recipients, on_failure, on_recovery, is_active,
+                   created_at, updated_at
+            FROM email_notification_configs
+            WHERE repository_id = ?
+            "#,
+        )
+        .bind(repository_id.to_string())
+        .fetch_optional(pool)
+        .await?;
+
+        row.map(|r| Self::row_to_config(&r)).transpose()
+    }
+
+    /// Creates or replaces the email config for a repository.
+    pub async fn upsert(pool: &DbPool, config: &EmailNotificationConfig) -> Result<()> {
+        let now = Utc::now().to_rfc3339();
+        sqlx::query(
+            r#"
+            INSERT INTO email_notification_configs (
+                id, repository_id, recipients, on_failure, on_recovery, is_active,
+                created_at, updated_at
+            ) VALUES (?, ?, ?, ?, ?, ?, ?, ?)
+            ON CONFLICT(repository_id) DO UPDATE SET
+                recipients = excluded.recipients,
+                on_failure = excluded.on_failure,
+                on_recovery = excluded.on_recovery,
+                is_active = excluded.is_active,
+                updated_at = excluded.updated_at
+            "#,
+        )
+        .bind(config.id.to_string())
+        .bind(config.repository_id.to_string())
+        .bind(config.recipients.join(","))
+        .bind(config.on_failure)
+        .bind(config.on_recovery)
+        .bind(config.is_active)
+        .bind(config.created_at.to_rfc3339())
+        .bind(&now)
+        .execute(pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Deletes the email config for a repository.
+    pub async fn delete_for_repo(pool: &DbPool, repository_id: &RepositoryId) -> Result<bool> {
+        let result = sqlx::query("DELETE FROM email_notification_configs WHERE repository_id = ?")
+            .bind(repository_id.to_string())
+            .execute(pool)
+            .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    fn row_to_config(row: &sqlx::sqlite::SqliteRow) -> Result<EmailNotificationConfig> {
+        let id_str: String = row.get("id");
+        let repo_id_str: String = row.get("repository_id");
+        let recipients_str: String = row.get("recipients");
+        let created_at_str: String = row.get("created_at");
+        let updated_at_str: String = row.get("updated_at");
+
+        Ok(EmailNotificationConfig {
+            id: EmailConfigId::from_string(&id_str)
+                .map_err(|e| OoreError::Database(sqlx::Error::Decode(Box::new(e))))?,
+            repository_id: RepositoryId::from_string(&repo_id_str)
+                .map_err(|e| OoreError::Database(sqlx::Error::Decode(Box::new(e))))?,
+            recipients: parse_email_recipients(&recipients_str),
+            on_failure: row.get("on_failure"),
+            on_recovery: row.get("on_recovery"),
+            is_active: row.get("is_active"),
+            created_at: parse_datetime(&created_at_str)?,
+            updated_at: parse_datetime(&updated_at_str)?,
+        })
+    }
+}
+
+fn decode_error(message: String) -> OoreError {
+    OoreError::Database(sqlx::Error::Decode(Box::new(std::io::Error::new(
+        std::io::ErrorKind::InvalidData,
+        message,
+    ))))
+}
+
+fn parse_datetime(s: &str) -> Result<DateTime<Utc>> {
+    chrono::DateTime::parse_from_rfc3339(s)
+        .map(|dt| dt.with_timezone(&Utc))
+        .map_err(|e| decode_error(e.to_string()))
+}
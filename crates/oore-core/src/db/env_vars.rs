@@ -0,0 +1,217 @@
+//! Database operations for repository environment variables.
+
+use chrono::{DateTime, Utc};
+use sqlx::Row;
+
+use super::DbPool;
+use crate::error::{OoreError, Result};
+use crate::models::{EnvVarId, RepositoryEnvVar, RepositoryId};
+use crate::oauth::{decrypt_with_aad, encrypt_with_aad, EncryptionKey};
+
+/// AAD table name used when encrypting environment variable values.
+pub const ENV_VAR_AAD_TABLE: &str = "repository_env_var";
+
+/// Encrypts a value for the given environment variable ID.
+pub fn encrypt_env_value(
+    key: &EncryptionKey,
+    id: &EnvVarId,
+    value: &str,
+) -> Result<(Vec<u8>, Vec<u8>)> {
+    encrypt_with_aad(key, value.as_bytes(), ENV_VAR_AAD_TABLE, &id.to_string())
+}
+
+/// Decrypts an environment variable's value.
+pub fn decrypt_env_value(key: &EncryptionKey, var: &RepositoryEnvVar) -> Result<String> {
+    let bytes = decrypt_with_aad(
+        key,
+        &var.value_encrypted,
+        &var.value_nonce,
+        ENV_VAR_AAD_TABLE,
+        &var.id.to_string(),
+    )?;
+    String::from_utf8(bytes)
+        .map_err(|_| OoreError::Encryption(format!("Value of {} is not valid UTF-8", var.name)))
+}
+
+/// Repository environment variable repository.
+pub struct EnvVarRepo;
+
+impl EnvVarRepo {
+    /// Creates a new environment variable.
+    pub async fn create(pool: &DbPool, var: &RepositoryEnvVar) -> Result<()> {
+        let now = Utc::now().to_rfc3339();
+        sqlx::query(
+            r#"
+            INSERT INTO repository_env_vars (
+                id, repository_id, group_name, name,
+                value_encrypted, value_nonce, is_secret,
+                created_at, updated_at
+            ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)
+            "#,
+        )
+        .bind(var.id.to_string())
+        .bind(var.repository_id.to_string())
+        .bind(&var.group_name)
+        .bind(&var.name)
+        .bind(&var.value_encrypted)
+        .bind(&var.value_nonce)
+        .bind(var.is_secret)
+        .bind(&now)
+        .bind(&now)
+        .execute(pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Gets an environment variable by ID.
+    pub async fn get_by_id(pool: &DbPool, id: &EnvVarId) -> Result<Option<RepositoryEnvVar>> {
+        let row = sqlx::query(
+            r#"
+            SELECT id, repository_id, group_name, name,
+                   value_encrypted, value_nonce, is_secret,
+                   created_at, updated_at
+            FROM repository_env_vars
+            WHERE id = ?
+            "#,
+        )
+        .bind(id.to_string())
+        .fetch_optional(pool)
+        .await?;
+
+        row.map(|r| Self::row_to_var(&r)).transpose()
+    }
+
+    /// Gets an environment variable by group and name.
+    pub async fn get_by_name(
+        pool: &DbPool,
+        repository_id: &RepositoryId,
+        group_name: Option<&str>,
+        name: &str,
+    ) -> Result<Option<RepositoryEnvVar>> {
+        let row = sqlx::query(
+            r#"
+            SELECT id, repository_id, group_name, name,
+                   value_encrypted, value_nonce, is_secret,
+                   created_at, updated_at
+            FROM repository_env_vars
+            WHERE repository_id = ? AND COALESCE(group_name, '') = COALESCE(?, '') AND name = ?
+            "#,
+        )
+        .bind(repository_id.to_string())
+        .bind(group_name)
+        .bind(name)
+        .fetch_optional(pool)
+        .await?;
+
+        row.map(|r| Self::row_to_var(&r)).transpose()
+    }
+
+    /// Lists all environment variables for a repository.
+    pub async fn list_for_repo(
+        pool: &DbPool,
+        repository_id: &RepositoryId,
+    ) -> Result<Vec<RepositoryEnvVar>> {
+        let rows = sqlx::query(
+            r#"
+            SELECT id, repository_id, group_name, name,
+                   value_encrypted, value_nonce, is_secret,
+                   created_at, updated_at
+            FROM repository_env_vars
+            WHERE repository_id = ?
+            ORDER BY COALESCE(group_name, '') ASC, name ASC
+            "#,
+        )
+        .bind(repository_id.to_string())
+        .fetch_all(pool)
+        .await?;
+
+        rows.iter().map(Self::row_to_var).collect()
+    }
+
+    /// Lists the distinct group names defined for a repository.
+    pub async fn list_groups(pool: &DbPool, repository_id: &RepositoryId) -> Result<Vec<String>> {
+        let rows = sqlx::query(
+            r#"
+            SELECT DISTINCT group_name
+            FROM repository_env_vars
+            WHERE repository_id = ? AND group_name IS NOT NULL
+            ORDER BY group_name ASC
+            "#,
+        )
+        .bind(repository_id.to_string())
+        .fetch_all(pool)
+        .await?;
+
+        Ok(rows.iter().map(|r| r.get("group_name")).collect())
+    }
+
+    /// Updates an environment variable's value and secret flag.
+    pub async fn update(
+        pool: &DbPool,
+        id: &EnvVarId,
+        value_encrypted: &[u8],
+        value_nonce: &[u8],
+        is_secret: bool,
+    ) -> Result<bool> {
+        let now = Utc::now().to_rfc3339();
+        let result = sqlx::query(
+            r#"
+            UPDATE repository_env_vars
+            SET value_encrypted = ?, value_nonce = ?, is_secret = ?, updated_at = ?
+            WHERE id = ?
+            "#,
+        )
+        .bind(value_encrypted)
+        .bind(value_nonce)
+        .bind(is_secret)
+        .bind(&now)
+        .bind(id.to_string())
+        .execute(pool)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Deletes an environment variable.
+    pub async fn delete(pool: &DbPool, id: &EnvVarId) -> Result<bool> {
+        let result = sqlx::query("DELETE FROM repository_env_vars WHERE id = ?")
+            .bind(id.to_string())
+            .execute(pool)
+            .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    fn row_to_var(row: &sqlx::sqlite::SqliteRow) -> Result<RepositoryEnvVar> {
+        let id_str: String = row.get("id");
+        let repo_id_str: String = row.get("repository_id");
+        let created_at_str: String = row.get("created_at");
+        let updated_at_str: String = row.get("updated_at");
+
+        Ok(RepositoryEnvVar {
+            id: EnvVarId::from_string(&id_str)
+                .map_err(|e| OoreError::Database(sqlx::Error::Decode(Box::new(e))))?,
+            repository_id: RepositoryId::from_string(&repo_id_str)
+                .map_err(|e| OoreError::Database(sqlx::Error::Decode(Box::new(e))))?,
+            group_name: row.get("group_name"),
+            name: row.get("name"),
+            value_encrypted: row.get("value_encrypted"),
+            value_nonce: row.get("value_nonce"),
+            is_secret: row.get("is_secret"),
+            created_at: parse_datetime(&created_at_str)?,
+            updated_at: parse_datetime(&updated_at_str)?,
+        })
+    }
+}
+
+fn parse_datetime(s: &str) -> Result<DateTime<Utc>> {
+    chrono::DateTime::parse_from_rfc3339(s)
+        .map(|dt| dt.with_timezone(&Utc))
+        .map_err(|e| {
+            OoreError::Database(sqlx::Error::Decode(Box::new(std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                e.to_string(),
+            ))))
+        })
+}
@@ -2,9 +2,15 @@
 
 pub mod artifact;
 pub mod credentials;
+pub mod email;
+pub mod env_vars;
+pub mod outbound_webhooks;
 pub mod pipeline;
+pub mod publishing;
 pub mod repository;
+pub mod schedules;
 pub mod signing;
+pub mod slack;
 
 use sqlx::sqlite::{SqliteConnectOptions, SqlitePool, SqlitePoolOptions};
 use std::str::FromStr;
@@ -48,8 +54,21 @@ pub async fn create_pool(database_url: &str) -> Result<DbPool> {
 }
 
 /// Runs database migrations.
+///
+/// Foreign keys are off while migrations run: rebuilding a table drops the
+/// old one, which would otherwise cascade to its child rows. SQLite ignores
+/// the pragma inside the transaction each migration runs in, so it's set on
+/// the connection beforehand.
 pub async fn run_migrations(pool: &DbPool) -> Result<()> {
-    sqlx::migrate!("./migrations").run(pool).await?;
+    let mut conn = pool.acquire().await?;
+    sqlx::query("PRAGMA foreign_keys = OFF")
+        .execute(&mut *conn)
+        .await?;
+    let result = sqlx::migrate!("./migrations").run(&mut *conn).await;
+    sqlx::query("PRAGMA foreign_keys = ON")
+        .execute(&mut *conn)
+        .await?;
+    result?;
     tracing::info!("Database migrations completed");
     Ok(())
 }
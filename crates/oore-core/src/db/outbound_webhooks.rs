@@ -0,0 +1,326 @@
+//! Database operations for outbound webhooks and their delivery queue.
+
+use chrono::{DateTime, Utc};
+use sqlx::Row;
+
+use super::DbPool;
+use crate::error::{OoreError, Result};
+use crate::models::{
+    format_notification_events, parse_notification_events, BuildId, DeliveryStatus, OutboundDelivery,
+    OutboundDeliveryId, OutboundWebhook, OutboundWebhookId, RepositoryId,
+};
+use crate::oauth::{decrypt_with_aad, encrypt_with_aad, EncryptionKey};
+
+/// AAD table name used when encrypting outbound webhook secrets.
+pub const OUTBOUND_WEBHOOK_AAD_TABLE: &str = "outbound_webhook";
+
+/// Encrypts a signing secret for the given webhook ID.
+pub fn encrypt_webhook_secret(
+    key: &EncryptionKey,
+    id: &OutboundWebhookId,
+    secret: &str,
+) -> Result<(Vec<u8>, Vec<u8>)> {
+    encrypt_with_aad(key, secret.as_bytes(), OUTBOUND_WEBHOOK_AAD_TABLE, &id.to_string())
+}
+
+/// Decrypts a webhook's signing secret.
+pub fn decrypt_webhook_secret(key: &EncryptionKey, webhook: &OutboundWebhook) -> Result<String> {
+    let bytes = decrypt_with_aad(
+        key,
+        &webhook.secret_encrypted,
+        &webhook.secret_nonce,
+        OUTBOUND_WEBHOOK_AAD_TABLE,
+        &webhook.id.to_string(),
+    )?;
+    String::from_utf8(bytes)
+        .map_err(|_| OoreError::Encryption("Webhook secret is not valid UTF-8".to_string()))
+}
+
+/// Outbound webhook repository.
+pub struct OutboundWebhookRepo;
+
+impl OutboundWebhookRepo {
+    /// Creates a new outbound webhook.
+    pub async fn create(pool: &DbPool, webhook: &OutboundWebhook) -> Result<()> {
+        let now = Utc::now().to_rfc3339();
+        sqlx::query(
+            r#"
+            INSERT INTO outbound_webhooks (
+                id, repository_id, url, events,
+                secret_encrypted, secret_nonce, is_active,
+                created_at, updated_at
+            ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)
+            "#,
+        )
+        .bind(webhook.id.to_string())
+        .bind(webhook.repository_id.to_string())
+        .bind(&webhook.url)
+        .bind(format_notification_events(&webhook.events))
+        .bind(&webhook.secret_encrypted)
+        .bind(&webhook.secret_nonce)
+        .bind(webhook.is_active)
+        .bind(&now)
+        .bind(&now)
+        .execute(pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Gets an outbound webhook by ID.
+    pub async fn get_by_id(
+        pool: &DbPool,
+        id: &OutboundWebhookId,
+    ) -> Result<Option<OutboundWebhook>> {
+        let row = sqlx::query(
+            r#"
+            SELECT id, repository_id, url, events,
+                   secret_encrypted, secret_nonce, is_active,
+                   created_at, updated_at
+            FROM outbound_webhooks
+            WHERE id = ?
+            "#,
+        )
+        .bind(id.to_string())
+        .fetch_optional(pool)
+        .await?;
+
+        row.map(|r| Self::row_to_webhook(&r)).transpose()
+    }
+
+    /// Lists all outbound webhooks for a repository.
+    pub async fn list_for_repo(
+        pool: &DbPool,
+        repository_id: &RepositoryId,
+    ) -> Result<Vec<OutboundWebhook>> {
+        let rows = sqlx::query(
+            r#"
+            SELECT id, repository_id, url, events,
+                   secret_encrypted, secret_nonce, is_active,
+                   created_at, updated_at
+            FROM outbound_webhooks
+            WHERE repository_id = ?
+            ORDER BY created_at ASC
+            "#,
+        )
+        .bind(repository_id.to_string())
+        .fetch_all(pool)
+        .await?;
+
+        rows.iter().map(Self::row_to_webhook).collect()
+    }
+
+    /// Updates an outbound webhook's URL, events, secret and active flag.
+    pub async fn update(pool: &DbPool, webhook: &OutboundWebhook) -> Result<bool> {
+        let now = Utc::now().to_rfc3339();
+        let result = sqlx::query(
+            r#"
+            UPDATE outbound_webhooks
+            SET url = ?, events = ?, secret_encrypted = ?, secret_nonce = ?,
+                is_active = ?, updated_at = ?
+            WHERE id = ?
+            "#,
+        )
+        .bind(&webhook.url)
+        .bind(format_notification_events(&webhook.events))
+        .bind(&webhook.secret_encrypted)
+        .bind(&webhook.secret_nonce)
+        .bind(webhook.is_active)
+        .bind(&now)
+        .bind(webhook.id.to_string())
+        .execute(pool)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Deletes an outbound webhook and its delivery history.
+    pub async fn delete(pool: &DbPool, id: &OutboundWebhookId) -> Result<bool> {
+        let result = sqlx::query("DELETE FROM outbound_webhooks WHERE id = ?")
+            .bind(id.to_string())
+            .execute(pool)
+            .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    fn row_to_webhook(row: &sqlx::sqlite::SqliteRow) -> Result<OutboundWebhook> {
+        let id_str: String = row.get("id");
+        let repo_id_str: String = row.get("repository_id");
+        let events_str: String = row.get("events");
+        let created_at_str: String = row.get("created_at");
+        let updated_at_str: String = row.get("updated_at");
+
+        Ok(OutboundWebhook {
+            id: OutboundWebhookId::from_string(&id_str)
+                .map_err(|e| OoreError::Database(sqlx::Error::Decode(Box::new(e))))?,
+            repository_id: RepositoryId::from_string(&repo_id_str)
+                .map_err(|e| OoreError::Database(sqlx::Error::Decode(Box::new(e))))?,
+            url: row.get("url"),
+            events: parse_notification_events(&events_str).map_err(decode_error)?,
+            secret_encrypted: row.get("secret_encrypted"),
+            secret_nonce: row.get("secret_nonce"),
+            is_active: row.get("is_active"),
+            created_at: parse_datetime(&created_at_str)?,
+            updated_at: parse_datetime(&updated_at_str)?,
+        })
+    }
+}
+
+/// Outbound webhook delivery repository.
+pub struct OutboundDeliveryRepo;
+
+impl OutboundDeliveryRepo {
+    /// Queues a new delivery.
+    pub async fn create(pool: &DbPool, delivery: &OutboundDelivery) -> Result<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO outbound_webhook_deliveries (
+                id, webhook_id, build_id, event, payload, status,
+                attempts, next_attempt_at, created_at
+            ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)
+            "#,
+        )
+        .bind(delivery.id.to_string())
+        .bind(delivery.webhook_id.to_string())
+        .bind(delivery.build_id.as_ref().map(|id| id.to_string()))
+        .bind(delivery.event.as_str())
+        .bind(&delivery.payload)
+        .bind(delivery.status.as_str())
+        .bind(delivery.attempts)
+        .bind(delivery.next_attempt_at.map(|t| t.to_rfc3339()))
+        .bind(delivery.created_at.to_rfc3339())
+        .execute(pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Lists the most recent deliveries for a webhook, newest first.
+    pub async fn list_for_webhook(
+        pool: &DbPool,
+        webhook_id: &OutboundWebhookId,
+        limit: i64,
+    ) -> Result<Vec<OutboundDelivery>> {
+        let rows = sqlx::query(
+            r#"
+            SELECT id, webhook_id, build_id, event, payload, status, attempts,
+                   next_attempt_at, response_status, error_message,
+                   created_at, delivered_at
+            FROM outbound_webhook_deliveries
+            WHERE webhook_id = ?
+            ORDER BY created_at DESC, id DESC
+            LIMIT ?
+            "#,
+        )
+        .bind(webhook_id.to_string())
+        .bind(limit)
+        .fetch_all(pool)
+        .await?;
+
+        rows.iter().map(Self::row_to_delivery).collect()
+    }
+
+    /// Lists pending deliveries whose next attempt is due, oldest first.
+    pub async fn list_due(
+        pool: &DbPool,
+        now: DateTime<Utc>,
+        limit: i64,
+    ) -> Result<Vec<OutboundDelivery>> {
+        let rows = sqlx::query(
+            r#"
+            SELECT id, webhook_id, build_id, event, payload, status, attempts,
+                   next_attempt_at, response_status, error_message,
+                   created_at, delivered_at
+            FROM outbound_webhook_deliveries
+            WHERE status = 'pending' AND next_attempt_at <= ?
+            ORDER BY next_attempt_at ASC
+            LIMIT ?
+            "#,
+        )
+        .bind(now.to_rfc3339())
+        .bind(limit)
+        .fetch_all(pool)
+        .await?;
+
+        rows.iter().map(Self::row_to_delivery).collect()
+    }
+
+    /// Records the outcome of a delivery attempt.
+    ///
+    /// `next_attempt_at` should be set only when the delivery stays pending.
+    pub async fn record_attempt(
+        pool: &DbPool,
+        id: &OutboundDeliveryId,
+        status: DeliveryStatus,
+        attempts: i32,
+        next_attempt_at: Option<DateTime<Utc>>,
+        response_status: Option<i32>,
+        error_message: Option<&str>,
+    ) -> Result<()> {
+        let delivered_at = (status == DeliveryStatus::Success).then(|| Utc::now().to_rfc3339());
+        sqlx::query(
+            r#"
+            UPDATE outbound_webhook_deliveries
+            SET status = ?, attempts = ?, next_attempt_at = ?,
+                response_status = ?, error_message = ?, delivered_at = ?
+            WHERE id = ?
+            "#,
+        )
+        .bind(status.as_str())
+        .bind(attempts)
+        .bind(next_attempt_at.map(|t| t.to_rfc3339()))
+        .bind(response_status)
+        .bind(error_message)
+        .bind(delivered_at)
+        .bind(id.to_string())
+        .execute(pool)
+        .await?;
+
+        Ok(())
+    }
+
+    fn row_to_delivery(row: &sqlx::sqlite::SqliteRow) -> Result<OutboundDelivery> {
+        let id_str: String = row.get("id");
+        let webhook_id_str: String = row.get("webhook_id");
+        let build_id_str: Option<String> = row.get("build_id");
+        let event_str: String = row.get("event");
+        let status_str: String = row.get("status");
+        let next_attempt_at_str: Option<String> = row.get("next_attempt_at");
+        let created_at_str: String = row.get("created_at");
+        let delivered_at_str: Option<String> = row.get("delivered_at");
+
+        Ok(OutboundDelivery {
+            id: OutboundDeliveryId::from_string(&id_str)
+                .map_err(|e| OoreError::Database(sqlx::Error::Decode(Box::new(e))))?,
+            webhook_id: OutboundWebhookId::from_string(&webhook_id_str)
+                .map_err(|e| OoreError::Database(sqlx::Error::Decode(Box::new(e))))?,
+            build_id: build_id_str
+                .map(|s| BuildId::from_string(&s))
+                .transpose()
+                .map_err(|e| OoreError::Database(sqlx::Error::Decode(Box::new(e))))?,
+            event: event_str.parse().map_err(decode_error)?,
+            payload: row.get("payload"),
+            status: status_str.parse().map_err(decode_error)?,
+            attempts: row.get("attempts"),
+            next_attempt_at: next_attempt_at_str.as_deref().map(parse_datetime).transpose()?,
+            response_status: row.get("response_status"),
+            error_message: row.get("error_message"),
+            created_at: parse_datetime(&created_at_str)?,
+            delivered_at: delivered_at_str.as_deref().map(parse_datetime).transpose()?,
+        })
+    }
+}
+
+fn decode_error(message: String) -> OoreError {
+    OoreError::Database(sqlx::Error::Decode(Box::new(std::io::Error::new(
+        std::io::ErrorKind::InvalidData,
+        message,
+    ))))
+}
+
+fn parse_datetime(s: &str) -> Result<DateTime<Utc>> {
+    chrono::DateTime::parse_from_rfc3339(s)
+        .map(|dt| dt.with_timezone(&Utc))
+        .map_err(|e| decode_error(e.to_string()))
+}
@@ -403,8 +403,8 @@ impl BuildLogRepo {
         sqlx::query(
             r#"
             INSERT INTO build_logs (
-                id, build_id, step_index, stream, log_file_path, line_count, created_at
-            ) VALUES (?, ?, ?, ?, ?, ?, ?)
+                id, build_id, step_index, stream, log_file_path, line_count, masked_count, created_at
+            ) VALUES (?, ?, ?, ?, ?, ?, ?, ?)
             "#,
         )
         .bind(log.id.to_string())
@@ -413,6 +413,7 @@ impl BuildLogRepo {
         .bind(log.stream.as_str())
         .bind(&log.log_file_path)
         .bind(log.line_count)
+        .bind(log.masked_count)
         .bind(&now)
         .execute(pool)
         .await?;
@@ -424,7 +425,7 @@ impl BuildLogRepo {
     pub async fn get_by_id(pool: &DbPool, id: &BuildLogId) -> Result<Option<BuildLog>> {
         let row = sqlx::query(
             r#"
-            SELECT id, build_id, step_index, stream, log_file_path, line_count, created_at
+            SELECT id, build_id, step_index, stream, log_file_path, line_count, masked_count, created_at
             FROM build_logs
             WHERE id = ?
             "#,
@@ -440,7 +441,7 @@ impl BuildLogRepo {
     pub async fn list_for_build(pool: &DbPool, build_id: &BuildId) -> Result<Vec<BuildLog>> {
         let rows = sqlx::query(
             r#"
-            SELECT id, build_id, step_index, stream, log_file_path, line_count, created_at
+            SELECT id, build_id, step_index, stream, log_file_path, line_count, masked_count, created_at
             FROM build_logs
             WHERE build_id = ?
             ORDER BY step_index ASC, stream ASC
@@ -461,7 +462,7 @@ impl BuildLogRepo {
     ) -> Result<Vec<BuildLog>> {
         let rows = sqlx::query(
             r#"
-            SELECT id, build_id, step_index, stream, log_file_path, line_count, created_at
+            SELECT id, build_id, step_index, stream, log_file_path, line_count, masked_count, created_at
             FROM build_logs
             WHERE build_id = ? AND step_index = ?
             ORDER BY stream ASC
@@ -506,6 +507,7 @@ impl BuildLogRepo {
             })?,
             log_file_path: row.get("log_file_path"),
             line_count: row.get("line_count"),
+            masked_count: row.get("masked_count"),
             created_at: chrono::DateTime::parse_from_rfc3339(&created_at_str)
                 .map_err(|e| OoreError::DateParse {
                     field: "build_log.created_at",
@@ -0,0 +1,354 @@
+//! Database operations for store publishing credentials and results.
+
+use chrono::{DateTime, Utc};
+use sqlx::Row;
+
+use super::DbPool;
+use crate::error::{OoreError, Result};
+use crate::models::{
+    BuildId, BuildPublication, BuildPublicationId, FirebaseCredential, FirebaseCredentialId,
+    GooglePlayCredential, GooglePlayCredentialId, PublishStatus, RepositoryId,
+};
+
+// ============================================================================
+// Google Play Credentials
+// ============================================================================
+
+/// Google Play credential repository.
+pub struct GooglePlayCredentialRepo;
+
+impl GooglePlayCredentialRepo {
+    /// Creates a new credential.
+    pub async fn create(pool: &DbPool, credential: &GooglePlayCredential) -> Result<()> {
+        let now = Utc::now().to_rfc3339();
+        sqlx::query(
+            r#"
+            INSERT INTO google_play_credentials (
+                id, repository_id, name, client_email,
+                service_account_encrypted, service_account_nonce,
+                is_active, created_at, updated_at
+            ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)
+            "#,
+        )
+        .bind(credential.id.to_string())
+        .bind(credential.repository_id.to_string())
+        .bind(&credential.name)
+        .bind(&credential.client_email)
+        .bind(&credential.service_account_encrypted)
+        .bind(&credential.service_account_nonce)
+        .bind(credential.is_active)
+        .bind(&now)
+        .bind(&now)
+        .execute(pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Gets a credential by ID.
+    pub async fn get_by_id(
+        pool: &DbPool,
+        id: &GooglePlayCredentialId,
+    ) -> Result<Option<GooglePlayCredential>> {
+        let row = sqlx::query(
+            r#"
+            SELECT id, repository_id, name, client_email,
+                   service_account_encrypted, service_account_nonce,
+                   is_active, created_at, updated_at
+            FROM google_play_credentials
+            WHERE id = ?
+            "#,
+        )
+        .bind(id.to_string())
+        .fetch_optional(pool)
+        .await?;
+
+        row.map(|r| Self::row_to_credential(&r)).transpose()
+    }
+
+    /// Lists active credentials for a repository.
+    pub async fn list_active_for_repo(
+        pool: &DbPool,
+        repository_id: &RepositoryId,
+    ) -> Result<Vec<GooglePlayCredential>> {
+        let rows = sqlx::query(
+            r#"
+            SELECT id, repository_id, name, client_email,
+                   service_account_encrypted, service_account_nonce,
+                   is_active, created_at, updated_at
+            FROM google_play_credentials
+            WHERE repository_id = ? AND is_active = 1
+            ORDER BY created_at DESC
+            "#,
+        )
+        .bind(repository_id.to_string())
+        .fetch_all(pool)
+        .await?;
+
+        rows.iter().map(Self::row_to_credential).collect()
+    }
+
+    /// Lists all credentials for a repository (including inactive).
+    pub async fn list_all_for_repo(
+        pool: &DbPool,
+        repository_id: &RepositoryId,
+    ) -> Result<Vec<GooglePlayCredential>> {
+        let rows = sqlx::query(
+            r#"
+            SELECT id, repository_id, name, client_email,
+                   service_account_encrypted, service_account_nonce,
+                   is_active, created_at, updated_at
+            FROM google_play_credentials
+            WHERE repository_id = ?
+            ORDER BY created_at DESC
+            "#,
+        )
+        .bind(repository_id.to_string())
+        .fetch_all(pool)
+        .await?;
+
+        rows.iter().map(Self::row_to_credential).collect()
+    }
+
+    /// Deletes a credential.
+    pub async fn delete(pool: &DbPool, id: &GooglePlayCredentialId) -> Result<bool> {
+        let result = sqlx::query("DELETE FROM google_play_credentials WHERE id = ?")
+            .bind(id.to_string())
+            .execute(pool)
+            .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    fn row_to_credential(row: &sqlx::sqlite::SqliteRow) -> Result<GooglePlayCredential> {
+        let id_str: String = row.get("id");
+        let repo_id_str: String = row.get("repository_id");
+        let created_at_str: String = row.get("created_at");
+        let updated_at_str: String = row.get("updated_at");
+
+        Ok(GooglePlayCredential {
+            id: GooglePlayCredentialId::from_string(&id_str)
+                .map_err(|e| OoreError::Database(sqlx::Error::Decode(Box::new(e))))?,
+            repository_id: RepositoryId::from_string(&repo_id_str)
+                .map_err(|e| OoreError::Database(sqlx::Error::Decode(Box::new(e))))?,
+            name: row.get("name"),
+            client_email: row.get("client_email"),
+            service_account_encrypted: row.get("service_account_encrypted"),
+            service_account_nonce: row.get("service_account_nonce"),
+            is_active: row.get("is_active"),
+            created_at: parse_datetime(&created_at_str)?,
+            updated_at: parse_datetime(&updated_at_str)?,
+        })
+    }
+}
+
+// ============================================================================
+// Firebase Credentials
+// ============================================================================
+
+/// Firebase credential repository.
+pub struct FirebaseCredentialRepo;
+
+impl FirebaseCredentialRepo {
+    /// Gets the Firebase credential for a repository.
+    pub async fn get_for_repo(
+        pool: &DbPool,
+        repository_id: &RepositoryId,
+    ) -> Result<Option<FirebaseCredential>> {
+        let row = sqlx::query(
+            r#"
+            SELECT id, repository_id, client_email,
+                   service_account_encrypted, service_account_nonce,
+                   created_at, updated_at
+            FROM firebase_credentials
+            WHERE repository_id = ?
+            "#,
+        )
+        .bind(repository_id.to_string())
+        .fetch_optional(pool)
+        .await?;
+
+        row.map(|r| Self::row_to_credential(&r)).transpose()
+    }
+
+    /// Creates or replaces the Firebase credential for a repository.
+    pub async fn upsert(pool: &DbPool, credential: &FirebaseCredential) -> Result<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO firebase_credentials (
+                id, repository_id, client_email,
+                service_account_encrypted, service_account_nonce,
+                created_at, updated_at
+            ) VALUES (?, ?, ?, ?, ?, ?, ?)
+            ON CONFLICT(repository_id) DO UPDATE SET
+                client_email = excluded.client_email,
+                service_account_encrypted = excluded.service_account_encrypted,
+                service_account_nonce = excluded.service_account_nonce,
+                updated_at = excluded.updated_at
+            "#,
+        )
+        .bind(credential.id.to_string())
+        .bind(credential.repository_id.to_string())
+        .bind(&credential.client_email)
+        .bind(&credential.service_account_encrypted)
+        .bind(&credential.service_account_nonce)
+        .bind(credential.created_at.to_rfc3339())
+        .bind(credential.updated_at.to_rfc3339())
+        .execute(pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Deletes the Firebase credential for a repository.
+    pub async fn delete_for_repo(pool: &DbPool, repository_id: &RepositoryId) -> Result<bool> {
+        let result = sqlx::query("DELETE FROM firebase_credentials WHERE repository_id = ?")
+            .bind(repository_id.to_string())
+            .execute(pool)
+            .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    fn row_to_credential(row: &sqlx::sqlite::SqliteRow) -> Result<FirebaseCredential> {
+        let id_str: String = row.get("id");
+        let repo_id_str: String = row.get("repository_id");
+        let created_at_str: String = row.get("created_at");
+        let updated_at_str: String = row.get("updated_at");
+
+        Ok(FirebaseCredential {
+            id: FirebaseCredentialId::from_string(&id_str)
+                .map_err(|e| OoreError::Database(sqlx::Error::Decode(Box::new(e))))?,
+            repository_id: RepositoryId::from_string(&repo_id_str)
+                .map_err(|e| OoreError::Database(sqlx::Error::Decode(Box::new(e))))?,
+            client_email: row.get("client_email"),
+            service_account_encrypted: row.get("service_account_encrypted"),
+            service_account_nonce: row.get("service_account_nonce"),
+            created_at: parse_datetime(&created_at_str)?,
+            updated_at: parse_datetime(&updated_at_str)?,
+        })
+    }
+}
+
+// ============================================================================
+// Build Publications
+// ============================================================================
+
+/// Build publication repository.
+pub struct BuildPublicationRepo;
+
+impl BuildPublicationRepo {
+    /// Records a new build publication.
+    pub async fn create(pool: &DbPool, publication: &BuildPublication) -> Result<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO build_publications (
+                id, build_id, publisher, step_index, status, summary, error, created_at,
+                finished_at
+            ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)
+            "#,
+        )
+        .bind(publication.id.to_string())
+        .bind(publication.build_id.to_string())
+        .bind(&publication.publisher)
+        .bind(publication.step_index)
+        .bind(publication.status.as_str())
+        .bind(&publication.summary)
+        .bind(&publication.error)
+        .bind(publication.created_at.to_rfc3339())
+        .bind(publication.finished_at.map(|t| t.to_rfc3339()))
+        .execute(pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Records the final status of a build publication.
+    pub async fn finish(
+        pool: &DbPool,
+        id: &BuildPublicationId,
+        status: PublishStatus,
+        summary: Option<&str>,
+        error: Option<&str>,
+    ) -> Result<()> {
+        sqlx::query(
+            r#"
+            UPDATE build_publications
+            SET status = ?, summary = ?, error = ?, finished_at = ?
+            WHERE id = ?
+            "#,
+        )
+        .bind(status.as_str())
+        .bind(summary)
+        .bind(error)
+        .bind(Utc::now().to_rfc3339())
+        .bind(id.to_string())
+        .execute(pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Lists publications for a build, oldest first.
+    pub async fn list_for_build(
+        pool: &DbPool,
+        build_id: &BuildId,
+    ) -> Result<Vec<BuildPublication>> {
+        let rows = sqlx::query(
+            r#"
+            SELECT id, build_id, publisher, step_index, status, summary, error, created_at,
+                   finished_at
+            FROM build_publications
+            WHERE build_id = ?
+            ORDER BY step_index ASC
+            "#,
+        )
+        .bind(build_id.to_string())
+        .fetch_all(pool)
+        .await?;
+
+        rows.iter().map(Self::row_to_publication).collect()
+    }
+
+    fn row_to_publication(row: &sqlx::sqlite::SqliteRow) -> Result<BuildPublication> {
+        let id_str: String = row.get("id");
+        let build_id_str: String = row.get("build_id");
+        let status_str: String = row.get("status");
+        let created_at_str: String = row.get("created_at");
+        let finished_at_str: Option<String> = row.get("finished_at");
+
+        Ok(BuildPublication {
+            id: BuildPublicationId::from_string(&id_str)
+                .map_err(|e| OoreError::Database(sqlx::Error::Decode(Box::new(e))))?,
+            build_id: BuildId::from_string(&build_id_str)
+                .map_err(|e| OoreError::Database(sqlx::Error::Decode(Box::new(e))))?,
+            publisher: row.get("publisher"),
+            step_index: row.get("step_index"),
+            status: status_str.parse().map_err(|e: String| {
+                OoreError::Database(sqlx::Error::Decode(Box::new(std::io::Error::new(
+                    std::io::ErrorKind::InvalidData,
+                    e,
+                ))))
+            })?,
+            summary: row.get("summary"),
+            error: row.get("error"),
+            created_at: parse_datetime(&created_at_str)?,
+            finished_at: finished_at_str.map(|s| parse_datetime(&s)).transpose()?,
+        })
+    }
+}
+
+// ============================================================================
+// Helper Functions
+// ============================================================================
+
+fn parse_datetime(s: &str) -> Result<DateTime<Utc>> {
+    chrono::DateTime::parse_from_rfc3339(s)
+        .map(|dt| dt.with_timezone(&Utc))
+        .map_err(|e| {
+            OoreError::Database(sqlx::Error::Decode(Box::new(std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                e.to_string(),
+            ))))
+        })
+}
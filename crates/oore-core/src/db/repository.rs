@@ -1,6 +1,6 @@
 //! Database operations for repositories, webhook events, and builds.
 
-use chrono::Utc;
+use chrono::{DateTime, Utc};
 use sqlx::Row;
 
 use super::DbPool;
@@ -14,7 +14,8 @@ use crate::models::{
 const REPO_SELECT_COLUMNS: &str = r#"
     id, name, provider, owner, repo_name, clone_url, default_branch,
     webhook_secret_hmac, is_active, github_repository_id,
-    github_installation_id, gitlab_project_id, created_at, updated_at
+    github_installation_id, gitlab_project_id, skip_draft_pull_requests,
+    required_pull_request_label, cancel_previous_builds, executor, created_at, updated_at
 "#;
 
 /// SQL column list for webhook event SELECT queries.
@@ -27,7 +28,7 @@ const WEBHOOK_EVENT_SELECT_COLUMNS: &str = r#"
 const BUILD_SELECT_COLUMNS: &str = r#"
     id, repository_id, webhook_event_id, commit_sha, branch,
     trigger_type, status, started_at, finished_at, created_at,
-    workflow_name, config_source, error_message
+    workflow_name, config_source, error_message, superseded_by
 "#;
 
 /// Repository database operations.
@@ -42,8 +43,9 @@ impl RepositoryRepo {
             INSERT INTO repositories (
                 id, name, provider, owner, repo_name, clone_url, default_branch,
                 webhook_secret_hmac, is_active, github_repository_id,
-                github_installation_id, gitlab_project_id, created_at, updated_at
-            ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+                github_installation_id, gitlab_project_id, skip_draft_pull_requests,
+                required_pull_request_label, cancel_previous_builds, executor, created_at, updated_at
+            ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
             "#,
         )
         .bind(repo.id.to_string())
@@ -58,6 +60,10 @@ impl RepositoryRepo {
         .bind(repo.github_repository_id)
         .bind(repo.github_installation_id)
         .bind(repo.gitlab_project_id)
+        .bind(repo.skip_draft_pull_requests)
+        .bind(&repo.required_pull_request_label)
+        .bind(repo.cancel_previous_builds)
+        .bind(repo.executor.map(|e| e.as_str()))
         .bind(&now)
         .bind(&now)
         .execute(pool)
@@ -148,7 +154,9 @@ impl RepositoryRepo {
             r#"
             UPDATE repositories SET
                 name = ?, default_branch = ?, webhook_secret_hmac = ?, is_active = ?,
-                github_installation_id = ?, gitlab_project_id = ?, updated_at = ?
+                github_installation_id = ?, gitlab_project_id = ?,
+                skip_draft_pull_requests = ?, required_pull_request_label = ?,
+                cancel_previous_builds = ?, executor = ?, updated_at = ?
             WHERE id = ?
             "#,
         )
@@ -158,6 +166,10 @@ impl RepositoryRepo {
         .bind(repo.is_active)
         .bind(repo.github_installation_id)
         .bind(repo.gitlab_project_id)
+        .bind(repo.skip_draft_pull_requests)
+        .bind(&repo.required_pull_request_label)
+        .bind(repo.cancel_previous_builds)
+        .bind(repo.executor.map(|e| e.as_str()))
         .bind(&now)
         .bind(repo.id.to_string())
         .execute(pool)
@@ -194,6 +206,7 @@ impl RepositoryRepo {
     fn row_to_repository(row: &sqlx::sqlite::SqliteRow) -> Result<Repository> {
         let id_str: String = row.get("id");
         let provider_str: String = row.get("provider");
+        let executor_str: Option<String> = row.get("executor");
         let created_at_str: String = row.get("created_at");
         let updated_at_str: String = row.get("updated_at");
 
@@ -211,6 +224,10 @@ impl RepositoryRepo {
             github_repository_id: row.get("github_repository_id"),
             github_installation_id: row.get("github_installation_id"),
             gitlab_project_id: row.get("gitlab_project_id"),
+            skip_draft_pull_requests: row.get("skip_draft_pull_requests"),
+            required_pull_request_label: row.get("required_pull_request_label"),
+            cancel_previous_builds: row.get("cancel_previous_builds"),
+            executor: executor_str.map(|s| s.parse()).transpose()?,
             created_at: chrono::DateTime::parse_from_rfc3339(&created_at_str)
                 .map_err(|e| OoreError::DateParse {
                     field: "repository.created_at",
@@ -486,6 +503,88 @@ impl BuildRepo {
         rows.iter().map(Self::row_to_build).collect()
     }
 
+    /// Gets the most recent finished build on the same repository and branch
+    /// created before `build`. Cancelled builds are skipped.
+    pub async fn get_previous_finished(pool: &DbPool, build: &Build) -> Result<Option<Build>> {
+        let query = format!(
+            r#"
+            SELECT {BUILD_SELECT_COLUMNS} FROM builds
+            WHERE repository_id = ? AND branch = ? AND created_at < ? AND id != ?
+              AND status IN ('success', 'failure')
+            ORDER BY created_at DESC
+            LIMIT 1
+            "#
+        );
+        let row = sqlx::query(&query)
+            .bind(build.repository_id.to_string())
+            .bind(&build.branch)
+            .bind(build.created_at.to_rfc3339())
+            .bind(build.id.to_string())
+            .fetch_optional(pool)
+            .await?;
+
+        row.map(|r| Self::row_to_build(&r)).transpose()
+    }
+
+    /// Checks whether `commit_sha` has a successful build, optionally of a
+    /// specific workflow.
+    pub async fn has_successful_build(
+        pool: &DbPool,
+        repository_id: &RepositoryId,
+        commit_sha: &str,
+        workflow_name: Option<&str>,
+    ) -> Result<bool> {
+        let row = sqlx::query(
+            r#"
+            SELECT COUNT(*) AS count FROM builds
+            WHERE repository_id = ? AND commit_sha = ? AND status = 'success'
+              AND (? IS NULL OR workflow_name = ?)
+            "#,
+        )
+        .bind(repository_id.to_string())
+        .bind(commit_sha)
+        .bind(workflow_name)
+        .bind(workflow_name)
+        .fetch_one(pool)
+        .await?;
+
+        Ok(row.get::<i64, _>("count") > 0)
+    }
+
+    /// Lists builds that finished in `[since, until)`, oldest first.
+    pub async fn list_finished_between(
+        pool: &DbPool,
+        since: DateTime<Utc>,
+        until: DateTime<Utc>,
+    ) -> Result<Vec<Build>> {
+        let query = format!(
+            "SELECT {BUILD_SELECT_COLUMNS} FROM builds WHERE finished_at >= ? AND finished_at < ? ORDER BY finished_at ASC"
+        );
+        let rows = sqlx::query(&query)
+            .bind(since.to_rfc3339())
+            .bind(until.to_rfc3339())
+            .fetch_all(pool)
+            .await?;
+
+        rows.iter().map(Self::row_to_build).collect()
+    }
+
+    /// Moves a pending build to running.
+    ///
+    /// Returns `false` if the build is no longer pending, e.g. because it was
+    /// cancelled or superseded after being queued.
+    pub async fn start(pool: &DbPool, id: &BuildId) -> Result<bool> {
+        let result = sqlx::query(
+            "UPDATE builds SET status = 'running', started_at = ? WHERE id = ? AND status = 'pending'",
+        )
+        .bind(Utc::now().to_rfc3339())
+        .bind(id.to_string())
+        .execute(pool)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
     /// Marks running builds as failed (for recovery after crash).
     pub async fn fail_running_builds(pool: &DbPool, error_message: &str) -> Result<u64> {
         let now = Utc::now().to_rfc3339();
@@ -503,6 +602,57 @@ impl BuildRepo {
         Ok(result.rows_affected())
     }
 
+    /// Cancels pending and running builds of the same repository, branch and
+    /// trigger type created before `build`, recording `build` as the one that
+    /// superseded them.
+    ///
+    /// Returns the builds that were cancelled, with their previous status.
+    pub async fn cancel_superseded(pool: &DbPool, build: &Build) -> Result<Vec<Build>> {
+        let query = format!(
+            r#"
+            SELECT {BUILD_SELECT_COLUMNS} FROM builds
+            WHERE repository_id = ? AND branch = ? AND trigger_type = ? AND id != ?
+              AND status IN ('pending', 'running')
+              AND created_at < (SELECT created_at FROM builds WHERE id = ?)
+            ORDER BY created_at ASC
+            "#
+        );
+        let rows = sqlx::query(&query)
+            .bind(build.repository_id.to_string())
+            .bind(&build.branch)
+            .bind(build.trigger_type.as_str())
+            .bind(build.id.to_string())
+            .bind(build.id.to_string())
+            .fetch_all(pool)
+            .await?;
+
+        let now = Utc::now().to_rfc3339();
+        let error_message = format!("Superseded by build {}", build.id);
+        let mut cancelled = Vec::new();
+        for row in &rows {
+            let previous = Self::row_to_build(row)?;
+            // The status check skips builds that finished in the meantime
+            let result = sqlx::query(
+                r#"
+                UPDATE builds SET status = 'cancelled', finished_at = ?, error_message = ?, superseded_by = ?
+                WHERE id = ? AND status IN ('pending', 'running')
+                "#,
+            )
+            .bind(&now)
+            .bind(&error_message)
+            .bind(build.id.to_string())
+            .bind(previous.id.to_string())
+            .execute(pool)
+            .await?;
+
+            if result.rows_affected() > 0 {
+                cancelled.push(previous);
+            }
+        }
+
+        Ok(cancelled)
+    }
+
     /// Updates build with workflow info when starting execution.
     pub async fn update_workflow_info(
         pool: &DbPool,
@@ -579,6 +729,7 @@ impl BuildRepo {
         let started_at_str: Option<String> = row.get("started_at");
         let finished_at_str: Option<String> = row.get("finished_at");
         let config_source_str: Option<String> = row.get("config_source");
+        let superseded_by_str: Option<String> = row.get("superseded_by");
 
         let parse_datetime =
             |s: &str, field: &'static str| -> Result<chrono::DateTime<Utc>> {
@@ -631,6 +782,10 @@ impl BuildRepo {
                     ))))
                 })?,
             error_message: row.get("error_message"),
+            superseded_by: superseded_by_str
+                .map(|s| BuildId::from_string(&s))
+                .transpose()
+                .map_err(|e| OoreError::Database(sqlx::Error::Decode(Box::new(e))))?,
         })
     }
 }
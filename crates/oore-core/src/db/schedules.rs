@@ -0,0 +1,221 @@
+//! Database operations for build schedules.
+
+use chrono::{DateTime, Utc};
+use sqlx::Row;
+
+use super::DbPool;
+use crate::error::{OoreError, Result};
+use crate::models::{BuildId, BuildSchedule, BuildScheduleId, RepositoryId};
+
+/// SQL column list for build schedule SELECT queries.
+const SCHEDULE_SELECT_COLUMNS: &str = r#"
+    id, repository_id, cron_expression, branch, workflow_name, timezone,
+    skip_if_built, is_active, next_run_at, last_run_at, last_build_id,
+    last_error, created_at, updated_at
+"#;
+
+/// Build schedule repository.
+pub struct BuildScheduleRepo;
+
+impl BuildScheduleRepo {
+    /// Creates a new schedule.
+    pub async fn create(pool: &DbPool, schedule: &BuildSchedule) -> Result<()> {
+        let now = Utc::now().to_rfc3339();
+        sqlx::query(
+            r#"
+            INSERT INTO build_schedules (
+                id, repository_id, cron_expression, branch, workflow_name, timezone,
+                skip_if_built, is_active, next_run_at, created_at, updated_at
+            ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+            "#,
+        )
+        .bind(schedule.id.to_string())
+        .bind(schedule.repository_id.to_string())
+        .bind(&schedule.cron_expression)
+        .bind(&schedule.branch)
+        .bind(&schedule.workflow_name)
+        .bind(&schedule.timezone)
+        .bind(schedule.skip_if_built)
+        .bind(schedule.is_active)
+        .bind(schedule.next_run_at.map(|t| t.to_rfc3339()))
+        .bind(&now)
+        .bind(&now)
+        .execute(pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Gets a schedule by ID.
+    pub async fn get_by_id(pool: &DbPool, id: &BuildScheduleId) -> Result<Option<BuildSchedule>> {
+        let query = format!("SELECT {SCHEDULE_SELECT_COLUMNS} FROM build_schedules WHERE id = ?");
+        let row = sqlx::query(&query)
+            .bind(id.to_string())
+            .fetch_optional(pool)
+            .await?;
+
+        row.map(|r| Self::row_to_schedule(&r)).transpose()
+    }
+
+    /// Lists all schedules for a repository.
+    pub async fn list_for_repo(
+        pool: &DbPool,
+        repository_id: &RepositoryId,
+    ) -> Result<Vec<BuildSchedule>> {
+        let query = format!(
+            "SELECT {SCHEDULE_SELECT_COLUMNS} FROM build_schedules WHERE repository_id = ? ORDER BY created_at ASC"
+        );
+        let rows = sqlx::query(&query)
+            .bind(repository_id.to_string())
+            .fetch_all(pool)
+            .await?;
+
+        rows.iter().map(Self::row_to_schedule).collect()
+    }
+
+    /// Lists active schedules whose next run is at or before `now`.
+    pub async fn list_due(pool: &DbPool, now: DateTime<Utc>) -> Result<Vec<BuildSchedule>> {
+        let query = format!(
+            r#"
+            SELECT {SCHEDULE_SELECT_COLUMNS} FROM build_schedules
+            WHERE is_active = 1 AND next_run_at IS NOT NULL AND next_run_at <= ?
+            ORDER BY next_run_at ASC
+            "#
+        );
+        let rows = sqlx::query(&query)
+            .bind(now.to_rfc3339())
+            .fetch_all(pool)
+            .await?;
+
+        rows.iter().map(Self::row_to_schedule).collect()
+    }
+
+    /// Updates a schedule's settings and next run time.
+    pub async fn update(pool: &DbPool, schedule: &BuildSchedule) -> Result<bool> {
+        let now = Utc::now().to_rfc3339();
+        let result = sqlx::query(
+            r#"
+            UPDATE build_schedules SET
+                cron_expression = ?, branch = ?, workflow_name = ?, timezone = ?,
+                skip_if_built = ?, is_active = ?, next_run_at = ?, updated_at = ?
+            WHERE id = ?
+            "#,
+        )
+        .bind(&schedule.cron_expression)
+        .bind(&schedule.branch)
+        .bind(&schedule.workflow_name)
+        .bind(&schedule.timezone)
+        .bind(schedule.skip_if_built)
+        .bind(schedule.is_active)
+        .bind(schedule.next_run_at.map(|t| t.to_rfc3339()))
+        .bind(&now)
+        .bind(schedule.id.to_string())
+        .execute(pool)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Moves a due schedule to its next run.
+    ///
+    /// Only succeeds if `next_run_at` still holds `due_at`, so a schedule
+    /// edited in the meantime isn't run with stale settings.
+    pub async fn claim_run(
+        pool: &DbPool,
+        id: &BuildScheduleId,
+        due_at: DateTime<Utc>,
+        next_run_at: Option<DateTime<Utc>>,
+    ) -> Result<bool> {
+        let now = Utc::now().to_rfc3339();
+        let result = sqlx::query(
+            r#"
+            UPDATE build_schedules SET next_run_at = ?, last_run_at = ?
+            WHERE id = ? AND next_run_at = ?
+            "#,
+        )
+        .bind(next_run_at.map(|t| t.to_rfc3339()))
+        .bind(&now)
+        .bind(id.to_string())
+        .bind(due_at.to_rfc3339())
+        .execute(pool)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Records the outcome of a run: the build it created, or why it didn't.
+    pub async fn record_run(
+        pool: &DbPool,
+        id: &BuildScheduleId,
+        build_id: Option<&BuildId>,
+        error: Option<&str>,
+    ) -> Result<()> {
+        sqlx::query(
+            r#"
+            UPDATE build_schedules
+            SET last_build_id = COALESCE(?, last_build_id), last_error = ?
+            WHERE id = ?
+            "#,
+        )
+        .bind(build_id.map(|id| id.to_string()))
+        .bind(error)
+        .bind(id.to_string())
+        .execute(pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Deletes a schedule.
+    pub async fn delete(pool: &DbPool, id: &BuildScheduleId) -> Result<bool> {
+        let result = sqlx::query("DELETE FROM build_schedules WHERE id = ?")
+            .bind(id.to_string())
+            .execute(pool)
+            .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    fn row_to_schedule(row: &sqlx::sqlite::SqliteRow) -> Result<BuildSchedule> {
+        let id_str: String = row.get("id");
+        let repo_id_str: String = row.get("repository_id");
+        let next_run_at_str: Option<String> = row.get("next_run_at");
+        let last_run_at_str: Option<String> = row.get("last_run_at");
+        let last_build_id_str: Option<String> = row.get("last_build_id");
+        let created_at_str: String = row.get("created_at");
+        let updated_at_str: String = row.get("updated_at");
+
+        Ok(BuildSchedule {
+            id: BuildScheduleId::from_string(&id_str)
+                .map_err(|e| OoreError::Database(sqlx::Error::Decode(Box::new(e))))?,
+            repository_id: RepositoryId::from_string(&repo_id_str)
+                .map_err(|e| OoreError::Database(sqlx::Error::Decode(Box::new(e))))?,
+            cron_expression: row.get("cron_expression"),
+            branch: row.get("branch"),
+            workflow_name: row.get("workflow_name"),
+            timezone: row.get("timezone"),
+            skip_if_built: row.get("skip_if_built"),
+            is_active: row.get("is_active"),
+            next_run_at: next_run_at_str.map(|s| parse_datetime(&s)).transpose()?,
+            last_run_at: last_run_at_str.map(|s| parse_datetime(&s)).transpose()?,
+            last_build_id: last_build_id_str
+                .map(|s| BuildId::from_string(&s))
+                .transpose()
+                .map_err(|e| OoreError::Database(sqlx::Error::Decode(Box::new(e))))?,
+            last_error: row.get("last_error"),
+            created_at: parse_datetime(&created_at_str)?,
+            updated_at: parse_datetime(&updated_at_str)?,
+        })
+    }
+}
+
+fn parse_datetime(s: &str) -> Result<DateTime<Utc>> {
+    chrono::DateTime::parse_from_rfc3339(s)
+        .map(|dt| dt.with_timezone(&Utc))
+        .map_err(|e| {
+            OoreError::Database(sqlx::Error::Decode(Box::new(std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                e.to_string(),
+            ))))
+        })
+}
@@ -0,0 +1,136 @@
+//! Database operations for Slack notification configs.
+
+use chrono::{DateTime, Utc};
+use sqlx::Row;
+
+use super::DbPool;
+use crate::error::{OoreError, Result};
+use crate::models::{
+    format_notification_events, parse_notification_events, RepositoryId, SlackConfig, SlackConfigId,
+};
+use crate::oauth::{decrypt_with_aad, encrypt_with_aad, EncryptionKey};
+
+/// AAD table name used when encrypting Slack webhook URLs.
+pub const SLACK_CONFIG_AAD_TABLE: &str = "slack_notification_config";
+
+/// Encrypts an incoming-webhook URL for the given config ID.
+pub fn encrypt_slack_url(
+    key: &EncryptionKey,
+    id: &SlackConfigId,
+    url: &str,
+) -> Result<(Vec<u8>, Vec<u8>)> {
+    encrypt_with_aad(key, url.as_bytes(), SLACK_CONFIG_AAD_TABLE, &id.to_string())
+}
+
+/// Decrypts a config's incoming-webhook URL.
+pub fn decrypt_slack_url(key: &EncryptionKey, config: &SlackConfig) -> Result<String> {
+    let bytes = decrypt_with_aad(
+        key,
+        &config.webhook_url_encrypted,
+        &config.webhook_url_nonce,
+        SLACK_CONFIG_AAD_TABLE,
+        &config.id.to_string(),
+    )?;
+    String::from_utf8(bytes)
+        .map_err(|_| OoreError::Encryption("Slack webhook URL is not valid UTF-8".to_string()))
+}
+
+/// Slack notification config repository.
+pub struct SlackConfigRepo;
+
+impl SlackConfigRepo {
+    /// Gets the Slack config for a repository.
+    pub async fn get_for_repo(
+        pool: &DbPool,
+        repository_id: &RepositoryId,
+    ) -> Result<Option<SlackConfig>> {
+        let row = sqlx::query(
+            r#"
+            SELECT id, repository_id, webhook_url_encrypted, webhook_url_nonce,
+                   events, is_active, created_at, updated_at
+            FROM slack_notification_configs
+            WHERE repository_id = ?
+            "#,
+        )
+        .bind(repository_id.to_string())
+        .fetch_optional(pool)
+        .await?;
+
+        row.map(|r| Self::row_to_config(&r)).transpose()
+    }
+
+    /// Creates or replaces the Slack config for a repository.
+    pub async fn upsert(pool: &DbPool, config: &SlackConfig) -> Result<()> {
+        let now = Utc::now().to_rfc3339();
+        sqlx::query(
+            r#"
+            INSERT INTO slack_notification_configs (
+                id, repository_id, webhook_url_encrypted, webhook_url_nonce,
+                events, is_active, created_at, updated_at
+            ) VALUES (?, ?, ?, ?, ?, ?, ?, ?)
+            ON CONFLICT(repository_id) DO UPDATE SET
+                webhook_url_encrypted = excluded.webhook_url_encrypted,
+                webhook_url_nonce = excluded.webhook_url_nonce,
+                events = excluded.events,
+                is_active = excluded.is_active,
+                updated_at = excluded.updated_at
+            "#,
+        )
+        .bind(config.id.to_string())
+        .bind(config.repository_id.to_string())
+        .bind(&config.webhook_url_encrypted)
+        .bind(&config.webhook_url_nonce)
+        .bind(format_notification_events(&config.events))
+        .bind(config.is_active)
+        .bind(config.created_at.to_rfc3339())
+        .bind(&now)
+        .execute(pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Deletes the Slack config for a repository.
+    pub async fn delete_for_repo(pool: &DbPool, repository_id: &RepositoryId) -> Result<bool> {
+        let result = sqlx::query("DELETE FROM slack_notification_configs WHERE repository_id = ?")
+            .bind(repository_id.to_string())
+            .execute(pool)
+            .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    fn row_to_config(row: &sqlx::sqlite::SqliteRow) -> Result<SlackConfig> {
+        let id_str: String = row.get("id");
+        let repo_id_str: String = row.get("repository_id");
+        let events_str: String = row.get("events");
+        let created_at_str: String = row.get("created_at");
+        let updated_at_str: String = row.get("updated_at");
+
+        Ok(SlackConfig {
+            id: SlackConfigId::from_string(&id_str)
+                .map_err(|e| OoreError::Database(sqlx::Error::Decode(Box::new(e))))?,
+            repository_id: RepositoryId::from_string(&repo_id_str)
+                .map_err(|e| OoreError::Database(sqlx::Error::Decode(Box::new(e))))?,
+            webhook_url_encrypted: row.get("webhook_url_encrypted"),
+            webhook_url_nonce: row.get("webhook_url_nonce"),
+            events: parse_notification_events(&events_str).map_err(decode_error)?,
+            is_active: row.get("is_active"),
+            created_at: parse_datetime(&created_at_str)?,
+            updated_at: parse_datetime(&updated_at_str)?,
+        })
+    }
+}
+
+fn decode_error(message: String) -> OoreError {
+    OoreError::Database(sqlx::Error::Decode(Box::new(std::io::Error::new(
+        std::io::ErrorKind::InvalidData,
+        message,
+    ))))
+}
+
+fn parse_datetime(s: &str) -> Result<DateTime<Utc>> {
+    chrono::DateTime::parse_from_rfc3339(s)
+        .map(|dt| dt.with_timezone(&Utc))
+        .map_err(|e| decode_error(e.to_string()))
+}
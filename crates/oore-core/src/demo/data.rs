@@ -212,6 +212,10 @@ pub fn generate_demo_repositories() -> Vec<Repository> {
             github_repository_id: Some(100001),
             github_installation_id: Some(50001),
             gitlab_project_id: None,
+            skip_draft_pull_requests: false,
+            required_pull_request_label: None,
+            cancel_previous_builds: false,
+            executor: None,
             created_at: now - Duration::days(45),
             updated_at: now - Duration::hours(2),
         },
@@ -228,6 +232,10 @@ pub fn generate_demo_repositories() -> Vec<Repository> {
             github_repository_id: Some(100002),
             github_installation_id: Some(50001),
             gitlab_project_id: None,
+            skip_draft_pull_requests: false,
+            required_pull_request_label: None,
+            cancel_previous_builds: false,
+            executor: None,
             created_at: now - Duration::days(60),
             updated_at: now - Duration::days(1),
         },
@@ -244,6 +252,10 @@ pub fn generate_demo_repositories() -> Vec<Repository> {
             github_repository_id: Some(100003),
             github_installation_id: Some(50001),
             gitlab_project_id: None,
+            skip_draft_pull_requests: false,
+            required_pull_request_label: None,
+            cancel_previous_builds: false,
+            executor: None,
             created_at: now - Duration::days(30),
             updated_at: now - Duration::hours(6),
         },
@@ -260,6 +272,10 @@ pub fn generate_demo_repositories() -> Vec<Repository> {
             github_repository_id: Some(100004),
             github_installation_id: Some(50002),
             gitlab_project_id: None,
+            skip_draft_pull_requests: false,
+            required_pull_request_label: None,
+            cancel_previous_builds: false,
+            executor: None,
             created_at: now - Duration::days(20),
             updated_at: now - Duration::hours(12),
         },
@@ -276,6 +292,10 @@ pub fn generate_demo_repositories() -> Vec<Repository> {
             github_repository_id: Some(100005),
             github_installation_id: Some(50002),
             gitlab_project_id: None,
+            skip_draft_pull_requests: false,
+            required_pull_request_label: None,
+            cancel_previous_builds: false,
+            executor: None,
             created_at: now - Duration::days(15),
             updated_at: now - Duration::days(2),
         },
@@ -292,6 +312,10 @@ pub fn generate_demo_repositories() -> Vec<Repository> {
             github_repository_id: Some(100006),
             github_installation_id: Some(50003),
             gitlab_project_id: None,
+            skip_draft_pull_requests: false,
+            required_pull_request_label: None,
+            cancel_previous_builds: false,
+            executor: None,
             created_at: now - Duration::days(90),
             updated_at: now - Duration::days(30),
         },
@@ -308,6 +332,10 @@ pub fn generate_demo_repositories() -> Vec<Repository> {
             github_repository_id: Some(100007),
             github_installation_id: Some(50001),
             gitlab_project_id: None,
+            skip_draft_pull_requests: false,
+            required_pull_request_label: None,
+            cancel_previous_builds: false,
+            executor: None,
             created_at: now - Duration::days(10),
             updated_at: now - Duration::hours(1),
         },
@@ -325,6 +353,10 @@ pub fn generate_demo_repositories() -> Vec<Repository> {
             github_repository_id: None,
             github_installation_id: None,
             gitlab_project_id: Some(200001),
+            skip_draft_pull_requests: false,
+            required_pull_request_label: None,
+            cancel_previous_builds: false,
+            executor: None,
             created_at: now - Duration::days(25),
             updated_at: now - Duration::hours(4),
         },
@@ -341,6 +373,10 @@ pub fn generate_demo_repositories() -> Vec<Repository> {
             github_repository_id: None,
             github_installation_id: None,
             gitlab_project_id: Some(200002),
+            skip_draft_pull_requests: false,
+            required_pull_request_label: None,
+            cancel_previous_builds: false,
+            executor: None,
             created_at: now - Duration::days(50),
             updated_at: now - Duration::days(3),
         },
@@ -357,6 +393,10 @@ pub fn generate_demo_repositories() -> Vec<Repository> {
             github_repository_id: None,
             github_installation_id: None,
             gitlab_project_id: Some(200003),
+            skip_draft_pull_requests: false,
+            required_pull_request_label: None,
+            cancel_previous_builds: false,
+            executor: None,
             created_at: now - Duration::days(18),
             updated_at: now - Duration::hours(8),
         },
@@ -373,6 +413,10 @@ pub fn generate_demo_repositories() -> Vec<Repository> {
             github_repository_id: None,
             github_installation_id: None,
             gitlab_project_id: Some(200004),
+            skip_draft_pull_requests: false,
+            required_pull_request_label: None,
+            cancel_previous_builds: false,
+            executor: None,
             created_at: now - Duration::days(35),
             updated_at: now - Duration::hours(18),
         },
@@ -389,6 +433,10 @@ pub fn generate_demo_repositories() -> Vec<Repository> {
             github_repository_id: None,
             github_installation_id: None,
             gitlab_project_id: Some(200005),
+            skip_draft_pull_requests: false,
+            required_pull_request_label: None,
+            cancel_previous_builds: false,
+            executor: None,
             created_at: now - Duration::days(40),
             updated_at: now - Duration::days(5),
         },
@@ -470,6 +518,7 @@ pub fn generate_demo_builds(repositories: &[Repository]) -> Vec<Build> {
                     .map(String::from),
                 config_source: Some(ConfigSource::Repository),
                 error_message,
+                superseded_by: None,
             });
         }
     }
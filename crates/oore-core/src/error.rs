@@ -75,6 +75,9 @@ pub enum OoreError {
     #[error("No matching workflow for trigger")]
     NoMatchingWorkflow,
 
+    #[error("Skipped: no relevant changes")]
+    NoRelevantChanges,
+
     #[error("Build step not found: {0}")]
     BuildStepNotFound(String),
 
@@ -104,6 +107,15 @@ pub enum OoreError {
 
     #[error("Artifact error: {0}")]
     Artifact(String),
+
+    #[error("Cache error: {0}")]
+    Cache(String),
+
+    #[error("Notification error: {0}")]
+    Notification(String),
+
+    #[error("Publishing error: {0}")]
+    Publishing(String),
 }
 
 /// Result type alias for Oore operations.
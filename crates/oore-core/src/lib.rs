@@ -9,9 +9,12 @@ pub mod demo;
 pub mod error;
 pub mod flutter;
 pub mod models;
+pub mod notifications;
 pub mod oauth;
 pub mod pipeline;
 pub mod providers;
+pub mod publishing;
+pub mod schedule;
 pub mod signing;
 pub mod webhook;
 
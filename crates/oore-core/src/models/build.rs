@@ -64,6 +64,14 @@ impl BuildStatus {
             BuildStatus::Cancelled => "cancelled",
         }
     }
+
+    /// Returns true if the build has finished and will not change again.
+    pub fn is_terminal(&self) -> bool {
+        matches!(
+            self,
+            BuildStatus::Success | BuildStatus::Failure | BuildStatus::Cancelled
+        )
+    }
 }
 
 impl std::fmt::Display for BuildStatus {
@@ -93,18 +101,23 @@ impl std::str::FromStr for BuildStatus {
 #[ts(export, export_to = "../../../types/")]
 pub enum TriggerType {
     Push,
+    Tag,
     PullRequest,
     MergeRequest,
     Manual,
+    /// Started by a cron schedule.
+    Scheduled,
 }
 
 impl TriggerType {
     pub fn as_str(&self) -> &'static str {
         match self {
             TriggerType::Push => "push",
+            TriggerType::Tag => "tag",
             TriggerType::PullRequest => "pull_request",
             TriggerType::MergeRequest => "merge_request",
             TriggerType::Manual => "manual",
+            TriggerType::Scheduled => "scheduled",
         }
     }
 }
@@ -121,9 +134,11 @@ impl std::str::FromStr for TriggerType {
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         match s.to_lowercase().as_str() {
             "push" => Ok(TriggerType::Push),
+            "tag" => Ok(TriggerType::Tag),
             "pull_request" => Ok(TriggerType::PullRequest),
             "merge_request" => Ok(TriggerType::MergeRequest),
             "manual" => Ok(TriggerType::Manual),
+            "scheduled" => Ok(TriggerType::Scheduled),
             _ => Err(format!("Unknown trigger type: {}", s)),
         }
     }
@@ -136,6 +151,7 @@ pub struct Build {
     pub repository_id: RepositoryId,
     pub webhook_event_id: Option<WebhookEventId>,
     pub commit_sha: String,
+    /// Branch name, or the tag name for tag builds.
     pub branch: String,
     pub trigger_type: TriggerType,
     pub status: BuildStatus,
@@ -148,6 +164,8 @@ pub struct Build {
     pub config_source: Option<ConfigSource>,
     /// Error message if build failed during setup.
     pub error_message: Option<String>,
+    /// Newer build of the same branch that cancelled this one.
+    pub superseded_by: Option<BuildId>,
 }
 
 impl Build {
@@ -173,6 +191,7 @@ impl Build {
             workflow_name: None,
             config_source: None,
             error_message: None,
+            superseded_by: None,
         }
     }
 }
@@ -194,6 +213,7 @@ pub struct BuildResponse {
     pub workflow_name: Option<String>,
     pub config_source: Option<String>,
     pub error_message: Option<String>,
+    pub superseded_by: Option<String>,
 }
 
 impl From<Build> for BuildResponse {
@@ -212,6 +232,7 @@ impl From<Build> for BuildResponse {
             workflow_name: build.workflow_name,
             config_source: build.config_source.map(|s| s.as_str().to_string()),
             error_message: build.error_message,
+            superseded_by: build.superseded_by.map(|id| id.to_string()),
         }
     }
 }
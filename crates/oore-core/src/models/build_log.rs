@@ -85,6 +85,9 @@ pub struct BuildLog {
     /// Relative path to the log file (e.g., "{build_id}/step-{n}-stdout.log").
     pub log_file_path: String,
     pub line_count: i32,
+    /// Number of secret values masked in this log.
+    #[serde(default)]
+    pub masked_count: i32,
     pub created_at: DateTime<Utc>,
 }
 
@@ -103,6 +106,7 @@ impl BuildLog {
             stream,
             log_file_path,
             line_count: 0,
+            masked_count: 0,
             created_at: Utc::now(),
         }
     }
@@ -118,6 +122,7 @@ pub struct BuildLogResponse {
     pub stream: String,
     pub log_file_path: String,
     pub line_count: i32,
+    pub masked_count: i32,
     pub created_at: DateTime<Utc>,
 }
 
@@ -130,6 +135,7 @@ impl From<BuildLog> for BuildLogResponse {
             stream: log.stream.as_str().to_string(),
             log_file_path: log.log_file_path,
             line_count: log.line_count,
+            masked_count: log.masked_count,
             created_at: log.created_at,
         }
     }
@@ -227,6 +233,7 @@ mod tests {
         assert_eq!(log.stream, LogStream::Stdout);
         assert_eq!(log.log_file_path, "abc123/step-0-stdout.log");
         assert_eq!(log.line_count, 0);
+        assert_eq!(log.masked_count, 0);
     }
 
     #[test]
@@ -0,0 +1,302 @@
+//! Email notification models.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+use ulid::Ulid;
+
+use super::RepositoryId;
+
+/// How the SMTP connection is secured.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[serde(rename_all = "lowercase")]
+#[ts(export, export_to = "../../../types/")]
+pub enum SmtpTls {
+    /// Plain TCP. Only suitable for local relays and test sinks.
+    None,
+    /// Plain TCP upgraded with `STARTTLS` (usually port 587).
+    StartTls,
+    /// TLS from the first byte (usually port 465).
+    Tls,
+}
+
+impl SmtpTls {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SmtpTls::None => "none",
+            SmtpTls::StartTls => "starttls",
+            SmtpTls::Tls => "tls",
+        }
+    }
+
+    /// The conventional port for this mode.
+    pub fn default_port(&self) -> u16 {
+        match self {
+            SmtpTls::None => 25,
+            SmtpTls::StartTls => 587,
+            SmtpTls::Tls => 465,
+        }
+    }
+}
+
+impl std::fmt::Display for SmtpTls {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+impl std::str::FromStr for SmtpTls {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "none" => Ok(SmtpTls::None),
+            "starttls" => Ok(SmtpTls::StartTls),
+            "tls" => Ok(SmtpTls::Tls),
+            _ => Err(format!("Unknown SMTP TLS mode: {}", s)),
+        }
+    }
+}
+
+/// Server-wide SMTP settings stored in the database (internal model).
+#[derive(Debug, Clone)]
+pub struct SmtpSettings {
+    pub host: String,
+    pub port: u16,
+    pub tls: SmtpTls,
+    pub username: Option<String>,
+    /// Password (AES-256-GCM with AAD), if the server requires auth.
+    pub password_encrypted: Option<Vec<u8>>,
+    pub password_nonce: Option<Vec<u8>>,
+    pub from_address: String,
+    pub digest_recipients: Vec<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Request to set the server-wide SMTP settings.
+///
+/// `password` may be omitted to keep the stored one; send an empty string
+/// to clear it.
+#[derive(Debug, Clone, Deserialize, TS)]
+#[ts(export, export_to = "../../../types/")]
+pub struct SetSmtpSettingsRequest {
+    pub host: String,
+    #[serde(default)]
+    #[ts(optional)]
+    pub port: Option<u16>,
+    pub tls: SmtpTls,
+    #[serde(default)]
+    #[ts(optional)]
+    pub username: Option<String>,
+    #[serde(default)]
+    #[ts(optional)]
+    pub password: Option<String>,
+    pub from_address: String,
+    #[serde(default)]
+    pub digest_recipients: Vec<String>,
+}
+
+/// Response for the SMTP settings. The password is never returned.
+#[derive(Debug, Clone, Serialize, TS)]
+#[ts(export, export_to = "../../../types/")]
+pub struct SmtpSettingsResponse {
+    pub host: String,
+    pub port: u16,
+    pub tls: SmtpTls,
+    pub username: Option<String>,
+    pub has_password: bool,
+    pub from_address: String,
+    pub digest_recipients: Vec<String>,
+    pub updated_at: String,
+}
+
+impl From<SmtpSettings> for SmtpSettingsResponse {
+    fn from(settings: SmtpSettings) -> Self {
+        Self {
+            host: settings.host,
+            port: settings.port,
+            tls: settings.tls,
+            username: settings.username,
+            has_password: settings.password_encrypted.is_some(),
+            from_address: settings.from_address,
+            digest_recipients: settings.digest_recipients,
+            updated_at: settings.updated_at.to_rfc3339(),
+        }
+    }
+}
+
+/// Unique identifier for an email notification config.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct EmailConfigId(pub Ulid);
+
+impl EmailConfigId {
+    /// Creates a new random email config ID.
+    pub fn new() -> Self {
+        Self(Ulid::new())
+    }
+
+    /// Creates an email config ID from a string.
+    pub fn from_string(s: &str) -> Result<Self, ulid::DecodeError> {
+        Ok(Self(Ulid::from_string(s)?))
+    }
+}
+
+impl Default for EmailConfigId {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl std::fmt::Display for EmailConfigId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+/// Email notification config for a repository.
+#[derive(Debug, Clone)]
+pub struct EmailNotificationConfig {
+    pub id: EmailConfigId,
+    pub repository_id: RepositoryId,
+    pub recipients: Vec<String>,
+    /// Email when a build fails.
+    pub on_failure: bool,
+    /// Email when a build succeeds after the previous one on its branch failed.
+    pub on_recovery: bool,
+    pub is_active: bool,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Request to create or replace a repository's email config.
+#[derive(Debug, Clone, Deserialize, TS)]
+#[ts(export, export_to = "../../../types/")]
+pub struct SetEmailConfigRequest {
+    pub recipients: Vec<String>,
+    #[serde(default = "default_true")]
+    pub on_failure: bool,
+    #[serde(default = "default_true")]
+    pub on_recovery: bool,
+    #[serde(default = "default_true")]
+    pub is_active: bool,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+/// Response for an email config.
+#[derive(Debug, Clone, Serialize, TS)]
+#[ts(export, export_to = "../../../types/")]
+pub struct EmailConfigResponse {
+    pub id: String,
+    pub repository_id: String,
+    pub recipients: Vec<String>,
+    pub on_failure: bool,
+    pub on_recovery: bool,
+    pub is_active: bool,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+impl From<EmailNotificationConfig> for EmailConfigResponse {
+    fn from(config: EmailNotificationConfig) -> Self {
+        Self {
+            id: config.id.to_string(),
+            repository_id: config.repository_id.to_string(),
+            recipients: config.recipients,
+            on_failure: config.on_failure,
+            on_recovery: config.on_recovery,
+            is_active: config.is_active,
+            created_at: config.created_at.to_rfc3339(),
+            updated_at: config.updated_at.to_rfc3339(),
+        }
+    }
+}
+
+/// Validates an email address for use in SMTP envelopes and headers.
+///
+/// This is deliberately loose about what a mailbox may contain, but rejects
+/// anything that could break out of a header or an SMTP command.
+pub fn validate_email_address(address: &str) -> Result<(), String> {
+    let invalid = || format!("Invalid email address: {}", address);
+
+    if address.len() > 254
+        || address
+            .chars()
+            .any(|c| c.is_whitespace() || c.is_control() || matches!(c, '<' | '>' | ',' | ';'))
+    {
+        return Err(invalid());
+    }
+
+    match address.split_once('@') {
+        Some((local, domain))
+            if !local.is_empty()
+                && !domain.is_empty()
+                && !domain.contains('@')
+                && !domain.starts_with('.')
+                && !domain.ends_with('.') =>
+        {
+            Ok(())
+        }
+        _ => Err(invalid()),
+    }
+}
+
+/// Validates a recipient list.
+pub fn validate_email_recipients(recipients: &[String]) -> Result<(), String> {
+    if recipients.is_empty() {
+        return Err("At least one recipient is required".to_string());
+    }
+    recipients
+        .iter()
+        .try_for_each(|r| validate_email_address(r))
+}
+
+/// Parses a stored comma-separated recipient list.
+pub fn parse_email_recipients(s: &str) -> Vec<String> {
+    s.split(',')
+        .map(str::trim)
+        .filter(|r| !r.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_smtp_tls_roundtrip() {
+        for tls in [SmtpTls::None, SmtpTls::StartTls, SmtpTls::Tls] {
+            assert_eq!(tls.as_str().parse::<SmtpTls>(), Ok(tls));
+        }
+        assert_eq!("STARTTLS".parse::<SmtpTls>(), Ok(SmtpTls::StartTls));
+        assert!("ssl".parse::<SmtpTls>().is_err());
+    }
+
+    #[test]
+    fn test_validate_email_address() {
+        assert!(validate_email_address("dev@example.com").is_ok());
+        assert!(validate_email_address("first.last+ci@sub.example.co").is_ok());
+
+        assert!(validate_email_address("").is_err());
+        assert!(validate_email_address("no-at-sign").is_err());
+        assert!(validate_email_address("@example.com").is_err());
+        assert!(validate_email_address("dev@").is_err());
+        assert!(validate_email_address("a@b@example.com").is_err());
+        assert!(validate_email_address("dev@example.com\r\nBcc: x@evil.com").is_err());
+        assert!(validate_email_address("dev@example.com>").is_err());
+    }
+
+    #[test]
+    fn test_parse_email_recipients() {
+        assert_eq!(
+            parse_email_recipients("a@example.com, b@example.com,,"),
+            vec!["a@example.com", "b@example.com"]
+        );
+        assert!(parse_email_recipients("").is_empty());
+    }
+}
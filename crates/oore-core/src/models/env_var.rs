@@ -0,0 +1,197 @@
+//! Repository environment variable models.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+use ulid::Ulid;
+
+use super::RepositoryId;
+
+/// Unique identifier for a repository environment variable.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct EnvVarId(pub Ulid);
+
+impl EnvVarId {
+    /// Creates a new random environment variable ID.
+    pub fn new() -> Self {
+        Self(Ulid::new())
+    }
+
+    /// Creates an environment variable ID from a string.
+    pub fn from_string(s: &str) -> Result<Self, ulid::DecodeError> {
+        Ok(Self(Ulid::from_string(s)?))
+    }
+}
+
+impl Default for EnvVarId {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl std::fmt::Display for EnvVarId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+/// Repository environment variable (internal model with encrypted value).
+#[derive(Debug, Clone)]
+pub struct RepositoryEnvVar {
+    pub id: EnvVarId,
+    pub repository_id: RepositoryId,
+    /// Group name; `None` means the variable applies to every build.
+    pub group_name: Option<String>,
+    pub name: String,
+    /// Encrypted value (AES-256-GCM with AAD).
+    pub value_encrypted: Vec<u8>,
+    pub value_nonce: Vec<u8>,
+    pub is_secret: bool,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Request to create a repository environment variable.
+#[derive(Debug, Clone, Deserialize, TS)]
+#[ts(export, export_to = "../../../types/")]
+pub struct CreateEnvVarRequest {
+    pub name: String,
+    pub value: String,
+    #[serde(default)]
+    pub is_secret: bool,
+    #[serde(default)]
+    pub group: Option<String>,
+}
+
+/// Request to update a repository environment variable.
+#[derive(Debug, Clone, Deserialize, TS)]
+#[ts(export, export_to = "../../../types/")]
+pub struct UpdateEnvVarRequest {
+    #[serde(default)]
+    pub value: Option<String>,
+    #[serde(default)]
+    pub is_secret: Option<bool>,
+}
+
+/// Response for a repository environment variable.
+///
+/// `value` is only populated for plain variables.
+#[derive(Debug, Clone, Serialize, TS)]
+#[ts(export, export_to = "../../../types/")]
+pub struct EnvVarResponse {
+    pub id: String,
+    pub repository_id: String,
+    pub group: Option<String>,
+    pub name: String,
+    pub value: Option<String>,
+    pub is_secret: bool,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+impl EnvVarResponse {
+    /// Builds a response, including the decrypted value only for plain variables.
+    pub fn from_var(var: RepositoryEnvVar, plain_value: Option<String>) -> Self {
+        Self {
+            id: var.id.to_string(),
+            repository_id: var.repository_id.to_string(),
+            group: var.group_name,
+            name: var.name,
+            value: if var.is_secret { None } else { plain_value },
+            is_secret: var.is_secret,
+            created_at: var.created_at.to_rfc3339(),
+            updated_at: var.updated_at.to_rfc3339(),
+        }
+    }
+}
+
+/// Validates an environment variable name.
+///
+/// Names must be valid shell identifiers and may not shadow variables
+/// that Oore sets for every build.
+pub fn validate_env_var_name(name: &str) -> Result<(), String> {
+    let mut chars = name.chars();
+    let valid = match chars.next() {
+        Some(c) if c.is_ascii_alphabetic() || c == '_' => {
+            chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+        }
+        _ => false,
+    };
+    if !valid {
+        return Err(format!(
+            "Invalid variable name '{}': use letters, digits and underscores, not starting with a digit",
+            name
+        ));
+    }
+
+    if name == "CI" || name == "OORE" || name.starts_with("OORE_") {
+        return Err(format!("Variable name '{}' is reserved", name));
+    }
+
+    Ok(())
+}
+
+/// Validates an environment variable group name.
+pub fn validate_env_group_name(group: &str) -> Result<(), String> {
+    if group.is_empty()
+        || group.len() > 64
+        || !group
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | '.'))
+    {
+        return Err(format!(
+            "Invalid group name '{}': use up to 64 letters, digits, '-', '_' or '.'",
+            group
+        ));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate_env_var_name() {
+        assert!(validate_env_var_name("API_TOKEN").is_ok());
+        assert!(validate_env_var_name("_private").is_ok());
+        assert!(validate_env_var_name("").is_err());
+        assert!(validate_env_var_name("1TOKEN").is_err());
+        assert!(validate_env_var_name("MY-VAR").is_err());
+        assert!(validate_env_var_name("OORE_BUILD_ID").is_err());
+        assert!(validate_env_var_name("CI").is_err());
+    }
+
+    #[test]
+    fn test_validate_env_group_name() {
+        assert!(validate_env_group_name("app_store_credentials").is_ok());
+        assert!(validate_env_group_name("firebase-prod").is_ok());
+        assert!(validate_env_group_name("").is_err());
+        assert!(validate_env_group_name("has space").is_err());
+    }
+
+    #[test]
+    fn test_env_var_response_hides_secret_value() {
+        let now = Utc::now();
+        let var = RepositoryEnvVar {
+            id: EnvVarId::new(),
+            repository_id: RepositoryId::new(),
+            group_name: Some("release".to_string()),
+            name: "API_TOKEN".to_string(),
+            value_encrypted: vec![],
+            value_nonce: vec![],
+            is_secret: true,
+            created_at: now,
+            updated_at: now,
+        };
+
+        let response = EnvVarResponse::from_var(var.clone(), Some("secret".to_string()));
+        assert!(response.value.is_none());
+        assert_eq!(response.group, Some("release".to_string()));
+
+        let plain = RepositoryEnvVar { is_secret: false, ..var };
+        let response = EnvVarResponse::from_var(plain, Some("value".to_string()));
+        assert_eq!(response.value, Some("value".to_string()));
+    }
+}
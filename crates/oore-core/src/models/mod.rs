@@ -4,18 +4,32 @@ pub mod artifact;
 pub mod build;
 pub mod build_log;
 pub mod build_step;
+pub mod email;
+pub mod env_var;
+pub mod notification;
+pub mod outbound_webhook;
 pub mod pipeline;
 pub mod provider;
+pub mod publishing;
 pub mod repository;
+pub mod schedule;
 pub mod signing;
+pub mod slack;
 pub mod webhook;
 
 pub use artifact::*;
 pub use build::*;
 pub use build_log::*;
 pub use build_step::*;
+pub use email::*;
+pub use env_var::*;
+pub use notification::*;
+pub use outbound_webhook::*;
 pub use pipeline::*;
 pub use provider::*;
+pub use publishing::*;
 pub use repository::*;
+pub use schedule::*;
 pub use signing::*;
+pub use slack::*;
 pub use webhook::*;
@@ -0,0 +1,139 @@
+//! Build notification events.
+
+use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+
+use super::BuildStatus;
+
+/// A build lifecycle event that notifiers can subscribe to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, TS)]
+#[ts(export, export_to = "../../../types/")]
+pub enum NotificationEvent {
+    #[serde(rename = "build.started")]
+    BuildStarted,
+    #[serde(rename = "build.succeeded")]
+    BuildSucceeded,
+    #[serde(rename = "build.failed")]
+    BuildFailed,
+    #[serde(rename = "build.cancelled")]
+    BuildCancelled,
+}
+
+impl NotificationEvent {
+    /// All events, in lifecycle order.
+    pub const ALL: [NotificationEvent; 4] = [
+        NotificationEvent::BuildStarted,
+        NotificationEvent::BuildSucceeded,
+        NotificationEvent::BuildFailed,
+        NotificationEvent::BuildCancelled,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            NotificationEvent::BuildStarted => "build.started",
+            NotificationEvent::BuildSucceeded => "build.succeeded",
+            NotificationEvent::BuildFailed => "build.failed",
+            NotificationEvent::BuildCancelled => "build.cancelled",
+        }
+    }
+
+    /// Returns the event for a build entering the given status, if any.
+    pub fn for_status(status: BuildStatus) -> Option<Self> {
+        match status {
+            BuildStatus::Pending => None,
+            BuildStatus::Running => Some(NotificationEvent::BuildStarted),
+            BuildStatus::Success => Some(NotificationEvent::BuildSucceeded),
+            BuildStatus::Failure => Some(NotificationEvent::BuildFailed),
+            BuildStatus::Cancelled => Some(NotificationEvent::BuildCancelled),
+        }
+    }
+}
+
+impl std::fmt::Display for NotificationEvent {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+impl std::str::FromStr for NotificationEvent {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim() {
+            "build.started" => Ok(NotificationEvent::BuildStarted),
+            "build.succeeded" => Ok(NotificationEvent::BuildSucceeded),
+            "build.failed" => Ok(NotificationEvent::BuildFailed),
+            "build.cancelled" => Ok(NotificationEvent::BuildCancelled),
+            _ => Err(format!("Unknown notification event: {}", s)),
+        }
+    }
+}
+
+/// Validates a notifier's event filter.
+pub fn validate_notification_events(events: &[NotificationEvent]) -> Result<(), String> {
+    if events.is_empty() {
+        return Err("At least one event is required".to_string());
+    }
+    Ok(())
+}
+
+/// Formats an event filter for storage.
+pub fn format_notification_events(events: &[NotificationEvent]) -> String {
+    // Store in canonical order without duplicates
+    NotificationEvent::ALL
+        .iter()
+        .filter(|e| events.contains(e))
+        .map(|e| e.as_str())
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+/// Parses a stored event filter.
+pub fn parse_notification_events(s: &str) -> Result<Vec<NotificationEvent>, String> {
+    s.split(',')
+        .filter(|e| !e.trim().is_empty())
+        .map(str::parse)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_notification_event_roundtrip() {
+        for event in NotificationEvent::ALL {
+            assert_eq!(event.as_str().parse::<NotificationEvent>(), Ok(event));
+            assert_eq!(
+                serde_json::to_value(event).unwrap(),
+                serde_json::json!(event.as_str())
+            );
+        }
+        assert!("build.queued".parse::<NotificationEvent>().is_err());
+    }
+
+    #[test]
+    fn test_notification_events_roundtrip() {
+        let events = vec![
+            NotificationEvent::BuildFailed,
+            NotificationEvent::BuildStarted,
+            NotificationEvent::BuildFailed,
+        ];
+        let stored = format_notification_events(&events);
+        assert_eq!(stored, "build.started,build.failed");
+        assert_eq!(
+            parse_notification_events(&stored).unwrap(),
+            vec![NotificationEvent::BuildStarted, NotificationEvent::BuildFailed]
+        );
+        assert!(parse_notification_events("build.started,bogus").is_err());
+    }
+
+    #[test]
+    fn test_notification_event_for_status() {
+        assert_eq!(NotificationEvent::for_status(BuildStatus::Pending), None);
+        assert_eq!(
+            NotificationEvent::for_status(BuildStatus::Failure),
+            Some(NotificationEvent::BuildFailed)
+        );
+    }
+}
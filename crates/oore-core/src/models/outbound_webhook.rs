@@ -0,0 +1,290 @@
+//! Outbound webhook models.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+use ulid::Ulid;
+
+use super::{BuildId, NotificationEvent, RepositoryId};
+
+/// Unique identifier for an outbound webhook.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct OutboundWebhookId(pub Ulid);
+
+impl OutboundWebhookId {
+    /// Creates a new random outbound webhook ID.
+    pub fn new() -> Self {
+        Self(Ulid::new())
+    }
+
+    /// Creates an outbound webhook ID from a string.
+    pub fn from_string(s: &str) -> Result<Self, ulid::DecodeError> {
+        Ok(Self(Ulid::from_string(s)?))
+    }
+}
+
+impl Default for OutboundWebhookId {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl std::fmt::Display for OutboundWebhookId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+/// Unique identifier for an outbound webhook delivery.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct OutboundDeliveryId(pub Ulid);
+
+impl OutboundDeliveryId {
+    /// Creates a new random delivery ID.
+    pub fn new() -> Self {
+        Self(Ulid::new())
+    }
+
+    /// Creates a delivery ID from a string.
+    pub fn from_string(s: &str) -> Result<Self, ulid::DecodeError> {
+        Ok(Self(Ulid::from_string(s)?))
+    }
+}
+
+impl Default for OutboundDeliveryId {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl std::fmt::Display for OutboundDeliveryId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+/// Outbound webhook (internal model with encrypted secret).
+#[derive(Debug, Clone)]
+pub struct OutboundWebhook {
+    pub id: OutboundWebhookId,
+    pub repository_id: RepositoryId,
+    pub url: String,
+    pub events: Vec<NotificationEvent>,
+    /// Signing secret (AES-256-GCM with AAD).
+    pub secret_encrypted: Vec<u8>,
+    pub secret_nonce: Vec<u8>,
+    pub is_active: bool,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl OutboundWebhook {
+    /// Returns true if this webhook should receive the given event.
+    pub fn subscribes_to(&self, event: NotificationEvent) -> bool {
+        self.is_active && self.events.contains(&event)
+    }
+}
+
+/// Delivery status of an outbound webhook request.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[serde(rename_all = "lowercase")]
+#[ts(export, export_to = "../../../types/")]
+pub enum DeliveryStatus {
+    /// Waiting for its first attempt or a retry.
+    Pending,
+    Success,
+    /// Gave up after the maximum number of attempts.
+    Failed,
+}
+
+impl DeliveryStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DeliveryStatus::Pending => "pending",
+            DeliveryStatus::Success => "success",
+            DeliveryStatus::Failed => "failed",
+        }
+    }
+}
+
+impl std::fmt::Display for DeliveryStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+impl std::str::FromStr for DeliveryStatus {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "pending" => Ok(DeliveryStatus::Pending),
+            "success" => Ok(DeliveryStatus::Success),
+            "failed" => Ok(DeliveryStatus::Failed),
+            _ => Err(format!("Unknown delivery status: {}", s)),
+        }
+    }
+}
+
+/// A queued or completed outbound webhook delivery.
+#[derive(Debug, Clone)]
+pub struct OutboundDelivery {
+    pub id: OutboundDeliveryId,
+    pub webhook_id: OutboundWebhookId,
+    pub build_id: Option<BuildId>,
+    pub event: NotificationEvent,
+    /// JSON body, stored so retries send identical bytes.
+    pub payload: String,
+    pub status: DeliveryStatus,
+    pub attempts: i32,
+    pub next_attempt_at: Option<DateTime<Utc>>,
+    pub response_status: Option<i32>,
+    pub error_message: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub delivered_at: Option<DateTime<Utc>>,
+}
+
+impl OutboundDelivery {
+    /// Creates a pending delivery due immediately.
+    pub fn new(
+        webhook_id: OutboundWebhookId,
+        build_id: Option<BuildId>,
+        event: NotificationEvent,
+        payload: String,
+    ) -> Self {
+        let now = Utc::now();
+        Self {
+            id: OutboundDeliveryId::new(),
+            webhook_id,
+            build_id,
+            event,
+            payload,
+            status: DeliveryStatus::Pending,
+            attempts: 0,
+            next_attempt_at: Some(now),
+            response_status: None,
+            error_message: None,
+            created_at: now,
+            delivered_at: None,
+        }
+    }
+}
+
+/// Request to create an outbound webhook.
+#[derive(Debug, Clone, Deserialize, TS)]
+#[ts(export, export_to = "../../../types/")]
+pub struct CreateOutboundWebhookRequest {
+    pub url: String,
+    pub events: Vec<NotificationEvent>,
+    pub secret: String,
+    #[serde(default = "default_true")]
+    pub is_active: bool,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+/// Request to update an outbound webhook.
+#[derive(Debug, Clone, Deserialize, TS)]
+#[ts(export, export_to = "../../../types/")]
+pub struct UpdateOutboundWebhookRequest {
+    #[serde(default)]
+    pub url: Option<String>,
+    #[serde(default)]
+    pub events: Option<Vec<NotificationEvent>>,
+    #[serde(default)]
+    pub secret: Option<String>,
+    #[serde(default)]
+    pub is_active: Option<bool>,
+}
+
+/// Response for an outbound webhook. The secret is never returned.
+#[derive(Debug, Clone, Serialize, TS)]
+#[ts(export, export_to = "../../../types/")]
+pub struct OutboundWebhookResponse {
+    pub id: String,
+    pub repository_id: String,
+    pub url: String,
+    pub events: Vec<NotificationEvent>,
+    pub is_active: bool,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+impl From<OutboundWebhook> for OutboundWebhookResponse {
+    fn from(webhook: OutboundWebhook) -> Self {
+        Self {
+            id: webhook.id.to_string(),
+            repository_id: webhook.repository_id.to_string(),
+            url: webhook.url,
+            events: webhook.events,
+            is_active: webhook.is_active,
+            created_at: webhook.created_at.to_rfc3339(),
+            updated_at: webhook.updated_at.to_rfc3339(),
+        }
+    }
+}
+
+/// Response for an outbound webhook delivery.
+#[derive(Debug, Clone, Serialize, TS)]
+#[ts(export, export_to = "../../../types/")]
+pub struct OutboundDeliveryResponse {
+    pub id: String,
+    pub webhook_id: String,
+    pub build_id: Option<String>,
+    pub event: NotificationEvent,
+    pub status: DeliveryStatus,
+    pub attempts: i32,
+    pub next_attempt_at: Option<String>,
+    pub response_status: Option<i32>,
+    pub error_message: Option<String>,
+    pub created_at: String,
+    pub delivered_at: Option<String>,
+}
+
+impl From<OutboundDelivery> for OutboundDeliveryResponse {
+    fn from(delivery: OutboundDelivery) -> Self {
+        Self {
+            id: delivery.id.to_string(),
+            webhook_id: delivery.webhook_id.to_string(),
+            build_id: delivery.build_id.map(|id| id.to_string()),
+            event: delivery.event,
+            status: delivery.status,
+            attempts: delivery.attempts,
+            next_attempt_at: delivery.next_attempt_at.map(|t| t.to_rfc3339()),
+            response_status: delivery.response_status,
+            error_message: delivery.error_message,
+            created_at: delivery.created_at.to_rfc3339(),
+            delivered_at: delivery.delivered_at.map(|t| t.to_rfc3339()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_subscribes_to() {
+        let now = Utc::now();
+        let webhook = OutboundWebhook {
+            id: OutboundWebhookId::new(),
+            repository_id: RepositoryId::new(),
+            url: "https://example.com/hook".to_string(),
+            events: vec![NotificationEvent::BuildFailed],
+            secret_encrypted: vec![],
+            secret_nonce: vec![],
+            is_active: true,
+            created_at: now,
+            updated_at: now,
+        };
+        assert!(webhook.subscribes_to(NotificationEvent::BuildFailed));
+        assert!(!webhook.subscribes_to(NotificationEvent::BuildSucceeded));
+
+        let inactive = OutboundWebhook { is_active: false, ..webhook };
+        assert!(!inactive.subscribes_to(NotificationEvent::BuildFailed));
+    }
+}
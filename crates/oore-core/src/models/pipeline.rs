@@ -175,6 +175,14 @@ pub struct Workflow {
     #[serde(default = "default_max_build_duration")]
     pub max_build_duration: u32,
 
+    /// Machine size for container builds (`linux`, `linux_x2` or `linux_x4`).
+    #[serde(default)]
+    pub instance_type: Option<String>,
+
+    /// Container image for container builds. The server default when unset.
+    #[serde(default)]
+    pub image: Option<String>,
+
     /// Environment configuration.
     #[serde(default)]
     pub environment: WorkflowEnvironment,
@@ -183,6 +191,10 @@ pub struct Workflow {
     #[serde(default)]
     pub triggering: Option<TriggeringConfig>,
 
+    /// Conditions the triggering change must meet for this workflow to run.
+    #[serde(default)]
+    pub when: Option<WhenCondition>,
+
     /// Build scripts to execute.
     #[serde(default)]
     pub scripts: Vec<Step>,
@@ -190,6 +202,18 @@ pub struct Workflow {
     /// Artifact paths to collect after build.
     #[serde(default)]
     pub artifacts: Vec<String>,
+
+    /// Directories to keep between builds.
+    #[serde(default)]
+    pub cache: Option<CacheConfig>,
+
+    /// How the repository is checked out.
+    #[serde(default)]
+    pub clone: Option<CloneConfig>,
+
+    /// Where to publish the collected artifacts after a successful build.
+    #[serde(default)]
+    pub publishing: PublishingConfig,
 }
 
 fn default_max_build_duration() -> u32 {
@@ -203,6 +227,13 @@ pub struct WorkflowEnvironment {
     #[serde(default)]
     pub vars: HashMap<String, String>,
 
+    /// Repository environment variable groups to include, by name.
+    ///
+    /// Ungrouped repository variables are always included; grouped ones
+    /// only when their group is listed here.
+    #[serde(default)]
+    pub groups: Vec<String>,
+
     /// Flutter version to use.
     #[serde(default)]
     pub flutter: Option<String>,
@@ -214,6 +245,56 @@ pub struct WorkflowEnvironment {
     /// CocoaPods version to use.
     #[serde(default)]
     pub cocoapods: Option<String>,
+
+    /// Android keystore(s) to sign with, referenced by name.
+    ///
+    /// When empty, the repository's most recently uploaded active keystore is used.
+    #[serde(default)]
+    pub android_signing: Vec<String>,
+
+    /// iOS code signing selection.
+    ///
+    /// Parsed so the schema is stable, but rejected by validation until iOS
+    /// builds can be code signed.
+    #[serde(default)]
+    pub ios_signing: Option<IosSigningConfig>,
+}
+
+/// iOS code signing selection for a workflow.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IosSigningConfig {
+    /// Distribution type, which determines the provisioning profile type.
+    pub distribution_type: IosDistributionType,
+
+    /// Bundle identifier the provisioning profile must cover.
+    pub bundle_identifier: String,
+}
+
+/// iOS distribution types (Codemagic naming).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum IosDistributionType {
+    Development,
+    AdHoc,
+    AppStore,
+    Enterprise,
+}
+
+impl IosDistributionType {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            IosDistributionType::Development => "development",
+            IosDistributionType::AdHoc => "ad_hoc",
+            IosDistributionType::AppStore => "app_store",
+            IosDistributionType::Enterprise => "enterprise",
+        }
+    }
+}
+
+impl std::fmt::Display for IosDistributionType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
 }
 
 /// Triggering configuration for a workflow.
@@ -226,6 +307,15 @@ pub struct TriggeringConfig {
     /// Branch patterns for filtering.
     #[serde(default)]
     pub branch_patterns: BranchPatterns,
+
+    /// Tag patterns for filtering tag builds. Branch patterns don't apply to tags.
+    #[serde(default)]
+    pub tag_patterns: BranchPatterns,
+
+    /// Cancel older builds of the same branch or PR once this workflow is
+    /// selected, even if the repository setting is off.
+    #[serde(default)]
+    pub cancel_previous_builds: bool,
 }
 
 /// Events that can trigger a workflow.
@@ -237,7 +327,7 @@ pub enum TriggerEvent {
     Tag,
 }
 
-/// Branch patterns for include/exclude filtering.
+/// Branch or tag patterns for include/exclude filtering.
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct BranchPatterns {
     /// Branch patterns to include.
@@ -249,6 +339,153 @@ pub struct BranchPatterns {
     pub exclude: Vec<String>,
 }
 
+/// Conditions under which a triggered workflow runs.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct WhenCondition {
+    /// Files the change must touch.
+    #[serde(default)]
+    pub changeset: Option<ChangesetCondition>,
+}
+
+/// Changed-file filter. The workflow runs if any changed file matches
+/// `includes` and none of `excludes`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ChangesetCondition {
+    /// Glob patterns relative to the repository root. Empty, or `.`, matches
+    /// every file; a plain directory matches everything under it.
+    #[serde(default)]
+    pub includes: Vec<String>,
+
+    /// Glob patterns for files that don't count as changes.
+    #[serde(default)]
+    pub excludes: Vec<String>,
+}
+
+/// Dependency cache configuration for a workflow.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CacheConfig {
+    /// Paths to save after a successful build and restore before the next
+    /// one. Relative paths are relative to the repository root; `~`, `$HOME`
+    /// and other build environment variables are expanded.
+    #[serde(default)]
+    pub cache_paths: Vec<String>,
+}
+
+/// Checkout options for a workflow.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CloneConfig {
+    /// Number of commits of history to keep. Full history when unset.
+    #[serde(default)]
+    pub depth: Option<u32>,
+
+    /// Whether to check out git submodules.
+    #[serde(default)]
+    pub submodules: SubmoduleMode,
+
+    /// Whether to download Git LFS objects.
+    #[serde(default)]
+    pub lfs: bool,
+
+    /// Directories to check out. Files in the repository root are always
+    /// checked out; everything when empty.
+    #[serde(default)]
+    pub sparse_paths: Vec<String>,
+}
+
+/// Submodule checkout mode.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SubmoduleMode {
+    /// Submodules are not checked out.
+    #[default]
+    None,
+    /// Submodules and their nested submodules are checked out.
+    Recursive,
+}
+
+/// Publishing targets for a workflow.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PublishingConfig {
+    /// Scripts run in the workspace after artifacts are collected.
+    #[serde(default)]
+    pub scripts: Vec<Step>,
+
+    /// Upload Android App Bundles to Google Play.
+    #[serde(default)]
+    pub google_play: Option<GooglePlayPublishing>,
+
+    /// Upload the IPA to App Store Connect.
+    #[serde(default)]
+    pub app_store_connect: Option<AppStoreConnectPublishing>,
+
+    /// Distribute the APK, AAB or IPA with Firebase App Distribution.
+    #[serde(default)]
+    pub firebase: Option<FirebasePublishing>,
+}
+
+/// Google Play publishing options (Codemagic naming).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GooglePlayPublishing {
+    /// Name of the repository's stored service account credential.
+    pub credentials: String,
+
+    /// Application ID of the app on Google Play (e.g. `com.example.app`).
+    pub package_name: String,
+
+    /// Release track: `internal`, `alpha`, `beta`, `production` or a custom track.
+    pub track: String,
+
+    /// In-app update priority, 0-5.
+    #[serde(default)]
+    pub in_app_update_priority: Option<u8>,
+
+    /// Staged rollout fraction, between 0 and 1 exclusive.
+    #[serde(default)]
+    pub rollout_fraction: Option<f64>,
+
+    /// Create the release as a draft instead of rolling it out.
+    #[serde(default)]
+    pub submit_as_draft: bool,
+}
+
+/// App Store Connect publishing options (Codemagic naming).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AppStoreConnectPublishing {
+    /// Name of the repository's stored App Store Connect API key.
+    pub api_key: String,
+
+    /// Submit the processed build for TestFlight beta review.
+    #[serde(default)]
+    pub submit_to_testflight: bool,
+
+    /// TestFlight beta groups to add the build to, by name.
+    #[serde(default)]
+    pub beta_groups: Vec<String>,
+
+    /// Attach the build to its App Store version and submit it for review.
+    #[serde(default)]
+    pub submit_to_app_review: bool,
+}
+
+/// Firebase App Distribution options (Codemagic naming).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FirebasePublishing {
+    /// Firebase app ID, e.g. `1:1234567890:android:0a1b2c3d4e5f`.
+    pub app_id: String,
+
+    /// Tester group aliases to distribute to.
+    #[serde(default)]
+    pub groups: Vec<String>,
+
+    /// Individual tester emails to distribute to.
+    #[serde(default)]
+    pub testers: Vec<String>,
+
+    /// Release notes shown to testers.
+    #[serde(default)]
+    pub release_notes: Option<String>,
+}
+
 /// A single script step in a workflow.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Step {
@@ -311,6 +548,71 @@ pub struct CreatePipelineConfigRequest {
     pub config_format: StoredConfigFormat,
 }
 
+/// Request to validate a pipeline config without saving it.
+#[derive(Debug, Clone, Deserialize, TS)]
+#[ts(export, export_to = "../../../types/")]
+pub struct ValidatePipelineRequest {
+    pub config_content: String,
+    #[serde(default)]
+    pub config_format: StoredConfigFormat,
+    /// Report unknown fields as errors instead of warnings.
+    #[serde(default)]
+    pub strict: bool,
+}
+
+/// Severity of a pipeline diagnostic.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[ts(export, export_to = "../../../types/")]
+#[serde(rename_all = "lowercase")]
+pub enum DiagnosticSeverity {
+    Error,
+    Warning,
+}
+
+/// A problem found while validating a pipeline config.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, TS)]
+#[ts(export, export_to = "../../../types/")]
+pub struct PipelineDiagnostic {
+    pub severity: DiagnosticSeverity,
+    /// Location in the config, e.g. `workflows.ios.scripts[0].script`.
+    /// Empty for problems with the document as a whole.
+    pub path: String,
+    pub message: String,
+    /// 1-based line in the source, when it can be determined.
+    pub line: Option<u32>,
+    /// 1-based column in the source, when it can be determined.
+    pub column: Option<u32>,
+    /// Suggested fix, e.g. "did you mean `branch_patterns`?".
+    pub suggestion: Option<String>,
+}
+
+impl PipelineDiagnostic {
+    /// Creates an error diagnostic without a source location.
+    pub fn error(path: impl Into<String>, message: impl Into<String>) -> Self {
+        Self {
+            severity: DiagnosticSeverity::Error,
+            path: path.into(),
+            message: message.into(),
+            line: None,
+            column: None,
+            suggestion: None,
+        }
+    }
+}
+
+/// API response for pipeline validation.
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[ts(export, export_to = "../../../types/")]
+pub struct ValidatePipelineResponse {
+    pub valid: bool,
+    pub format: StoredConfigFormat,
+    /// Workflow names, when the config could be parsed.
+    pub workflows: Vec<String>,
+    pub diagnostics: Vec<PipelineDiagnostic>,
+    /// Message of the first error, if any.
+    pub error: Option<String>,
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -571,6 +873,7 @@ exclude:
         assert!(config.events.is_empty());
         assert!(config.branch_patterns.include.is_empty());
         assert!(config.branch_patterns.exclude.is_empty());
+        assert!(!config.cancel_previous_builds);
     }
 
     #[test]
@@ -582,12 +885,14 @@ events:
 branch_patterns:
   include:
     - main
+cancel_previous_builds: true
 "#;
         let config: TriggeringConfig = serde_yaml::from_str(yaml).unwrap();
         assert_eq!(config.events.len(), 2);
         assert!(config.events.contains(&TriggerEvent::Push));
         assert!(config.events.contains(&TriggerEvent::PullRequest));
         assert_eq!(config.branch_patterns.include.len(), 1);
+        assert!(config.cancel_previous_builds);
     }
 
     #[test]
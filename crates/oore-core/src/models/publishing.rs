@@ -0,0 +1,295 @@
+//! Store publishing credential and result models.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+use ulid::Ulid;
+
+use super::{BuildId, RepositoryId};
+
+/// Unique identifier for a Google Play credential.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct GooglePlayCredentialId(pub Ulid);
+
+impl GooglePlayCredentialId {
+    /// Creates a new random credential ID.
+    pub fn new() -> Self {
+        Self(Ulid::new())
+    }
+
+    /// Creates a credential ID from a string.
+    pub fn from_string(s: &str) -> Result<Self, ulid::DecodeError> {
+        Ok(Self(Ulid::from_string(s)?))
+    }
+}
+
+impl Default for GooglePlayCredentialId {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl std::fmt::Display for GooglePlayCredentialId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+/// Google Play service account (internal model with encrypted key).
+#[derive(Debug, Clone)]
+pub struct GooglePlayCredential {
+    pub id: GooglePlayCredentialId,
+    pub repository_id: RepositoryId,
+    /// Name referenced by `publishing.google_play.credentials`.
+    pub name: String,
+    /// Service account email from the key file.
+    pub client_email: String,
+    /// Encrypted service account JSON key.
+    pub service_account_encrypted: Vec<u8>,
+    pub service_account_nonce: Vec<u8>,
+    pub is_active: bool,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Request to upload a Google Play service account.
+#[derive(Debug, Clone, Deserialize, TS)]
+#[ts(export, export_to = "../../../types/")]
+pub struct UploadGooglePlayCredentialRequest {
+    pub name: String,
+    /// Service account JSON key, as downloaded from Google Cloud.
+    pub service_account_json: String,
+}
+
+/// Response for a Google Play credential (no secrets).
+#[derive(Debug, Clone, Serialize, TS)]
+#[ts(export, export_to = "../../../types/")]
+pub struct GooglePlayCredentialResponse {
+    pub id: String,
+    pub repository_id: String,
+    pub name: String,
+    pub client_email: String,
+    pub is_active: bool,
+    pub created_at: String,
+}
+
+impl From<GooglePlayCredential> for GooglePlayCredentialResponse {
+    fn from(credential: GooglePlayCredential) -> Self {
+        Self {
+            id: credential.id.to_string(),
+            repository_id: credential.repository_id.to_string(),
+            name: credential.name,
+            client_email: credential.client_email,
+            is_active: credential.is_active,
+            created_at: credential.created_at.to_rfc3339(),
+        }
+    }
+}
+
+/// Unique identifier for a Firebase credential.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct FirebaseCredentialId(pub Ulid);
+
+impl FirebaseCredentialId {
+    /// Creates a new random credential ID.
+    pub fn new() -> Self {
+        Self(Ulid::new())
+    }
+
+    /// Creates a credential ID from a string.
+    pub fn from_string(s: &str) -> Result<Self, ulid::DecodeError> {
+        Ok(Self(Ulid::from_string(s)?))
+    }
+}
+
+impl Default for FirebaseCredentialId {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl std::fmt::Display for FirebaseCredentialId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+/// Firebase service account for App Distribution (one per repository).
+#[derive(Debug, Clone)]
+pub struct FirebaseCredential {
+    pub id: FirebaseCredentialId,
+    pub repository_id: RepositoryId,
+    /// Service account email from the key file.
+    pub client_email: String,
+    /// Encrypted service account JSON key.
+    pub service_account_encrypted: Vec<u8>,
+    pub service_account_nonce: Vec<u8>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Request to set the Firebase service account for a repository.
+#[derive(Debug, Clone, Deserialize, TS)]
+#[ts(export, export_to = "../../../types/")]
+pub struct SetFirebaseCredentialRequest {
+    /// Service account JSON key, as downloaded from Google Cloud.
+    pub service_account_json: String,
+}
+
+/// Response for the Firebase credential (no secrets).
+#[derive(Debug, Clone, Serialize, TS)]
+#[ts(export, export_to = "../../../types/")]
+pub struct FirebaseCredentialResponse {
+    pub id: String,
+    pub repository_id: String,
+    pub client_email: String,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+impl From<FirebaseCredential> for FirebaseCredentialResponse {
+    fn from(credential: FirebaseCredential) -> Self {
+        Self {
+            id: credential.id.to_string(),
+            repository_id: credential.repository_id.to_string(),
+            client_email: credential.client_email,
+            created_at: credential.created_at.to_rfc3339(),
+            updated_at: credential.updated_at.to_rfc3339(),
+        }
+    }
+}
+
+/// Unique identifier for a build publication.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct BuildPublicationId(pub Ulid);
+
+impl BuildPublicationId {
+    /// Creates a new random build publication ID.
+    pub fn new() -> Self {
+        Self(Ulid::new())
+    }
+
+    /// Creates a build publication ID from a string.
+    pub fn from_string(s: &str) -> Result<Self, ulid::DecodeError> {
+        Ok(Self(Ulid::from_string(s)?))
+    }
+}
+
+impl Default for BuildPublicationId {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl std::fmt::Display for BuildPublicationId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+/// Status of publishing a build to one target.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[serde(rename_all = "lowercase")]
+#[ts(export, export_to = "../../../types/")]
+pub enum PublishStatus {
+    Running,
+    Success,
+    Failure,
+}
+
+impl PublishStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PublishStatus::Running => "running",
+            PublishStatus::Success => "success",
+            PublishStatus::Failure => "failure",
+        }
+    }
+}
+
+impl std::fmt::Display for PublishStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+impl std::str::FromStr for PublishStatus {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "running" => Ok(PublishStatus::Running),
+            "success" => Ok(PublishStatus::Success),
+            "failure" => Ok(PublishStatus::Failure),
+            _ => Err(format!("Unknown publish status: {}", s)),
+        }
+    }
+}
+
+/// Outcome of publishing a build to one target.
+#[derive(Debug, Clone)]
+pub struct BuildPublication {
+    pub id: BuildPublicationId,
+    pub build_id: BuildId,
+    /// Publishing target key under `publishing:` (e.g. `firebase`).
+    pub publisher: String,
+    /// Build step whose logs hold the publishing output.
+    pub step_index: i32,
+    pub status: PublishStatus,
+    /// Short outcome summary on success.
+    pub summary: Option<String>,
+    pub error: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub finished_at: Option<DateTime<Utc>>,
+}
+
+impl BuildPublication {
+    /// Creates a running publication for a publishing target.
+    pub fn new(build_id: BuildId, publisher: &str, step_index: i32) -> Self {
+        Self {
+            id: BuildPublicationId::new(),
+            build_id,
+            publisher: publisher.to_string(),
+            step_index,
+            status: PublishStatus::Running,
+            summary: None,
+            error: None,
+            created_at: Utc::now(),
+            finished_at: None,
+        }
+    }
+}
+
+/// API response for a build publication.
+#[derive(Debug, Clone, Serialize, TS)]
+#[ts(export, export_to = "../../../types/")]
+pub struct BuildPublicationResponse {
+    pub id: String,
+    pub build_id: String,
+    pub publisher: String,
+    pub step_index: i32,
+    pub status: PublishStatus,
+    pub summary: Option<String>,
+    pub error: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub finished_at: Option<DateTime<Utc>>,
+}
+
+impl From<BuildPublication> for BuildPublicationResponse {
+    fn from(publication: BuildPublication) -> Self {
+        Self {
+            id: publication.id.to_string(),
+            build_id: publication.build_id.to_string(),
+            publisher: publication.publisher,
+            step_index: publication.step_index,
+            status: publication.status,
+            summary: publication.summary,
+            error: publication.error,
+            created_at: publication.created_at,
+            finished_at: publication.finished_at,
+        }
+    }
+}
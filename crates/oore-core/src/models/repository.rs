@@ -6,6 +6,7 @@ use ts_rs::TS;
 use ulid::Ulid;
 
 use super::GitProvider;
+use crate::error::OoreError;
 
 /// Unique identifier for a repository.
 #[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
@@ -42,6 +43,55 @@ impl From<Ulid> for RepositoryId {
     }
 }
 
+/// How build steps are run.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ExecutorKind {
+    /// Directly on the host.
+    #[default]
+    Shell,
+    /// In a per-build Docker or Podman container.
+    Container,
+    /// On the host, isolated in Linux namespaces as an unprivileged user.
+    Sandbox,
+}
+
+impl ExecutorKind {
+    /// Returns the executor kind as a lowercase string.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ExecutorKind::Shell => "shell",
+            ExecutorKind::Container => "container",
+            ExecutorKind::Sandbox => "sandbox",
+        }
+    }
+}
+
+impl std::fmt::Display for ExecutorKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+impl std::str::FromStr for ExecutorKind {
+    type Err = OoreError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "shell" => Ok(ExecutorKind::Shell),
+            "container" => Ok(ExecutorKind::Container),
+            "sandbox" => Ok(ExecutorKind::Sandbox),
+            _ => Err(OoreError::Configuration(format!("Unknown executor '{}'", s))),
+        }
+    }
+}
+
+impl From<ExecutorKind> for String {
+    fn from(kind: ExecutorKind) -> Self {
+        kind.as_str().to_string()
+    }
+}
+
 /// A connected Git repository.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Repository {
@@ -62,6 +112,14 @@ pub struct Repository {
     pub github_installation_id: Option<i64>,
     /// GitLab's numeric project ID.
     pub gitlab_project_id: Option<i64>,
+    /// Don't build draft pull/merge requests.
+    pub skip_draft_pull_requests: bool,
+    /// Only build pull/merge requests carrying this label.
+    pub required_pull_request_label: Option<String>,
+    /// Cancel older builds of a branch or PR when a newer commit arrives.
+    pub cancel_previous_builds: bool,
+    /// Executor to run builds with, overriding the server default.
+    pub executor: Option<ExecutorKind>,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
@@ -89,6 +147,10 @@ impl Repository {
             github_repository_id: None,
             github_installation_id: None,
             gitlab_project_id: None,
+            skip_draft_pull_requests: false,
+            required_pull_request_label: None,
+            cancel_previous_builds: false,
+            executor: None,
             created_at: now,
             updated_at: now,
         }
@@ -113,6 +175,10 @@ pub struct RepositoryResponse {
     pub github_installation_id: Option<i64>,
     #[ts(type = "number | null")]
     pub gitlab_project_id: Option<i64>,
+    pub skip_draft_pull_requests: bool,
+    pub required_pull_request_label: Option<String>,
+    pub cancel_previous_builds: bool,
+    pub executor: Option<String>,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
@@ -131,6 +197,10 @@ impl From<Repository> for RepositoryResponse {
             github_repository_id: repo.github_repository_id,
             github_installation_id: repo.github_installation_id,
             gitlab_project_id: repo.gitlab_project_id,
+            skip_draft_pull_requests: repo.skip_draft_pull_requests,
+            required_pull_request_label: repo.required_pull_request_label,
+            cancel_previous_builds: repo.cancel_previous_builds,
+            executor: repo.executor.map(String::from),
             created_at: repo.created_at,
             updated_at: repo.updated_at,
         }
@@ -170,4 +240,11 @@ pub struct UpdateRepositoryRequest {
     pub github_installation_id: Option<i64>,
     #[ts(type = "number | null")]
     pub gitlab_project_id: Option<i64>,
+    pub skip_draft_pull_requests: Option<bool>,
+    /// Label pull/merge requests need to be built. Empty to remove.
+    pub required_pull_request_label: Option<String>,
+    pub cancel_previous_builds: Option<bool>,
+    /// Executor to run builds with (`shell`, `container` or `sandbox`). Empty
+    /// to use the server default.
+    pub executor: Option<String>,
 }
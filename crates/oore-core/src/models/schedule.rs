@@ -0,0 +1,186 @@
+//! Build schedule models.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+use ulid::Ulid;
+
+use super::{BuildId, RepositoryId};
+
+/// Unique identifier for a build schedule.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct BuildScheduleId(pub Ulid);
+
+impl BuildScheduleId {
+    /// Creates a new random schedule ID.
+    pub fn new() -> Self {
+        Self(Ulid::new())
+    }
+
+    /// Creates a schedule ID from a string.
+    pub fn from_string(s: &str) -> Result<Self, ulid::DecodeError> {
+        Ok(Self(Ulid::from_string(s)?))
+    }
+}
+
+impl Default for BuildScheduleId {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl std::fmt::Display for BuildScheduleId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+/// A cron schedule that builds a branch periodically.
+#[derive(Debug, Clone)]
+pub struct BuildSchedule {
+    pub id: BuildScheduleId,
+    pub repository_id: RepositoryId,
+    /// Five-field cron expression, read in `timezone`.
+    pub cron_expression: String,
+    pub branch: String,
+    /// Workflow to run; `None` picks the pipeline's default workflow.
+    pub workflow_name: Option<String>,
+    /// `UTC`, a fixed offset such as `+02:00`, or an IANA name.
+    pub timezone: String,
+    /// Skip the run when the branch head already has a successful build.
+    pub skip_if_built: bool,
+    pub is_active: bool,
+    /// When the scheduler runs this next; `None` if the expression never matches.
+    pub next_run_at: Option<DateTime<Utc>>,
+    pub last_run_at: Option<DateTime<Utc>>,
+    pub last_build_id: Option<BuildId>,
+    /// Why the last run didn't create a build.
+    pub last_error: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Request to create a build schedule.
+#[derive(Debug, Clone, Deserialize, TS)]
+#[ts(export, export_to = "../../../types/")]
+pub struct CreateBuildScheduleRequest {
+    pub cron_expression: String,
+    /// Defaults to the repository's default branch.
+    #[serde(default)]
+    #[ts(optional)]
+    pub branch: Option<String>,
+    #[serde(default)]
+    #[ts(optional)]
+    pub workflow_name: Option<String>,
+    /// Defaults to `UTC`.
+    #[serde(default)]
+    #[ts(optional)]
+    pub timezone: Option<String>,
+    #[serde(default)]
+    pub skip_if_built: bool,
+    #[serde(default)]
+    #[ts(optional)]
+    pub is_active: Option<bool>,
+}
+
+/// Request to update a build schedule.
+#[derive(Debug, Clone, Deserialize, TS)]
+#[ts(export, export_to = "../../../types/")]
+pub struct UpdateBuildScheduleRequest {
+    #[serde(default)]
+    #[ts(optional)]
+    pub cron_expression: Option<String>,
+    #[serde(default)]
+    #[ts(optional)]
+    pub branch: Option<String>,
+    /// Empty to use the default workflow.
+    #[serde(default)]
+    #[ts(optional)]
+    pub workflow_name: Option<String>,
+    #[serde(default)]
+    #[ts(optional)]
+    pub timezone: Option<String>,
+    #[serde(default)]
+    #[ts(optional)]
+    pub skip_if_built: Option<bool>,
+    #[serde(default)]
+    #[ts(optional)]
+    pub is_active: Option<bool>,
+}
+
+/// API response for a build schedule.
+#[derive(Debug, Clone, Serialize, TS)]
+#[ts(export, export_to = "../../../types/")]
+pub struct BuildScheduleResponse {
+    pub id: String,
+    pub repository_id: String,
+    pub cron_expression: String,
+    pub branch: String,
+    pub workflow_name: Option<String>,
+    pub timezone: String,
+    pub skip_if_built: bool,
+    pub is_active: bool,
+    pub next_run_at: Option<String>,
+    pub last_run_at: Option<String>,
+    pub last_build_id: Option<String>,
+    pub last_error: Option<String>,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+impl From<BuildSchedule> for BuildScheduleResponse {
+    fn from(schedule: BuildSchedule) -> Self {
+        Self {
+            id: schedule.id.to_string(),
+            repository_id: schedule.repository_id.to_string(),
+            cron_expression: schedule.cron_expression,
+            branch: schedule.branch,
+            workflow_name: schedule.workflow_name,
+            timezone: schedule.timezone,
+            skip_if_built: schedule.skip_if_built,
+            is_active: schedule.is_active,
+            next_run_at: schedule.next_run_at.map(|t| t.to_rfc3339()),
+            last_run_at: schedule.last_run_at.map(|t| t.to_rfc3339()),
+            last_build_id: schedule.last_build_id.map(|id| id.to_string()),
+            last_error: schedule.last_error,
+            created_at: schedule.created_at.to_rfc3339(),
+            updated_at: schedule.updated_at.to_rfc3339(),
+        }
+    }
+}
+
+/// Validates a schedule's branch name.
+///
+/// Rejects names git would refuse and anything that could be read as an option.
+pub fn validate_schedule_branch(branch: &str) -> Result<(), String> {
+    let valid = !branch.is_empty()
+        && !branch.starts_with(['-', '/'])
+        && !branch.ends_with(['/', '.'])
+        && !branch.contains("..")
+        && !branch.contains("//")
+        && !branch.ends_with(".lock")
+        && !branch
+            .chars()
+            .any(|c| c.is_ascii_control() || c.is_whitespace() || "~^:?*[\\".contains(c));
+    if valid {
+        Ok(())
+    } else {
+        Err(format!("Invalid branch name '{}'", branch))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate_schedule_branch() {
+        assert!(validate_schedule_branch("main").is_ok());
+        assert!(validate_schedule_branch("release/2.0").is_ok());
+        assert!(validate_schedule_branch("").is_err());
+        assert!(validate_schedule_branch("--upload-pack=x").is_err());
+        assert!(validate_schedule_branch("feature branch").is_err());
+        assert!(validate_schedule_branch("a..b").is_err());
+    }
+}
@@ -0,0 +1,101 @@
+//! Slack notification models.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+use ulid::Ulid;
+
+use super::{NotificationEvent, RepositoryId};
+
+/// Unique identifier for a Slack notification config.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct SlackConfigId(pub Ulid);
+
+impl SlackConfigId {
+    /// Creates a new random Slack config ID.
+    pub fn new() -> Self {
+        Self(Ulid::new())
+    }
+
+    /// Creates a Slack config ID from a string.
+    pub fn from_string(s: &str) -> Result<Self, ulid::DecodeError> {
+        Ok(Self(Ulid::from_string(s)?))
+    }
+}
+
+impl Default for SlackConfigId {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl std::fmt::Display for SlackConfigId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+/// Slack notification config for a repository (internal model with encrypted URL).
+#[derive(Debug, Clone)]
+pub struct SlackConfig {
+    pub id: SlackConfigId,
+    pub repository_id: RepositoryId,
+    /// Incoming-webhook URL (AES-256-GCM with AAD).
+    pub webhook_url_encrypted: Vec<u8>,
+    pub webhook_url_nonce: Vec<u8>,
+    pub events: Vec<NotificationEvent>,
+    pub is_active: bool,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl SlackConfig {
+    /// Returns true if a message should be posted for the given event.
+    pub fn subscribes_to(&self, event: NotificationEvent) -> bool {
+        self.is_active && self.events.contains(&event)
+    }
+}
+
+/// Request to create or replace a repository's Slack config.
+///
+/// `webhook_url` may be omitted when updating an existing config.
+#[derive(Debug, Clone, Deserialize, TS)]
+#[ts(export, export_to = "../../../types/")]
+pub struct SetSlackConfigRequest {
+    #[serde(default)]
+    #[ts(optional)]
+    pub webhook_url: Option<String>,
+    pub events: Vec<NotificationEvent>,
+    #[serde(default = "default_true")]
+    pub is_active: bool,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+/// Response for a Slack config. The webhook URL is never returned.
+#[derive(Debug, Clone, Serialize, TS)]
+#[ts(export, export_to = "../../../types/")]
+pub struct SlackConfigResponse {
+    pub id: String,
+    pub repository_id: String,
+    pub events: Vec<NotificationEvent>,
+    pub is_active: bool,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+impl From<SlackConfig> for SlackConfigResponse {
+    fn from(config: SlackConfig) -> Self {
+        Self {
+            id: config.id.to_string(),
+            repository_id: config.repository_id.to_string(),
+            events: config.events,
+            is_active: config.is_active,
+            created_at: config.created_at.to_rfc3339(),
+            updated_at: config.updated_at.to_rfc3339(),
+        }
+    }
+}
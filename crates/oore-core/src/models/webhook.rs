@@ -47,6 +47,8 @@ impl From<Ulid> for WebhookEventId {
 #[serde(rename_all = "snake_case")]
 pub enum WebhookEventType {
     Push,
+    /// Push of a tag (`refs/tags/...`).
+    Tag,
     PullRequest,
     MergeRequest,
     /// GitHub App installation events (created, deleted, etc.)
@@ -59,6 +61,7 @@ impl WebhookEventType {
     pub fn as_str(&self) -> &'static str {
         match self {
             WebhookEventType::Push => "push",
+            WebhookEventType::Tag => "tag",
             WebhookEventType::PullRequest => "pull_request",
             WebhookEventType::MergeRequest => "merge_request",
             WebhookEventType::Installation => "installation",
@@ -79,6 +82,7 @@ impl std::str::FromStr for WebhookEventType {
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         match s.to_lowercase().as_str() {
             "push" => Ok(WebhookEventType::Push),
+            "tag" => Ok(WebhookEventType::Tag),
             "pull_request" => Ok(WebhookEventType::PullRequest),
             "merge_request" => Ok(WebhookEventType::MergeRequest),
             "installation" => Ok(WebhookEventType::Installation),
@@ -139,6 +143,7 @@ pub struct ParsedWebhookEvent {
     pub repository_owner: String,
     pub repository_name: String,
     pub commit_sha: String,
+    /// Branch name, or the tag name for tag pushes.
     pub branch: String,
     /// GitHub's numeric repository ID.
     pub github_repository_id: Option<i64>,
@@ -150,6 +155,20 @@ pub struct ParsedWebhookEvent {
     pub pull_request_number: Option<i64>,
     /// PR/MR action (opened, synchronize, closed, etc.).
     pub action: Option<String>,
+    /// Files changed by a push, when the payload lists all of them.
+    pub changed_files: Option<Vec<String>>,
+    /// Commit or ref to diff against when `changed_files` is unknown: the
+    /// previous head for pushes, the target branch for PRs/MRs.
+    pub diff_base: Option<String>,
+    /// Message of the head commit, when the payload includes it. GitHub pull
+    /// request payloads don't.
+    pub commit_message: Option<String>,
+    /// Whether the PR/MR is a draft.
+    pub draft: bool,
+    /// PR/MR labels.
+    pub labels: Vec<String>,
+    /// Label added by a `labeled` PR action or a GitLab MR `update`.
+    pub added_label: Option<String>,
 }
 
 /// Parsed installation event from GitHub.
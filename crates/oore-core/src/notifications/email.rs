@@ -0,0 +1,672 @@
+//! Email notifications: build failure and recovery mail, and the daily digest.
+//!
+//! Every message has a plain-text and an HTML part. Delivery goes through an
+//! [`EmailTransport`], so tests and development setups can swap the SMTP
+//! server for a local sink.
+
+use std::collections::BTreeMap;
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use lettre::message::header::{ContentTransferEncoding, ContentType};
+use lettre::message::{Mailbox, MultiPart, SinglePart};
+use lettre::Message;
+
+use super::smtp::SmtpConfig;
+use super::{format_duration, format_duration_secs, FailedStep};
+use crate::error::{OoreError, Result};
+use crate::models::{
+    parse_email_recipients, validate_email_address, Build, BuildStatus, Repository, RepositoryId,
+};
+
+/// Hour of day (UTC) the digest is sent when `OORE_EMAIL_DIGEST_HOUR` is unset.
+pub const DEFAULT_DIGEST_HOUR_UTC: u32 = 8;
+
+/// A rendered email ready to send.
+#[derive(Debug, Clone)]
+pub struct Email {
+    pub from: String,
+    pub to: Vec<String>,
+    pub subject: String,
+    pub text: String,
+    pub html: String,
+}
+
+/// Subject and bodies produced by a template.
+#[derive(Debug, Clone)]
+pub struct EmailContent {
+    pub subject: String,
+    pub text: String,
+    pub html: String,
+}
+
+impl EmailContent {
+    /// Addresses the content, producing a sendable email.
+    pub fn into_email(self, from: &str, to: &[String]) -> Email {
+        Email {
+            from: from.to_string(),
+            to: to.to_vec(),
+            subject: self.subject,
+            text: self.text,
+            html: self.html,
+        }
+    }
+}
+
+/// Delivers emails.
+#[async_trait]
+pub trait EmailTransport: Send + Sync {
+    async fn send(&self, email: &Email) -> Result<()>;
+}
+
+/// Resolved email settings: where to send from and how.
+#[derive(Debug, Clone)]
+pub struct EmailSettings {
+    pub smtp: SmtpConfig,
+    pub from: String,
+    /// Recipients of the daily digest. Empty disables the digest.
+    pub digest_recipients: Vec<String>,
+}
+
+impl EmailSettings {
+    /// Loads settings from the environment.
+    ///
+    /// Uses `OORE_SMTP_*` for the connection, `OORE_SMTP_FROM` for the
+    /// sender and `OORE_EMAIL_DIGEST_RECIPIENTS` (comma-separated) for the
+    /// digest. Returns `None` when SMTP is not configured.
+    pub fn from_env() -> Result<Option<Self>> {
+        let Some(smtp) = SmtpConfig::from_env()? else {
+            return Ok(None);
+        };
+
+        let from = std::env::var("OORE_SMTP_FROM").unwrap_or_default();
+        validate_email_address(from.trim())
+            .map_err(|e| OoreError::Configuration(format!("OORE_SMTP_FROM: {}", e)))?;
+
+        let digest_recipients = parse_email_recipients(
+            &std::env::var("OORE_EMAIL_DIGEST_RECIPIENTS").unwrap_or_default(),
+        );
+        for r in &digest_recipients {
+            validate_email_address(r).map_err(|e| {
+                OoreError::Configuration(format!("OORE_EMAIL_DIGEST_RECIPIENTS: {}", e))
+            })?;
+        }
+
+        Ok(Some(Self {
+            smtp,
+            from: from.trim().to_string(),
+            digest_recipients,
+        }))
+    }
+}
+
+/// Reads the digest hour (0-23, UTC) from `OORE_EMAIL_DIGEST_HOUR`.
+pub fn digest_hour_from_env() -> u32 {
+    std::env::var("OORE_EMAIL_DIGEST_HOUR")
+        .ok()
+        .and_then(|v| v.trim().parse().ok())
+        .filter(|h| *h < 24)
+        .unwrap_or(DEFAULT_DIGEST_HOUR_UTC)
+}
+
+impl Email {
+    /// Builds the `multipart/alternative` MIME message.
+    pub fn to_message(&self, date: DateTime<Utc>) -> Result<Message> {
+        let domain = self
+            .from
+            .rsplit_once('@')
+            .map(|(_, d)| d)
+            .unwrap_or("localhost");
+
+        let mut builder = Message::builder()
+            .from(parse_mailbox(&self.from)?)
+            // Line breaks are dropped so the subject can't inject headers
+            .subject(self.subject.replace(['\r', '\n'], ""))
+            .date(date.into())
+            .message_id(Some(format!("<{}@{}>", ulid::Ulid::new(), domain)));
+        for to in &self.to {
+            builder = builder.to(parse_mailbox(to)?);
+        }
+
+        let part = |content_type: ContentType, body: &str| {
+            SinglePart::builder()
+                .header(content_type)
+                .header(ContentTransferEncoding::Base64)
+                .body(body.to_string())
+        };
+        builder
+            .multipart(
+                MultiPart::alternative()
+                    .singlepart(part(ContentType::TEXT_PLAIN, &self.text))
+                    .singlepart(part(ContentType::TEXT_HTML, &self.html)),
+            )
+            .map_err(|e| OoreError::Notification(format!("Failed to build email: {}", e)))
+    }
+}
+
+fn parse_mailbox(address: &str) -> Result<Mailbox> {
+    address
+        .parse()
+        .map_err(|e| OoreError::Notification(format!("Invalid email address {}: {}", address, e)))
+}
+
+/// Why a build email is being sent.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BuildEmailKind {
+    Failure,
+    /// The build succeeded and the previous one on its branch failed.
+    Recovery,
+}
+
+/// Everything needed to render a build email.
+#[derive(Debug, Clone)]
+pub struct BuildEmailSummary<'a> {
+    pub kind: BuildEmailKind,
+    pub build: &'a Build,
+    pub repository: &'a Repository,
+    pub failed_step: Option<FailedStep>,
+    pub base_url: &'a str,
+}
+
+/// Renders a failure or recovery email.
+pub fn render_build_email(summary: &BuildEmailSummary<'_>) -> EmailContent {
+    let build = summary.build;
+    let repo_name = format!(
+        "{}/{}",
+        summary.repository.owner, summary.repository.repo_name
+    );
+    let short_sha: String = build.commit_sha.chars().take(7).collect();
+    let build_url = build_url(summary.base_url, build);
+    let verb = match summary.kind {
+        BuildEmailKind::Failure => "failed",
+        BuildEmailKind::Recovery => "fixed",
+    };
+
+    let subject = format!(
+        "[{}] Build {} on {} ({})",
+        repo_name, verb, build.branch, short_sha
+    );
+
+    let mut details = vec![
+        ("Repository", repo_name.clone()),
+        ("Branch", build.branch.clone()),
+        ("Commit", short_sha),
+        (
+            "Workflow",
+            build
+                .workflow_name
+                .clone()
+                .unwrap_or_else(|| "-".to_string()),
+        ),
+    ];
+    if let Some(duration) = format_duration(build) {
+        details.push(("Duration", duration));
+    }
+
+    let headline = match summary.kind {
+        BuildEmailKind::Failure => format!("Build failed for {}", repo_name),
+        BuildEmailKind::Recovery => format!("Build is green again for {}", repo_name),
+    };
+
+    // Plain text
+    let mut text = format!("{}\n\n", headline);
+    for (label, value) in &details {
+        text.push_str(&format!("{:<11} {}\n", format!("{}:", label), value));
+    }
+    if summary.kind == BuildEmailKind::Failure {
+        if let Some(step) = &summary.failed_step {
+            text.push_str(&format!("\nFailed step: {}\n", step.name));
+            if !step.log_tail.trim().is_empty() {
+                text.push_str(&format!("\n{}\n", step.log_tail.trim_end()));
+            }
+        } else if let Some(error) = &build.error_message {
+            text.push_str(&format!("\nError: {}\n", error));
+        }
+    }
+    text.push_str(&format!("\nView build: {}\n", build_url));
+
+    // HTML
+    let color = match summary.kind {
+        BuildEmailKind::Failure => "#d1242f",
+        BuildEmailKind::Recovery => "#1a7f37",
+    };
+    let mut html = format!(
+        "<!DOCTYPE html>\n<html><body style=\"font-family: -apple-system, Segoe UI, Helvetica, Arial, sans-serif; color: #1f2328;\">\n<h2 style=\"color: {};\">{}</h2>\n<table cellpadding=\"4\">\n",
+        color,
+        escape_html(&headline)
+    );
+    for (label, value) in &details {
+        html.push_str(&format!(
+            "<tr><td><strong>{}</strong></td><td>{}</td></tr>\n",
+            label,
+            escape_html(value)
+        ));
+    }
+    html.push_str("</table>\n");
+    if summary.kind == BuildEmailKind::Failure {
+        if let Some(step) = &summary.failed_step {
+            html.push_str(&format!(
+                "<p><strong>Failed step:</strong> {}</p>\n",
+                escape_html(&step.name)
+            ));
+            if !step.log_tail.trim().is_empty() {
+                html.push_str(&format!(
+                    "<pre style=\"background: #f6f8fa; padding: 12px; overflow-x: auto;\">{}</pre>\n",
+                    escape_html(step.log_tail.trim_end())
+                ));
+            }
+        } else if let Some(error) = &build.error_message {
+            html.push_str(&format!(
+                "<p><strong>Error:</strong> {}</p>\n",
+                escape_html(error)
+            ));
+        }
+    }
+    html.push_str(&format!(
+        "<p><a href=\"{}\">View build</a></p>\n</body></html>\n",
+        escape_html(&build_url)
+    ));
+
+    EmailContent {
+        subject,
+        text,
+        html,
+    }
+}
+
+/// Build counts for one repository over the digest period.
+#[derive(Debug, Clone)]
+pub struct RepositoryDigest {
+    pub repository_id: RepositoryId,
+    /// `owner/repo_name`
+    pub name: String,
+    pub total: usize,
+    pub succeeded: usize,
+    pub failed: usize,
+    pub cancelled: usize,
+    /// Mean duration of builds with both start and finish times.
+    pub average_duration_secs: Option<i64>,
+    /// Most recent failed build, if any.
+    pub last_failure: Option<Build>,
+}
+
+/// Groups finished builds by repository, sorted by repository name.
+///
+/// Builds whose repository is not in `repositories` are ignored.
+pub fn summarize_builds(builds: &[Build], repositories: &[Repository]) -> Vec<RepositoryDigest> {
+    let mut by_repo: BTreeMap<String, RepositoryDigest> = BTreeMap::new();
+    let mut durations: BTreeMap<String, Vec<i64>> = BTreeMap::new();
+
+    for build in builds {
+        let Some(repository) = repositories.iter().find(|r| r.id == build.repository_id) else {
+            continue;
+        };
+        let name = format!("{}/{}", repository.owner, repository.repo_name);
+        let digest = by_repo
+            .entry(name.clone())
+            .or_insert_with(|| RepositoryDigest {
+                repository_id: repository.id.clone(),
+                name: name.clone(),
+                total: 0,
+                succeeded: 0,
+                failed: 0,
+                cancelled: 0,
+                average_duration_secs: None,
+                last_failure: None,
+            });
+
+        digest.total += 1;
+        match build.status {
+            BuildStatus::Success => digest.succeeded += 1,
+            BuildStatus::Failure => {
+                digest.failed += 1;
+                if digest
+                    .last_failure
+                    .as_ref()
+                    .is_none_or(|b| b.finished_at <= build.finished_at)
+                {
+                    digest.last_failure = Some(build.clone());
+                }
+            }
+            BuildStatus::Cancelled => digest.cancelled += 1,
+            BuildStatus::Pending | BuildStatus::Running => {}
+        }
+
+        if let (Some(started), Some(finished)) = (build.started_at, build.finished_at) {
+            durations
+                .entry(name)
+                .or_default()
+                .push((finished - started).num_seconds().max(0));
+        }
+    }
+
+    for (name, secs) in durations {
+        if let Some(digest) = by_repo.get_mut(&name) {
+            digest.average_duration_secs = Some(secs.iter().sum::<i64>() / secs.len() as i64);
+        }
+    }
+
+    by_repo.into_values().collect()
+}
+
+/// Renders the daily digest for the period `[since, until)`.
+pub fn render_digest(
+    digests: &[RepositoryDigest],
+    since: DateTime<Utc>,
+    until: DateTime<Utc>,
+    base_url: &str,
+) -> EmailContent {
+    let total: usize = digests.iter().map(|d| d.total).sum();
+    let failed: usize = digests.iter().map(|d| d.failed).sum();
+    let period = format!(
+        "{} – {} UTC",
+        since.format("%Y-%m-%d %H:%M"),
+        until.format("%Y-%m-%d %H:%M")
+    );
+
+    let subject = format!(
+        "Oore daily digest: {} build{}, {} failed",
+        total,
+        if total == 1 { "" } else { "s" },
+        failed
+    );
+
+    // Plain text
+    let mut text = format!("Builds finished {}\n\n", period);
+    if digests.is_empty() {
+        text.push_str("No builds finished in this period.\n");
+    }
+    for d in digests {
+        text.push_str(&format!(
+            "{}\n  {} total, {} succeeded, {} failed, {} cancelled",
+            d.name, d.total, d.succeeded, d.failed, d.cancelled
+        ));
+        if let Some(avg) = d.average_duration_secs {
+            text.push_str(&format!(", avg {}", format_duration_secs(avg)));
+        }
+        text.push('\n');
+        if let Some(build) = &d.last_failure {
+            text.push_str(&format!(
+                "  Last failure: {} on {}\n",
+                build_url(base_url, build),
+                build.branch
+            ));
+        }
+        text.push('\n');
+    }
+
+    // HTML
+    let mut html = format!(
+        "<!DOCTYPE html>\n<html><body style=\"font-family: -apple-system, Segoe UI, Helvetica, Arial, sans-serif; color: #1f2328;\">\n<h2>Daily build digest</h2>\n<p>Builds finished {}</p>\n",
+        escape_html(&period)
+    );
+    if digests.is_empty() {
+        html.push_str("<p>No builds finished in this period.</p>\n");
+    } else {
+        html.push_str(
+            "<table cellpadding=\"6\" style=\"border-collapse: collapse;\">\n<tr style=\"text-align: left; border-bottom: 1px solid #d0d7de;\"><th>Repository</th><th>Total</th><th>Succeeded</th><th>Failed</th><th>Cancelled</th><th>Avg duration</th><th>Last failure</th></tr>\n",
+        );
+        for d in digests {
+            let last_failure = d
+                .last_failure
+                .as_ref()
+                .map(|b| {
+                    format!(
+                        "<a href=\"{}\">{}</a>",
+                        escape_html(&build_url(base_url, b)),
+                        escape_html(&b.branch)
+                    )
+                })
+                .unwrap_or_else(|| "-".to_string());
+            html.push_str(&format!(
+                "<tr><td>{}</td><td>{}</td><td>{}</td><td style=\"color: {};\">{}</td><td>{}</td><td>{}</td><td>{}</td></tr>\n",
+                escape_html(&d.name),
+                d.total,
+                d.succeeded,
+                if d.failed > 0 { "#d1242f" } else { "inherit" },
+                d.failed,
+                d.cancelled,
+                d.average_duration_secs
+                    .map(format_duration_secs)
+                    .unwrap_or_else(|| "-".to_string()),
+                last_failure
+            ));
+        }
+        html.push_str("</table>\n");
+    }
+    html.push_str("</body></html>\n");
+
+    EmailContent {
+        subject,
+        text,
+        html,
+    }
+}
+
+fn build_url(base_url: &str, build: &Build) -> String {
+    format!("{}/builds/{}", base_url.trim_end_matches('/'), build.id)
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&#39;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{GitProvider, TriggerType};
+    use crate::notifications::smtp::tests::{sink_config, smtp_sink};
+    use crate::notifications::smtp::SmtpTransport;
+    use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
+    use chrono::Duration;
+
+    fn repository(owner: &str, name: &str) -> Repository {
+        Repository::new(
+            name.to_string(),
+            GitProvider::GitHub,
+            owner.to_string(),
+            name.to_string(),
+            format!("https://github.com/{}/{}.git", owner, name),
+        )
+    }
+
+    fn finished_build(repository: &Repository, status: BuildStatus, secs: i64) -> Build {
+        let mut build = Build::new(
+            repository.id.clone(),
+            None,
+            "0123456789abcdef0123456789abcdef01234567".to_string(),
+            "main".to_string(),
+            TriggerType::Push,
+        );
+        let started = Utc::now() - Duration::hours(1);
+        build.status = status;
+        build.started_at = Some(started);
+        build.finished_at = Some(started + Duration::seconds(secs));
+        build
+    }
+
+    /// Decodes the base64 body parts of a MIME message.
+    fn decode_parts(mime: &str) -> Vec<String> {
+        mime.split("Content-Transfer-Encoding: base64\r\n\r\n")
+            .skip(1)
+            .map(|part| {
+                let encoded: String = part.lines().take_while(|l| !l.starts_with("--")).collect();
+                String::from_utf8(BASE64.decode(encoded).unwrap()).unwrap()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_render_failure_email() {
+        let repo = repository("acme", "app");
+        let mut build = finished_build(&repo, BuildStatus::Failure, 125);
+        build.branch = "fix/<script>".to_string();
+        build.workflow_name = Some("android".to_string());
+
+        let content = render_build_email(&BuildEmailSummary {
+            kind: BuildEmailKind::Failure,
+            build: &build,
+            repository: &repo,
+            failed_step: Some(FailedStep {
+                name: "Run tests".to_string(),
+                log_tail: "Expected <true> but was <false>".to_string(),
+            }),
+            base_url: "https://ci.example.com/",
+        });
+
+        assert_eq!(
+            content.subject,
+            "[acme/app] Build failed on fix/<script> (0123456)"
+        );
+        assert!(content.text.contains("Failed step: Run tests"));
+        assert!(content.text.contains("Expected <true> but was <false>"));
+        assert!(content.text.contains("2m 5s"));
+        assert!(content
+            .text
+            .contains(&format!("https://ci.example.com/builds/{}", build.id)));
+
+        assert!(content.html.contains("fix/&lt;script&gt;"));
+        assert!(!content.html.contains("<script>"));
+        assert!(content.html.contains("Expected &lt;true&gt;"));
+    }
+
+    #[test]
+    fn test_render_recovery_email_omits_failure_details() {
+        let repo = repository("acme", "app");
+        let build = finished_build(&repo, BuildStatus::Success, 30);
+
+        let content = render_build_email(&BuildEmailSummary {
+            kind: BuildEmailKind::Recovery,
+            build: &build,
+            repository: &repo,
+            failed_step: None,
+            base_url: "https://ci.example.com",
+        });
+
+        assert!(content.subject.contains("Build fixed on main"));
+        assert!(content.text.contains("green again"));
+        assert!(!content.text.contains("Failed step"));
+    }
+
+    #[test]
+    fn test_summarize_builds() {
+        let app = repository("acme", "app");
+        let web = repository("acme", "web");
+        let builds = vec![
+            finished_build(&web, BuildStatus::Success, 60),
+            finished_build(&app, BuildStatus::Success, 100),
+            finished_build(&app, BuildStatus::Failure, 200),
+            finished_build(&app, BuildStatus::Cancelled, 0),
+            // Unknown repository
+            finished_build(&repository("other", "gone"), BuildStatus::Failure, 10),
+        ];
+
+        let digests = summarize_builds(&builds, &[app.clone(), web.clone()]);
+
+        assert_eq!(digests.len(), 2);
+        assert_eq!(digests[0].name, "acme/app");
+        assert_eq!(
+            (
+                digests[0].total,
+                digests[0].succeeded,
+                digests[0].failed,
+                digests[0].cancelled
+            ),
+            (3, 1, 1, 1)
+        );
+        assert_eq!(digests[0].average_duration_secs, Some(100));
+        assert_eq!(digests[0].last_failure.as_ref().unwrap().id, builds[2].id);
+        assert_eq!(digests[1].name, "acme/web");
+        assert!(digests[1].last_failure.is_none());
+    }
+
+    #[test]
+    fn test_render_digest() {
+        let app = repository("acme", "app");
+        let builds = vec![
+            finished_build(&app, BuildStatus::Success, 100),
+            finished_build(&app, BuildStatus::Failure, 200),
+        ];
+        let digests = summarize_builds(&builds, std::slice::from_ref(&app));
+        let until = Utc::now();
+
+        let content = render_digest(
+            &digests,
+            until - Duration::days(1),
+            until,
+            "https://ci.example.com",
+        );
+
+        assert_eq!(content.subject, "Oore daily digest: 2 builds, 1 failed");
+        assert!(content.text.contains("acme/app"));
+        assert!(content
+            .text
+            .contains("2 total, 1 succeeded, 1 failed, 0 cancelled, avg 2m 30s"));
+        assert!(content.html.contains("<td>acme/app</td>"));
+
+        let empty = render_digest(
+            &[],
+            until - Duration::days(1),
+            until,
+            "https://ci.example.com",
+        );
+        assert!(empty.text.contains("No builds finished"));
+    }
+
+    #[test]
+    fn test_to_message() {
+        let email = Email {
+            from: "ci@example.com".to_string(),
+            to: vec!["a@example.com".to_string(), "b@example.com".to_string()],
+            subject: "Build fällt\r\nBcc: x@evil.com".to_string(),
+            text: "plain body".to_string(),
+            html: "<p>html body</p>".to_string(),
+        };
+
+        let mime = String::from_utf8(email.to_message(Utc::now()).unwrap().formatted()).unwrap();
+
+        assert!(mime.contains("From: ci@example.com\r\n"));
+        assert!(mime.contains("To: a@example.com, b@example.com\r\n"));
+        assert!(mime.contains("Subject: Build =?utf-8?b?"));
+        assert!(!mime.contains("\r\nBcc:"));
+        assert!(mime.contains("Message-ID: <"));
+        assert!(mime.contains("@example.com>\r\n"));
+        assert_eq!(decode_parts(&mime), vec!["plain body", "<p>html body</p>"]);
+    }
+
+    #[tokio::test]
+    async fn test_digest_through_smtp_sink() {
+        let (port, sink) = smtp_sink(false).await;
+        let app = repository("acme", "app");
+        let digests = summarize_builds(
+            &[finished_build(&app, BuildStatus::Failure, 42)],
+            std::slice::from_ref(&app),
+        );
+        let until = Utc::now();
+        let email = render_digest(
+            &digests,
+            until - Duration::days(1),
+            until,
+            "https://ci.example.com",
+        )
+        .into_email("ci@example.com", &["team@example.com".to_string()]);
+
+        let transport: Box<dyn EmailTransport> = Box::new(SmtpTransport::new(sink_config(port)));
+        transport.send(&email).await.unwrap();
+
+        let received = sink.await.unwrap();
+        assert!(received
+            .commands
+            .contains(&"RCPT TO:<team@example.com>".to_string()));
+        assert!(received
+            .data
+            .contains("Subject: Oore daily digest: 1 build, 1 failed"));
+        let parts = decode_parts(&received.data);
+        assert!(parts[0].contains("acme/app"));
+        assert!(parts[1].contains("<td>acme/app</td>"));
+    }
+}
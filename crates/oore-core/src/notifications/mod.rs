@@ -0,0 +1,52 @@
+//! Build notifications sent to external services.
+
+pub mod email;
+pub mod slack;
+pub mod smtp;
+pub mod webhook;
+
+use crate::models::Build;
+
+/// Number of log lines from the failed step included in failure notifications.
+pub const LOG_TAIL_LINES: usize = 20;
+
+/// The step that failed a build, with the end of its output.
+#[derive(Debug, Clone)]
+pub struct FailedStep {
+    pub name: String,
+    pub log_tail: String,
+}
+
+/// Returns the last `n` lines of `text`.
+pub fn tail_lines(text: &str, n: usize) -> String {
+    let lines: Vec<&str> = text.trim_end().lines().collect();
+    lines[lines.len().saturating_sub(n)..].join("\n")
+}
+
+/// Formats a finished build's wall-clock duration (e.g. `2m 5s`).
+pub fn format_duration(build: &Build) -> Option<String> {
+    let started = build.started_at?;
+    let finished = build.finished_at?;
+    Some(format_duration_secs((finished - started).num_seconds()))
+}
+
+/// Formats a number of seconds as a short human-readable duration.
+pub fn format_duration_secs(secs: i64) -> String {
+    match secs.max(0) {
+        s if s < 60 => format!("{}s", s),
+        s if s < 3600 => format!("{}m {}s", s / 60, s % 60),
+        s => format!("{}h {}m", s / 3600, (s % 3600) / 60),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tail_lines() {
+        assert_eq!(tail_lines("a\nb\nc\nd\n", 2), "c\nd");
+        assert_eq!(tail_lines("a\nb", 10), "a\nb");
+        assert_eq!(tail_lines("", 5), "");
+    }
+}
@@ -0,0 +1,359 @@
+//! Slack incoming-webhook messages.
+//!
+//! Messages use Block Kit with a plain `text` fallback, which Slack shows in
+//! push notifications and clients that cannot render blocks.
+
+use serde_json::{json, Value};
+
+use super::{format_duration, FailedStep};
+use crate::error::{OoreError, Result};
+use crate::models::{Build, NotificationEvent, Repository};
+use crate::oauth::{create_http_client_with_pinning, validate_outbound_url, SsrfConfig};
+
+/// Longest log excerpt included in a message. Slack rejects section text
+/// over 3000 characters, so this leaves room for the code fence.
+const MAX_LOG_TAIL_CHARS: usize = 2500;
+
+/// Longest response body excerpt kept when Slack rejects a message.
+const MAX_ERROR_BODY_LEN: usize = 256;
+
+/// Everything needed to render a Slack message for a build event.
+#[derive(Debug, Clone)]
+pub struct SlackBuildSummary<'a> {
+    pub event: NotificationEvent,
+    pub build: &'a Build,
+    pub repository: &'a Repository,
+    /// Set for failed builds when a non-ignored step failed.
+    pub failed_step: Option<FailedStep>,
+    pub base_url: &'a str,
+}
+
+/// Builds the Block Kit message for a build event.
+pub fn build_slack_message(summary: &SlackBuildSummary<'_>) -> Value {
+    let build = summary.build;
+    let repository = summary.repository;
+    let build_url = format!(
+        "{}/builds/{}",
+        summary.base_url.trim_end_matches('/'),
+        build.id
+    );
+    let repo_name = format!("{}/{}", repository.owner, repository.repo_name);
+    let short_sha: String = build.commit_sha.chars().take(7).collect();
+
+    let (emoji, verb) = match summary.event {
+        NotificationEvent::BuildStarted => (":hourglass_flowing_sand:", "started"),
+        NotificationEvent::BuildSucceeded => (":white_check_mark:", "succeeded"),
+        NotificationEvent::BuildFailed => (":x:", "failed"),
+        NotificationEvent::BuildCancelled => (":no_entry_sign:", "was cancelled"),
+    };
+    let title = format!("{} Build {} for {}", emoji, verb, repo_name);
+
+    let mut fields = vec![
+        mrkdwn(format!("*Repository*\n{}", escape(&repo_name))),
+        mrkdwn(format!("*Branch*\n`{}`", escape(&build.branch))),
+        mrkdwn(format!("*Commit*\n`{}`", escape(&short_sha))),
+        mrkdwn(format!(
+            "*Workflow*\n{}",
+            escape(build.workflow_name.as_deref().unwrap_or("-"))
+        )),
+    ];
+    if let Some(duration) = format_duration(build) {
+        fields.push(mrkdwn(format!("*Duration*\n{}", duration)));
+    }
+
+    let mut blocks = vec![
+        json!({
+            "type": "header",
+            "text": { "type": "plain_text", "text": title, "emoji": true },
+        }),
+        json!({ "type": "section", "fields": fields }),
+    ];
+
+    if summary.event == NotificationEvent::BuildFailed {
+        if let Some(step) = &summary.failed_step {
+            let mut text = format!("*Failed step:* {}", escape(&step.name));
+            let tail = truncate_start(step.log_tail.trim_end(), MAX_LOG_TAIL_CHARS);
+            if !tail.is_empty() {
+                text.push_str(&format!("\n```{}```", escape(&tail)));
+            }
+            blocks.push(json!({ "type": "section", "text": mrkdwn(text) }));
+        } else if let Some(error) = &build.error_message {
+            let error = truncate_start(error, MAX_LOG_TAIL_CHARS);
+            blocks.push(json!({
+                "type": "section",
+                "text": mrkdwn(format!("*Error:* {}", escape(&error))),
+            }));
+        }
+    }
+
+    blocks.push(json!({
+        "type": "actions",
+        "elements": [{
+            "type": "button",
+            "text": { "type": "plain_text", "text": "View build" },
+            "url": build_url,
+        }],
+    }));
+
+    json!({
+        "text": format!("Build {} for {} on {} ({})", verb, repo_name, build.branch, short_sha),
+        "blocks": blocks,
+    })
+}
+
+/// Posts a message to an incoming-webhook URL with the given client.
+///
+/// Slack answers `200 ok` on success and a short plain-text reason
+/// (e.g. `invalid_payload`, `no_service`) otherwise.
+pub async fn post_slack_message(
+    client: &reqwest::Client,
+    url: &str,
+    message: &Value,
+) -> Result<()> {
+    let response = client
+        .post(url)
+        .header(
+            reqwest::header::USER_AGENT,
+            concat!("oore/", env!("CARGO_PKG_VERSION")),
+        )
+        .json(message)
+        .send()
+        .await
+        .map_err(|e| OoreError::Notification(format!("Slack request failed: {}", e)))?;
+
+    let status = response.status();
+    if status.is_success() {
+        return Ok(());
+    }
+
+    let body = response.text().await.unwrap_or_default();
+    let excerpt: String = body.chars().take(MAX_ERROR_BODY_LEN).collect();
+    Err(OoreError::Notification(format!(
+        "Slack returned {}: {}",
+        status,
+        excerpt.trim()
+    )))
+}
+
+/// Validates a webhook URL against `ssrf` and posts a message to it over a
+/// connection pinned to the resolved addresses.
+pub async fn send_slack_message(ssrf: &SsrfConfig, url: &str, message: &Value) -> Result<()> {
+    let validated = validate_outbound_url(url, ssrf)?;
+    let client = create_http_client_with_pinning(ssrf, &validated)?;
+    post_slack_message(&client, validated.url.as_str(), message).await
+}
+
+fn mrkdwn(text: String) -> Value {
+    json!({ "type": "mrkdwn", "text": text })
+}
+
+/// Escapes the characters Slack treats as control sequences in mrkdwn.
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+/// Keeps the last `max` characters of `text`, marking the cut.
+fn truncate_start(text: &str, max: usize) -> String {
+    let count = text.chars().count();
+    if count <= max {
+        return text.to_string();
+    }
+    let kept: String = text.chars().skip(count - max).collect();
+    format!("…{}", kept)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{GitProvider, TriggerType};
+    use chrono::{Duration, Utc};
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+
+    fn sample() -> (Repository, Build) {
+        let repository = Repository::new(
+            "app".to_string(),
+            GitProvider::GitHub,
+            "acme".to_string(),
+            "app".to_string(),
+            "https://github.com/acme/app.git".to_string(),
+        );
+        let mut build = Build::new(
+            repository.id.clone(),
+            None,
+            "0123456789abcdef0123456789abcdef01234567".to_string(),
+            "feature/<login>".to_string(),
+            TriggerType::Push,
+        );
+        let started = Utc::now();
+        build.started_at = Some(started);
+        build.finished_at = Some(started + Duration::seconds(125));
+        build.workflow_name = Some("ios-release".to_string());
+        (repository, build)
+    }
+
+    /// Minimal HTTP stand-in for Slack: accepts one request, returns its body.
+    async fn serve_once(
+        status_line: &'static str,
+        reply: &'static str,
+    ) -> (String, tokio::task::JoinHandle<String>) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!(
+            "http://{}/services/T000/B000/XXXX",
+            listener.local_addr().unwrap()
+        );
+
+        let handle = tokio::spawn(async move {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            let mut buf = Vec::new();
+            let mut chunk = [0u8; 4096];
+            let body_start = loop {
+                let n = socket.read(&mut chunk).await.unwrap();
+                buf.extend_from_slice(&chunk[..n]);
+                if let Some(pos) = buf.windows(4).position(|w| w == b"\r\n\r\n") {
+                    break pos + 4;
+                }
+            };
+            let headers = String::from_utf8_lossy(&buf[..body_start]).to_lowercase();
+            let content_length: usize = headers
+                .lines()
+                .find_map(|l| l.strip_prefix("content-length:"))
+                .and_then(|v| v.trim().parse().ok())
+                .unwrap_or(0);
+            while buf.len() < body_start + content_length {
+                let n = socket.read(&mut chunk).await.unwrap();
+                buf.extend_from_slice(&chunk[..n]);
+            }
+
+            let response = format!(
+                "HTTP/1.1 {}\r\ncontent-length: {}\r\nconnection: close\r\n\r\n{}",
+                status_line,
+                reply.len(),
+                reply
+            );
+            socket.write_all(response.as_bytes()).await.unwrap();
+            String::from_utf8_lossy(&buf[body_start..]).to_string()
+        });
+
+        (url, handle)
+    }
+
+    #[test]
+    fn test_build_slack_message_failure() {
+        let (repository, mut build) = sample();
+        build.status = crate::models::BuildStatus::Failure;
+        let summary = SlackBuildSummary {
+            event: NotificationEvent::BuildFailed,
+            build: &build,
+            repository: &repository,
+            failed_step: Some(FailedStep {
+                name: "Run tests".to_string(),
+                log_tail: "error: 2 tests failed\n".to_string(),
+            }),
+            base_url: "https://ci.example.com/",
+        };
+
+        let message = build_slack_message(&summary);
+        let text = message.to_string();
+
+        assert!(message["text"].as_str().unwrap().contains("failed"));
+        assert_eq!(message["blocks"][0]["type"], "header");
+        assert!(text.contains("acme/app"));
+        assert!(text.contains("`0123456`"));
+        assert!(text.contains("ios-release"));
+        assert!(text.contains("2m 5s"));
+        assert!(text.contains("feature/&lt;login&gt;"));
+        assert!(text.contains("*Failed step:* Run tests"));
+        assert!(text.contains("error: 2 tests failed"));
+
+        let actions = message["blocks"].as_array().unwrap().last().unwrap();
+        assert_eq!(
+            actions["elements"][0]["url"],
+            format!("https://ci.example.com/builds/{}", build.id)
+        );
+    }
+
+    #[test]
+    fn test_build_slack_message_truncates_log_tail() {
+        let (repository, build) = sample();
+        let summary = SlackBuildSummary {
+            event: NotificationEvent::BuildFailed,
+            build: &build,
+            repository: &repository,
+            failed_step: Some(FailedStep {
+                name: "Build".to_string(),
+                log_tail: format!("{}END", "x".repeat(10_000)),
+            }),
+            base_url: "https://ci.example.com",
+        };
+
+        let message = build_slack_message(&summary);
+        let section = message["blocks"][2]["text"]["text"].as_str().unwrap();
+        assert!(section.chars().count() < 3000);
+        assert!(section.contains("END"));
+    }
+
+    #[test]
+    fn test_build_slack_message_success_has_no_step() {
+        let (repository, build) = sample();
+        let summary = SlackBuildSummary {
+            event: NotificationEvent::BuildSucceeded,
+            build: &build,
+            repository: &repository,
+            failed_step: None,
+            base_url: "https://ci.example.com",
+        };
+
+        let message = build_slack_message(&summary);
+        let blocks = message["blocks"].as_array().unwrap();
+        assert_eq!(blocks.len(), 3);
+        assert!(!message.to_string().contains("Failed step"));
+    }
+
+    #[tokio::test]
+    async fn test_post_slack_message() {
+        let (url, server) = serve_once("200 OK", "ok").await;
+        let (repository, build) = sample();
+        let message = build_slack_message(&SlackBuildSummary {
+            event: NotificationEvent::BuildSucceeded,
+            build: &build,
+            repository: &repository,
+            failed_step: None,
+            base_url: "https://ci.example.com",
+        });
+
+        post_slack_message(&reqwest::Client::new(), &url, &message)
+            .await
+            .unwrap();
+
+        let received: Value = serde_json::from_str(&server.await.unwrap()).unwrap();
+        assert_eq!(received, message);
+    }
+
+    #[tokio::test]
+    async fn test_post_slack_message_error() {
+        let (url, server) = serve_once("400 Bad Request", "invalid_payload").await;
+
+        let err = post_slack_message(&reqwest::Client::new(), &url, &json!({ "text": "hi" }))
+            .await
+            .unwrap_err();
+        server.await.unwrap();
+
+        assert!(matches!(err, OoreError::Notification(_)));
+        assert!(err.to_string().contains("invalid_payload"));
+    }
+
+    #[tokio::test]
+    async fn test_send_slack_message_rejects_private_url() {
+        let err = send_slack_message(
+            &SsrfConfig::default(),
+            "https://10.0.0.1/services/T000/B000/XXXX",
+            &json!({ "text": "hi" }),
+        )
+        .await
+        .unwrap_err();
+        assert!(err.to_string().contains("private"));
+    }
+}
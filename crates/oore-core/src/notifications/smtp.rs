@@ -0,0 +1,344 @@
+//! SMTP delivery through [`lettre`].
+//!
+//! Supports plain, STARTTLS and implicit-TLS connections, with one message per
+//! connection. Pointing it at a local sink (MailHog, Mailpit, or the one in the
+//! tests below) with `tls = none` is enough for development.
+
+use std::time::Duration;
+
+use async_trait::async_trait;
+use chrono::Utc;
+use lettre::transport::smtp::authentication::Credentials;
+use lettre::transport::smtp::extension::ClientId;
+use lettre::{AsyncSmtpTransport, AsyncTransport, Tokio1Executor};
+
+use super::email::{Email, EmailTransport};
+use crate::error::{OoreError, Result};
+use crate::models::{validate_email_address, SmtpTls};
+
+/// Upper bound on a whole SMTP session.
+const SMTP_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// Name sent in `EHLO`.
+const EHLO_NAME: &str = "localhost";
+
+/// SMTP connection settings.
+#[derive(Clone)]
+pub struct SmtpConfig {
+    pub host: String,
+    pub port: u16,
+    pub tls: SmtpTls,
+    pub username: Option<String>,
+    pub password: Option<String>,
+}
+
+impl std::fmt::Debug for SmtpConfig {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SmtpConfig")
+            .field("host", &self.host)
+            .field("port", &self.port)
+            .field("tls", &self.tls)
+            .field("username", &self.username)
+            .field("password", &self.password.as_ref().map(|_| "[REDACTED]"))
+            .finish()
+    }
+}
+
+impl SmtpConfig {
+    /// Loads settings from `OORE_SMTP_*` environment variables.
+    ///
+    /// Returns `None` when `OORE_SMTP_HOST` is unset.
+    pub fn from_env() -> Result<Option<Self>> {
+        let Some(host) = env_non_empty("OORE_SMTP_HOST") else {
+            return Ok(None);
+        };
+
+        let tls = match env_non_empty("OORE_SMTP_TLS") {
+            Some(v) => v.parse().map_err(OoreError::Configuration)?,
+            None => SmtpTls::StartTls,
+        };
+        let port = match env_non_empty("OORE_SMTP_PORT") {
+            Some(v) => v
+                .parse()
+                .map_err(|_| OoreError::Configuration(format!("Invalid OORE_SMTP_PORT: {}", v)))?,
+            None => tls.default_port(),
+        };
+
+        Ok(Some(Self {
+            host,
+            port,
+            tls,
+            username: env_non_empty("OORE_SMTP_USERNAME"),
+            password: env_non_empty("OORE_SMTP_PASSWORD"),
+        }))
+    }
+
+    fn credentials(&self) -> Option<(&str, &str)> {
+        match (&self.username, &self.password) {
+            (Some(u), Some(p)) => Some((u.as_str(), p.as_str())),
+            _ => None,
+        }
+    }
+
+    fn is_loopback(&self) -> bool {
+        self.host == "localhost"
+            || self
+                .host
+                .trim_matches(|c| c == '[' || c == ']')
+                .parse::<std::net::IpAddr>()
+                .is_ok_and(|ip| ip.is_loopback())
+    }
+}
+
+fn env_non_empty(name: &str) -> Option<String> {
+    std::env::var(name)
+        .ok()
+        .map(|v| v.trim().to_string())
+        .filter(|v| !v.is_empty())
+}
+
+/// Sends mail through an SMTP server, one connection per message.
+#[derive(Debug, Clone)]
+pub struct SmtpTransport {
+    config: SmtpConfig,
+}
+
+impl SmtpTransport {
+    pub fn new(config: SmtpConfig) -> Self {
+        Self { config }
+    }
+}
+
+#[async_trait]
+impl EmailTransport for SmtpTransport {
+    async fn send(&self, email: &Email) -> Result<()> {
+        validate_email_address(&email.from).map_err(OoreError::Notification)?;
+        if email.to.is_empty() {
+            return Err(OoreError::Notification(
+                "Email has no recipients".to_string(),
+            ));
+        }
+        for to in &email.to {
+            validate_email_address(to).map_err(OoreError::Notification)?;
+        }
+
+        // Never send credentials in the clear to a remote host
+        if self.config.credentials().is_some()
+            && self.config.tls == SmtpTls::None
+            && !self.config.is_loopback()
+        {
+            return Err(OoreError::Notification(
+                "SMTP credentials require STARTTLS or TLS".to_string(),
+            ));
+        }
+
+        tokio::time::timeout(SMTP_TIMEOUT, self.deliver(email))
+            .await
+            .map_err(|_| OoreError::Notification("SMTP session timed out".to_string()))?
+    }
+}
+
+impl SmtpTransport {
+    async fn deliver(&self, email: &Email) -> Result<()> {
+        let config = &self.config;
+        let smtp_error = |e: lettre::transport::smtp::Error| {
+            OoreError::Notification(format!(
+                "SMTP delivery via {}:{} failed: {}",
+                config.host, config.port, e
+            ))
+        };
+
+        let builder = match config.tls {
+            SmtpTls::None => AsyncSmtpTransport::<Tokio1Executor>::builder_dangerous(&config.host),
+            SmtpTls::StartTls => AsyncSmtpTransport::<Tokio1Executor>::starttls_relay(&config.host)
+                .map_err(smtp_error)?,
+            SmtpTls::Tls => {
+                AsyncSmtpTransport::<Tokio1Executor>::relay(&config.host).map_err(smtp_error)?
+            }
+        };
+        let mut builder = builder
+            .port(config.port)
+            .hello_name(ClientId::Domain(EHLO_NAME.to_string()))
+            .timeout(Some(SMTP_TIMEOUT));
+        if let Some((user, pass)) = config.credentials() {
+            builder = builder.credentials(Credentials::new(user.to_string(), pass.to_string()));
+        }
+
+        let message = email.to_message(Utc::now())?;
+        builder.build().send(message).await.map_err(smtp_error)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
+    use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+    use tokio::net::TcpListener;
+
+    /// What a [`smtp_sink`] received.
+    #[derive(Debug, Default)]
+    pub(crate) struct SinkReceived {
+        pub commands: Vec<String>,
+        pub data: String,
+    }
+
+    /// A local SMTP sink for tests. Accepts one session, then returns the
+    /// commands and message data it received.
+    pub(crate) async fn smtp_sink(auth: bool) -> (u16, tokio::task::JoinHandle<SinkReceived>) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+
+        let handle = tokio::spawn(async move {
+            let (socket, _) = listener.accept().await.unwrap();
+            let (read, mut write) = socket.into_split();
+            let mut reader = BufReader::new(read);
+            let mut received = SinkReceived::default();
+
+            write.write_all(b"220 sink ESMTP\r\n").await.unwrap();
+            loop {
+                let mut line = String::new();
+                if reader.read_line(&mut line).await.unwrap() == 0 {
+                    break;
+                }
+                let line = line.trim_end().to_string();
+                received.commands.push(line.clone());
+
+                let reply: &[u8] = match line.split_whitespace().next().unwrap_or("") {
+                    "EHLO" if auth => b"250-sink\r\n250-8BITMIME\r\n250 AUTH LOGIN PLAIN\r\n",
+                    "EHLO" => b"250-sink\r\n250 8BITMIME\r\n",
+                    "AUTH" => b"235 2.7.0 Authentication successful\r\n",
+                    "MAIL" | "RCPT" => b"250 OK\r\n",
+                    "DATA" => {
+                        write
+                            .write_all(b"354 End data with <CR><LF>.<CR><LF>\r\n")
+                            .await
+                            .unwrap();
+                        loop {
+                            let mut data_line = String::new();
+                            reader.read_line(&mut data_line).await.unwrap();
+                            if data_line == ".\r\n" {
+                                break;
+                            }
+                            received.data.push_str(&data_line);
+                        }
+                        b"250 OK: queued\r\n"
+                    }
+                    "QUIT" => {
+                        write.write_all(b"221 Bye\r\n").await.unwrap();
+                        break;
+                    }
+                    _ => b"502 Command not implemented\r\n",
+                };
+                write.write_all(reply).await.unwrap();
+            }
+            received
+        });
+
+        (port, handle)
+    }
+
+    pub(crate) fn sink_config(port: u16) -> SmtpConfig {
+        SmtpConfig {
+            host: "127.0.0.1".to_string(),
+            port,
+            tls: SmtpTls::None,
+            username: None,
+            password: None,
+        }
+    }
+
+    fn sample_email() -> Email {
+        Email {
+            from: "ci@example.com".to_string(),
+            to: vec![
+                "dev@example.com".to_string(),
+                "lead@example.com".to_string(),
+            ],
+            subject: "Build failed".to_string(),
+            text: "Hello\n.hidden line\n".to_string(),
+            html: "<p>Hello</p>".to_string(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_send_to_sink() {
+        let (port, sink) = smtp_sink(false).await;
+
+        SmtpTransport::new(sink_config(port))
+            .send(&sample_email())
+            .await
+            .unwrap();
+
+        let received = sink.await.unwrap();
+        assert_eq!(received.commands[0], "EHLO localhost");
+        assert!(received
+            .commands
+            .contains(&"MAIL FROM:<ci@example.com>".to_string()));
+        assert!(received
+            .commands
+            .contains(&"RCPT TO:<dev@example.com>".to_string()));
+        assert!(received
+            .commands
+            .contains(&"RCPT TO:<lead@example.com>".to_string()));
+        assert!(!received.commands.iter().any(|c| c.starts_with("AUTH")));
+        assert!(received.data.contains("Subject: Build failed\r\n"));
+        assert!(received.data.contains("multipart/alternative"));
+    }
+
+    #[tokio::test]
+    async fn test_send_authenticates_with_plain() {
+        let (port, sink) = smtp_sink(true).await;
+        let mut config = sink_config(port);
+        config.username = Some("ci".to_string());
+        config.password = Some("hunter2".to_string());
+
+        SmtpTransport::new(config)
+            .send(&sample_email())
+            .await
+            .unwrap();
+
+        let received = sink.await.unwrap();
+        let expected = format!("AUTH PLAIN {}", BASE64.encode("\0ci\0hunter2"));
+        assert!(received.commands.contains(&expected));
+    }
+
+    #[tokio::test]
+    async fn test_send_refuses_cleartext_credentials_to_remote_host() {
+        let config = SmtpConfig {
+            host: "smtp.example.com".to_string(),
+            port: 25,
+            tls: SmtpTls::None,
+            username: Some("ci".to_string()),
+            password: Some("hunter2".to_string()),
+        };
+
+        let err = SmtpTransport::new(config)
+            .send(&sample_email())
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("STARTTLS"));
+    }
+
+    #[tokio::test]
+    async fn test_send_rejects_header_injection() {
+        let mut email = sample_email();
+        email.to = vec!["dev@example.com\r\nBcc: x@evil.com".to_string()];
+
+        let err = SmtpTransport::new(sink_config(1))
+            .send(&email)
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("Invalid email address"));
+    }
+
+    #[test]
+    fn test_smtp_config_debug_hides_password() {
+        let config = SmtpConfig {
+            password: Some("hunter2".to_string()),
+            ..sink_config(25)
+        };
+        assert!(!format!("{:?}", config).contains("hunter2"));
+    }
+}
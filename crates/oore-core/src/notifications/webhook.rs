@@ -0,0 +1,260 @@
+//! Outbound webhook payloads, signing, and delivery.
+//!
+//! Each request carries an `X-Oore-Signature-256: sha256=<hex>` header with
+//! the HMAC-SHA256 of the raw body, keyed by the webhook's secret. This is
+//! the same scheme GitHub uses, so receivers can reuse existing verifiers.
+
+use chrono::{DateTime, Duration, Utc};
+use serde_json::json;
+
+use crate::crypto::hmac_sha256_hex;
+use crate::models::{Build, BuildResponse, NotificationEvent, OutboundDelivery, Repository};
+use crate::oauth::{create_http_client_with_pinning, validate_outbound_url, SsrfConfig};
+
+/// Header carrying the HMAC-SHA256 signature of the body.
+pub const SIGNATURE_HEADER: &str = "X-Oore-Signature-256";
+
+/// Header carrying the event name (e.g. `build.failed`).
+pub const EVENT_HEADER: &str = "X-Oore-Event";
+
+/// Header carrying the delivery ID, stable across retries.
+pub const DELIVERY_HEADER: &str = "X-Oore-Delivery";
+
+/// Attempts made before a delivery is marked failed.
+pub const MAX_DELIVERY_ATTEMPTS: i32 = 8;
+
+/// Delay before the first retry; doubles after every failed attempt.
+const RETRY_BASE_DELAY_SECS: i64 = 30;
+
+/// Upper bound on the delay between retries.
+const RETRY_MAX_DELAY_SECS: i64 = 60 * 60;
+
+/// Longest response body excerpt kept in the delivery history.
+const MAX_ERROR_BODY_LEN: usize = 512;
+
+/// Builds the JSON body for a build event.
+pub fn build_payload(
+    event: NotificationEvent,
+    build: &Build,
+    repository: &Repository,
+    base_url: &str,
+) -> serde_json::Value {
+    json!({
+        "event": event,
+        "timestamp": Utc::now().to_rfc3339(),
+        "build": BuildResponse::from(build.clone()),
+        "repository": {
+            "id": repository.id.to_string(),
+            "name": repository.name,
+            "provider": repository.provider.as_str(),
+            "owner": repository.owner,
+            "repo_name": repository.repo_name,
+        },
+        "url": format!("{}/builds/{}", base_url.trim_end_matches('/'), build.id),
+    })
+}
+
+/// Computes the `X-Oore-Signature-256` header value for a body.
+pub fn sign_payload(secret: &str, body: &[u8]) -> String {
+    format!("sha256={}", hmac_sha256_hex(secret.as_bytes(), body))
+}
+
+/// Returns when to retry after the given number of failed attempts,
+/// or `None` once the attempt budget is spent.
+pub fn next_retry_at(attempts: i32, now: DateTime<Utc>) -> Option<DateTime<Utc>> {
+    if attempts >= MAX_DELIVERY_ATTEMPTS {
+        return None;
+    }
+    let exponent = attempts.saturating_sub(1).clamp(0, 20) as u32;
+    let delay = RETRY_BASE_DELAY_SECS
+        .saturating_mul(1 << exponent)
+        .min(RETRY_MAX_DELAY_SECS);
+    Some(now + Duration::seconds(delay))
+}
+
+/// Result of a single delivery attempt.
+#[derive(Debug, Clone, Default)]
+pub struct DeliveryAttempt {
+    /// HTTP status returned by the receiver, if a response arrived.
+    pub response_status: Option<u16>,
+    /// Why the attempt failed, if it did.
+    pub error: Option<String>,
+}
+
+impl DeliveryAttempt {
+    /// Returns true if the receiver accepted the delivery.
+    pub fn is_success(&self) -> bool {
+        self.error.is_none()
+            && self
+                .response_status
+                .is_some_and(|s| (200..300).contains(&s))
+    }
+
+    fn failed(error: impl Into<String>) -> Self {
+        Self {
+            response_status: None,
+            error: Some(error.into()),
+        }
+    }
+}
+
+/// Sends a delivery to a webhook URL.
+///
+/// The URL is re-validated against `ssrf` on every attempt and the request
+/// is pinned to the resolved addresses, so a DNS change after the webhook
+/// was saved cannot redirect it to an internal host.
+pub async fn send_delivery(
+    ssrf: &SsrfConfig,
+    url: &str,
+    secret: &str,
+    delivery: &OutboundDelivery,
+) -> DeliveryAttempt {
+    let validated = match validate_outbound_url(url, ssrf) {
+        Ok(v) => v,
+        Err(e) => return DeliveryAttempt::failed(e.to_string()),
+    };
+
+    let client = match create_http_client_with_pinning(ssrf, &validated) {
+        Ok(c) => c,
+        Err(e) => return DeliveryAttempt::failed(e.to_string()),
+    };
+
+    let body = delivery.payload.as_bytes().to_vec();
+    let signature = sign_payload(secret, &body);
+
+    let response = client
+        .post(validated.url)
+        .header(reqwest::header::CONTENT_TYPE, "application/json")
+        .header(
+            reqwest::header::USER_AGENT,
+            concat!("oore/", env!("CARGO_PKG_VERSION")),
+        )
+        .header(EVENT_HEADER, delivery.event.as_str())
+        .header(DELIVERY_HEADER, delivery.id.to_string())
+        .header(SIGNATURE_HEADER, signature)
+        .body(body)
+        .send()
+        .await;
+
+    let response = match response {
+        Ok(r) => r,
+        Err(e) => return DeliveryAttempt::failed(format!("Request failed: {}", e)),
+    };
+
+    let status = response.status();
+    if status.is_success() {
+        return DeliveryAttempt {
+            response_status: Some(status.as_u16()),
+            error: None,
+        };
+    }
+
+    let body = response.text().await.unwrap_or_default();
+    let mut excerpt: String = body.chars().take(MAX_ERROR_BODY_LEN).collect();
+    if excerpt.len() < body.len() {
+        excerpt.push('…');
+    }
+    DeliveryAttempt {
+        response_status: Some(status.as_u16()),
+        error: Some(format!("Receiver returned {}: {}", status, excerpt.trim())),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{GitProvider, OutboundWebhookId, TriggerType};
+
+    #[test]
+    fn test_sign_payload() {
+        let body = br#"{"event":"build.failed"}"#;
+        let signature = sign_payload("topsecret", body);
+        assert_eq!(
+            signature,
+            format!("sha256={}", hmac_sha256_hex(b"topsecret", body))
+        );
+        // Receivers can verify it exactly like a GitHub webhook
+        assert!(crate::crypto::verify_github_signature(
+            "topsecret",
+            &signature,
+            body
+        ));
+    }
+
+    #[test]
+    fn test_next_retry_at_backs_off_exponentially() {
+        let now = Utc::now();
+        let delay = |attempts| next_retry_at(attempts, now).map(|t| (t - now).num_seconds());
+
+        assert_eq!(delay(1), Some(30));
+        assert_eq!(delay(2), Some(60));
+        assert_eq!(delay(3), Some(120));
+        assert_eq!(delay(7), Some(1920));
+        assert_eq!(delay(MAX_DELIVERY_ATTEMPTS), None);
+    }
+
+    #[test]
+    fn test_next_retry_at_is_capped() {
+        let now = Utc::now();
+        // Never exceeds the cap, even for large attempt counts below the limit
+        for attempts in 1..MAX_DELIVERY_ATTEMPTS {
+            let delay = (next_retry_at(attempts, now).unwrap() - now).num_seconds();
+            assert!(delay <= RETRY_MAX_DELAY_SECS);
+        }
+    }
+
+    #[test]
+    fn test_build_payload() {
+        let repository = Repository::new(
+            "app".to_string(),
+            GitProvider::GitHub,
+            "acme".to_string(),
+            "app".to_string(),
+            "https://github.com/acme/app.git".to_string(),
+        );
+        let build = Build::new(
+            repository.id.clone(),
+            None,
+            "a".repeat(40),
+            "main".to_string(),
+            TriggerType::Push,
+        );
+
+        let payload = build_payload(
+            NotificationEvent::BuildFailed,
+            &build,
+            &repository,
+            "https://ci.example.com/",
+        );
+
+        assert_eq!(payload["event"], "build.failed");
+        assert_eq!(payload["build"]["id"], build.id.to_string());
+        assert_eq!(payload["repository"]["owner"], "acme");
+        assert_eq!(
+            payload["url"],
+            format!("https://ci.example.com/builds/{}", build.id)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_send_delivery_rejects_private_url() {
+        let delivery = OutboundDelivery::new(
+            OutboundWebhookId::new(),
+            None,
+            NotificationEvent::BuildStarted,
+            "{}".to_string(),
+        );
+
+        let attempt = send_delivery(
+            &SsrfConfig::default(),
+            "https://127.0.0.1/hook",
+            "secret",
+            &delivery,
+        )
+        .await;
+
+        assert!(!attempt.is_success());
+        assert!(attempt.response_status.is_none());
+        assert!(attempt.error.unwrap().contains("private"));
+    }
+}
@@ -538,6 +538,61 @@ impl GitHubClient {
         }
     }
 
+    /// Gets the message of a commit.
+    ///
+    /// Pull request webhooks don't include the head commit's message.
+    pub async fn get_commit_message(
+        &self,
+        creds: &GitHubAppCredentials,
+        installation_id: i64,
+        owner: &str,
+        repo: &str,
+        sha: &str,
+    ) -> Result<String> {
+        let token = self.get_installation_token(creds, installation_id).await?;
+
+        let url = format!(
+            "{}/repos/{}/{}/commits/{}",
+            GITHUB_API_BASE, owner, repo, sha
+        );
+
+        let response = self
+            .client
+            .get(&url)
+            .header("Accept", "application/vnd.github+json")
+            .header("Authorization", format!("Bearer {}", token))
+            .header("X-GitHub-Api-Version", "2022-11-28")
+            .send()
+            .await
+            .map_err(|e| OoreError::Provider(format!("GitHub API request failed: {}", e)))?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let body = response.text().await.unwrap_or_default();
+            return Err(OoreError::Provider(format!(
+                "GitHub API error {}: {}",
+                status, body
+            )));
+        }
+
+        #[derive(Deserialize)]
+        struct CommitResponse {
+            commit: CommitDetails,
+        }
+
+        #[derive(Deserialize)]
+        struct CommitDetails {
+            message: String,
+        }
+
+        let commit: CommitResponse = response
+            .json()
+            .await
+            .map_err(|e| OoreError::Provider(format!("Failed to parse commit response: {}", e)))?;
+
+        Ok(commit.commit.message)
+    }
+
     /// Posts a commit status to GitHub.
     ///
     /// This updates the status check shown on commits and pull requests.
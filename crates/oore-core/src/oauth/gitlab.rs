@@ -363,6 +363,7 @@ impl GitLabClient {
                 "url": webhook_url,
                 "token": token,
                 "push_events": true,
+                "tag_push_events": true,
                 "merge_requests_events": true,
                 "enable_ssl_verification": true
             }))
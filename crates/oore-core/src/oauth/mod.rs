@@ -150,11 +150,54 @@ pub fn validate_gitlab_instance_url(url_str: &str, config: &SsrfConfig) -> Resul
     let normalized_url = Url::parse(&origin)
         .map_err(|e| OoreError::Configuration(format!("Failed to normalize URL: {}", e)))?;
 
+    let resolved_ips = resolve_and_check_host(host, url.port(), config)?;
+
+    Ok(ValidatedUrl {
+        url: normalized_url,
+        resolved_ips,
+    })
+}
+
+/// Validates a user-supplied URL for outbound requests (e.g. notification webhooks).
+///
+/// Unlike [`validate_gitlab_instance_url`], the path and query are kept.
+/// The same HTTPS, userinfo and private-IP rules apply.
+pub fn validate_outbound_url(url_str: &str, config: &SsrfConfig) -> Result<ValidatedUrl> {
+    let url = Url::parse(url_str)
+        .map_err(|e| OoreError::Configuration(format!("Invalid URL: {}", e)))?;
+
+    if url.scheme() != "https" {
+        return Err(OoreError::Configuration(
+            "HTTPS is required for outbound webhooks".to_string(),
+        ));
+    }
+
+    if !url.username().is_empty() || url.password().is_some() {
+        return Err(OoreError::Configuration(
+            "URL must not contain username or password".to_string(),
+        ));
+    }
+
+    let host = url
+        .host_str()
+        .ok_or_else(|| OoreError::Configuration("URL must have a host".to_string()))?;
+
+    let resolved_ips = resolve_and_check_host(host, url.port(), config)?;
+
+    Ok(ValidatedUrl { url, resolved_ips })
+}
+
+/// Resolves a host and rejects private or loopback addresses not explicitly allowed.
+fn resolve_and_check_host(
+    host: &str,
+    port: Option<u16>,
+    config: &SsrfConfig,
+) -> Result<Vec<IpAddr>> {
     // Check allowed hosts
     let host_with_port = format!(
         "{}{}",
         host.to_lowercase(),
-        url.port().map(|p| format!(":{}", p)).unwrap_or_default()
+        port.map(|p| format!(":{}", p)).unwrap_or_default()
     );
 
     let is_allowed_host = config.allowed_hosts.is_empty()
@@ -191,10 +234,7 @@ pub fn validate_gitlab_instance_url(url_str: &str, config: &SsrfConfig) -> Resul
         }
     }
 
-    Ok(ValidatedUrl {
-        url: normalized_url,
-        resolved_ips,
-    })
+    Ok(resolved_ips)
 }
 
 /// Resolves a hostname to IP addresses.
@@ -489,6 +529,23 @@ mod tests {
         assert!(result.is_err());
     }
 
+    #[test]
+    fn test_validate_outbound_url() {
+        let config = SsrfConfig::default();
+
+        // Path and query are preserved
+        let validated =
+            validate_outbound_url("https://93.184.216.34:8443/hooks/oore?x=1", &config).unwrap();
+        assert_eq!(validated.url.path(), "/hooks/oore");
+        assert_eq!(validated.url.query(), Some("x=1"));
+
+        // HTTP, userinfo and private addresses rejected
+        assert!(validate_outbound_url("http://93.184.216.34/hook", &config).is_err());
+        assert!(validate_outbound_url("https://u:p@93.184.216.34/hook", &config).is_err());
+        assert!(validate_outbound_url("https://127.0.0.1/hook", &config).is_err());
+        assert!(validate_outbound_url("https://10.1.2.3/hook", &config).is_err());
+    }
+
     #[test]
     fn test_encryption_key() {
         // Valid base64 key
@@ -0,0 +1,939 @@
+//! Dependency caching between builds.
+//!
+//! Cached paths are stored as one gzip-compressed tarball per repository,
+//! scope, workflow, path list and lockfile contents, so a change to any
+//! lockfile starts a fresh cache. Archives are evicted least recently used
+//! first once the cache directory grows past its size limit.
+//!
+//! Saving and restoring run in the server process, so cache paths are
+//! confined to the build's workspace and home directory, and symlinks that
+//! lead out of them are refused.
+
+use std::collections::{HashMap, HashSet};
+use std::fs::File;
+use std::io::{BufReader, BufWriter};
+use std::path::{Component, Path, PathBuf};
+use std::time::SystemTime;
+
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+
+use crate::crypto::sha256_hex;
+use crate::error::{OoreError, Result};
+use crate::models::{RepositoryId, TriggerType};
+
+/// Paths cached for every Flutter project.
+pub const FLUTTER_CACHE_PATHS: &[&str] = &[
+    "~/.pub-cache",
+    ".dart_tool",
+    "~/.gradle/caches",
+    "~/.gradle/wrapper",
+    "ios/Pods",
+];
+
+/// Lockfiles whose contents key the cache, relative to the repository root.
+const LOCKFILES: &[&str] = &[
+    "pubspec.lock",
+    "Podfile.lock",
+    "ios/Podfile.lock",
+    "gradle.lockfile",
+    "android/gradle.lockfile",
+    "android/app/gradle.lockfile",
+];
+
+/// Archive file extension.
+const ARCHIVE_EXTENSION: &str = "tar.gz";
+
+/// A cache path from the pipeline and where it resolves to.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CachePath {
+    /// The path as written in the pipeline.
+    pub spec: String,
+    /// The resolved path relative to its root, `~/`-prefixed under the home
+    /// directory. Unlike `path`, it's the same from build to build.
+    pub key: String,
+    /// Absolute path on disk.
+    pub path: PathBuf,
+    /// The workspace or home directory `path` is inside.
+    pub root: PathBuf,
+}
+
+impl CachePath {
+    /// Name of this path's top-level directory inside the archive.
+    fn archive_name(&self) -> String {
+        sha256_hex(self.key.as_bytes())[..16].to_string()
+    }
+
+    /// Fails if symlinks lead `path` out of its root.
+    fn check_contained(&self) -> Result<()> {
+        if is_contained(&self.path, &self.root) {
+            Ok(())
+        } else {
+            Err(OoreError::Cache(format!(
+                "Cache path '{}' leads outside the workspace and home directory",
+                self.spec
+            )))
+        }
+    }
+}
+
+/// Resolves pipeline cache paths against the workspace and home directory.
+///
+/// `~` is the home directory; `$VAR` and `${VAR}` are expanded from `env`,
+/// falling back to the process environment. Relative paths are relative to
+/// `workspace`. Paths must end up inside the workspace or the home
+/// directory. Paths that can't be resolved are returned as errors alongside
+/// the rest; duplicates are dropped.
+pub fn resolve_cache_paths(
+    specs: &[String],
+    workspace: &Path,
+    home: Option<&Path>,
+    env: &HashMap<String, String>,
+) -> (Vec<CachePath>, Vec<String>) {
+    let mut paths = Vec::new();
+    let mut errors = Vec::new();
+    let mut seen = HashSet::new();
+
+    for spec in specs {
+        let spec = spec.trim();
+        if spec.is_empty() {
+            continue;
+        }
+        match resolve_cache_path(spec, workspace, home, env) {
+            Ok(path) => {
+                if seen.insert(path.path.clone()) {
+                    paths.push(path);
+                }
+            }
+            Err(e) => errors.push(e),
+        }
+    }
+
+    (paths, errors)
+}
+
+fn resolve_cache_path(
+    spec: &str,
+    workspace: &Path,
+    home: Option<&Path>,
+    env: &HashMap<String, String>,
+) -> std::result::Result<CachePath, String> {
+    let lookup = |name: &str| {
+        env.get(name)
+            .cloned()
+            .or_else(|| std::env::var(name).ok())
+            .filter(|value| !value.is_empty())
+    };
+
+    let expanded = match spec.strip_prefix('~') {
+        Some(rest) if rest.is_empty() || rest.starts_with('/') => {
+            let home =
+                home.ok_or_else(|| format!("Cache path '{}': there is no home directory", spec))?;
+            format!("{}{}", home.display(), rest)
+        }
+        _ => spec.to_string(),
+    };
+    let expanded = expand_variables(&expanded, lookup)
+        .map_err(|name| format!("Cache path '{}': ${} is not set", spec, name))?;
+
+    let path = Path::new(&expanded);
+    if path.components().any(|c| matches!(c, Component::ParentDir)) {
+        return Err(format!("Cache path '{}' must not contain '..'", spec));
+    }
+
+    // Drops `.` components so equivalent spellings dedupe
+    let path: PathBuf = workspace.join(path).components().collect();
+    if path == workspace || Some(path.as_path()) == home {
+        return Err(format!(
+            "Cache path '{}' must not be the workspace or home directory itself",
+            spec
+        ));
+    }
+
+    // The workspace may be inside the home directory, so it goes first
+    let (root, key) = if let Ok(relative) = path.strip_prefix(workspace) {
+        (workspace, relative.display().to_string())
+    } else if let Some(home) = home
+        && let Ok(relative) = path.strip_prefix(home)
+    {
+        (home, format!("~/{}", relative.display()))
+    } else {
+        return Err(format!(
+            "Cache path '{}' must be inside the workspace or home directory",
+            spec
+        ));
+    };
+
+    Ok(CachePath {
+        spec: spec.to_string(),
+        key,
+        root: root.to_path_buf(),
+        path,
+    })
+}
+
+/// Returns whether `path` is inside `root` once symlinks in the part of it
+/// that exists are resolved.
+fn is_contained(path: &Path, root: &Path) -> bool {
+    let Ok(root) = root.canonicalize() else {
+        return false;
+    };
+
+    let mut existing = path;
+    let mut missing = Vec::new();
+    loop {
+        match existing.canonicalize() {
+            Ok(resolved) => {
+                let resolved = missing.iter().rev().fold(resolved, |p, c| p.join(c));
+                return resolved.starts_with(&root);
+            }
+            Err(_) => match (existing.parent(), existing.file_name()) {
+                (Some(parent), Some(name)) => {
+                    missing.push(name);
+                    existing = parent;
+                }
+                _ => return false,
+            },
+        }
+    }
+}
+
+/// Expands `$VAR` and `${VAR}`, returning the name of the first unset variable.
+fn expand_variables(
+    input: &str,
+    lookup: impl Fn(&str) -> Option<String>,
+) -> std::result::Result<String, String> {
+    let mut output = String::with_capacity(input.len());
+    let mut rest = input;
+
+    while let Some(start) = rest.find('$') {
+        output.push_str(&rest[..start]);
+        let after = &rest[start + 1..];
+        let (name, remaining) = match after.strip_prefix('{') {
+            Some(braced) => match braced.find('}') {
+                Some(end) => (&braced[..end], &braced[end + 1..]),
+                None => return Err(after.to_string()),
+            },
+            None => {
+                let end = after
+                    .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+                    .unwrap_or(after.len());
+                (&after[..end], &after[end..])
+            }
+        };
+        if name.is_empty() {
+            output.push('$');
+        } else {
+            output.push_str(&lookup(name).ok_or_else(|| name.to_string())?);
+        }
+        rest = remaining;
+    }
+    output.push_str(rest);
+
+    Ok(output)
+}
+
+/// Computes the cache key for a workflow's cache paths and the lockfiles in
+/// `workspace`.
+///
+/// Builds only share caches within a `scope` (see [`cache_scope`]).
+/// Returns the key and the lockfiles that went into it.
+pub async fn cache_key(
+    workspace: &Path,
+    scope: &str,
+    workflow_name: &str,
+    paths: &[CachePath],
+) -> (String, Vec<&'static str>) {
+    let mut input = format!("scope\0{}\0workflow\0{}\0", scope, workflow_name);
+    for path in paths {
+        input.push_str(&format!("path\0{}\0", path.key));
+    }
+
+    let mut lockfiles = Vec::new();
+    let mut input = input.into_bytes();
+    for lockfile in LOCKFILES {
+        if let Ok(content) = tokio::fs::read(workspace.join(lockfile)).await {
+            input.extend_from_slice(format!("lockfile\0{}\0", lockfile).as_bytes());
+            input.extend_from_slice(sha256_hex(&content).as_bytes());
+            input.push(0);
+            lockfiles.push(*lockfile);
+        }
+    }
+
+    (sha256_hex(&input), lockfiles)
+}
+
+/// Cache scope of a build.
+///
+/// Pull and merge request builds run code from outside the repository's
+/// branches, so each request gets its own scope and can't poison the caches
+/// that branch, tag, manual and scheduled builds restore.
+pub fn cache_scope(trigger_type: TriggerType, branch: &str) -> String {
+    match trigger_type {
+        TriggerType::PullRequest | TriggerType::MergeRequest => {
+            format!("{}:{}", trigger_type.as_str(), branch)
+        }
+        _ => "trusted".to_string(),
+    }
+}
+
+/// Outcome of restoring a cache.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CacheRestore {
+    /// An archive for the key was found and extracted.
+    Hit {
+        /// Compressed archive size.
+        archive_bytes: u64,
+        /// Number of files, directories and links extracted.
+        entries: usize,
+    },
+    /// No archive exists for the key.
+    Miss,
+}
+
+/// Outcome of saving a cache.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CacheSave {
+    /// Compressed archive size.
+    pub archive_bytes: u64,
+    /// Cache paths that existed and were archived.
+    pub saved_paths: Vec<String>,
+    /// Archives removed to stay within the size limit.
+    pub evicted: usize,
+}
+
+/// On-disk dependency cache.
+#[derive(Debug, Clone)]
+pub struct DependencyCache {
+    dir: PathBuf,
+    max_size_bytes: u64,
+}
+
+impl DependencyCache {
+    /// Creates a cache stored under `dir`, limited to `max_size_bytes` in total.
+    pub fn new(dir: impl Into<PathBuf>, max_size_bytes: u64) -> Self {
+        Self {
+            dir: dir.into(),
+            max_size_bytes,
+        }
+    }
+
+    /// Total archive size the cache is limited to.
+    pub fn max_size_bytes(&self) -> u64 {
+        self.max_size_bytes
+    }
+
+    /// Path of the archive for `key`.
+    pub fn archive_path(&self, repository_id: &RepositoryId, key: &str) -> PathBuf {
+        self.dir
+            .join(repository_id.to_string())
+            .join(format!("{}.{}", key, ARCHIVE_EXTENSION))
+    }
+
+    /// Returns whether an archive exists for `key`.
+    pub async fn contains(&self, repository_id: &RepositoryId, key: &str) -> bool {
+        tokio::fs::try_exists(self.archive_path(repository_id, key))
+            .await
+            .unwrap_or(false)
+    }
+
+    /// Extracts the archive for `key` into the cache paths.
+    pub async fn restore(
+        &self,
+        repository_id: &RepositoryId,
+        key: &str,
+        paths: &[CachePath],
+    ) -> Result<CacheRestore> {
+        let archive = self.archive_path(repository_id, key);
+        let paths = paths.to_vec();
+
+        run_blocking(move || {
+            let file = match File::open(&archive) {
+                Ok(file) => file,
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                    return Ok(CacheRestore::Miss);
+                }
+                Err(e) => return Err(e.into()),
+            };
+            let archive_bytes = file.metadata()?.len();
+            // Mark as recently used for eviction
+            file.set_modified(SystemTime::now())?;
+
+            for path in &paths {
+                std::fs::create_dir_all(&path.root)?;
+                path.check_contained()?;
+            }
+
+            let entries = extract_archive(BufReader::new(file), &paths)?;
+            Ok(CacheRestore::Hit {
+                archive_bytes,
+                entries,
+            })
+        })
+        .await
+    }
+
+    /// Archives the existing cache paths under `key`, then evicts old
+    /// archives if the cache is over its size limit.
+    ///
+    /// Returns `None` if none of the paths exist.
+    pub async fn save(
+        &self,
+        repository_id: &RepositoryId,
+        key: &str,
+        paths: &[CachePath],
+    ) -> Result<Option<CacheSave>> {
+        let archive = self.archive_path(repository_id, key);
+        let paths = paths.to_vec();
+        let cache = self.clone();
+
+        run_blocking(move || {
+            let existing: Vec<&CachePath> = paths
+                .iter()
+                .filter(|p| p.path.symlink_metadata().is_ok())
+                .collect();
+            if existing.is_empty() {
+                return Ok(None);
+            }
+            // Steps may have replaced cache paths with symlinks
+            for path in &existing {
+                path.check_contained()?;
+            }
+
+            let parent = archive
+                .parent()
+                .ok_or_else(|| OoreError::Cache("Invalid cache path".to_string()))?;
+            std::fs::create_dir_all(parent)?;
+
+            // Concurrent builds may save the same key; the last rename wins
+            let temp = tempfile::Builder::new()
+                .prefix(".save-")
+                .suffix(".tmp")
+                .tempfile_in(parent)?;
+            {
+                let encoder = GzEncoder::new(BufWriter::new(temp.as_file()), Compression::fast());
+                let mut builder = tar::Builder::new(encoder);
+                builder.follow_symlinks(false);
+                for path in &existing {
+                    let name = path.archive_name();
+                    if path.path.is_dir() {
+                        builder.append_dir_all(&name, &path.path)?;
+                    } else {
+                        builder.append_path_with_name(&path.path, &name)?;
+                    }
+                }
+                builder.into_inner()?.finish()?;
+            }
+            temp.persist(&archive).map_err(|e| OoreError::Io(e.error))?;
+
+            let archive_bytes = std::fs::metadata(&archive)?.len();
+            let evicted = cache.evict()?;
+
+            Ok(Some(CacheSave {
+                archive_bytes,
+                saved_paths: existing.iter().map(|p| p.spec.clone()).collect(),
+                evicted,
+            }))
+        })
+        .await
+    }
+
+    /// Total size of all archives in bytes.
+    pub fn total_size(&self) -> Result<u64> {
+        Ok(self.archives()?.iter().map(|(_, size, _)| size).sum())
+    }
+
+    /// Removes least recently used archives until the cache fits its limit.
+    ///
+    /// Returns the number of archives removed.
+    pub fn evict(&self) -> Result<usize> {
+        let mut archives = self.archives()?;
+        let mut total: u64 = archives.iter().map(|(_, size, _)| size).sum();
+        archives.sort_by_key(|(_, _, used)| *used);
+
+        let mut evicted = 0;
+        for (path, size, _) in archives {
+            if total <= self.max_size_bytes {
+                break;
+            }
+            match std::fs::remove_file(&path) {
+                Ok(()) => {
+                    tracing::debug!("Evicted cache archive {}", path.display());
+                    total = total.saturating_sub(size);
+                    evicted += 1;
+                }
+                Err(e) => {
+                    tracing::warn!("Failed to evict cache archive {}: {}", path.display(), e)
+                }
+            }
+        }
+
+        Ok(evicted)
+    }
+
+    /// Lists archives with their size and last use.
+    fn archives(&self) -> Result<Vec<(PathBuf, u64, SystemTime)>> {
+        let mut archives = Vec::new();
+        let repos = match std::fs::read_dir(&self.dir) {
+            Ok(repos) => repos,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(archives),
+            Err(e) => return Err(e.into()),
+        };
+
+        for repo in repos {
+            let repo = repo?;
+            if !repo.file_type()?.is_dir() {
+                continue;
+            }
+            for entry in std::fs::read_dir(repo.path())? {
+                let entry = entry?;
+                let path = entry.path();
+                let is_archive = path
+                    .file_name()
+                    .and_then(|n| n.to_str())
+                    .is_some_and(|n| n.ends_with(ARCHIVE_EXTENSION));
+                if !is_archive {
+                    continue;
+                }
+                let metadata = entry.metadata()?;
+                let used = metadata.modified().unwrap_or(SystemTime::UNIX_EPOCH);
+                archives.push((path, metadata.len(), used));
+            }
+        }
+
+        Ok(archives)
+    }
+}
+
+/// Extracts an archive written by [`DependencyCache::save`] into `paths`.
+///
+/// Entries for paths that are no longer configured are ignored.
+fn extract_archive(reader: impl std::io::Read, paths: &[CachePath]) -> Result<usize> {
+    let targets: HashMap<String, &Path> = paths
+        .iter()
+        .map(|p| (p.archive_name(), p.path.as_path()))
+        .collect();
+
+    let mut archive = tar::Archive::new(GzDecoder::new(reader));
+    archive.set_preserve_mtime(true);
+    archive.set_overwrite(true);
+
+    let mut extracted = 0;
+    for entry in archive.entries()? {
+        let mut entry = entry?;
+        let entry_path = entry.path()?.into_owned();
+
+        let mut components = entry_path.components();
+        let Some(Component::Normal(name)) = components.next() else {
+            continue;
+        };
+        let Some(target) = name.to_str().and_then(|name| targets.get(name)) else {
+            continue;
+        };
+        let relative = components.as_path();
+        if !relative
+            .components()
+            .all(|c| matches!(c, Component::Normal(_)))
+        {
+            return Err(OoreError::Cache(format!(
+                "Cache archive entry '{}' is outside its path",
+                entry_path.display()
+            )));
+        }
+
+        if relative.as_os_str().is_empty() {
+            // The cache path itself, checked to be inside its root
+            if let Some(parent) = target.parent() {
+                std::fs::create_dir_all(parent)?;
+            }
+            entry.unpack(target)?;
+        } else {
+            unpack_inside(&mut entry, target, relative)?;
+        }
+        extracted += 1;
+    }
+
+    Ok(extracted)
+}
+
+/// Unpacks `entry` to `relative` under `target`, with the same checks as
+/// [`tar::Entry::unpack_in`].
+///
+/// Archive entries are prefixed with the cache path's archive name, so
+/// `unpack_in` itself can't be used.
+fn unpack_inside<R: std::io::Read>(
+    entry: &mut tar::Entry<R>,
+    target: &Path,
+    relative: &Path,
+) -> Result<()> {
+    let outside = || {
+        OoreError::Cache(format!(
+            "Cache archive entry '{}' leads outside {}",
+            relative.display(),
+            target.display()
+        ))
+    };
+    if entry.header().entry_type().is_hard_link() {
+        return Err(outside());
+    }
+
+    let destination = target.join(relative);
+    let parent = destination.parent().ok_or_else(outside)?;
+
+    // Create missing directories one at a time, each inside the target once
+    // symlinks extracted earlier are resolved
+    std::fs::create_dir_all(target)?;
+    let mut missing = Vec::new();
+    let mut ancestor = parent;
+    while ancestor.symlink_metadata().is_err() {
+        missing.push(ancestor);
+        ancestor = ancestor.parent().ok_or_else(outside)?;
+    }
+    for dir in missing.into_iter().rev() {
+        if !is_contained(dir, target) {
+            return Err(outside());
+        }
+        std::fs::create_dir(dir)?;
+    }
+    if !is_contained(parent, target) {
+        return Err(outside());
+    }
+
+    // Unpacking a directory onto a symlink would follow it
+    if destination
+        .symlink_metadata()
+        .is_ok_and(|m| m.file_type().is_symlink())
+    {
+        std::fs::remove_file(&destination)?;
+    }
+
+    entry.unpack(&destination)?;
+    Ok(())
+}
+
+async fn run_blocking<T: Send + 'static>(
+    f: impl FnOnce() -> Result<T> + Send + 'static,
+) -> Result<T> {
+    tokio::task::spawn_blocking(f)
+        .await
+        .map_err(|e| OoreError::Cache(format!("Cache task failed: {}", e)))?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn env(vars: &[(&str, &str)]) -> HashMap<String, String> {
+        vars.iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect()
+    }
+
+    fn specs(paths: &[&str]) -> Vec<String> {
+        paths.iter().map(|p| p.to_string()).collect()
+    }
+
+    /// A cache path inside `workspace`.
+    fn cache_path(workspace: &Path, spec: &str) -> CachePath {
+        CachePath {
+            spec: spec.to_string(),
+            key: spec.to_string(),
+            path: workspace.join(spec),
+            root: workspace.to_path_buf(),
+        }
+    }
+
+    #[test]
+    fn test_resolve_cache_paths() {
+        let workspace = Path::new("/work/build");
+        let home = Path::new("/work/build.home");
+        let env = env(&[("HOME", "/work/build.home"), ("PODS", "ios/Pods")]);
+
+        let (paths, errors) = resolve_cache_paths(
+            &specs(&[
+                "~/.pub-cache",
+                "$HOME/.gradle/caches",
+                "$PODS",
+                "./ios/Pods",
+                "/work/build/build/generated",
+            ]),
+            workspace,
+            Some(home),
+            &env,
+        );
+        assert!(errors.is_empty(), "{:?}", errors);
+        let resolved: Vec<(&Path, &str)> = paths
+            .iter()
+            .map(|p| (p.path.as_path(), p.key.as_str()))
+            .collect();
+        assert_eq!(
+            resolved,
+            vec![
+                (Path::new("/work/build.home/.pub-cache"), "~/.pub-cache"),
+                (
+                    Path::new("/work/build.home/.gradle/caches"),
+                    "~/.gradle/caches"
+                ),
+                (Path::new("/work/build/ios/Pods"), "ios/Pods"),
+                (Path::new("/work/build/build/generated"), "build/generated"),
+            ]
+        );
+        assert_eq!(paths[0].root, home);
+        assert_eq!(paths[2].root, workspace);
+
+        // The key doesn't depend on where the build's workspace is
+        let (other, _) = resolve_cache_paths(
+            &specs(&["~/.pub-cache", "ios/Pods"]),
+            Path::new("/work/other"),
+            Some(Path::new("/work/other.home")),
+            &HashMap::new(),
+        );
+        assert_eq!(other[0].key, paths[0].key);
+        assert_eq!(other[1].key, paths[2].key);
+
+        let (paths, errors) = resolve_cache_paths(
+            &specs(&[
+                "../outside",
+                "$OORE_UNSET_CACHE_VAR/x",
+                ".",
+                "/",
+                "~",
+                "/etc/oore",
+                "$SECRETS",
+                "ok",
+            ]),
+            workspace,
+            Some(home),
+            &self::env(&[("SECRETS", "/var/lib/oore")]),
+        );
+        assert_eq!(paths.len(), 1);
+        assert_eq!(errors.len(), 7);
+        assert!(errors[1].contains("$OORE_UNSET_CACHE_VAR is not set"));
+        assert!(errors[5].contains("must be inside the workspace or home directory"));
+
+        // Without a home directory only the workspace is allowed
+        let (paths, errors) =
+            resolve_cache_paths(&specs(&["~/.pub-cache", "ok"]), workspace, None, &env);
+        assert_eq!(paths.len(), 1);
+        assert!(errors[0].contains("there is no home directory"));
+    }
+
+    #[tokio::test]
+    async fn test_cache_key_follows_lockfiles() {
+        let workspace = tempfile::tempdir().unwrap();
+        let paths = vec![cache_path(workspace.path(), ".dart_tool")];
+
+        let (empty, lockfiles) = cache_key(workspace.path(), "trusted", "ios", &paths).await;
+        assert!(lockfiles.is_empty());
+
+        std::fs::write(workspace.path().join("pubspec.lock"), "packages: {}").unwrap();
+        let (first, lockfiles) = cache_key(workspace.path(), "trusted", "ios", &paths).await;
+        assert_eq!(lockfiles, vec!["pubspec.lock"]);
+        assert_ne!(first, empty);
+        assert_eq!(
+            cache_key(workspace.path(), "trusted", "ios", &paths)
+                .await
+                .0,
+            first
+        );
+        assert_ne!(
+            cache_key(workspace.path(), "trusted", "android", &paths)
+                .await
+                .0,
+            first
+        );
+
+        // Pull request builds get their own caches
+        let scope = cache_scope(TriggerType::PullRequest, "feature");
+        assert_eq!(scope, "pull_request:feature");
+        assert_eq!(cache_scope(TriggerType::Push, "main"), "trusted");
+        assert_ne!(
+            cache_key(workspace.path(), &scope, "ios", &paths).await.0,
+            first
+        );
+
+        std::fs::write(workspace.path().join("pubspec.lock"), "packages: {a: 1}").unwrap();
+        assert_ne!(
+            cache_key(workspace.path(), "trusted", "ios", &paths)
+                .await
+                .0,
+            first
+        );
+    }
+
+    #[tokio::test]
+    async fn test_save_and_restore() {
+        let dir = tempfile::tempdir().unwrap();
+        let cache = DependencyCache::new(dir.path().join("cache"), u64::MAX);
+        let repository_id = RepositoryId::new();
+
+        let workspace = dir.path().join("workspace");
+        let pods = workspace.join("ios/Pods");
+        std::fs::create_dir_all(pods.join("Headers")).unwrap();
+        std::fs::write(pods.join("Manifest.lock"), "PODS: []").unwrap();
+        #[cfg(unix)]
+        std::os::unix::fs::symlink("../Manifest.lock", pods.join("Headers/link")).unwrap();
+        std::fs::write(workspace.join("single"), "one file").unwrap();
+
+        let paths = vec![
+            cache_path(&workspace, "ios/Pods"),
+            cache_path(&workspace, "single"),
+            cache_path(&workspace, "missing"),
+        ];
+
+        assert_eq!(
+            cache.restore(&repository_id, "key", &paths).await.unwrap(),
+            CacheRestore::Miss
+        );
+
+        let saved = cache
+            .save(&repository_id, "key", &paths)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(saved.saved_paths, vec!["ios/Pods", "single"]);
+        assert_eq!(saved.evicted, 0);
+        assert!(cache.contains(&repository_id, "key").await);
+        assert_eq!(cache.total_size().unwrap(), saved.archive_bytes);
+
+        std::fs::remove_dir_all(&workspace).unwrap();
+        let restored = cache.restore(&repository_id, "key", &paths).await.unwrap();
+        assert!(matches!(restored, CacheRestore::Hit { entries, .. } if entries >= 4));
+        assert_eq!(
+            std::fs::read_to_string(pods.join("Manifest.lock")).unwrap(),
+            "PODS: []"
+        );
+        assert_eq!(
+            std::fs::read_to_string(workspace.join("single")).unwrap(),
+            "one file"
+        );
+        #[cfg(unix)]
+        assert!(std::fs::symlink_metadata(pods.join("Headers/link"))
+            .unwrap()
+            .file_type()
+            .is_symlink());
+
+        // Restoring over existing files replaces them
+        std::fs::write(workspace.join("single"), "changed").unwrap();
+        cache.restore(&repository_id, "key", &paths).await.unwrap();
+        assert_eq!(
+            std::fs::read_to_string(workspace.join("single")).unwrap(),
+            "one file"
+        );
+
+        // Nothing to save
+        assert!(cache
+            .save(&repository_id, "other", &paths[2..])
+            .await
+            .unwrap()
+            .is_none());
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_symlinks_cannot_lead_outside() {
+        let dir = tempfile::tempdir().unwrap();
+        let cache = DependencyCache::new(dir.path().join("cache"), u64::MAX);
+        let repository_id = RepositoryId::new();
+        let workspace = dir.path().join("workspace");
+        let outside = dir.path().join("outside");
+        std::fs::create_dir_all(&workspace).unwrap();
+        std::fs::create_dir_all(&outside).unwrap();
+        std::fs::write(outside.join("secret"), "secret").unwrap();
+
+        // A cache path replaced by a symlink to outside isn't saved
+        std::os::unix::fs::symlink(&outside, workspace.join("deps")).unwrap();
+        let paths = vec![cache_path(&workspace, "deps")];
+        assert!(cache.save(&repository_id, "key", &paths).await.is_err());
+        assert!(!cache.contains(&repository_id, "key").await);
+
+        // Nor restored through one
+        std::fs::remove_file(workspace.join("deps")).unwrap();
+        std::fs::create_dir(workspace.join("deps")).unwrap();
+        std::fs::write(workspace.join("deps/file"), "cached").unwrap();
+        cache.save(&repository_id, "key", &paths).await.unwrap();
+        std::fs::remove_dir_all(workspace.join("deps")).unwrap();
+        std::os::unix::fs::symlink(&outside, workspace.join("deps")).unwrap();
+        assert!(cache.restore(&repository_id, "key", &paths).await.is_err());
+        assert!(!outside.join("file").exists());
+
+        // An archive with a symlink to outside can't write through it
+        let archive = cache.archive_path(&repository_id, "crafted");
+        {
+            let name = paths[0].archive_name();
+            let mut builder = tar::Builder::new(GzEncoder::new(
+                File::create(&archive).unwrap(),
+                Compression::fast(),
+            ));
+            let mut header = tar::Header::new_gnu();
+            header.set_entry_type(tar::EntryType::Symlink);
+            header.set_size(0);
+            builder
+                .append_link(&mut header, format!("{}/link", name), &outside)
+                .unwrap();
+            let mut header = tar::Header::new_gnu();
+            header.set_size(7);
+            header.set_mode(0o644);
+            builder
+                .append_data(
+                    &mut header,
+                    format!("{}/link/planted", name),
+                    &b"planted"[..],
+                )
+                .unwrap();
+            builder.into_inner().unwrap().finish().unwrap();
+        }
+        std::fs::remove_file(workspace.join("deps")).unwrap();
+        let result = cache.restore(&repository_id, "crafted", &paths).await;
+        assert!(result.is_err());
+        assert!(!outside.join("planted").exists());
+        assert_eq!(
+            std::fs::read_to_string(outside.join("secret")).unwrap(),
+            "secret"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_evicts_least_recently_used() {
+        let dir = tempfile::tempdir().unwrap();
+        let repository_id = RepositoryId::new();
+        let source = dir.path().join("data");
+        std::fs::write(&source, vec![7u8; 64]).unwrap();
+        let paths = vec![cache_path(dir.path(), "data")];
+
+        let unlimited = DependencyCache::new(dir.path().join("cache"), u64::MAX);
+        let size = unlimited
+            .save(&repository_id, "a", &paths)
+            .await
+            .unwrap()
+            .unwrap()
+            .archive_bytes;
+        unlimited.save(&repository_id, "b", &paths).await.unwrap();
+
+        // Use "a" after "b" so "b" is the least recently used
+        let old = SystemTime::now() - std::time::Duration::from_secs(3600);
+        File::options()
+            .write(true)
+            .open(unlimited.archive_path(&repository_id, "b"))
+            .unwrap()
+            .set_modified(old)
+            .unwrap();
+        unlimited
+            .restore(&repository_id, "a", &paths)
+            .await
+            .unwrap();
+
+        // Room for two archives: saving a third evicts "b"
+        let cache = DependencyCache::new(dir.path().join("cache"), size * 2 + size / 2);
+        let saved = cache
+            .save(&repository_id, "c", &paths)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(saved.evicted, 1);
+        assert!(cache.contains(&repository_id, "a").await);
+        assert!(!cache.contains(&repository_id, "b").await);
+        assert!(cache.contains(&repository_id, "c").await);
+    }
+}
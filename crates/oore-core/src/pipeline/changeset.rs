@@ -0,0 +1,263 @@
+//! Changed-file filtering for `when.changeset` workflow conditions.
+
+use std::path::Path;
+
+use tokio::process::Command;
+
+use crate::error::{OoreError, Result};
+use crate::models::ChangesetCondition;
+
+use super::executor::{run_git, sanitize_git_error};
+
+/// Checks whether any changed file is relevant to the condition.
+pub fn changeset_matches(condition: &ChangesetCondition, changed_files: &[String]) -> bool {
+    changed_files.iter().any(|file| {
+        let included = condition.includes.is_empty()
+            || condition
+                .includes
+                .iter()
+                .any(|pattern| matches_path_pattern(pattern, file));
+
+        included
+            && !condition
+                .excludes
+                .iter()
+                .any(|pattern| matches_path_pattern(pattern, file))
+    })
+}
+
+/// Matches a repository-relative file path against a changeset pattern.
+///
+/// `.` matches every file, and a pattern without glob characters also
+/// matches everything under it as a directory.
+fn matches_path_pattern(pattern: &str, path: &str) -> bool {
+    let pattern = pattern.trim_start_matches("./").trim_end_matches('/');
+    if pattern.is_empty() || pattern == "." {
+        return true;
+    }
+
+    if !pattern.contains(['*', '?', '[']) {
+        return path == pattern
+            || path
+                .strip_prefix(pattern)
+                .is_some_and(|rest| rest.starts_with('/'));
+    }
+
+    let options = glob::MatchOptions {
+        require_literal_separator: true,
+        ..Default::default()
+    };
+    match glob::Pattern::new(pattern) {
+        Ok(p) => p.matches_with(path, options),
+        Err(_) => {
+            tracing::warn!("Invalid changeset pattern '{}', using exact match", pattern);
+            pattern == path
+        }
+    }
+}
+
+/// Fetches the commits [`diff_changed_files`] needs into a shallow checkout,
+/// such as one from [`checkout_config_files`](super::checkout_config_files).
+///
+/// Only commits come over; trees are fetched as the diff reads them.
+pub async fn fetch_diff_history(workspace: &Path, base: &str, head: &str) -> Result<()> {
+    if base.starts_with('-') || head.starts_with('-') {
+        return Err(OoreError::BuildExecution(format!(
+            "Invalid diff range: {}...{}",
+            base, head
+        )));
+    }
+
+    // A branch base is fetched to the same remote-tracking ref a clone has
+    let base = match base.strip_prefix("origin/") {
+        Some(branch) => format!("+refs/heads/{0}:refs/remotes/origin/{0}", branch),
+        None => base.to_string(),
+    };
+    let mut args = vec!["fetch", "--quiet", "--filter=tree:0"];
+    if workspace.join(".git/shallow").exists() {
+        args.push("--unshallow");
+    }
+    args.extend(["origin", &base, head]);
+    run_git(Some(workspace), &args)
+        .await
+        .map_err(|e| OoreError::BuildExecution(format!("git fetch failed: {}", e)))
+}
+
+/// Lists files changed on `head` since it diverged from `base`.
+///
+/// `base` is a commit SHA or a ref such as `origin/main`; both must already
+/// be present in the cloned workspace.
+pub async fn diff_changed_files(workspace: &Path, base: &str, head: &str) -> Result<Vec<String>> {
+    if base.starts_with('-') || head.starts_with('-') {
+        return Err(OoreError::BuildExecution(format!(
+            "Invalid diff range: {}...{}",
+            base, head
+        )));
+    }
+
+    let output = Command::new("git")
+        .current_dir(workspace)
+        .args(["diff", "--name-only", &format!("{}...{}", base, head), "--"])
+        .output()
+        .await?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(OoreError::BuildExecution(format!(
+            "git diff failed: {}",
+            sanitize_git_error(stderr.trim())
+        )));
+    }
+
+    Ok(String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .filter(|line| !line.is_empty())
+        .map(str::to_string)
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn condition(includes: &[&str], excludes: &[&str]) -> ChangesetCondition {
+        ChangesetCondition {
+            includes: includes.iter().map(|s| s.to_string()).collect(),
+            excludes: excludes.iter().map(|s| s.to_string()).collect(),
+        }
+    }
+
+    fn files(paths: &[&str]) -> Vec<String> {
+        paths.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn test_changeset_includes() {
+        let android = condition(&["apps/android"], &[]);
+        assert!(changeset_matches(
+            &android,
+            &files(&["apps/android/build.gradle"])
+        ));
+        assert!(!changeset_matches(
+            &android,
+            &files(&["apps/android-tv/build.gradle"])
+        ));
+        assert!(!changeset_matches(&android, &files(&["apps/ios/Podfile"])));
+
+        let dart = condition(&["packages/**/*.dart"], &[]);
+        assert!(changeset_matches(
+            &dart,
+            &files(&["packages/core/lib/core.dart"])
+        ));
+        assert!(!changeset_matches(
+            &dart,
+            &files(&["packages/core/pubspec.yaml"])
+        ));
+
+        assert!(!changeset_matches(
+            &condition(&["*.yaml"], &[]),
+            &files(&["apps/pubspec.yaml"])
+        ));
+        assert!(!changeset_matches(&condition(&["."], &[]), &[]));
+    }
+
+    #[test]
+    fn test_changeset_excludes() {
+        let docs_ignored = condition(&["."], &["**/*.md", "docs/"]);
+        assert!(!changeset_matches(
+            &docs_ignored,
+            &files(&["README.md", "docs/setup.txt"])
+        ));
+        assert!(changeset_matches(
+            &docs_ignored,
+            &files(&["README.md", "lib/main.dart"])
+        ));
+
+        let no_includes = condition(&[], &["**/*.md"]);
+        assert!(changeset_matches(&no_includes, &files(&["lib/main.dart"])));
+        assert!(!changeset_matches(
+            &no_includes,
+            &files(&["apps/ios/CHANGELOG.md"])
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_diff_changed_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let git = |args: &[&str]| {
+            let status = std::process::Command::new("git")
+                .current_dir(dir.path())
+                .args(["-c", "user.name=oore", "-c", "user.email=oore@example.com"])
+                .args(args)
+                .output()
+                .unwrap()
+                .status;
+            assert!(status.success(), "git {:?} failed", args);
+        };
+
+        git(&["init", "-q"]);
+        std::fs::write(dir.path().join("README.md"), "readme").unwrap();
+        git(&["add", "."]);
+        git(&["commit", "-qm", "first"]);
+        git(&["tag", "base"]);
+        std::fs::create_dir(dir.path().join("lib")).unwrap();
+        std::fs::write(dir.path().join("lib/main.dart"), "void main() {}").unwrap();
+        git(&["add", "."]);
+        git(&["commit", "-qm", "second"]);
+
+        let changed = diff_changed_files(dir.path(), "base", "HEAD")
+            .await
+            .unwrap();
+        assert_eq!(changed, vec!["lib/main.dart".to_string()]);
+
+        assert!(diff_changed_files(dir.path(), "--output=x", "HEAD")
+            .await
+            .is_err());
+        assert!(diff_changed_files(dir.path(), "missing", "HEAD")
+            .await
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn test_fetch_diff_history() {
+        let dir = tempfile::tempdir().unwrap();
+        let remote = dir.path().join("remote");
+        std::fs::create_dir(&remote).unwrap();
+        let git = |args: &[&str]| {
+            let output = std::process::Command::new("git")
+                .current_dir(&remote)
+                .args(["-c", "user.name=oore", "-c", "user.email=oore@example.com"])
+                .args(args)
+                .output()
+                .unwrap();
+            assert!(output.status.success(), "git {:?} failed", args);
+            String::from_utf8(output.stdout).unwrap().trim().to_string()
+        };
+
+        git(&["init", "-q", "-b", "main"]);
+        std::fs::write(remote.join("oore.yaml"), "").unwrap();
+        git(&["add", "."]);
+        git(&["commit", "-qm", "first"]);
+        git(&["checkout", "-qb", "feature"]);
+        std::fs::write(remote.join("main.dart"), "").unwrap();
+        git(&["add", "."]);
+        git(&["commit", "-qm", "second"]);
+        let head = git(&["rev-parse", "HEAD"]);
+
+        let workspace = dir.path().join("workspace");
+        crate::pipeline::checkout_config_files(remote.to_str().unwrap(), &head, &workspace, None)
+            .await
+            .unwrap();
+        assert!(diff_changed_files(&workspace, "origin/main", &head)
+            .await
+            .is_err());
+
+        fetch_diff_history(&workspace, "origin/main", &head)
+            .await
+            .unwrap();
+        let changed = diff_changed_files(&workspace, "origin/main", &head)
+            .await
+            .unwrap();
+        assert_eq!(changed, vec!["main.dart".to_string()]);
+    }
+}
@@ -0,0 +1,445 @@
+//! Container-based build execution.
+//!
+//! Each build gets one long-lived Docker or Podman container, and every step
+//! runs in it with `exec`. The workspace and a per-build home directory are
+//! bind-mounted at the same paths they have on the host, so build environment
+//! paths, dependency cache restores and signing files work unchanged.
+
+use async_trait::async_trait;
+use std::collections::HashMap;
+use std::os::unix::fs::MetadataExt;
+use std::path::{Path, PathBuf};
+use tokio::process::Command;
+use tokio::sync::watch;
+
+use crate::error::{OoreError, Result};
+use crate::models::{CloneConfig, Workflow};
+
+use super::executor::{
+    home_path, run_logged, BuildExecutor, BuildLimits, OutputSender, ShellExecutor, StepResult,
+};
+use super::masking::SecretMasker;
+
+/// Container runtime settings.
+#[derive(Debug, Clone)]
+pub struct ContainerConfig {
+    /// Runtime CLI, `docker` or `podman`.
+    pub runtime: String,
+    /// Image used when a workflow doesn't set `image`.
+    pub image: String,
+    /// CPUs available to a `linux` instance.
+    pub cpus: f64,
+    /// Memory available to a `linux` instance, in megabytes.
+    pub memory_mb: u64,
+}
+
+impl Default for ContainerConfig {
+    fn default() -> Self {
+        Self {
+            runtime: "docker".to_string(),
+            image: "ghcr.io/cirruslabs/flutter:stable".to_string(),
+            cpus: 2.0,
+            memory_mb: 4096,
+        }
+    }
+}
+
+impl ContainerConfig {
+    /// Loads settings from environment variables with defaults.
+    pub fn from_env() -> Self {
+        let mut config = Self::default();
+
+        if let Ok(val) = std::env::var("OORE_CONTAINER_RUNTIME") {
+            config.runtime = val;
+        }
+
+        if let Ok(val) = std::env::var("OORE_CONTAINER_IMAGE") {
+            config.image = val;
+        }
+
+        if let Ok(val) = std::env::var("OORE_CONTAINER_CPUS")
+            && let Ok(v) = val.parse()
+        {
+            config.cpus = v;
+        }
+
+        if let Ok(val) = std::env::var("OORE_CONTAINER_MEMORY_MB")
+            && let Ok(v) = val.parse()
+        {
+            config.memory_mb = v;
+        }
+
+        config
+    }
+}
+
+/// Container-based build executor.
+///
+/// Repositories are cloned on the host; only steps run in the container.
+/// Containers run as the user owning the workspace, so images must work for
+/// a non-root user.
+pub struct ContainerExecutor {
+    host: ShellExecutor,
+    config: ContainerConfig,
+    limits: BuildLimits,
+}
+
+impl ContainerExecutor {
+    /// Creates a container executor that clones with `host`.
+    pub fn new(host: ShellExecutor, config: ContainerConfig) -> Self {
+        Self {
+            host,
+            config,
+            limits: BuildLimits::from_env(),
+        }
+    }
+
+    /// Runs a runtime command, returning its stderr on failure.
+    async fn runtime(&self, args: &[&str]) -> std::result::Result<(), String> {
+        let output = Command::new(&self.config.runtime)
+            .args(args)
+            .output()
+            .await
+            .map_err(|e| format!("{}: {}", self.config.runtime, e))?;
+
+        if output.status.success() {
+            Ok(())
+        } else {
+            Err(String::from_utf8_lossy(&output.stderr).trim().to_string())
+        }
+    }
+}
+
+#[async_trait]
+impl BuildExecutor for ContainerExecutor {
+    async fn clone_repo(
+        &self,
+        clone_url: &str,
+        commit_sha: &str,
+        workspace: &Path,
+        auth_token: Option<&str>,
+        config: &CloneConfig,
+    ) -> Result<()> {
+        self.host
+            .clone_repo(clone_url, commit_sha, workspace, auth_token, config)
+            .await
+    }
+
+    async fn prepare_build(&self, workspace: &Path, workflow: &Workflow) -> Result<()> {
+        let scale = instance_scale(workflow.instance_type.as_deref())?;
+        let image = workflow.image.as_deref().unwrap_or(&self.config.image);
+
+        let home = home_path(workspace);
+        tokio::fs::create_dir_all(&home).await?;
+        let workspace_str = workspace
+            .to_str()
+            .ok_or_else(|| OoreError::BuildExecution("Invalid workspace path".to_string()))?;
+        let home_str = home
+            .to_str()
+            .ok_or_else(|| OoreError::BuildExecution("Invalid home path".to_string()))?;
+
+        // Files written to the mounts stay owned by the server's user
+        let owner = tokio::fs::metadata(workspace).await?;
+        let user = format!("{}:{}", owner.uid(), owner.gid());
+        let cpus = (self.config.cpus * scale as f64).to_string();
+        let memory = format!("{}m", self.config.memory_mb * scale as u64);
+        let workspace_mount = format!("{}:{}", workspace_str, workspace_str);
+        let home_mount = format!("{}:{}", home_str, home_str);
+        let home_env = format!("HOME={}", home_str);
+
+        // A container left behind by an interrupted build would hold the name
+        let name = container_name(workspace);
+        let _ = self.runtime(&["rm", "--force", &name]).await;
+
+        self.runtime(&[
+            "run",
+            "--detach",
+            "--name",
+            &name,
+            "--label",
+            "oore.build=true",
+            "--user",
+            &user,
+            "--cpus",
+            &cpus,
+            "--memory",
+            &memory,
+            "--volume",
+            &workspace_mount,
+            "--volume",
+            &home_mount,
+            "--workdir",
+            workspace_str,
+            "--env",
+            &home_env,
+            "--entrypoint",
+            "sleep",
+            image,
+            "infinity",
+        ])
+        .await
+        .map_err(|e| {
+            OoreError::BuildExecution(format!("Failed to start container from {}: {}", image, e))
+        })?;
+
+        tracing::debug!("Started build container {} from {}", name, image);
+        Ok(())
+    }
+
+    fn home_dir(&self, workspace: &Path) -> Option<PathBuf> {
+        Some(home_path(workspace))
+    }
+
+    async fn execute_step(
+        &self,
+        workspace: &Path,
+        script: &str,
+        env: &HashMap<String, String>,
+        timeout_secs: u64,
+        log_dir: &Path,
+        step_index: i32,
+        secrets: &SecretMasker,
+        output: Option<&OutputSender>,
+        cancel_rx: &mut watch::Receiver<bool>,
+    ) -> Result<StepResult> {
+        let name = container_name(workspace);
+        let mut command = Command::new(&self.config.runtime);
+        command.arg("exec").arg("--workdir").arg(workspace);
+
+        // `--env KEY` copies the value from the runtime CLI's environment,
+        // which keeps secrets off its command line. PATH and HOME would change
+        // how the CLI itself runs, so they're passed by value.
+        for (key, value) in env {
+            if key == "PATH" || key == "HOME" {
+                command.arg("--env").arg(format!("{}={}", key, value));
+            } else {
+                command.arg("--env").arg(key).env(key, value);
+            }
+        }
+        command.arg(&name).arg("/bin/bash").arg("-c").arg(script);
+
+        let result = run_logged(
+            command,
+            &self.limits,
+            timeout_secs,
+            log_dir,
+            step_index,
+            secrets,
+            output,
+            cancel_rx,
+        )
+        .await;
+
+        // Killing the runtime CLI leaves the step running in the container
+        match &result {
+            Err(OoreError::BuildCancelled) => {
+                if let Err(e) = self.runtime(&["kill", &name]).await {
+                    tracing::warn!("Failed to kill build container {}: {}", name, e);
+                }
+            }
+            Err(OoreError::BuildTimeout(_)) => {
+                // Later steps may still run, so the container comes back up
+                if let Err(e) = self.runtime(&["restart", "--time", "0", &name]).await {
+                    tracing::warn!("Failed to restart build container {}: {}", name, e);
+                }
+            }
+            _ => {}
+        }
+
+        result
+    }
+
+    async fn cleanup(&self, workspace: &Path) -> Result<()> {
+        let name = container_name(workspace);
+        if let Err(e) = self.runtime(&["rm", "--force", &name]).await {
+            tracing::debug!("Failed to remove build container {}: {}", name, e);
+        }
+
+        let home = home_path(workspace);
+        if home.exists() {
+            tokio::fs::remove_dir_all(&home).await?;
+        }
+
+        self.host.cleanup(workspace).await
+    }
+}
+
+/// CPU and memory multiplier for a workflow's instance type.
+fn instance_scale(instance_type: Option<&str>) -> Result<u32> {
+    match instance_type {
+        None | Some("linux") => Ok(1),
+        Some("linux_x2") => Ok(2),
+        Some("linux_x4") => Ok(4),
+        Some(other) if other.starts_with("mac") => Err(OoreError::Configuration(format!(
+            "Instance type '{}' needs macOS and can't run in a container",
+            other
+        ))),
+        Some(other) => Err(OoreError::Configuration(format!(
+            "Unknown instance type '{}'",
+            other
+        ))),
+    }
+}
+
+/// Name of the container for the build using `workspace`.
+fn container_name(workspace: &Path) -> String {
+    let id: String = workspace
+        .file_name()
+        .map(|name| name.to_string_lossy())
+        .unwrap_or_default()
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '-' || c == '_' {
+                c
+            } else {
+                '-'
+            }
+        })
+        .collect();
+    format!("oore-build-{}", id.to_lowercase())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::os::unix::fs::PermissionsExt;
+
+    use crate::pipeline::MASK;
+
+    /// Writes a fake runtime that logs its arguments and runs `exec`
+    /// commands on the host.
+    fn fake_runtime(dir: &Path) -> (PathBuf, PathBuf) {
+        let log = dir.join("runtime.log");
+        let runtime = dir.join("runtime");
+        std::fs::write(
+            &runtime,
+            format!(
+                r#"#!/bin/bash
+echo "$@" >> '{}'
+if [ "$1" = exec ]; then
+    while [ "${{1#oore-build-}}" = "$1" ]; do shift; done
+    shift
+    exec "$@"
+fi
+"#,
+                log.display()
+            ),
+        )
+        .unwrap();
+        std::fs::set_permissions(&runtime, std::fs::Permissions::from_mode(0o755)).unwrap();
+        (runtime, log)
+    }
+
+    fn workflow(instance_type: Option<&str>, image: Option<&str>) -> Workflow {
+        let mut workflow = crate::pipeline::create_minimal_pipeline("true")
+            .workflows
+            .remove("default")
+            .unwrap();
+        workflow.instance_type = instance_type.map(String::from);
+        workflow.image = image.map(String::from);
+        workflow
+    }
+
+    #[test]
+    fn test_instance_scale() {
+        assert_eq!(instance_scale(None).unwrap(), 1);
+        assert_eq!(instance_scale(Some("linux_x4")).unwrap(), 4);
+        assert!(instance_scale(Some("mac_mini_m2")).is_err());
+        assert!(instance_scale(Some("huge")).is_err());
+    }
+
+    #[test]
+    fn test_container_paths() {
+        let workspace = Path::new("/var/lib/oore/workspaces/01HXYZ");
+        assert_eq!(container_name(workspace), "oore-build-01hxyz");
+        assert_eq!(
+            home_path(workspace),
+            Path::new("/var/lib/oore/workspaces/01HXYZ.home")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_container_build_lifecycle() {
+        let dir = tempfile::tempdir().unwrap();
+        let (runtime, log) = fake_runtime(dir.path());
+        let config = ContainerConfig {
+            runtime: runtime.to_str().unwrap().to_string(),
+            cpus: 1.5,
+            memory_mb: 1024,
+            ..Default::default()
+        };
+        let executor = ContainerExecutor::new(ShellExecutor::new(), config);
+
+        let workspace = dir.path().join("build1");
+        std::fs::create_dir(&workspace).unwrap();
+        executor
+            .prepare_build(
+                &workspace,
+                &workflow(Some("linux_x2"), Some("flutter:3.24")),
+            )
+            .await
+            .unwrap();
+        assert!(home_path(&workspace).is_dir());
+
+        let logs = std::fs::read_to_string(&log).unwrap();
+        let run = logs.lines().find(|l| l.starts_with("run ")).unwrap();
+        assert!(run.contains("--name oore-build-build1"));
+        assert!(run.contains("--cpus 3 --memory 2048m"));
+        assert!(run.contains(&format!("--volume {ws}:{ws}", ws = workspace.display())));
+        assert!(run.ends_with("--entrypoint sleep flutter:3.24 infinity"));
+
+        let env = HashMap::from([("SECRET_TOKEN".to_string(), "s3cret".to_string())]);
+        let (_tx, mut cancel_rx) = watch::channel(false);
+        let logs_dir = dir.path().join("logs");
+        let result = executor
+            .execute_step(
+                &workspace,
+                "echo \"token=$SECRET_TOKEN\"",
+                &env,
+                60,
+                &logs_dir,
+                0,
+                &SecretMasker::new(["s3cret"]),
+                None,
+                &mut cancel_rx,
+            )
+            .await
+            .unwrap();
+        assert_eq!(result.exit_code, 0);
+        let stdout = std::fs::read_to_string(&result.stdout_path).unwrap();
+        assert_eq!(stdout.trim(), format!("token={}", MASK));
+
+        // The secret is passed by name only
+        let logs = std::fs::read_to_string(&log).unwrap();
+        let exec = logs.lines().find(|l| l.starts_with("exec ")).unwrap();
+        assert!(exec.contains("--env SECRET_TOKEN oore-build-build1"));
+        assert!(!logs.contains("s3cret"));
+
+        executor.cleanup(&workspace).await.unwrap();
+        assert!(!workspace.exists());
+        assert!(!home_path(&workspace).exists());
+        let logs = std::fs::read_to_string(&log).unwrap();
+        assert!(logs
+            .lines()
+            .last()
+            .unwrap()
+            .starts_with("rm --force oore-build-build1"));
+    }
+
+    #[tokio::test]
+    async fn test_mac_instance_type_is_rejected() {
+        let dir = tempfile::tempdir().unwrap();
+        let (runtime, log) = fake_runtime(dir.path());
+        let config = ContainerConfig {
+            runtime: runtime.to_str().unwrap().to_string(),
+            ..Default::default()
+        };
+        let executor = ContainerExecutor::new(ShellExecutor::new(), config);
+
+        let result = executor
+            .prepare_build(dir.path(), &workflow(Some("mac_mini_m2"), None))
+            .await;
+        assert!(matches!(result, Err(OoreError::Configuration(_))));
+        assert!(!log.exists());
+    }
+}
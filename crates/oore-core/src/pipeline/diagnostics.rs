@@ -0,0 +1,422 @@
+//! Helpers for structured pipeline diagnostics.
+//!
+//! Paths use the `workflows.ios.scripts[0].script` form. Source locations are
+//! found by walking the indentation of the original YAML or HUML text, so
+//! values that came from an anchor or merge key resolve to the nearest
+//! enclosing key that appears in the source.
+
+use crate::models::{DiagnosticSeverity, PipelineDiagnostic};
+use crate::publishing;
+
+/// One step in a diagnostic path.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum PathSegment {
+    Key(String),
+    Index(usize),
+}
+
+/// Codemagic workflow fields that oore does not implement.
+const UNSUPPORTED_CODEMAGIC_FIELDS: &[&str] = &[
+    "groups",
+    "integrations",
+    "labels",
+    "working_directory",
+];
+
+const TOP_LEVEL_FIELDS: &[&str] = &["workflows", "definitions", "includes"];
+const WORKFLOW_FIELDS: &[&str] = &[
+    "name",
+    "max_build_duration",
+    "instance_type",
+    "image",
+    "environment",
+    "triggering",
+    "when",
+    "scripts",
+    "artifacts",
+    "cache",
+    "clone",
+    "publishing",
+];
+const ENVIRONMENT_FIELDS: &[&str] = &[
+    "vars",
+    "groups",
+    "flutter",
+    "xcode",
+    "cocoapods",
+    "android_signing",
+    "ios_signing",
+];
+const IOS_SIGNING_FIELDS: &[&str] = &["distribution_type", "bundle_identifier"];
+const TRIGGERING_FIELDS: &[&str] = &[
+    "events",
+    "branch_patterns",
+    "tag_patterns",
+    "cancel_previous_builds",
+];
+const BRANCH_PATTERN_FIELDS: &[&str] = &["include", "exclude"];
+const WHEN_FIELDS: &[&str] = &["changeset"];
+const CHANGESET_FIELDS: &[&str] = &["includes", "excludes"];
+const CACHE_FIELDS: &[&str] = &["cache_paths"];
+const CLONE_FIELDS: &[&str] = &["depth", "submodules", "lfs", "sparse_paths"];
+const STEP_FIELDS: &[&str] = &["name", "script", "timeout", "ignore_failure"];
+const GOOGLE_PLAY_FIELDS: &[&str] = &[
+    "credentials",
+    "package_name",
+    "track",
+    "in_app_update_priority",
+    "rollout_fraction",
+    "submit_as_draft",
+];
+const APP_STORE_CONNECT_FIELDS: &[&str] = &[
+    "api_key",
+    "submit_to_testflight",
+    "beta_groups",
+    "submit_to_app_review",
+];
+const FIREBASE_FIELDS: &[&str] = &["app_id", "groups", "testers", "release_notes"];
+
+/// Fields accepted under `parent`, used for "did you mean" suggestions.
+fn known_fields(parent: &[PathSegment]) -> Vec<&'static str> {
+    use PathSegment::{Index, Key};
+
+    match parent {
+        [] => TOP_LEVEL_FIELDS.to_vec(),
+        [Key(workflows), Key(_)] if workflows == "workflows" => WORKFLOW_FIELDS.to_vec(),
+        [.., Key(scripts), Index(_)] if scripts == "scripts" => STEP_FIELDS.to_vec(),
+        [.., Key(key)] => match key.as_str() {
+            "environment" => ENVIRONMENT_FIELDS.to_vec(),
+            "ios_signing" => IOS_SIGNING_FIELDS.to_vec(),
+            "triggering" => TRIGGERING_FIELDS.to_vec(),
+            "branch_patterns" | "tag_patterns" => BRANCH_PATTERN_FIELDS.to_vec(),
+            "when" => WHEN_FIELDS.to_vec(),
+            "changeset" => CHANGESET_FIELDS.to_vec(),
+            "cache" => CACHE_FIELDS.to_vec(),
+            "clone" => CLONE_FIELDS.to_vec(),
+            "publishing" => publishing::supported_keys().collect(),
+            "google_play" => GOOGLE_PLAY_FIELDS.to_vec(),
+            "app_store_connect" => APP_STORE_CONNECT_FIELDS.to_vec(),
+            "firebase" => FIREBASE_FIELDS.to_vec(),
+            _ => Vec::new(),
+        },
+        _ => Vec::new(),
+    }
+}
+
+/// Closest known field to `unknown`, if any is plausibly a typo of it.
+fn closest_field<'a>(unknown: &str, candidates: &[&'a str]) -> Option<&'a str> {
+    let max_distance = (unknown.len() / 3).max(2);
+    candidates
+        .iter()
+        .map(|candidate| (strsim::damerau_levenshtein(unknown, candidate), *candidate))
+        .filter(|(distance, _)| *distance <= max_distance)
+        .min_by_key(|(distance, _)| *distance)
+        .map(|(_, candidate)| candidate)
+}
+
+/// Diagnostic for a field that the pipeline model does not use.
+pub(crate) fn unknown_field(path: &[PathSegment], strict: bool) -> PipelineDiagnostic {
+    let Some((PathSegment::Key(field), parent)) = path.split_last() else {
+        return PipelineDiagnostic {
+            severity: severity(strict),
+            path: format_path(path),
+            message: "Unexpected value".to_string(),
+            line: None,
+            column: None,
+            suggestion: None,
+        };
+    };
+
+    let in_workflow =
+        matches!(parent, [PathSegment::Key(w), PathSegment::Key(_)] if w == "workflows");
+    let in_publishing = matches!(parent.last(), Some(PathSegment::Key(p)) if p == "publishing");
+
+    let message = if in_workflow && UNSUPPORTED_CODEMAGIC_FIELDS.contains(&field.as_str()) {
+        format!("Codemagic field '{}' is not supported", field)
+    } else if in_publishing {
+        format!("Publishing target '{}' is not supported", field)
+    } else {
+        format!("Unknown field '{}'", field)
+    };
+
+    PipelineDiagnostic {
+        severity: severity(strict),
+        path: format_path(path),
+        message,
+        line: None,
+        column: None,
+        suggestion: closest_field(field, &known_fields(parent))
+            .map(|candidate| format!("did you mean `{}`?", candidate)),
+    }
+}
+
+fn severity(strict: bool) -> DiagnosticSeverity {
+    if strict {
+        DiagnosticSeverity::Error
+    } else {
+        DiagnosticSeverity::Warning
+    }
+}
+
+/// Converts a path reported by `serde_ignored`.
+pub(crate) fn ignored_path(path: &serde_ignored::Path) -> Vec<PathSegment> {
+    let mut segments = Vec::new();
+    let mut current = path;
+    loop {
+        current = match current {
+            serde_ignored::Path::Root => break,
+            serde_ignored::Path::Seq { parent, index } => {
+                segments.push(PathSegment::Index(*index));
+                parent
+            }
+            serde_ignored::Path::Map { parent, key } => {
+                segments.push(PathSegment::Key(key.clone()));
+                parent
+            }
+            serde_ignored::Path::Some { parent }
+            | serde_ignored::Path::NewtypeStruct { parent }
+            | serde_ignored::Path::NewtypeVariant { parent } => parent,
+        };
+    }
+    segments.reverse();
+    segments
+}
+
+/// Converts a path reported by `serde_path_to_error`.
+pub(crate) fn error_path(path: &serde_path_to_error::Path) -> Vec<PathSegment> {
+    path.iter()
+        .filter_map(|segment| match segment {
+            serde_path_to_error::Segment::Seq { index } => Some(PathSegment::Index(*index)),
+            serde_path_to_error::Segment::Map { key } => Some(PathSegment::Key(key.clone())),
+            serde_path_to_error::Segment::Enum { variant } => {
+                Some(PathSegment::Key(variant.clone()))
+            }
+            serde_path_to_error::Segment::Unknown => None,
+        })
+        .collect()
+}
+
+/// Formats a path as `workflows.ios.scripts[0].script`.
+pub(crate) fn format_path(path: &[PathSegment]) -> String {
+    let mut formatted = String::new();
+    for segment in path {
+        match segment {
+            PathSegment::Key(key) => {
+                if !formatted.is_empty() {
+                    formatted.push('.');
+                }
+                formatted.push_str(key);
+            }
+            PathSegment::Index(index) => formatted.push_str(&format!("[{}]", index)),
+        }
+    }
+    formatted
+}
+
+/// Parses a path produced by [`format_path`].
+fn parse_path(path: &str) -> Vec<PathSegment> {
+    let mut segments = Vec::new();
+    for part in path.split('.').filter(|part| !part.is_empty()) {
+        let (key, mut indexes) = part.split_once('[').unwrap_or((part, ""));
+        if !key.is_empty() {
+            segments.push(PathSegment::Key(key.to_string()));
+        }
+        while let Some((index, rest)) = indexes.split_once(']') {
+            if let Ok(index) = index.parse() {
+                segments.push(PathSegment::Index(index));
+            }
+            indexes = rest.trim_start_matches('[');
+        }
+    }
+    segments
+}
+
+/// A non-blank, non-comment source line.
+#[derive(Clone, Copy)]
+struct SourceLine<'a> {
+    number: usize,
+    indent: usize,
+    text: &'a str,
+}
+
+/// Whether `text` is a `key:` entry for `key`, optionally quoted.
+fn is_key_line(text: &str, key: &str) -> bool {
+    let unquoted = text.strip_prefix(key);
+    let double = text
+        .strip_prefix('"')
+        .and_then(|t| t.strip_prefix(key))
+        .and_then(|t| t.strip_prefix('"'));
+    let single = text
+        .strip_prefix('\'')
+        .and_then(|t| t.strip_prefix(key))
+        .and_then(|t| t.strip_prefix('\''));
+    [unquoted, double, single]
+        .into_iter()
+        .flatten()
+        .any(|rest| rest.trim_start().starts_with(':'))
+}
+
+fn is_list_item(text: &str) -> bool {
+    text == "-" || text.starts_with("- ")
+}
+
+/// Finds the 1-based line and column of `path` in YAML or HUML `source`.
+///
+/// Returns the location of the deepest part of the path that could be found,
+/// or `None` if not even the first segment appears in the source.
+pub fn locate(source: &str, path: &str) -> Option<(u32, u32)> {
+    let mut block: Vec<SourceLine> = source
+        .lines()
+        .enumerate()
+        .filter_map(|(i, raw)| {
+            let text = raw.trim_start();
+            (!text.is_empty() && !text.starts_with('#') && !text.starts_with('%')).then_some(
+                SourceLine {
+                    number: i + 1,
+                    indent: raw.len() - text.len(),
+                    text,
+                },
+            )
+        })
+        .collect();
+
+    let mut location = None;
+    for segment in parse_path(path) {
+        let Some(level) = block.first().map(|line| line.indent) else {
+            break;
+        };
+        let mut entries = block
+            .iter()
+            .enumerate()
+            .filter(|(_, line)| line.indent == level);
+        let found = match &segment {
+            PathSegment::Key(key) => entries.find(|(_, line)| is_key_line(line.text, key)),
+            PathSegment::Index(index) => entries
+                .filter(|(_, line)| is_list_item(line.text))
+                .nth(*index),
+        };
+        let Some((position, line)) = found else {
+            break;
+        };
+        let line = *line;
+        location = Some((line.number as u32, line.indent as u32 + 1));
+
+        let mut children = Vec::new();
+        if let PathSegment::Index(_) = segment {
+            // The first key of a list item shares the line with its dash
+            let rest = line.text[1..].trim_start();
+            if !rest.is_empty() {
+                children.push(SourceLine {
+                    number: line.number,
+                    indent: line.indent + line.text.len() - rest.len(),
+                    text: rest,
+                });
+            }
+        }
+        children.extend(block[position + 1..].iter().copied().take_while(|child| {
+            child.indent > line.indent
+                // YAML allows a key's list items at the key's own indentation
+                || (child.indent == line.indent
+                    && matches!(segment, PathSegment::Key(_))
+                    && is_list_item(child.text))
+        }));
+        block = children;
+    }
+
+    location
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(k: &str) -> PathSegment {
+        PathSegment::Key(k.to_string())
+    }
+
+    #[test]
+    fn test_format_and_parse_path() {
+        let path = vec![
+            key("workflows"),
+            key("ios"),
+            key("scripts"),
+            PathSegment::Index(2),
+            key("script"),
+        ];
+        assert_eq!(format_path(&path), "workflows.ios.scripts[2].script");
+        assert_eq!(parse_path("workflows.ios.scripts[2].script"), path);
+        assert_eq!(format_path(&[]), "");
+    }
+
+    #[test]
+    fn test_unknown_field_suggestion() {
+        let path = vec![
+            key("workflows"),
+            key("ios"),
+            key("triggering"),
+            key("branch_pattern"),
+        ];
+        let diagnostic = unknown_field(&path, false);
+        assert_eq!(diagnostic.severity, DiagnosticSeverity::Warning);
+        assert_eq!(diagnostic.path, "workflows.ios.triggering.branch_pattern");
+        assert_eq!(diagnostic.message, "Unknown field 'branch_pattern'");
+        assert_eq!(
+            diagnostic.suggestion.as_deref(),
+            Some("did you mean `branch_patterns`?")
+        );
+
+        let path = vec![key("workflows"), key("ios"), key("integrations")];
+        let diagnostic = unknown_field(&path, true);
+        assert_eq!(diagnostic.severity, DiagnosticSeverity::Error);
+        assert_eq!(
+            diagnostic.message,
+            "Codemagic field 'integrations' is not supported"
+        );
+        assert!(diagnostic.suggestion.is_none());
+    }
+
+    #[test]
+    fn test_locate() {
+        let yaml = r#"workflows:
+  ios:
+    scripts:
+    - name: Build
+      script: flutter build ios
+    - script: echo done
+    triggering:
+      branch_pattern:
+        include: [main]
+"#;
+        assert_eq!(locate(yaml, "workflows.ios"), Some((2, 3)));
+        assert_eq!(locate(yaml, "workflows.ios.scripts[1]"), Some((6, 5)));
+        assert_eq!(
+            locate(yaml, "workflows.ios.scripts[0].script"),
+            Some((5, 7))
+        );
+        assert_eq!(
+            locate(yaml, "workflows.ios.triggering.branch_pattern"),
+            Some((8, 7))
+        );
+        // Falls back to the deepest key that exists
+        assert_eq!(locate(yaml, "workflows.ios.environment.vars"), Some((2, 3)));
+        assert_eq!(locate(yaml, "missing"), None);
+    }
+
+    #[test]
+    fn test_locate_huml() {
+        let huml = r#"%HUML v0.2.0
+
+workflows::
+  build::
+    scripts::
+      - ::
+        name: "Build"
+        script: "flutter build apk"
+"#;
+        assert_eq!(locate(huml, "workflows.build.scripts[0]"), Some((6, 7)));
+        assert_eq!(
+            locate(huml, "workflows.build.scripts[0].script"),
+            Some((8, 9))
+        );
+    }
+}
@@ -0,0 +1,303 @@
+//! Secret masking for build logs.
+//!
+//! Replaces secret values (and their base64/URL-encoded forms) with
+//! [`MASK`] before step output reaches disk. Masking works on a byte
+//! stream, so a secret split across reads is still caught.
+
+use std::collections::HashSet;
+
+use base64::Engine;
+
+/// Replacement written in place of a secret.
+pub const MASK: &str = "********";
+
+/// Secrets shorter than this are not masked.
+///
+/// Masking values like `1` or `true` would shred otherwise useful logs
+/// without protecting anything meaningful.
+pub const MIN_SECRET_LEN: usize = 4;
+
+/// A set of secret values to mask in build output.
+#[derive(Debug, Clone, Default)]
+pub struct SecretMasker {
+    /// Byte patterns to mask, longest first so the longest match wins.
+    patterns: Vec<Vec<u8>>,
+}
+
+impl SecretMasker {
+    /// Creates a masker for the given secret values.
+    ///
+    /// Each secret is also matched in standard and URL-safe base64 (at any
+    /// alignment within the encoded data) and in percent-encoded form.
+    pub fn new<I, S>(secrets: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        let mut patterns: HashSet<Vec<u8>> = HashSet::new();
+
+        for secret in secrets {
+            let secret = secret.as_ref();
+            if secret.len() < MIN_SECRET_LEN {
+                continue;
+            }
+
+            patterns.insert(secret.as_bytes().to_vec());
+            patterns.extend(base64_variants(
+                &base64::engine::general_purpose::STANDARD_NO_PAD,
+                secret,
+            ));
+            patterns.extend(base64_variants(
+                &base64::engine::general_purpose::URL_SAFE_NO_PAD,
+                secret,
+            ));
+            patterns.insert(percent_encode(secret).into_bytes());
+        }
+
+        let mut patterns: Vec<Vec<u8>> = patterns
+            .into_iter()
+            .filter(|p| p.len() >= MIN_SECRET_LEN)
+            .collect();
+        patterns.sort_by(|a, b| b.len().cmp(&a.len()).then_with(|| a.cmp(b)));
+
+        Self { patterns }
+    }
+
+    /// Returns true if there is nothing to mask.
+    pub fn is_empty(&self) -> bool {
+        self.patterns.is_empty()
+    }
+
+    /// Masks a complete string, returning the masked text and the number of replacements.
+    pub fn mask(&self, input: &str) -> (String, usize) {
+        let mut stream = self.stream();
+        let mut output = stream.push(input.as_bytes());
+        output.extend(stream.finish());
+        (
+            String::from_utf8_lossy(&output).into_owned(),
+            stream.masked_count(),
+        )
+    }
+
+    /// Creates a streaming masker that carries partial matches across chunks.
+    pub fn stream(&self) -> MaskingStream {
+        MaskingStream {
+            masker: self.clone(),
+            pending: Vec::new(),
+            masked: 0,
+        }
+    }
+}
+
+/// Incremental masker for a single output stream.
+pub struct MaskingStream {
+    masker: SecretMasker,
+    /// Bytes that might be the start of a secret, held until more data arrives.
+    pending: Vec<u8>,
+    masked: usize,
+}
+
+impl MaskingStream {
+    /// Feeds a chunk of output and returns the bytes that are safe to write.
+    ///
+    /// A trailing fragment that could still turn into a secret is held back
+    /// until the next call to [`push`](Self::push) or [`finish`](Self::finish).
+    pub fn push(&mut self, chunk: &[u8]) -> Vec<u8> {
+        self.pending.extend_from_slice(chunk);
+        self.drain(false)
+    }
+
+    /// Flushes any held-back bytes at end of stream.
+    pub fn finish(&mut self) -> Vec<u8> {
+        self.drain(true)
+    }
+
+    /// Number of secrets replaced so far.
+    pub fn masked_count(&self) -> usize {
+        self.masked
+    }
+
+    fn drain(&mut self, at_eof: bool) -> Vec<u8> {
+        if self.masker.is_empty() {
+            return std::mem::take(&mut self.pending);
+        }
+
+        let mut output = Vec::with_capacity(self.pending.len());
+        let mut i = 0;
+
+        'scan: while i < self.pending.len() {
+            let rest = &self.pending[i..];
+
+            for pattern in &self.masker.patterns {
+                if rest.starts_with(pattern) {
+                    output.extend_from_slice(MASK.as_bytes());
+                    self.masked += 1;
+                    i += pattern.len();
+                    continue 'scan;
+                }
+                if !at_eof && rest.len() < pattern.len() && pattern.starts_with(rest) {
+                    // Possible secret cut off at the end of the buffer
+                    break 'scan;
+                }
+            }
+
+            output.push(self.pending[i]);
+            i += 1;
+        }
+
+        self.pending.drain(..i);
+        output
+    }
+}
+
+/// Base64 patterns for `secret` at each of the three possible byte offsets.
+///
+/// Encoded text only lines up with the secret when it starts on a 3-byte
+/// boundary, so it's also encoded behind one and two filler bytes. Edge
+/// characters that mix in neighbouring bytes are trimmed, which leaves a
+/// pattern that matches whatever surrounds the secret.
+fn base64_variants(engine: &impl Engine, secret: &str) -> Vec<Vec<u8>> {
+    (0..3)
+        .map(|offset| {
+            let mut data = vec![0u8; offset];
+            data.extend_from_slice(secret.as_bytes());
+            let encoded = engine.encode(&data).into_bytes();
+
+            let start = (offset * 8).div_ceil(6);
+            let end = if data.len() % 3 == 0 {
+                encoded.len()
+            } else {
+                encoded.len() - 1
+            };
+            encoded[start..end].to_vec()
+        })
+        .collect()
+}
+
+/// Percent-encodes everything outside the RFC 3986 unreserved set.
+fn percent_encode(value: &str) -> String {
+    let mut encoded = String::with_capacity(value.len());
+    for byte in value.bytes() {
+        if byte.is_ascii_alphanumeric() || matches!(byte, b'-' | b'_' | b'.' | b'~') {
+            encoded.push(byte as char);
+        } else {
+            encoded.push_str(&format!("%{:02X}", byte));
+        }
+    }
+    encoded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mask_plain_secret() {
+        let masker = SecretMasker::new(["hunter22"]);
+        let (masked, count) = masker.mask("password is hunter22, again hunter22\n");
+        assert_eq!(masked, "password is ********, again ********\n");
+        assert_eq!(count, 2);
+    }
+
+    #[test]
+    fn test_mask_ignores_short_secrets() {
+        let masker = SecretMasker::new(["abc", ""]);
+        assert!(masker.is_empty());
+        let (masked, count) = masker.mask("abc");
+        assert_eq!(masked, "abc");
+        assert_eq!(count, 0);
+    }
+
+    #[test]
+    fn test_mask_base64_forms() {
+        let secret = "s3cr3t?value>";
+        let masker = SecretMasker::new([secret]);
+
+        let standard = base64::engine::general_purpose::STANDARD.encode(secret);
+        let url_safe = base64::engine::general_purpose::URL_SAFE_NO_PAD.encode(secret);
+
+        let (masked, _) = masker.mask(&format!("std={} url={}", standard, url_safe));
+        assert!(!masked.contains(&standard.trim_end_matches('=').to_string()));
+        assert!(!masked.contains(&url_safe));
+        assert!(masked.starts_with("std=********"));
+    }
+
+    #[test]
+    fn test_mask_base64_at_any_offset() {
+        let secret = "s3cr3t?value>";
+        let masker = SecretMasker::new([secret]);
+
+        // e.g. `echo "x$SECRET" | base64`
+        for prefix in ["", "x", "xy", "xyz"] {
+            let encoded =
+                base64::engine::general_purpose::STANDARD.encode(format!("{}{}\n", prefix, secret));
+            let (masked, count) = masker.mask(&encoded);
+            assert_eq!(count, 1, "prefix {:?}: {}", prefix, masked);
+        }
+    }
+
+    #[test]
+    fn test_mask_url_encoded_form() {
+        let masker = SecretMasker::new(["p@ss word/1"]);
+        let (masked, count) = masker.mask("https://host/?token=p%40ss%20word%2F1&x=1");
+        assert_eq!(masked, "https://host/?token=********&x=1");
+        assert_eq!(count, 1);
+    }
+
+    #[test]
+    fn test_mask_secret_split_across_chunks() {
+        let masker = SecretMasker::new(["supersecret"]);
+        let mut stream = masker.stream();
+
+        let mut output = stream.push(b"value: super");
+        assert_eq!(output, b"value: ");
+        output.extend(stream.push(b"secret\n"));
+        output.extend(stream.finish());
+
+        assert_eq!(output, b"value: ********\n");
+        assert_eq!(stream.masked_count(), 1);
+    }
+
+    #[test]
+    fn test_mask_partial_prefix_released_at_eof() {
+        let masker = SecretMasker::new(["supersecret"]);
+        let mut stream = masker.stream();
+
+        let mut output = stream.push(b"just super");
+        output.extend(stream.push(b"b\n"));
+        assert_eq!(output, b"just superb\n");
+
+        assert!(stream.push(b"supers").is_empty());
+        assert_eq!(stream.finish(), b"supers");
+        assert_eq!(stream.masked_count(), 0);
+    }
+
+    #[test]
+    fn test_mask_longest_match_wins() {
+        let masker = SecretMasker::new(["token", "token-extended"]);
+        let (masked, count) = masker.mask("token-extended token");
+        assert_eq!(masked, "******** ********");
+        assert_eq!(count, 2);
+    }
+
+    #[test]
+    fn test_mask_multiline_secret() {
+        let secret = "-----BEGIN KEY-----\nabc123\n-----END KEY-----";
+        let masker = SecretMasker::new([secret]);
+        let mut stream = masker.stream();
+
+        let mut output = Vec::new();
+        for line in format!("{}\ndone\n", secret).split_inclusive('\n') {
+            output.extend(stream.push(line.as_bytes()));
+        }
+        output.extend(stream.finish());
+
+        assert_eq!(output, b"********\ndone\n");
+    }
+
+    #[test]
+    fn test_percent_encode() {
+        assert_eq!(percent_encode("a-b_c.d~e"), "a-b_c.d~e");
+        assert_eq!(percent_encode("a b/c"), "a%20b%2Fc");
+    }
+}
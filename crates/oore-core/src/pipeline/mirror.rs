@@ -0,0 +1,384 @@
+//! Persistent bare git mirrors used to speed up cloning.
+//!
+//! Each repository gets one bare mirror, named after a hash of its clone URL.
+//! Builds fetch into the mirror and clone their workspace from it with the
+//! workflow's clone options, so only new objects come over the network and
+//! only what the checkout needs is copied. The workspace's origin is pointed
+//! back at the provider once it's checked out, so it doesn't depend on the
+//! mirror afterwards. Credentials are passed on the command line for each
+//! fetch and never stored in the mirror.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime};
+
+use tokio::sync::OwnedMutexGuard;
+
+use crate::crypto::sha256_hex;
+use crate::error::{OoreError, Result};
+use crate::models::CloneConfig;
+
+use super::executor::{clone_args, run_git};
+
+/// Mirrors not fetched into for this long are removed by maintenance.
+const MIRROR_RETENTION: Duration = Duration::from_secs(30 * 24 * 60 * 60);
+
+/// File touched after every fetch, marking when the mirror was last used.
+const LAST_USED_FILE: &str = "oore-last-used";
+
+/// Disk use of the mirror directory.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct MirrorStats {
+    /// Number of mirrors.
+    pub mirrors: usize,
+    /// Total size of all mirrors in bytes.
+    pub total_bytes: u64,
+}
+
+/// Bare repository mirrors under one directory.
+#[derive(Debug, Clone)]
+pub struct GitMirrors {
+    dir: PathBuf,
+    /// Per-mirror locks, keyed by mirror directory name.
+    locks: Arc<Mutex<HashMap<String, Arc<tokio::sync::Mutex<()>>>>>,
+}
+
+impl GitMirrors {
+    /// Creates a mirror store under `dir`.
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self {
+            dir: dir.into(),
+            locks: Arc::default(),
+        }
+    }
+
+    /// Path of the mirror for `clone_url`.
+    pub fn mirror_path(&self, clone_url: &str) -> PathBuf {
+        self.dir.join(mirror_name(clone_url))
+    }
+
+    /// Locks a mirror. Fetches, reference clones and maintenance of the same
+    /// mirror never overlap.
+    async fn lock(&self, name: &str) -> OwnedMutexGuard<()> {
+        let lock = {
+            let mut locks = self.locks.lock().unwrap_or_else(|e| e.into_inner());
+            locks.entry(name.to_string()).or_default().clone()
+        };
+        lock.lock_owned().await
+    }
+
+    /// Clones the repository's mirror into `workspace`, making sure it has
+    /// `commit_sha`.
+    ///
+    /// `fetch_url` is the clone URL with any credentials injected. The
+    /// workspace's origin is the mirror until the caller has checked out the
+    /// commit and points it back at the provider. On error the mirror is
+    /// removed if it turns out to be corrupt, and the caller should fall back
+    /// to a direct clone.
+    pub async fn clone_into(
+        &self,
+        clone_url: &str,
+        fetch_url: &str,
+        commit_sha: &str,
+        workspace: &Path,
+        config: &CloneConfig,
+    ) -> Result<()> {
+        let name = mirror_name(clone_url);
+        let mirror = self.dir.join(&name);
+        let _guard = self.lock(&name).await;
+
+        let result = async {
+            self.update(&mirror, fetch_url, commit_sha).await?;
+
+            let workspace_str = workspace
+                .to_str()
+                .ok_or_else(|| OoreError::GitClone("Invalid workspace path".to_string()))?;
+            let mirror_str = mirror
+                .to_str()
+                .ok_or_else(|| OoreError::GitClone("Invalid mirror path".to_string()))?;
+            let commit = format!("{}^{{commit}}", commit_sha);
+            if run_git(Some(&mirror), &["cat-file", "-e", &commit])
+                .await
+                .is_err()
+            {
+                return Err(OoreError::GitClone(format!(
+                    "Mirror doesn't have commit {}",
+                    &commit_sha[..7.min(commit_sha.len())]
+                )));
+            }
+            // Depth and filters are ignored for plain paths, so go through file://
+            let mirror_url = format!("file://{}", mirror_str);
+            let mut args = vec!["clone".to_string()];
+            args.extend(clone_args(config));
+            args.extend([mirror_url, workspace_str.to_string()]);
+            let args: Vec<&str> = args.iter().map(String::as_str).collect();
+            run_git(None, &args)
+                .await
+                .map_err(|e| OoreError::GitClone(format!("Mirror clone failed: {}", e)))
+        }
+        .await;
+
+        if result.is_err() && mirror.exists() && is_corrupt(&mirror).await {
+            tracing::warn!("Removing corrupt git mirror {}", mirror.display());
+            if let Err(e) = tokio::fs::remove_dir_all(&mirror).await {
+                tracing::warn!("Failed to remove git mirror {}: {}", mirror.display(), e);
+            }
+        }
+
+        result
+    }
+
+    /// Creates the mirror if needed and fetches branches, tags and the commit.
+    async fn update(&self, mirror: &Path, fetch_url: &str, commit_sha: &str) -> Result<()> {
+        let mirror_str = mirror
+            .to_str()
+            .ok_or_else(|| OoreError::GitClone("Invalid mirror path".to_string()))?;
+
+        if !mirror.exists() {
+            tokio::fs::create_dir_all(&self.dir).await?;
+            run_git(None, &["init", "--quiet", "--bare", mirror_str])
+                .await
+                .map_err(|e| OoreError::GitClone(format!("Mirror init failed: {}", e)))?;
+        }
+        // Workspaces clone from the mirror without file contents
+        run_git(Some(mirror), &["config", "uploadpack.allowFilter", "true"])
+            .await
+            .map_err(|e| OoreError::GitClone(format!("Mirror config failed: {}", e)))?;
+
+        run_git(
+            Some(mirror),
+            &[
+                "fetch",
+                "--quiet",
+                "--prune",
+                "--no-write-fetch-head",
+                fetch_url,
+                "+refs/heads/*:refs/heads/*",
+                "+refs/tags/*:refs/tags/*",
+            ],
+        )
+        .await
+        .map_err(|e| OoreError::GitClone(format!("Mirror fetch failed: {}", e)))?;
+
+        // Pull request heads aren't under refs/heads; fetch the commit itself
+        let commit = format!("{}^{{commit}}", commit_sha);
+        let missing = run_git(Some(mirror), &["cat-file", "-e", &commit])
+            .await
+            .is_err();
+        let fetch_args = [
+            "fetch",
+            "--quiet",
+            "--no-write-fetch-head",
+            fetch_url,
+            commit_sha,
+        ];
+        if missing && let Err(e) = run_git(Some(mirror), &fetch_args).await {
+            // Falling back to a direct clone may still work
+            tracing::debug!("Mirror fetch of {} failed: {}", commit_sha, e);
+        }
+
+        tokio::fs::write(mirror.join(LAST_USED_FILE), b"").await?;
+        Ok(())
+    }
+
+    /// Runs `git gc` on every mirror, removing mirrors that are corrupt or
+    /// haven't been used within the retention period.
+    ///
+    /// Returns the disk use afterwards.
+    pub async fn maintain(&self) -> Result<MirrorStats> {
+        for (name, path) in self.list().await? {
+            let _guard = self.lock(&name).await;
+
+            if last_used(&path).await.is_some_and(|used| {
+                SystemTime::now()
+                    .duration_since(used)
+                    .is_ok_and(|age| age > MIRROR_RETENTION)
+            }) {
+                tracing::info!("Removing unused git mirror {}", path.display());
+                tokio::fs::remove_dir_all(&path).await?;
+                continue;
+            }
+
+            if let Err(e) = run_git(Some(&path), &["gc", "--quiet", "--prune=now"]).await {
+                tracing::warn!("git gc failed for mirror {}: {}", path.display(), e);
+                if is_corrupt(&path).await {
+                    tracing::warn!("Removing corrupt git mirror {}", path.display());
+                    tokio::fs::remove_dir_all(&path).await?;
+                }
+            }
+        }
+
+        self.stats().await
+    }
+
+    /// Number of mirrors and their total size on disk.
+    pub async fn stats(&self) -> Result<MirrorStats> {
+        let mirrors = self.list().await?;
+        let count = mirrors.len();
+        let paths: Vec<PathBuf> = mirrors.into_iter().map(|(_, path)| path).collect();
+
+        let total_bytes = tokio::task::spawn_blocking(move || {
+            paths.iter().map(|path| dir_size(path)).sum::<u64>()
+        })
+        .await
+        .map_err(|e| OoreError::GitClone(format!("Mirror stats failed: {}", e)))?;
+
+        Ok(MirrorStats {
+            mirrors: count,
+            total_bytes,
+        })
+    }
+
+    /// Lists mirror directories with their names.
+    async fn list(&self) -> Result<Vec<(String, PathBuf)>> {
+        let mut mirrors = Vec::new();
+        let mut entries = match tokio::fs::read_dir(&self.dir).await {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(mirrors),
+            Err(e) => return Err(e.into()),
+        };
+
+        while let Some(entry) = entries.next_entry().await? {
+            let name = entry.file_name().to_string_lossy().into_owned();
+            if name.ends_with(".git") && entry.file_type().await?.is_dir() {
+                mirrors.push((name, entry.path()));
+            }
+        }
+
+        Ok(mirrors)
+    }
+}
+
+/// Directory name of the mirror for `clone_url`.
+fn mirror_name(clone_url: &str) -> String {
+    format!("{}.git", &sha256_hex(clone_url.as_bytes())[..32])
+}
+
+/// Returns whether git reports the mirror's object store as broken.
+async fn is_corrupt(mirror: &Path) -> bool {
+    run_git(
+        Some(mirror),
+        &[
+            "fsck",
+            "--connectivity-only",
+            "--no-progress",
+            "--no-dangling",
+        ],
+    )
+    .await
+    .is_err()
+}
+
+async fn last_used(mirror: &Path) -> Option<SystemTime> {
+    let metadata = match tokio::fs::metadata(mirror.join(LAST_USED_FILE)).await {
+        Ok(metadata) => metadata,
+        Err(_) => tokio::fs::metadata(mirror).await.ok()?,
+    };
+    metadata.modified().ok()
+}
+
+fn dir_size(path: &Path) -> u64 {
+    let Ok(entries) = std::fs::read_dir(path) else {
+        return 0;
+    };
+    entries
+        .flatten()
+        .map(|entry| match entry.file_type() {
+            Ok(t) if t.is_dir() => dir_size(&entry.path()),
+            Ok(t) if t.is_file() => entry.metadata().map(|m| m.len()).unwrap_or(0),
+            _ => 0,
+        })
+        .sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Creates a repository with one commit and returns its head.
+    fn init_repo(dir: &Path) -> String {
+        git(dir, &["init", "-q", "-b", "main"]);
+        std::fs::write(dir.join("README.md"), "hello").unwrap();
+        git(dir, &["add", "README.md"]);
+        git(dir, &["commit", "-q", "-m", "first"]);
+        git(dir, &["rev-parse", "HEAD"])
+    }
+
+    fn git(dir: &Path, args: &[&str]) -> String {
+        let output = std::process::Command::new("git")
+            .current_dir(dir)
+            .args(["-c", "user.name=oore", "-c", "user.email=oore@example.com"])
+            .args(args)
+            .output()
+            .unwrap();
+        assert!(output.status.success(), "git {:?} failed", args);
+        String::from_utf8(output.stdout).unwrap().trim().to_string()
+    }
+
+    #[tokio::test]
+    async fn test_clone_through_mirror() {
+        let dir = tempfile::tempdir().unwrap();
+        let remote = dir.path().join("remote");
+        std::fs::create_dir(&remote).unwrap();
+        let head = init_repo(&remote);
+        let url = remote.to_str().unwrap();
+
+        let mirrors = GitMirrors::new(dir.path().join("mirrors"));
+        let workspace = dir.path().join("ws1");
+        mirrors
+            .clone_into(url, url, &head, &workspace, &CloneConfig::default())
+            .await
+            .unwrap();
+
+        let mirror = mirrors.mirror_path(url);
+        assert!(mirror.join(LAST_USED_FILE).exists());
+        git(&mirror, &["cat-file", "-e", &head]);
+        // The workspace has its own objects
+        assert!(!workspace.join(".git/objects/info/alternates").exists());
+        git(&workspace, &["cat-file", "-e", &head]);
+
+        // A new commit is fetched into the existing mirror
+        std::fs::write(remote.join("README.md"), "changed").unwrap();
+        git(&remote, &["commit", "-q", "-am", "second"]);
+        let second = git(&remote, &["rev-parse", "HEAD"]);
+        let workspace = dir.path().join("ws2");
+        let config = CloneConfig {
+            depth: Some(1),
+            ..Default::default()
+        };
+        mirrors
+            .clone_into(url, url, &second, &workspace, &config)
+            .await
+            .unwrap();
+        git(&mirror, &["cat-file", "-e", &second]);
+        // Only the requested history is copied
+        git(&workspace, &["cat-file", "-e", &second]);
+        assert_eq!(git(&workspace, &["rev-list", "--count", &second]), "1");
+
+        let stats = mirrors.maintain().await.unwrap();
+        assert_eq!(stats.mirrors, 1);
+        assert!(stats.total_bytes > 0);
+    }
+
+    #[tokio::test]
+    async fn test_corrupt_mirror_is_removed() {
+        let dir = tempfile::tempdir().unwrap();
+        let remote = dir.path().join("remote");
+        std::fs::create_dir(&remote).unwrap();
+        let head = init_repo(&remote);
+        let url = remote.to_str().unwrap();
+
+        let mirrors = GitMirrors::new(dir.path().join("mirrors"));
+        let mirror = mirrors.mirror_path(url);
+        std::fs::create_dir_all(mirror.join("objects")).unwrap();
+        std::fs::write(mirror.join("HEAD"), "garbage").unwrap();
+
+        let workspace = dir.path().join("ws");
+        assert!(mirrors
+            .clone_into(url, url, &head, &workspace, &CloneConfig::default())
+            .await
+            .is_err());
+        assert!(!mirror.exists());
+    }
+}
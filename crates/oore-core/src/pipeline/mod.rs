@@ -3,10 +3,23 @@
 //! This module provides functionality for parsing, resolving, and executing
 //! Codemagic-compatible build pipelines.
 
+pub mod cache;
+pub mod changeset;
+pub mod container;
+pub mod diagnostics;
 pub mod executor;
+pub mod masking;
+pub mod mirror;
 pub mod parser;
 pub mod resolver;
+pub mod sandbox;
 
+pub use cache::*;
+pub use changeset::*;
+pub use container::*;
 pub use executor::*;
+pub use masking::*;
+pub use mirror::*;
 pub use parser::*;
 pub use resolver::*;
+pub use sandbox::*;
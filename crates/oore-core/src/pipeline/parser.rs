@@ -1,9 +1,17 @@
 //! Pipeline parser for Codemagic-compatible configuration.
 //!
-//! Supports both YAML and HUML formats.
+//! Supports both YAML and HUML formats. YAML configs may use anchors and
+//! merge keys (conventionally declared under `definitions:`) and, when loaded
+//! from a repository, pull in other files with `includes:`.
 
+use std::path::{Component, Path};
+
+use serde_yaml::{Mapping, Value};
+
+use super::diagnostics::{self, PathSegment};
 use crate::error::{OoreError, Result};
-use crate::models::{ParsedPipeline, Step, Workflow, WorkflowEnvironment};
+use crate::models::{DiagnosticSeverity, ParsedPipeline, PipelineDiagnostic, Step, Workflow, WorkflowEnvironment, validate_env_group_name};
+use crate::publishing::publishers_for;
 
 /// Format of the pipeline configuration.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -15,12 +23,25 @@ pub enum ConfigFormat {
 /// Supported Codemagic YAML fields (subset of full spec).
 ///
 /// ```yaml
+/// definitions:               # Anchors only; ignored after resolution
+///   scripts:
+///     - &flutter_build
+///       script: flutter build
+/// includes:                  # Repository config files only
+///   - path/from/repo/root.yaml
 /// workflows:
 ///   <name>:
 ///     name: string           # Display name
 ///     max_build_duration: int  # Minutes (default 60)
+///     instance_type: linux | linux_x2 | linux_x4  # Container builds
+///     image: string          # Container builds
 ///     environment:
 ///       vars: map<string, string>
+///       groups: [env var group name]
+///       android_signing: [keystore name]
+///       ios_signing:
+///         distribution_type: development | ad_hoc | app_store | enterprise
+///         bundle_identifier: string
 ///     triggering:
 ///       events: [push, pull_request, tag]
 ///       branch_patterns:
@@ -33,26 +54,342 @@ pub enum ConfigFormat {
 ///         ignore_failure: bool
 ///     artifacts:
 ///       - glob pattern
+///     publishing:
+///       scripts:             # Run after artifacts are collected
+///         - name: string
+///           script: string
+///       google_play:
+///         credentials: string  # Stored service account name
+///         package_name: string
+///         track: string
+///         in_app_update_priority: int  # 0-5
+///         rollout_fraction: float      # 0 < x < 1
+///         submit_as_draft: bool
+///       app_store_connect:
+///         api_key: string      # Stored App Store Connect API key name
+///         submit_to_testflight: bool
+///         beta_groups: [beta group name]
+///         submit_to_app_review: bool
+///       firebase:
+///         app_id: string       # 1:<project number>:<android|ios>:<hash>
+///         groups: [tester group alias]
+///         testers: [email]
+///         release_notes: string
 /// ```
 
+/// Maximum depth of nested `includes:`.
+const MAX_INCLUDE_DEPTH: usize = 10;
+
+/// Outcome of checking a pipeline config.
+#[derive(Debug)]
+pub struct PipelineCheck {
+    /// The deserialized pipeline, if the config matched the expected shape.
+    /// It may still have error diagnostics.
+    pub pipeline: Option<ParsedPipeline>,
+    pub diagnostics: Vec<PipelineDiagnostic>,
+}
+
+impl PipelineCheck {
+    /// Whether the config can be used for builds.
+    pub fn is_valid(&self) -> bool {
+        self.pipeline.is_some() && self.first_error().is_none()
+    }
+
+    /// The first error diagnostic, if any.
+    pub fn first_error(&self) -> Option<&PipelineDiagnostic> {
+        self.diagnostics
+            .iter()
+            .find(|d| d.severity == DiagnosticSeverity::Error)
+    }
+
+    /// Logs warnings and turns the first error into a parse error.
+    fn into_result(self) -> Result<ParsedPipeline> {
+        if let Some(error) = self.first_error() {
+            return Err(OoreError::PipelineParse(error.message.clone()));
+        }
+
+        for warning in &self.diagnostics {
+            tracing::warn!("{} at '{}' will be ignored", warning.message, warning.path);
+        }
+
+        self.pipeline.ok_or_else(|| OoreError::PipelineParse("Invalid pipeline".to_string()))
+    }
+}
+
 /// Parses a Codemagic-compatible YAML string into a ParsedPipeline.
+///
+/// `includes:` is rejected because there is no repository to read from.
 pub fn parse_pipeline(yaml_content: &str) -> Result<ParsedPipeline> {
-    let parsed: ParsedPipeline = serde_yaml::from_str(yaml_content)
-        .map_err(|e| OoreError::PipelineParse(format!("Invalid YAML: {}", e)))?;
+    check_pipeline(yaml_content, ConfigFormat::Yaml, false).into_result()
+}
 
-    validate_pipeline(&parsed)?;
-    warn_unsupported_fields(yaml_content);
+/// Checks a pipeline config, collecting every problem instead of stopping at
+/// the first one.
+///
+/// In strict mode, fields the pipeline model doesn't use are errors rather
+/// than warnings. Diagnostics are located in `content` where possible.
+pub fn check_pipeline(content: &str, format: ConfigFormat, strict: bool) -> PipelineCheck {
+    let mut check = match parse_document(content, format) {
+        Ok(mut value) => {
+            let mut includes = None;
+            if format == ConfigFormat::Yaml
+                && let Some(mapping) = value.as_mapping_mut()
+                && mapping.remove("includes").is_some()
+            {
+                includes = Some(PipelineDiagnostic::error(
+                    "includes",
+                    "includes are only supported in config files committed to the repository",
+                ));
+            }
 
-    Ok(parsed)
+            let mut check = check_document(value, format, strict);
+            check.diagnostics.splice(0..0, includes);
+            check
+        }
+        Err(diagnostic) => PipelineCheck {
+            pipeline: None,
+            diagnostics: vec![diagnostic],
+        },
+    };
+
+    for diagnostic in &mut check.diagnostics {
+        if diagnostic.line.is_none()
+            && let Some((line, column)) = diagnostics::locate(content, &diagnostic.path)
+        {
+            diagnostic.line = Some(line);
+            diagnostic.column = Some(column);
+        }
+    }
+
+    check
+}
+
+/// Parses YAML or HUML into a generic document with merge keys applied.
+fn parse_document(content: &str, format: ConfigFormat) -> std::result::Result<Value, PipelineDiagnostic> {
+    match format {
+        ConfigFormat::Yaml => {
+            let mut value: Value = serde_yaml::from_str(content).map_err(|e| {
+                let mut diagnostic = PipelineDiagnostic::error("", format!("Invalid YAML: {}", e));
+                if let Some(location) = e.location() {
+                    diagnostic.line = Some(location.line() as u32);
+                    diagnostic.column = Some(location.column() as u32);
+                }
+                diagnostic
+            })?;
+            value
+                .apply_merge()
+                .map_err(|e| PipelineDiagnostic::error("", format!("Invalid YAML: {}", e)))?;
+            Ok(value)
+        }
+        ConfigFormat::Huml => huml_rs::serde::from_str(content).map_err(|e| {
+            // The serde error doesn't carry a position, so ask the parser.
+            // It sees the trimmed document, like the deserializer.
+            let trimmed = content.trim_start();
+            let skipped_lines = content[..content.len() - trimmed.len()].matches('\n').count();
+            match huml_rs::parse_huml(trimmed.trim_end()) {
+                Err(parse_error) => {
+                    let mut diagnostic = PipelineDiagnostic::error(
+                        "",
+                        format!("Invalid HUML: {}", parse_error.message),
+                    );
+                    diagnostic.line = Some((parse_error.line + skipped_lines) as u32);
+                    diagnostic.column = Some(parse_error.column as u32);
+                    diagnostic
+                }
+                Ok(_) => PipelineDiagnostic::error("", format!("Invalid HUML: {}", e)),
+            }
+        }),
+    }
+}
+
+/// Deserializes a resolved document and runs the pipeline checks.
+fn check_document(mut value: Value, format: ConfigFormat, strict: bool) -> PipelineCheck {
+    if let Some(mapping) = value.as_mapping_mut() {
+        mapping.remove("definitions");
+    }
+
+    let mut ignored: Vec<Vec<PathSegment>> = Vec::new();
+    let mut track = |path: serde_ignored::Path| ignored.push(diagnostics::ignored_path(&path));
+    let deserializer = serde_ignored::Deserializer::new(value, &mut track);
+    let parsed: std::result::Result<ParsedPipeline, _> = serde_path_to_error::deserialize(deserializer);
+
+    let mut found: Vec<PipelineDiagnostic> = ignored
+        .iter()
+        .map(|path| diagnostics::unknown_field(path, strict))
+        .collect();
+
+    match parsed {
+        Ok(pipeline) => {
+            found.extend(pipeline_diagnostics(&pipeline));
+            PipelineCheck {
+                pipeline: Some(pipeline),
+                diagnostics: found,
+            }
+        }
+        Err(e) => {
+            let format_name = match format {
+                ConfigFormat::Yaml => "YAML",
+                ConfigFormat::Huml => "HUML",
+            };
+            let path = diagnostics::format_path(&diagnostics::error_path(e.path()));
+            let message = if path.is_empty() {
+                format!("Invalid {}: {}", format_name, e.inner())
+            } else {
+                format!("Invalid {}: {}: {}", format_name, path, e.inner())
+            };
+            found.insert(0, PipelineDiagnostic::error(path, message));
+            PipelineCheck {
+                pipeline: None,
+                diagnostics: found,
+            }
+        }
+    }
+}
+
+/// Parses a YAML config file in a cloned repository, resolving `includes:`.
+///
+/// Include paths are relative to the repository root and may not leave it.
+/// Each file's anchors resolve within that file; the including file's
+/// top-level keys and workflows override those of its includes.
+pub fn parse_pipeline_file(workspace: &Path, path: &str) -> Result<ParsedPipeline> {
+    let root = workspace.canonicalize()?;
+    let mut chain = Vec::new();
+    let value = load_yaml_file(&root, path, &mut chain)?;
+    check_document(value, ConfigFormat::Yaml, false).into_result()
+}
+
+/// Loads `path` and everything it includes, merged into one document.
+///
+/// `chain` holds the files currently being loaded, outermost first.
+fn load_yaml_file(root: &Path, path: &str, chain: &mut Vec<String>) -> Result<Value> {
+    let include_error = |chain: &[String], message: String| {
+        if chain.is_empty() {
+            OoreError::PipelineParse(message)
+        } else {
+            OoreError::PipelineParse(format!(
+                "{} (included from {})",
+                message,
+                chain.join(" -> ")
+            ))
+        }
+    };
+
+    let relative = Path::new(path);
+    if path.is_empty()
+        || relative
+            .components()
+            .any(|c| !matches!(c, Component::Normal(_) | Component::CurDir))
+    {
+        return Err(include_error(
+            chain,
+            format!(
+                "Include path '{}' must be relative to the repository root without '..'",
+                path
+            ),
+        ));
+    }
+
+    let resolved = root.join(relative).canonicalize().map_err(|_| {
+        include_error(chain, format!("Included file '{}' not found", path))
+    })?;
+    // Symlinks can still point outside the repository
+    if !resolved.starts_with(root) {
+        return Err(include_error(
+            chain,
+            format!("Included file '{}' is outside the repository", path),
+        ));
+    }
+
+    let name = resolved
+        .strip_prefix(root)
+        .unwrap_or(&resolved)
+        .to_string_lossy()
+        .replace('\\', "/");
+    if chain.contains(&name) {
+        return Err(OoreError::PipelineParse(format!(
+            "Include cycle: {} -> {}",
+            chain.join(" -> "),
+            name
+        )));
+    }
+    if chain.len() >= MAX_INCLUDE_DEPTH {
+        return Err(include_error(
+            chain,
+            format!("Too many nested includes at '{}'", name),
+        ));
+    }
+
+    let content = std::fs::read_to_string(&resolved)
+        .map_err(|e| include_error(chain, format!("Failed to read '{}': {}", name, e)))?;
+    let mut value: Value = serde_yaml::from_str(&content)
+        .map_err(|e| include_error(chain, format!("Invalid YAML in {}: {}", name, e)))?;
+    value
+        .apply_merge()
+        .map_err(|e| include_error(chain, format!("Invalid YAML in {}: {}", name, e)))?;
+
+    let Value::Mapping(mut document) = value else {
+        return Err(include_error(
+            chain,
+            format!("{} must be a YAML mapping", name),
+        ));
+    };
+
+    let includes = match document.remove("includes") {
+        None => Vec::new(),
+        Some(Value::Sequence(items)) => items
+            .into_iter()
+            .map(|item| match item {
+                Value::String(include) => Ok(include),
+                _ => Err(include_error(
+                    chain,
+                    format!("includes in {} must be a list of file paths", name),
+                )),
+            })
+            .collect::<Result<Vec<_>>>()?,
+        Some(_) => {
+            return Err(include_error(
+                chain,
+                format!("includes in {} must be a list of file paths", name),
+            ));
+        }
+    };
+    // Anchors don't cross files, so definitions are only useful locally
+    document.remove("definitions");
+
+    let mut merged = Mapping::new();
+    chain.push(name);
+    for include in &includes {
+        let included = load_yaml_file(root, include, chain)?;
+        if let Value::Mapping(included) = included {
+            merge_documents(&mut merged, included);
+        }
+    }
+    chain.pop();
+    merge_documents(&mut merged, document);
+
+    Ok(Value::Mapping(merged))
+}
+
+/// Merges `overlay` into `base`. Top-level mappings such as `workflows` are
+/// merged by key; anything else, including individual workflows, is replaced.
+fn merge_documents(base: &mut Mapping, overlay: Mapping) {
+    for (key, value) in overlay {
+        match (base.get_mut(&key), value) {
+            (Some(Value::Mapping(existing)), Value::Mapping(entries)) => {
+                for (entry_key, entry) in entries {
+                    existing.insert(entry_key, entry);
+                }
+            }
+            (_, value) => {
+                base.insert(key, value);
+            }
+        }
+    }
 }
 
 /// Parses a HUML string into a ParsedPipeline.
 pub fn parse_pipeline_huml(huml_content: &str) -> Result<ParsedPipeline> {
-    let parsed: ParsedPipeline = huml_rs::serde::from_str(huml_content)
-        .map_err(|e| OoreError::PipelineParse(format!("Invalid HUML: {}", e)))?;
-
-    validate_pipeline(&parsed)?;
-    Ok(parsed)
+    check_pipeline(huml_content, ConfigFormat::Huml, false).into_result()
 }
 
 /// Parses a pipeline config, auto-detecting format from content.
@@ -71,97 +408,114 @@ pub fn parse_pipeline_auto(content: &str) -> Result<(ParsedPipeline, ConfigForma
     }
 }
 
-/// Validates that the parsed pipeline meets minimum requirements.
-fn validate_pipeline(pipeline: &ParsedPipeline) -> Result<()> {
+/// Checks that the parsed pipeline meets minimum requirements.
+fn pipeline_diagnostics(pipeline: &ParsedPipeline) -> Vec<PipelineDiagnostic> {
     if pipeline.workflows.is_empty() {
-        return Err(OoreError::PipelineParse(
-            "Pipeline must define at least one workflow".to_string(),
-        ));
+        return vec![PipelineDiagnostic::error(
+            "workflows",
+            "Pipeline must define at least one workflow",
+        )];
     }
 
-    for (name, workflow) in &pipeline.workflows {
-        validate_workflow(name, workflow)?;
-    }
+    let mut names: Vec<&String> = pipeline.workflows.keys().collect();
+    names.sort();
 
-    Ok(())
+    let mut found = Vec::new();
+    for name in names {
+        workflow_diagnostics(name, &pipeline.workflows[name], &mut found);
+    }
+    found
 }
 
-/// Validates a single workflow.
-fn validate_workflow(name: &str, workflow: &Workflow) -> Result<()> {
+/// Checks a single workflow.
+fn workflow_diagnostics(name: &str, workflow: &Workflow, found: &mut Vec<PipelineDiagnostic>) {
+    let path = |field: &str| format!("workflows.{}.{}", name, field);
+
     if workflow.scripts.is_empty() {
-        return Err(OoreError::PipelineParse(format!(
-            "Workflow '{}' must have at least one script",
-            name
-        )));
+        found.push(PipelineDiagnostic::error(
+            path("scripts"),
+            format!("Workflow '{}' must have at least one script", name),
+        ));
     }
 
     for (i, step) in workflow.scripts.iter().enumerate() {
         if step.script.trim().is_empty() {
-            return Err(OoreError::PipelineParse(format!(
-                "Workflow '{}' step {} has empty script",
-                name,
-                i + 1
-            )));
+            found.push(PipelineDiagnostic::error(
+                path(&format!("scripts[{}].script", i)),
+                format!("Workflow '{}' step {} has empty script", name, i + 1),
+            ));
         }
     }
 
-    if workflow.max_build_duration == 0 {
-        return Err(OoreError::PipelineParse(format!(
-            "Workflow '{}' max_build_duration must be > 0",
-            name
-        )));
+    for (i, group) in workflow.environment.groups.iter().enumerate() {
+        if let Err(e) = validate_env_group_name(group) {
+            found.push(PipelineDiagnostic::error(
+                path(&format!("environment.groups[{}]", i)),
+                format!("Workflow '{}' environment.groups: {}", name, e),
+            ));
+        }
     }
 
-    Ok(())
-}
+    if workflow.environment.android_signing.len() > 1 {
+        found.push(PipelineDiagnostic::error(
+            path("environment.android_signing"),
+            format!(
+                "Workflow '{}' android_signing must reference a single keystore",
+                name
+            ),
+        ));
+    }
 
-/// Logs warnings for unsupported Codemagic fields.
-fn warn_unsupported_fields(yaml_content: &str) {
-    // Parse as generic YAML to check for unsupported fields
-    let Ok(value) = serde_yaml::from_str::<serde_yaml::Value>(yaml_content) else {
-        return;
-    };
+    for (i, keystore) in workflow.environment.android_signing.iter().enumerate() {
+        if keystore.trim().is_empty() {
+            found.push(PipelineDiagnostic::error(
+                path(&format!("environment.android_signing[{}]", i)),
+                format!("Workflow '{}' android_signing has an empty keystore name", name),
+            ));
+        }
+    }
 
-    let unsupported_top_level = ["definitions", "includes"];
-    let unsupported_workflow_fields = [
-        "cache",
-        "publishing",
-        "groups",
-        "instance_type",
-        "integrations",
-        "labels",
-        "working_directory",
-    ];
-
-    // Check top-level fields
-    if let Some(mapping) = value.as_mapping() {
-        for field in unsupported_top_level {
-            if mapping.contains_key(field) {
-                tracing::warn!(
-                    "Unsupported Codemagic field '{}' will be ignored",
-                    field
-                );
-            }
+    if workflow.environment.ios_signing.is_some() {
+        found.push(PipelineDiagnostic::error(
+            path("environment.ios_signing"),
+            format!(
+                "Workflow '{}' ios_signing is not yet supported; iOS builds can't be code signed yet",
+                name
+            ),
+        ));
+    }
+
+    if workflow.max_build_duration == 0 {
+        found.push(PipelineDiagnostic::error(
+            path("max_build_duration"),
+            format!("Workflow '{}' max_build_duration must be > 0", name),
+        ));
+    }
+
+    for (i, step) in workflow.publishing.scripts.iter().enumerate() {
+        if step.script.trim().is_empty() {
+            found.push(PipelineDiagnostic::error(
+                path(&format!("publishing.scripts[{}].script", i)),
+                format!(
+                    "Workflow '{}' publishing.scripts step {} has empty script",
+                    name,
+                    i + 1
+                ),
+            ));
         }
+    }
 
-        // Check workflow-level fields
-        if let Some(workflows) = mapping.get("workflows").and_then(|v| v.as_mapping()) {
-            for (workflow_name, workflow_value) in workflows {
-                if let Some(wf_mapping) = workflow_value.as_mapping() {
-                    for field in unsupported_workflow_fields {
-                        if wf_mapping.contains_key(field) {
-                            let name = workflow_name
-                                .as_str()
-                                .unwrap_or("<unknown>");
-                            tracing::warn!(
-                                "Unsupported Codemagic field '{}' in workflow '{}' will be ignored",
-                                field,
-                                name
-                            );
-                        }
-                    }
-                }
-            }
+    for publisher in publishers_for(&workflow.publishing) {
+        if let Err(e) = publisher.validate_config() {
+            found.push(PipelineDiagnostic::error(
+                path(&format!("publishing.{}", publisher.key())),
+                format!(
+                    "Workflow '{}' publishing.{}: {}",
+                    name,
+                    publisher.key(),
+                    e
+                ),
+            ));
         }
     }
 }
@@ -173,8 +527,11 @@ pub fn create_minimal_pipeline(script: &str) -> ParsedPipeline {
     let workflow = Workflow {
         name: Some("default".to_string()),
         max_build_duration: 60,
+        instance_type: None,
+        image: None,
         environment: WorkflowEnvironment::default(),
         triggering: None,
+        when: None,
         scripts: vec![Step {
             name: Some("Run script".to_string()),
             script: script.to_string(),
@@ -182,6 +539,9 @@ pub fn create_minimal_pipeline(script: &str) -> ParsedPipeline {
             ignore_failure: false,
         }],
         artifacts: vec![],
+        cache: None,
+        clone: None,
+        publishing: Default::default(),
     };
 
     let mut workflows = HashMap::new();
@@ -193,7 +553,7 @@ pub fn create_minimal_pipeline(script: &str) -> ParsedPipeline {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::models::TriggerEvent;
+    use crate::models::{SubmoduleMode, TriggerEvent};
 
     #[test]
     fn test_parse_minimal_pipeline() {
@@ -466,6 +826,282 @@ workflows:
         assert_eq!(env.get("DEBUG"), Some(&"true".to_string()));
     }
 
+    #[test]
+    fn test_parse_signing_selectors() {
+        let yaml = r#"
+workflows:
+  release:
+    environment:
+      android_signing:
+        - upload-keystore
+    scripts:
+      - script: flutter build appbundle
+"#;
+
+        let pipeline = parse_pipeline(yaml).unwrap();
+        let environment = &pipeline.workflows["release"].environment;
+        assert_eq!(environment.android_signing, vec!["upload-keystore".to_string()]);
+    }
+
+    #[test]
+    fn test_parse_ios_signing_not_supported() {
+        let yaml = r#"
+workflows:
+  release:
+    environment:
+      ios_signing:
+        distribution_type: app_store
+        bundle_identifier: com.example.app
+    scripts:
+      - script: flutter build ipa
+"#;
+
+        let err = parse_pipeline(yaml).unwrap_err();
+        assert!(err.to_string().contains("ios_signing is not yet supported"), "{}", err);
+    }
+
+    #[test]
+    fn test_parse_signing_selectors_default_to_empty() {
+        let pipeline = parse_pipeline(
+            r#"
+workflows:
+  debug:
+    scripts:
+      - script: flutter build apk --debug
+"#,
+        )
+        .unwrap();
+        let environment = &pipeline.workflows["debug"].environment;
+        assert!(environment.android_signing.is_empty());
+        assert!(environment.ios_signing.is_none());
+    }
+
+    #[test]
+    fn test_parse_environment_groups() {
+        let yaml = r#"
+workflows:
+  release:
+    environment:
+      groups:
+        - app_store_credentials
+        - firebase
+    scripts:
+      - script: echo "build"
+"#;
+
+        let pipeline = parse_pipeline(yaml).unwrap();
+        let environment = &pipeline.workflows["release"].environment;
+        assert_eq!(
+            environment.groups,
+            vec!["app_store_credentials".to_string(), "firebase".to_string()]
+        );
+    }
+
+    #[test]
+    fn test_parse_invalid_environment_group_fails() {
+        let yaml = r#"
+workflows:
+  release:
+    environment:
+      groups: ["bad group"]
+    scripts:
+      - script: echo "build"
+"#;
+
+        let err = parse_pipeline(yaml).unwrap_err();
+        assert!(err.to_string().contains("environment.groups"));
+    }
+
+    #[test]
+    fn test_parse_multiple_android_keystores_fails() {
+        let yaml = r#"
+workflows:
+  release:
+    environment:
+      android_signing: [one, two]
+    scripts:
+      - script: echo "build"
+"#;
+
+        let err = parse_pipeline(yaml).unwrap_err();
+        assert!(err.to_string().contains("single keystore"));
+    }
+
+    #[test]
+    fn test_parse_google_play_publishing() {
+        let yaml = r#"
+workflows:
+  android-release:
+    scripts:
+      - script: flutter build appbundle
+    artifacts:
+      - build/**/outputs/**/*.aab
+    publishing:
+      google_play:
+        credentials: play-console
+        package_name: com.example.app
+        track: beta
+        in_app_update_priority: 3
+        rollout_fraction: 0.25
+"#;
+
+        let pipeline = parse_pipeline(yaml).unwrap();
+        let google_play = pipeline.workflows["android-release"]
+            .publishing
+            .google_play
+            .as_ref()
+            .unwrap();
+        assert_eq!(google_play.credentials, "play-console");
+        assert_eq!(google_play.package_name, "com.example.app");
+        assert_eq!(google_play.track, "beta");
+        assert_eq!(google_play.in_app_update_priority, Some(3));
+        assert_eq!(google_play.rollout_fraction, Some(0.25));
+        assert!(!google_play.submit_as_draft);
+    }
+
+    #[test]
+    fn test_parse_invalid_google_play_publishing_fails() {
+        let cases = [
+            ("credentials: ''\n        package_name: com.example.app\n        track: beta", "credentials"),
+            ("credentials: play\n        package_name: example\n        track: beta", "package_name"),
+            ("credentials: play\n        package_name: com.example.app\n        track: ../beta", "track"),
+            ("credentials: play\n        package_name: com.example.app\n        track: beta\n        in_app_update_priority: 6", "in_app_update_priority"),
+            ("credentials: play\n        package_name: com.example.app\n        track: beta\n        rollout_fraction: 1.0", "rollout_fraction"),
+            ("credentials: play\n        package_name: com.example.app\n        track: beta\n        rollout_fraction: 0.5\n        submit_as_draft: true", "submit_as_draft"),
+        ];
+
+        for (google_play, expected) in cases {
+            let yaml = format!(
+                "workflows:\n  release:\n    scripts:\n      - script: echo build\n    publishing:\n      google_play:\n        {}\n",
+                google_play
+            );
+            let err = parse_pipeline(&yaml).unwrap_err().to_string();
+            assert!(err.contains("publishing.google_play"), "{}", err);
+            assert!(err.contains(expected), "{}", err);
+        }
+    }
+
+    #[test]
+    fn test_parse_app_store_connect_publishing() {
+        let yaml = r#"
+workflows:
+  ios-release:
+    scripts:
+      - script: flutter build ipa
+    artifacts:
+      - build/ios/ipa/*.ipa
+    publishing:
+      app_store_connect:
+        api_key: ci-key
+        submit_to_testflight: true
+        beta_groups:
+          - QA
+          - Friends
+"#;
+
+        let pipeline = parse_pipeline(yaml).unwrap();
+        let app_store_connect = pipeline.workflows["ios-release"]
+            .publishing
+            .app_store_connect
+            .as_ref()
+            .unwrap();
+        assert_eq!(app_store_connect.api_key, "ci-key");
+        assert!(app_store_connect.submit_to_testflight);
+        assert_eq!(app_store_connect.beta_groups, vec!["QA", "Friends"]);
+        assert!(!app_store_connect.submit_to_app_review);
+
+        let yaml = "workflows:\n  release:\n    scripts:\n      - script: echo build\n    publishing:\n      app_store_connect:\n        api_key: ' '\n";
+        let err = parse_pipeline(yaml).unwrap_err().to_string();
+        assert!(err.contains("publishing.app_store_connect: api_key"), "{}", err);
+    }
+
+    #[test]
+    fn test_parse_firebase_publishing() {
+        let yaml = r#"
+workflows:
+  android-beta:
+    scripts:
+      - script: flutter build apk
+    artifacts:
+      - build/**/outputs/**/*.apk
+    publishing:
+      firebase:
+        app_id: 1:1234567890:android:0a1b2c3d4e5f
+        groups:
+          - qa-team
+        testers:
+          - qa@example.com
+        release_notes: Nightly build
+"#;
+
+        let pipeline = parse_pipeline(yaml).unwrap();
+        let firebase = pipeline.workflows["android-beta"]
+            .publishing
+            .firebase
+            .as_ref()
+            .unwrap();
+        assert_eq!(firebase.app_id, "1:1234567890:android:0a1b2c3d4e5f");
+        assert_eq!(firebase.groups, vec!["qa-team"]);
+        assert_eq!(firebase.testers, vec!["qa@example.com"]);
+        assert_eq!(firebase.release_notes.as_deref(), Some("Nightly build"));
+
+        let cases = [
+            ("app_id: com.example.app", "app_id"),
+            ("app_id: '1:42:web:ff'", "web"),
+            ("app_id: '1:42:ios:ff'\n        testers: [not-an-email]", "tester"),
+        ];
+        for (firebase, expected) in cases {
+            let yaml = format!(
+                "workflows:\n  release:\n    scripts:\n      - script: echo build\n    publishing:\n      firebase:\n        {}\n",
+                firebase
+            );
+            let err = parse_pipeline(&yaml).unwrap_err().to_string();
+            assert!(err.contains("publishing.firebase"), "{}", err);
+            assert!(err.contains(expected), "{}", err);
+        }
+    }
+
+    #[test]
+    fn test_parse_publishing_scripts() {
+        let yaml = r#"
+workflows:
+  release:
+    scripts:
+      - script: flutter build apk
+    publishing:
+      scripts:
+        - name: Upload to S3
+          script: aws s3 cp "$OORE_ARTIFACT_PATHS" s3://builds/
+          ignore_failure: true
+"#;
+
+        let pipeline = parse_pipeline(yaml).unwrap();
+        let scripts = &pipeline.workflows["release"].publishing.scripts;
+        assert_eq!(scripts.len(), 1);
+        assert_eq!(scripts[0].name.as_deref(), Some("Upload to S3"));
+        assert!(scripts[0].ignore_failure);
+
+        let yaml = "workflows:\n  release:\n    scripts:\n      - script: echo build\n    publishing:\n      scripts:\n        - script: ' '\n";
+        let err = parse_pipeline(yaml).unwrap_err().to_string();
+        assert!(err.contains("publishing.scripts step 1"), "{}", err);
+    }
+
+    #[test]
+    fn test_parse_invalid_distribution_type_fails() {
+        let yaml = r#"
+workflows:
+  release:
+    environment:
+      ios_signing:
+        distribution_type: store
+        bundle_identifier: com.example.app
+    scripts:
+      - script: echo "build"
+"#;
+
+        assert!(parse_pipeline(yaml).is_err());
+    }
+
     #[test]
     fn test_parse_artifacts() {
         let yaml = r#"
@@ -658,6 +1294,23 @@ workflows::
         assert_eq!(triggering.branch_patterns.exclude.len(), 1);
     }
 
+    #[test]
+    fn test_parse_huml_signing_selectors() {
+        let huml = "%HUML v0.2.0
+workflows::
+  release::
+    environment::
+      android_signing:: \"upload-keystore\"
+    scripts::
+      - ::
+        script: \"flutter build appbundle\"
+";
+
+        let pipeline = parse_pipeline_huml(huml).unwrap();
+        let environment = &pipeline.workflows["release"].environment;
+        assert_eq!(environment.android_signing, vec!["upload-keystore".to_string()]);
+    }
+
     #[test]
     fn test_parse_huml_empty_workflows_fails() {
         let huml = "%HUML v0.2.0
@@ -926,4 +1579,302 @@ workflows::
         assert!(artifacts.contains(&"build/ios/**/*.ipa".to_string()));
         assert!(artifacts.contains(&"build/android/**/*.apk".to_string()));
     }
+
+    #[test]
+    fn test_parse_definitions_anchors() {
+        let yaml = r#"
+definitions:
+  env: &common_env
+    flutter: stable
+    vars:
+      APP_ENV: production
+  scripts:
+    - &get_packages
+      name: Get packages
+      script: flutter pub get
+
+workflows:
+  android:
+    environment:
+      <<: *common_env
+      vars:
+        APP_ENV: staging
+    scripts:
+      - *get_packages
+      - script: flutter build apk
+  ios:
+    environment: *common_env
+    scripts:
+      - *get_packages
+"#;
+
+        let pipeline = parse_pipeline(yaml).unwrap();
+        let android = &pipeline.workflows["android"];
+        assert_eq!(android.environment.flutter.as_deref(), Some("stable"));
+        assert_eq!(
+            android.environment.vars.get("APP_ENV").map(String::as_str),
+            Some("staging")
+        );
+        assert_eq!(android.scripts.len(), 2);
+        assert_eq!(android.scripts[0].name.as_deref(), Some("Get packages"));
+
+        let ios = &pipeline.workflows["ios"];
+        assert_eq!(
+            ios.environment.vars.get("APP_ENV").map(String::as_str),
+            Some("production")
+        );
+    }
+
+    #[test]
+    fn test_parse_pipeline_rejects_includes() {
+        let yaml = r#"
+includes:
+  - ci/shared.yaml
+workflows:
+  build:
+    scripts:
+      - script: echo "build"
+"#;
+
+        let err = parse_pipeline(yaml).unwrap_err().to_string();
+        assert!(err.contains("includes are only supported"));
+    }
+
+    #[test]
+    fn test_parse_pipeline_file_includes() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir(dir.path().join("ci")).unwrap();
+        std::fs::write(
+            dir.path().join("ci/shared.yaml"),
+            r#"
+definitions:
+  scripts:
+    - &test
+      script: flutter test
+workflows:
+  test:
+    scripts:
+      - *test
+  release:
+    scripts:
+      - script: echo "shared release"
+"#,
+        )
+        .unwrap();
+        std::fs::write(
+            dir.path().join("codemagic.yaml"),
+            r#"
+includes:
+  - ci/shared.yaml
+workflows:
+  release:
+    scripts:
+      - script: echo "local release"
+"#,
+        )
+        .unwrap();
+
+        let pipeline = parse_pipeline_file(dir.path(), "codemagic.yaml").unwrap();
+        assert_eq!(pipeline.workflows.len(), 2);
+        assert_eq!(pipeline.workflows["test"].scripts[0].script, "flutter test");
+        assert_eq!(
+            pipeline.workflows["release"].scripts[0].script,
+            "echo \"local release\""
+        );
+    }
+
+    #[test]
+    fn test_parse_pipeline_file_rejects_traversal() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(
+            dir.path().join("codemagic.yaml"),
+            "includes:\n  - ../outside.yaml\nworkflows: {}\n",
+        )
+        .unwrap();
+
+        let err = parse_pipeline_file(dir.path(), "codemagic.yaml")
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("'../outside.yaml'"));
+        assert!(err.contains("included from codemagic.yaml"));
+    }
+
+    #[test]
+    fn test_parse_pipeline_file_include_errors_name_chain() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(
+            dir.path().join("codemagic.yaml"),
+            "includes:\n  - a.yaml\n",
+        )
+        .unwrap();
+        std::fs::write(dir.path().join("a.yaml"), "includes:\n  - b.yaml\n").unwrap();
+        std::fs::write(dir.path().join("b.yaml"), "includes:\n  - a.yaml\n").unwrap();
+
+        let err = parse_pipeline_file(dir.path(), "codemagic.yaml")
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("Include cycle: codemagic.yaml -> a.yaml -> b.yaml -> a.yaml"));
+
+        std::fs::write(dir.path().join("b.yaml"), "workflows: [\n").unwrap();
+        let err = parse_pipeline_file(dir.path(), "codemagic.yaml")
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("Invalid YAML in b.yaml"));
+        assert!(err.contains("included from codemagic.yaml -> a.yaml"));
+    }
+
+    #[test]
+    fn test_check_pipeline_collects_diagnostics() {
+        let yaml = r#"workflows:
+  ios:
+    triggering:
+      branch_pattern:
+        include: [main]
+    scripts: []
+  android:
+    max_build_duration: 0
+    scripts:
+      - script: ""
+"#;
+
+        let check = check_pipeline(yaml, ConfigFormat::Yaml, false);
+        assert!(!check.is_valid());
+        let summary: Vec<(DiagnosticSeverity, &str, Option<u32>)> = check
+            .diagnostics
+            .iter()
+            .map(|d| (d.severity, d.path.as_str(), d.line))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                (
+                    DiagnosticSeverity::Warning,
+                    "workflows.ios.triggering.branch_pattern",
+                    Some(4)
+                ),
+                (
+                    DiagnosticSeverity::Error,
+                    "workflows.android.scripts[0].script",
+                    Some(10)
+                ),
+                (
+                    DiagnosticSeverity::Error,
+                    "workflows.android.max_build_duration",
+                    Some(8)
+                ),
+                (DiagnosticSeverity::Error, "workflows.ios.scripts", Some(6)),
+            ]
+        );
+        assert_eq!(
+            check.diagnostics[0].suggestion.as_deref(),
+            Some("did you mean `branch_patterns`?")
+        );
+    }
+
+    #[test]
+    fn test_check_pipeline_strict() {
+        let yaml = r#"workflows:
+  build:
+    labels: [release]
+    scripts:
+      - script: flutter build apk
+"#;
+
+        let check = check_pipeline(yaml, ConfigFormat::Yaml, false);
+        assert!(check.is_valid());
+        assert_eq!(check.diagnostics.len(), 1);
+        assert_eq!(check.diagnostics[0].severity, DiagnosticSeverity::Warning);
+
+        let check = check_pipeline(yaml, ConfigFormat::Yaml, true);
+        assert!(!check.is_valid());
+        let error = check.first_error().unwrap();
+        assert_eq!(error.path, "workflows.build.labels");
+        assert_eq!(error.message, "Codemagic field 'labels' is not supported");
+        assert_eq!((error.line, error.column), (Some(3), Some(5)));
+
+        // Unknown fields never fail the non-strict parser
+        assert!(parse_pipeline(yaml).is_ok());
+    }
+
+    #[test]
+    fn test_parse_cache_paths() {
+        let yaml = r#"workflows:
+  build:
+    cache:
+      cache_paths:
+        - $HOME/.gradle/caches
+        - build/generated
+    scripts:
+      - script: flutter build apk
+"#;
+
+        let check = check_pipeline(yaml, ConfigFormat::Yaml, true);
+        assert!(check.diagnostics.is_empty());
+        let pipeline = check.pipeline.unwrap();
+        let cache = pipeline.workflows["build"].cache.as_ref().unwrap();
+        assert_eq!(cache.cache_paths, vec!["$HOME/.gradle/caches", "build/generated"]);
+    }
+
+    #[test]
+    fn test_parse_clone_options() {
+        let yaml = r#"workflows:
+  build:
+    clone:
+      depth: 1
+      submodules: recursive
+      lfs: true
+      sparse_paths:
+        - app
+    scripts:
+      - script: flutter build apk
+"#;
+
+        let check = check_pipeline(yaml, ConfigFormat::Yaml, true);
+        assert!(check.diagnostics.is_empty());
+        let pipeline = check.pipeline.unwrap();
+        let clone = pipeline.workflows["build"].clone.as_ref().unwrap();
+        assert_eq!(clone.depth, Some(1));
+        assert_eq!(clone.submodules, SubmoduleMode::Recursive);
+        assert!(clone.lfs);
+        assert_eq!(clone.sparse_paths, vec!["app"]);
+    }
+
+    #[test]
+    fn test_check_pipeline_type_and_syntax_errors() {
+        let yaml = r#"workflows:
+  build:
+    max_build_duration: soon
+    scripts:
+      - script: echo "build"
+"#;
+        let check = check_pipeline(yaml, ConfigFormat::Yaml, false);
+        assert!(check.pipeline.is_none());
+        let error = check.first_error().unwrap();
+        assert_eq!(error.path, "workflows.build.max_build_duration");
+        assert!(error.message.starts_with("Invalid YAML: workflows.build.max_build_duration:"));
+        assert_eq!(error.line, Some(3));
+
+        let check = check_pipeline("workflows:\n  build: [\n", ConfigFormat::Yaml, false);
+        let error = check.first_error().unwrap();
+        assert_eq!(error.path, "");
+        assert!(error.message.starts_with("Invalid YAML:"));
+        assert!(error.line.is_some());
+    }
+
+    #[test]
+    fn test_check_pipeline_huml_locations() {
+        let huml = "%HUML v0.2.0
+workflows::
+  default::
+    max_build_duration: 0
+    scripts::
+      - ::
+        script: \"echo Hello\"
+";
+
+        let check = check_pipeline(huml, ConfigFormat::Huml, false);
+        let error = check.first_error().unwrap();
+        assert_eq!(error.path, "workflows.default.max_build_duration");
+        assert_eq!((error.line, error.column), (Some(4), Some(5)));
+    }
 }
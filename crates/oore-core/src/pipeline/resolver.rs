@@ -4,16 +4,23 @@
 //! 1. Config files in the repository (oore.* or codemagic.*, HUML or YAML)
 //! 2. Stored UI config in the database (fallback)
 
+use std::collections::HashMap;
 use std::path::Path;
 
-use crate::db::{pipeline::PipelineConfigRepo, DbPool};
+use crate::db::{
+    env_vars::{decrypt_env_value, EnvVarRepo},
+    pipeline::PipelineConfigRepo,
+    signing::AndroidKeystoreRepo,
+    DbPool,
+};
 use crate::error::{OoreError, Result};
 use crate::models::{
-    ConfigSource, ParsedPipeline, RepositoryId, StoredConfigFormat, TriggerEvent, TriggerType,
-    Workflow,
+    AndroidKeystore, ConfigSource, ParsedPipeline, RepositoryId, StoredConfigFormat, TriggerEvent,
+    TriggerType, Workflow, WorkflowEnvironment,
 };
+use crate::oauth::EncryptionKey;
 
-use super::{parse_pipeline, parse_pipeline_huml};
+use super::{changeset_matches, parse_pipeline, parse_pipeline_file, parse_pipeline_huml};
 
 /// Config file names to check, in priority order.
 /// Format: (filename, is_huml)
@@ -55,11 +62,10 @@ pub async fn resolve_config(
         for (filename, is_huml) in CONFIG_FILES {
             let path = workspace_path.join(filename);
             if path.exists() {
-                let content = std::fs::read_to_string(&path)?;
                 let pipeline = if *is_huml {
-                    parse_pipeline_huml(&content)?
+                    parse_pipeline_huml(&std::fs::read_to_string(&path)?)?
                 } else {
-                    parse_pipeline(&content)?
+                    parse_pipeline_file(workspace_path, filename)?
                 };
                 tracing::debug!("Loaded pipeline config from {}", filename);
                 return Ok(ResolvedConfig {
@@ -98,35 +104,189 @@ pub async fn resolve_config(
     )))
 }
 
+/// Signing credentials selected for a workflow.
+#[derive(Debug, Clone, Default)]
+pub struct ResolvedSigning {
+    /// Keystore to sign Android builds with.
+    pub android_keystore: Option<AndroidKeystore>,
+}
+
+/// Resolves the workflow's signing selectors against the repository's stored credentials.
+///
+/// Without `android_signing`, the most recently uploaded active keystore is used (if any).
+/// Explicit selectors that don't match an active credential are an error, so a
+/// misconfigured release workflow fails up front instead of producing an unsigned build.
+pub async fn resolve_signing(
+    db: &DbPool,
+    repository_id: &RepositoryId,
+    environment: &WorkflowEnvironment,
+) -> Result<ResolvedSigning> {
+    let mut resolved = ResolvedSigning::default();
+
+    if let Some(name) = environment.android_signing.first() {
+        let keystores = AndroidKeystoreRepo::list_active_for_repo(db, repository_id).await?;
+        let available: Vec<String> = keystores.iter().map(|k| k.name.clone()).collect();
+
+        match keystores.into_iter().find(|k| &k.name == name) {
+            Some(keystore) => resolved.android_keystore = Some(keystore),
+            None => {
+                return Err(OoreError::Signing(format!(
+                    "Android keystore '{}' referenced by android_signing not found (available: {})",
+                    name,
+                    format_available(&available)
+                )));
+            }
+        }
+    } else {
+        resolved.android_keystore = AndroidKeystoreRepo::get_active_for_repo(db, repository_id).await?;
+    }
+
+    Ok(resolved)
+}
+
+fn format_available(names: &[String]) -> String {
+    if names.is_empty() {
+        "none".to_string()
+    } else {
+        names.join(", ")
+    }
+}
+
+/// Step environment built from repository variables and the workflow.
+#[derive(Debug, Clone, Default)]
+pub struct ResolvedEnvironment {
+    /// Merged variables, before system variables are applied.
+    pub vars: HashMap<String, String>,
+    /// Values of secret repository variables, for log masking.
+    pub secrets: Vec<String>,
+}
+
+/// Builds the step environment from repository variables and `environment.vars`.
+///
+/// Precedence, lowest to highest:
+/// 1. Ungrouped repository variables
+/// 2. Variables from `environment.groups`, later groups overriding earlier ones
+/// 3. The workflow's `environment.vars`
+///
+/// Signing exports and Oore's system variables (`CI`, `OORE_*`) are applied
+/// on top of this by the build processor. Referencing a group that has no
+/// variables is an error.
+pub async fn resolve_environment(
+    db: &DbPool,
+    encryption_key: Option<&EncryptionKey>,
+    repository_id: &RepositoryId,
+    environment: &WorkflowEnvironment,
+) -> Result<ResolvedEnvironment> {
+    let repo_vars = EnvVarRepo::list_for_repo(db, repository_id).await?;
+
+    // Rows are ordered by group name, so dedup leaves each group once
+    let available_groups: Vec<String> = {
+        let mut groups: Vec<String> = repo_vars.iter().filter_map(|v| v.group_name.clone()).collect();
+        groups.dedup();
+        groups
+    };
+    for group in &environment.groups {
+        if !available_groups.contains(group) {
+            return Err(OoreError::Configuration(format!(
+                "Environment group '{}' not found (available: {})",
+                group,
+                format_available(&available_groups)
+            )));
+        }
+    }
+
+    let selected: Vec<_> = repo_vars
+        .into_iter()
+        .filter(|v| v.group_name.as_ref().is_none_or(|g| environment.groups.contains(g)))
+        .collect();
+
+    let mut decrypted = Vec::with_capacity(selected.len());
+    let mut secrets = Vec::new();
+    if !selected.is_empty() {
+        let key = encryption_key.ok_or_else(|| {
+            OoreError::Encryption(
+                "Repository environment variables require an encryption key".to_string(),
+            )
+        })?;
+        for var in selected {
+            let value = decrypt_env_value(key, &var)?;
+            if var.is_secret {
+                secrets.push(value.clone());
+            }
+            decrypted.push((var.group_name, var.name, value));
+        }
+    }
+
+    Ok(ResolvedEnvironment {
+        vars: merge_environment(decrypted, &environment.groups, &environment.vars),
+        secrets,
+    })
+}
+
+/// Layers `(group, name, value)` repository variables under the workflow's vars.
+fn merge_environment(
+    repo_vars: Vec<(Option<String>, String, String)>,
+    groups: &[String],
+    workflow_vars: &HashMap<String, String>,
+) -> HashMap<String, String> {
+    let rank = |group: &Option<String>| match group {
+        None => 0,
+        Some(g) => groups.iter().position(|name| name == g).map_or(0, |i| i + 1),
+    };
+
+    let mut repo_vars = repo_vars;
+    repo_vars.sort_by_key(|(group, _, _)| rank(group));
+
+    let mut vars: HashMap<String, String> = repo_vars
+        .into_iter()
+        .map(|(_, name, value)| (name, value))
+        .collect();
+    vars.extend(workflow_vars.iter().map(|(k, v)| (k.clone(), v.clone())));
+    vars
+}
+
 /// Selects the appropriate workflow for a given trigger.
 ///
 /// Selection priority:
 /// 1. Match by triggering.events and branch_patterns
 /// 2. If only one workflow exists, use it as default
 /// 3. If multiple workflows match, return error (ambiguous)
+///
+/// With `changed_files`, workflows whose `when.changeset` none of the files
+/// match are left out; if that leaves nothing to run, returns
+/// [`OoreError::NoRelevantChanges`]. `None` means the changes are unknown and
+/// changeset conditions are ignored.
 pub fn select_workflow<'a>(
     pipeline: &'a ParsedPipeline,
     trigger_type: TriggerType,
     branch: &str,
+    changed_files: Option<&[String]>,
 ) -> Result<(String, &'a Workflow)> {
     let trigger_event = match trigger_type {
         TriggerType::Push => TriggerEvent::Push,
+        TriggerType::Tag => TriggerEvent::Tag,
         TriggerType::PullRequest | TriggerType::MergeRequest => TriggerEvent::PullRequest,
-        TriggerType::Manual => {
-            // For manual triggers, use first workflow or any that doesn't have specific triggers
+        TriggerType::Manual | TriggerType::Scheduled => {
+            // For manual and scheduled triggers, use first workflow or any that doesn't have specific triggers
             return select_default_or_first(pipeline);
         }
     };
 
     let mut matching_workflows: Vec<(String, &Workflow)> = Vec::new();
+    let mut skipped_for_changes = false;
 
     for (name, workflow) in &pipeline.workflows {
         if matches_trigger(workflow, trigger_event, branch) {
+            if !matches_changes(workflow, changed_files) {
+                skipped_for_changes = true;
+                continue;
+            }
             matching_workflows.push((name.clone(), workflow));
         }
     }
 
     match matching_workflows.len() {
+        0 if skipped_for_changes => Err(OoreError::NoRelevantChanges),
         0 => {
             // No explicit matches - check if we have a workflow without triggering config
             // that can be used as a default
@@ -148,15 +308,28 @@ pub fn select_workflow<'a>(
         1 => Ok(matching_workflows.into_iter().next().unwrap()),
         _ => {
             let names: Vec<_> = matching_workflows.iter().map(|(n, _)| n.as_str()).collect();
+            let (ref_kind, patterns_field) = match trigger_event {
+                TriggerEvent::Tag => ("tag", "tag_patterns"),
+                _ => ("branch", "branch_patterns"),
+            };
             Err(OoreError::PipelineParse(format!(
-                "Multiple workflows match trigger {} on branch '{}': {}. \
-                 Please configure triggering.branch_patterns to disambiguate.",
-                trigger_type, branch, names.join(", ")
+                "Multiple workflows match trigger {} on {} '{}': {}. \
+                 Please configure triggering.{} to disambiguate.",
+                trigger_type, ref_kind, branch, names.join(", "), patterns_field
             )))
         }
     }
 }
 
+/// Looks up a workflow by name, for builds that say which workflow to run.
+pub fn find_workflow<'a>(pipeline: &'a ParsedPipeline, name: &str) -> Result<(String, &'a Workflow)> {
+    pipeline
+        .workflows
+        .get_key_value(name)
+        .map(|(name, workflow)| (name.clone(), workflow))
+        .ok_or_else(|| OoreError::WorkflowNotFound(name.to_string()))
+}
+
 /// Selects the default workflow or the first one if only one exists.
 fn select_default_or_first(pipeline: &ParsedPipeline) -> Result<(String, &Workflow)> {
     // Try "default" workflow first
@@ -180,19 +353,26 @@ fn select_default_or_first(pipeline: &ParsedPipeline) -> Result<(String, &Workfl
 }
 
 /// Checks if a workflow matches the given trigger event and branch.
+///
+/// For tag events, `branch` is the tag name and is matched against
+/// `tag_patterns` instead of `branch_patterns`.
 fn matches_trigger(workflow: &Workflow, event: TriggerEvent, branch: &str) -> bool {
     let Some(triggering) = &workflow.triggering else {
         // No triggering config means workflow accepts all triggers
         return true;
     };
 
-    // Check event type matches
-    if !triggering.events.is_empty() && !triggering.events.contains(&event) {
+    // Check event type matches. Tags must be listed explicitly, so adding tag
+    // support doesn't start building tags for workflows filtered by branch.
+    let event_listed = triggering.events.contains(&event);
+    if !event_listed && (event == TriggerEvent::Tag || !triggering.events.is_empty()) {
         return false;
     }
 
-    // Check branch patterns
-    let patterns = &triggering.branch_patterns;
+    let patterns = match event {
+        TriggerEvent::Tag => &triggering.tag_patterns,
+        _ => &triggering.branch_patterns,
+    };
 
     // If no patterns defined, match all branches
     if patterns.include.is_empty() && patterns.exclude.is_empty() {
@@ -220,6 +400,16 @@ fn matches_trigger(workflow: &Workflow, event: TriggerEvent, branch: &str) -> bo
     false
 }
 
+/// Checks the workflow's `when.changeset` condition, if any, against the
+/// changed files.
+fn matches_changes(workflow: &Workflow, changed_files: Option<&[String]>) -> bool {
+    let condition = workflow.when.as_ref().and_then(|when| when.changeset.as_ref());
+    match (condition, changed_files) {
+        (Some(condition), Some(files)) => changeset_matches(condition, files),
+        _ => true,
+    }
+}
+
 /// Matches a branch name against a glob pattern.
 fn matches_branch_pattern(pattern: &str, branch: &str) -> bool {
     // Use glob pattern matching
@@ -248,7 +438,7 @@ workflows:
       - script: echo "build"
 "#;
         let pipeline = parse_pipeline(yaml).unwrap();
-        let (name, _) = select_workflow(&pipeline, TriggerType::Push, "main").unwrap();
+        let (name, _) = select_workflow(&pipeline, TriggerType::Push, "main", None).unwrap();
         assert_eq!(name, "build");
     }
 
@@ -264,10 +454,32 @@ workflows:
       - script: echo "other"
 "#;
         let pipeline = parse_pipeline(yaml).unwrap();
-        let (name, _) = select_workflow(&pipeline, TriggerType::Manual, "main").unwrap();
+        let (name, _) = select_workflow(&pipeline, TriggerType::Manual, "main", None).unwrap();
+        assert_eq!(name, "default");
+        let (name, _) = select_workflow(&pipeline, TriggerType::Scheduled, "main", None).unwrap();
         assert_eq!(name, "default");
     }
 
+    #[test]
+    fn test_find_workflow() {
+        let yaml = r#"
+workflows:
+  default:
+    scripts:
+      - script: echo "default"
+  nightly:
+    scripts:
+      - script: echo "nightly"
+"#;
+        let pipeline = parse_pipeline(yaml).unwrap();
+        let (name, _) = find_workflow(&pipeline, "nightly").unwrap();
+        assert_eq!(name, "nightly");
+        assert!(matches!(
+            find_workflow(&pipeline, "weekly"),
+            Err(OoreError::WorkflowNotFound(_))
+        ));
+    }
+
     #[test]
     fn test_select_by_trigger_event() {
         let yaml = r#"
@@ -287,10 +499,10 @@ workflows:
 "#;
         let pipeline = parse_pipeline(yaml).unwrap();
 
-        let (name, _) = select_workflow(&pipeline, TriggerType::Push, "main").unwrap();
+        let (name, _) = select_workflow(&pipeline, TriggerType::Push, "main", None).unwrap();
         assert_eq!(name, "push-build");
 
-        let (name, _) = select_workflow(&pipeline, TriggerType::PullRequest, "feature").unwrap();
+        let (name, _) = select_workflow(&pipeline, TriggerType::PullRequest, "feature", None).unwrap();
         assert_eq!(name, "pr-build");
     }
 
@@ -320,10 +532,10 @@ workflows:
 "#;
         let pipeline = parse_pipeline(yaml).unwrap();
 
-        let (name, _) = select_workflow(&pipeline, TriggerType::Push, "release/1.0").unwrap();
+        let (name, _) = select_workflow(&pipeline, TriggerType::Push, "release/1.0", None).unwrap();
         assert_eq!(name, "release");
 
-        let (name, _) = select_workflow(&pipeline, TriggerType::Push, "main").unwrap();
+        let (name, _) = select_workflow(&pipeline, TriggerType::Push, "main", None).unwrap();
         assert_eq!(name, "develop");
     }
 
@@ -345,10 +557,10 @@ workflows:
 "#;
         let pipeline = parse_pipeline(yaml).unwrap();
 
-        let result = select_workflow(&pipeline, TriggerType::Push, "wip/experiment");
+        let result = select_workflow(&pipeline, TriggerType::Push, "wip/experiment", None);
         assert!(result.is_err());
 
-        let (name, _) = select_workflow(&pipeline, TriggerType::Push, "feature/new").unwrap();
+        let (name, _) = select_workflow(&pipeline, TriggerType::Push, "feature/new", None).unwrap();
         assert_eq!(name, "build");
     }
 
@@ -378,7 +590,7 @@ workflows:
         let pipeline = parse_pipeline(yaml).unwrap();
 
         // MergeRequest (GitLab) should use pull_request workflow
-        let (name, _) = select_workflow(&pipeline, TriggerType::MergeRequest, "feature").unwrap();
+        let (name, _) = select_workflow(&pipeline, TriggerType::MergeRequest, "feature", None).unwrap();
         assert_eq!(name, "pr-build");
     }
 
@@ -398,7 +610,7 @@ workflows:
 "#;
         let pipeline = parse_pipeline(yaml).unwrap();
 
-        let (name, _) = select_workflow(&pipeline, TriggerType::Manual, "any-branch").unwrap();
+        let (name, _) = select_workflow(&pipeline, TriggerType::Manual, "any-branch", None).unwrap();
         assert_eq!(name, "default");
     }
 
@@ -416,7 +628,7 @@ workflows:
         let pipeline = parse_pipeline(yaml).unwrap();
 
         // Manual trigger with single workflow should use that workflow
-        let (name, _) = select_workflow(&pipeline, TriggerType::Manual, "main").unwrap();
+        let (name, _) = select_workflow(&pipeline, TriggerType::Manual, "main", None).unwrap();
         assert_eq!(name, "ci");
     }
 
@@ -440,7 +652,7 @@ workflows:
         let pipeline = parse_pipeline(yaml).unwrap();
 
         // Both workflows match push on main - should fail
-        let result = select_workflow(&pipeline, TriggerType::Push, "main");
+        let result = select_workflow(&pipeline, TriggerType::Push, "main", None);
         assert!(result.is_err());
         assert!(result.unwrap_err().to_string().contains("Multiple workflows match"));
     }
@@ -459,7 +671,7 @@ workflows:
         let pipeline = parse_pipeline(yaml).unwrap();
 
         // Push trigger, but only pr workflow exists
-        let result = select_workflow(&pipeline, TriggerType::Push, "main");
+        let result = select_workflow(&pipeline, TriggerType::Push, "main", None);
         assert!(result.is_err());
     }
 
@@ -474,10 +686,10 @@ workflows:
         let pipeline = parse_pipeline(yaml).unwrap();
 
         // No triggering config means it matches all events
-        let (name, _) = select_workflow(&pipeline, TriggerType::Push, "main").unwrap();
+        let (name, _) = select_workflow(&pipeline, TriggerType::Push, "main", None).unwrap();
         assert_eq!(name, "catch-all");
 
-        let (name, _) = select_workflow(&pipeline, TriggerType::PullRequest, "feature").unwrap();
+        let (name, _) = select_workflow(&pipeline, TriggerType::PullRequest, "feature", None).unwrap();
         assert_eq!(name, "catch-all");
     }
 
@@ -532,20 +744,100 @@ workflows:
 "#;
         let pipeline = parse_pipeline(yaml).unwrap();
 
-        let (name, _) = select_workflow(&pipeline, TriggerType::Push, "main").unwrap();
+        let (name, _) = select_workflow(&pipeline, TriggerType::Push, "main", None).unwrap();
         assert_eq!(name, "production");
 
-        let (name, _) = select_workflow(&pipeline, TriggerType::Push, "release/1.0").unwrap();
+        let (name, _) = select_workflow(&pipeline, TriggerType::Push, "release/1.0", None).unwrap();
         assert_eq!(name, "production");
 
         // Beta releases are excluded from production
-        let result = select_workflow(&pipeline, TriggerType::Push, "release/beta-1");
+        let result = select_workflow(&pipeline, TriggerType::Push, "release/beta-1", None);
         assert!(result.is_err());
 
-        let (name, _) = select_workflow(&pipeline, TriggerType::Push, "develop").unwrap();
+        let (name, _) = select_workflow(&pipeline, TriggerType::Push, "develop", None).unwrap();
         assert_eq!(name, "staging");
     }
 
+    #[test]
+    fn test_select_tag_workflow() {
+        let yaml = r#"
+workflows:
+  ci:
+    triggering:
+      branch_patterns:
+        include:
+          - main
+    scripts:
+      - script: echo "ci"
+  release:
+    triggering:
+      events:
+        - tag
+      tag_patterns:
+        include:
+          - "v*"
+        exclude:
+          - "*-rc*"
+    scripts:
+      - script: echo "release"
+"#;
+        let pipeline = parse_pipeline(yaml).unwrap();
+
+        let (name, _) = select_workflow(&pipeline, TriggerType::Tag, "v1.2.0", None).unwrap();
+        assert_eq!(name, "release");
+
+        // Branch patterns don't apply to tags, and tags must be listed in events
+        let (name, _) = select_workflow(&pipeline, TriggerType::Push, "main", None).unwrap();
+        assert_eq!(name, "ci");
+        assert!(select_workflow(&pipeline, TriggerType::Tag, "main", None).is_err());
+        assert!(select_workflow(&pipeline, TriggerType::Tag, "v2.0.0-rc1", None).is_err());
+    }
+
+    #[test]
+    fn test_select_workflow_by_changeset() {
+        let yaml = r#"
+workflows:
+  android:
+    when:
+      changeset:
+        includes:
+          - apps/android
+          - packages
+        excludes:
+          - "**/*.md"
+    scripts:
+      - script: echo "android"
+  ios:
+    when:
+      changeset:
+        includes:
+          - apps/ios
+    scripts:
+      - script: echo "ios"
+"#;
+        let pipeline = parse_pipeline(yaml).unwrap();
+        let changed = |paths: &[&str]| paths.iter().map(|p| p.to_string()).collect::<Vec<_>>();
+
+        let files = changed(&["apps/android/app/build.gradle"]);
+        let (name, _) = select_workflow(&pipeline, TriggerType::Push, "main", Some(&files)).unwrap();
+        assert_eq!(name, "android");
+
+        let files = changed(&["apps/ios/Podfile", "README.md"]);
+        let (name, _) = select_workflow(&pipeline, TriggerType::Push, "main", Some(&files)).unwrap();
+        assert_eq!(name, "ios");
+
+        let files = changed(&["packages/README.md"]);
+        assert!(matches!(
+            select_workflow(&pipeline, TriggerType::Push, "main", Some(&files)),
+            Err(OoreError::NoRelevantChanges)
+        ));
+
+        // Both apps changed, or the changes are unknown
+        let files = changed(&["apps/ios/Podfile", "packages/core/lib/core.dart"]);
+        assert!(select_workflow(&pipeline, TriggerType::Push, "main", Some(&files)).is_err());
+        assert!(select_workflow(&pipeline, TriggerType::Push, "main", None).is_err());
+    }
+
     #[test]
     fn test_select_exclude_takes_precedence() {
         let yaml = r#"
@@ -565,11 +857,11 @@ workflows:
         let pipeline = parse_pipeline(yaml).unwrap();
 
         // Include * but exclude main - main should not match
-        let result = select_workflow(&pipeline, TriggerType::Push, "main");
+        let result = select_workflow(&pipeline, TriggerType::Push, "main", None);
         assert!(result.is_err());
 
         // Other branches should match
-        let (name, _) = select_workflow(&pipeline, TriggerType::Push, "feature").unwrap();
+        let (name, _) = select_workflow(&pipeline, TriggerType::Push, "feature", None).unwrap();
         assert_eq!(name, "build");
     }
 
@@ -590,14 +882,14 @@ workflows:
         let pipeline = parse_pipeline(yaml).unwrap();
 
         // Empty include means all branches (except excluded)
-        let (name, _) = select_workflow(&pipeline, TriggerType::Push, "main").unwrap();
+        let (name, _) = select_workflow(&pipeline, TriggerType::Push, "main", None).unwrap();
         assert_eq!(name, "build");
 
-        let (name, _) = select_workflow(&pipeline, TriggerType::Push, "feature/x").unwrap();
+        let (name, _) = select_workflow(&pipeline, TriggerType::Push, "feature/x", None).unwrap();
         assert_eq!(name, "build");
 
         // wip/* should be excluded
-        let result = select_workflow(&pipeline, TriggerType::Push, "wip/experiment");
+        let result = select_workflow(&pipeline, TriggerType::Push, "wip/experiment", None);
         assert!(result.is_err());
     }
 
@@ -620,7 +912,7 @@ workflows:
 "#;
         let pipeline = parse_pipeline(yaml).unwrap();
 
-        let (name, workflow) = select_workflow(&pipeline, TriggerType::Push, "main").unwrap();
+        let (name, workflow) = select_workflow(&pipeline, TriggerType::Push, "main", None).unwrap();
         assert_eq!(name, "ios-build");
         assert_eq!(workflow.name, Some("iOS Build".to_string()));
         assert_eq!(workflow.max_build_duration, 45);
@@ -644,7 +936,7 @@ workflows:
         let pipeline = parse_pipeline(yaml).unwrap();
 
         // Manual trigger with multiple workflows and no "default" should fail
-        let result = select_workflow(&pipeline, TriggerType::Manual, "main");
+        let result = select_workflow(&pipeline, TriggerType::Manual, "main", None);
         assert!(result.is_err());
         assert!(result.unwrap_err().to_string().contains("no 'default' workflow"));
     }
@@ -660,10 +952,30 @@ workflows:
         let pipeline = parse_pipeline(yaml).unwrap();
 
         // Single workflow without triggering should match any event
-        let (name, _) = select_workflow(&pipeline, TriggerType::Push, "main").unwrap();
+        let (name, _) = select_workflow(&pipeline, TriggerType::Push, "main", None).unwrap();
         assert_eq!(name, "ci");
     }
 
+    #[test]
+    fn test_merge_environment_precedence() {
+        let repo_vars = vec![
+            (Some("prod".to_string()), "API_URL".to_string(), "https://prod".to_string()),
+            (None, "API_URL".to_string(), "https://default".to_string()),
+            (None, "LOG_LEVEL".to_string(), "info".to_string()),
+            (Some("staging".to_string()), "API_URL".to_string(), "https://staging".to_string()),
+            (Some("staging".to_string()), "TOKEN".to_string(), "staging-token".to_string()),
+            (Some("prod".to_string()), "TOKEN".to_string(), "prod-token".to_string()),
+        ];
+        let groups = vec!["staging".to_string(), "prod".to_string()];
+        let workflow_vars = HashMap::from([("LOG_LEVEL".to_string(), "debug".to_string())]);
+
+        let vars = merge_environment(repo_vars, &groups, &workflow_vars);
+
+        assert_eq!(vars.get("API_URL"), Some(&"https://prod".to_string()));
+        assert_eq!(vars.get("TOKEN"), Some(&"prod-token".to_string()));
+        assert_eq!(vars.get("LOG_LEVEL"), Some(&"debug".to_string()));
+    }
+
     #[test]
     fn test_matches_branch_pattern_exact_match() {
         assert!(matches_branch_pattern("main", "main"));
@@ -0,0 +1,782 @@
+//! Sandboxed build execution on Linux.
+//!
+//! Steps run on the host, but each one gets its own mount, PID, IPC and UTS
+//! namespaces and runs as an unprivileged user. The sandbox sees the host's
+//! file system except for hidden paths (the server's configuration, database
+//! and data directories, and the directory holding every build's workspace),
+//! which are covered with empty mounts. Only the build's own workspace and
+//! home directory are mounted back, and `/tmp`, `/var/tmp` and `/dev/shm` are
+//! private to the step.
+//!
+//! When the server runs as root, each running build gets its own uid from a
+//! configured range, so builds can't signal each other's processes or use up
+//! each other's process limit, and their workspaces are only accessible to
+//! that uid.
+//!
+//! With `bwrap` (bubblewrap) installed, steps also get a user namespace and
+//! everything outside the workspace and home is read-only. Without it the
+//! server must run as root: steps are started with `unshare` and drop to the
+//! sandbox uid with `setpriv` once the mounts are set up.
+//!
+//! Step environment variables are passed to the sandbox under a prefix and
+//! only restored after dropping privileges, so variables such as
+//! `LD_PRELOAD` or `BASH_ENV` never reach the privileged helpers.
+
+use async_trait::async_trait;
+use ipnet::IpNet;
+use std::collections::HashMap;
+use std::net::IpAddr;
+use std::os::unix::fs::PermissionsExt;
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use std::sync::Mutex;
+use tokio::io::AsyncWriteExt;
+use tokio::process::Command;
+use tokio::sync::watch;
+
+use crate::error::{OoreError, Result};
+use crate::models::{CloneConfig, Workflow};
+
+use super::executor::{
+    home_path, run_logged, BuildExecutor, BuildLimits, OutputSender, ShellExecutor, StepResult,
+};
+use super::masking::SecretMasker;
+
+/// Prefix of step environment variables while they pass through the
+/// privileged helpers.
+const ENV_PREFIX: &str = "OORE_SANDBOX_ENV_";
+
+/// PATH for the helpers when the server has none.
+const DEFAULT_PATH: &str = "/usr/local/sbin:/usr/local/bin:/usr/sbin:/usr/bin:/sbin:/bin";
+
+/// nftables table holding the network allowlist.
+const NFT_TABLE: &str = "oore_sandbox";
+
+/// Runs as root in the new namespaces when `bwrap` isn't available.
+///
+/// Arguments: workspace, home, hidden paths, `--`, then the command to run.
+/// The workspace and home are opened before anything is hidden and bound
+/// back afterwards, so they stay visible even under a hidden directory.
+const MOUNT_SCRIPT: &str = r#"set -e
+workspace=$1 home=$2
+shift 2
+exec 3<"$workspace" 4<"$home"
+for dir in /tmp /var/tmp /dev/shm; do
+    if [ -d "$dir" ]; then
+        mount -t tmpfs -o mode=1777 oore-tmp "$dir"
+    fi
+done
+while [ "$1" != -- ]; do
+    if [ -d "$1" ]; then
+        mount -t tmpfs -o size=64k,mode=0755 oore-hidden "$1"
+    elif [ -e "$1" ]; then
+        mount --bind /dev/null "$1"
+    fi
+    shift
+done
+shift
+mkdir -p "$workspace" "$home"
+mount --no-canonicalize --bind /proc/self/fd/3 "$workspace"
+mount --no-canonicalize --bind /proc/self/fd/4 "$home"
+exec 3<&- 4<&-
+cd "$workspace"
+exec "$@"
+"#;
+
+/// Runs as the sandbox user: applies rlimits, restores the step
+/// environment and runs the step script.
+///
+/// Arguments: CPU seconds, file size in 1 KiB blocks, process count, script.
+const LAUNCH_SCRIPT: &str = r#"ulimit -t "$1" && ulimit -f "$2" && ulimit -u "$3" || exit 125
+script=$4
+for name in $(compgen -e); do
+    case $name in
+        OORE_SANDBOX_ENV_*)
+            export "${name#OORE_SANDBOX_ENV_}=${!name}"
+            unset "$name"
+            ;;
+    esac
+done
+exec /bin/bash -c "$script"
+"#;
+
+/// Network access of sandboxed steps.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub enum NetworkPolicy {
+    /// The host's network, unrestricted.
+    #[default]
+    Unrestricted,
+    /// Loopback only, in a separate network namespace.
+    Disabled,
+    /// Only these hosts, IP addresses or CIDR ranges, plus loopback and DNS
+    /// to the resolvers in `/etc/resolv.conf`.
+    Allowlist(Vec<String>),
+}
+
+impl NetworkPolicy {
+    /// Parses `all`, `none` or a comma-separated allowlist.
+    pub fn parse(value: &str) -> Self {
+        match value.trim() {
+            "" | "all" => NetworkPolicy::Unrestricted,
+            "none" => NetworkPolicy::Disabled,
+            hosts => NetworkPolicy::Allowlist(
+                hosts
+                    .split(',')
+                    .map(str::trim)
+                    .filter(|host| !host.is_empty())
+                    .map(String::from)
+                    .collect(),
+            ),
+        }
+    }
+}
+
+/// Sandbox settings.
+#[derive(Debug, Clone)]
+pub struct SandboxConfig {
+    /// First uid (and gid) given to builds when the server runs as root.
+    pub first_uid: u32,
+    /// Number of uids builds are given, which caps concurrent sandbox builds.
+    pub uid_count: u32,
+    /// CPU time limit of each process, in seconds.
+    pub max_cpu_secs: u64,
+    /// Maximum number of processes of each build.
+    pub max_processes: u64,
+    /// Network access of steps.
+    pub network: NetworkPolicy,
+    /// Files and directories hidden from steps.
+    pub hidden_paths: Vec<PathBuf>,
+}
+
+impl Default for SandboxConfig {
+    fn default() -> Self {
+        Self {
+            first_uid: 200_000,
+            uid_count: 1000,
+            max_cpu_secs: 3600,
+            max_processes: 1024,
+            network: NetworkPolicy::Unrestricted,
+            // Server configuration and the default data directory
+            hidden_paths: vec![PathBuf::from("/etc/oore"), PathBuf::from("/var/lib/oore")],
+        }
+    }
+}
+
+impl SandboxConfig {
+    /// The uids builds are given.
+    pub fn uids(&self) -> std::ops::Range<u32> {
+        self.first_uid..self.first_uid.saturating_add(self.uid_count)
+    }
+}
+
+impl SandboxConfig {
+    /// Loads settings from environment variables with defaults.
+    ///
+    /// The SQLite database from `DATABASE_URL` is always hidden.
+    pub fn from_env() -> Self {
+        let mut config = Self::default();
+
+        if let Ok(val) = std::env::var("OORE_SANDBOX_FIRST_UID")
+            && let Ok(v) = val.parse()
+        {
+            config.first_uid = v;
+        }
+
+        if let Ok(val) = std::env::var("OORE_SANDBOX_UID_COUNT")
+            && let Ok(v) = val.parse()
+        {
+            config.uid_count = v;
+        }
+
+        if let Ok(val) = std::env::var("OORE_SANDBOX_MAX_CPU_SECS")
+            && let Ok(v) = val.parse()
+        {
+            config.max_cpu_secs = v;
+        }
+
+        if let Ok(val) = std::env::var("OORE_SANDBOX_MAX_PROCESSES")
+            && let Ok(v) = val.parse()
+        {
+            config.max_processes = v;
+        }
+
+        if let Ok(val) = std::env::var("OORE_SANDBOX_NETWORK") {
+            config.network = NetworkPolicy::parse(&val);
+        }
+
+        if let Ok(val) = std::env::var("OORE_SANDBOX_HIDE_PATHS") {
+            config.hidden_paths.extend(
+                val.split(',')
+                    .map(str::trim)
+                    .filter(|path| !path.is_empty())
+                    .map(PathBuf::from),
+            );
+        }
+
+        if let Ok(val) = std::env::var("DATABASE_URL")
+            && let Some(db) = sqlite_path(&val)
+        {
+            // The write-ahead log holds recent writes too
+            for suffix in ["", "-wal", "-shm"] {
+                let mut path = db.clone().into_os_string();
+                path.push(suffix);
+                config.hidden_paths.push(path.into());
+            }
+        }
+
+        config
+    }
+}
+
+/// Sandboxed build executor for Linux.
+///
+/// Repositories are cloned on the host; only steps run in the sandbox.
+pub struct SandboxExecutor {
+    host: ShellExecutor,
+    config: SandboxConfig,
+    limits: BuildLimits,
+    /// Path of `bwrap`, if installed.
+    bwrap: Option<PathBuf>,
+    /// Whether the server runs as root.
+    root: bool,
+    /// Uids of running builds, keyed by workspace.
+    uids: Mutex<HashMap<PathBuf, u32>>,
+}
+
+impl SandboxExecutor {
+    /// Creates a sandbox executor that clones with `host`.
+    pub fn new(host: ShellExecutor, mut config: SandboxConfig) -> Self {
+        config.hidden_paths = config
+            .hidden_paths
+            .iter()
+            .filter_map(|path| std::path::absolute(path).ok())
+            .collect();
+
+        Self {
+            host,
+            config,
+            limits: BuildLimits::from_env(),
+            bwrap: find_program("bwrap"),
+            root: is_root(),
+            uids: Mutex::default(),
+        }
+    }
+
+    /// Sets the limits applied to steps.
+    pub fn with_limits(mut self, limits: BuildLimits) -> Self {
+        self.limits = limits;
+        self
+    }
+
+    /// Returns whether steps can be sandboxed on this machine.
+    pub fn is_available(&self) -> bool {
+        cfg!(target_os = "linux") && (self.bwrap.is_some() || self.root)
+    }
+
+    /// Returns the uid of the build using `workspace`, giving it the lowest
+    /// free one if it has none yet.
+    fn uid(&self, workspace: &Path) -> Result<u32> {
+        let mut uids = self.uids.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(uid) = uids.get(workspace) {
+            return Ok(*uid);
+        }
+
+        let uid = self
+            .config
+            .uids()
+            .find(|uid| !uids.values().any(|used| used == uid))
+            .ok_or_else(|| {
+                OoreError::BuildExecution(format!(
+                    "All {} sandbox uids are in use",
+                    self.config.uid_count
+                ))
+            })?;
+        uids.insert(workspace.to_path_buf(), uid);
+        Ok(uid)
+    }
+
+    /// Paths hidden from the build using `workspace`: the configured ones
+    /// and the directory holding every build's workspace.
+    fn hidden_paths(&self, workspace: &Path) -> Vec<PathBuf> {
+        let mut paths = self.config.hidden_paths.clone();
+        if let Some(workspaces) = workspace.parent()
+            && workspaces.parent().is_some()
+        {
+            paths.push(workspaces.to_path_buf());
+        }
+        paths
+    }
+
+    /// Builds the command running `script` in the sandbox as `uid`.
+    fn command(
+        &self,
+        workspace: &Path,
+        uid: u32,
+        script: &str,
+        env: &HashMap<String, String>,
+    ) -> Command {
+        let home = home_path(workspace);
+        let hidden_paths = self.hidden_paths(workspace);
+        let setpriv = [
+            format!("--reuid={}", uid),
+            format!("--regid={}", uid),
+            "--clear-groups".to_string(),
+            "--no-new-privs".to_string(),
+        ];
+        let isolate_network = self.config.network == NetworkPolicy::Disabled;
+
+        let mut command = match &self.bwrap {
+            Some(bwrap) => {
+                let mut command = if self.root {
+                    let mut command = Command::new("setpriv");
+                    command.args(&setpriv).arg("--").arg(bwrap);
+                    command
+                } else {
+                    Command::new(bwrap)
+                };
+                command.args([
+                    "--die-with-parent",
+                    "--new-session",
+                    "--unshare-user",
+                    "--unshare-pid",
+                    "--unshare-ipc",
+                    "--unshare-uts",
+                ]);
+                if isolate_network {
+                    command.arg("--unshare-net");
+                }
+                command.args([
+                    "--ro-bind",
+                    "/",
+                    "/",
+                    "--dev",
+                    "/dev",
+                    "--proc",
+                    "/proc",
+                    "--tmpfs",
+                    "/tmp",
+                ]);
+                for path in &hidden_paths {
+                    if path.is_dir() {
+                        command.arg("--tmpfs").arg(path);
+                    } else if path.exists() {
+                        command.arg("--ro-bind").arg("/dev/null").arg(path);
+                    }
+                }
+                command
+                    .arg("--bind")
+                    .arg(workspace)
+                    .arg(workspace)
+                    .arg("--bind")
+                    .arg(&home)
+                    .arg(&home)
+                    .arg("--chdir")
+                    .arg(workspace)
+                    .arg("--");
+                command
+            }
+            None => {
+                let mut command = Command::new("unshare");
+                command.args([
+                    "--mount",
+                    "--pid",
+                    "--fork",
+                    "--kill-child",
+                    "--mount-proc",
+                    "--ipc",
+                    "--uts",
+                ]);
+                if isolate_network {
+                    command.arg("--net");
+                }
+                command
+                    .args(["--", "/bin/bash", "-c", MOUNT_SCRIPT, "oore-sandbox"])
+                    .arg(workspace)
+                    .arg(&home)
+                    .args(&hidden_paths)
+                    .arg("--")
+                    .arg("setpriv")
+                    .args(&setpriv)
+                    .arg("--");
+                command
+            }
+        };
+
+        command
+            .args(["/bin/bash", "-c", LAUNCH_SCRIPT, "oore-sandbox"])
+            .arg(self.config.max_cpu_secs.to_string())
+            .arg(self.limits.max_log_size_bytes.div_ceil(1024).to_string())
+            .arg(self.config.max_processes.to_string())
+            .arg(script);
+
+        // Nothing from the server's own environment reaches the step
+        let host_path = std::env::var("PATH").unwrap_or_else(|_| DEFAULT_PATH.to_string());
+        command
+            .env_clear()
+            .current_dir(workspace)
+            .env("PATH", &host_path);
+        for (key, value) in env {
+            command.env(format!("{}{}", ENV_PREFIX, key), value);
+        }
+        if !env.contains_key("PATH") {
+            command.env(format!("{}PATH", ENV_PREFIX), &host_path);
+        }
+        if !env.contains_key("HOME") {
+            command.env(format!("{}HOME", ENV_PREFIX), &home);
+        }
+
+        command
+    }
+
+    /// Gives the build's uid the workspace and home, including files the
+    /// server wrote since the last step, and closes them to other users.
+    async fn chown(&self, workspace: &Path, uid: u32) -> Result<()> {
+        let home = home_path(workspace);
+        let owner = format!("{}:{}", uid, uid);
+        let output = Command::new("chown")
+            .arg("-R")
+            .arg(&owner)
+            .arg(workspace)
+            .arg(&home)
+            .output()
+            .await?;
+
+        if !output.status.success() {
+            return Err(OoreError::BuildExecution(format!(
+                "Failed to give the workspace to the sandbox user: {}",
+                String::from_utf8_lossy(&output.stderr).trim()
+            )));
+        }
+
+        for dir in [workspace, home.as_path()] {
+            tokio::fs::set_permissions(dir, std::fs::Permissions::from_mode(0o700)).await?;
+        }
+        Ok(())
+    }
+
+    /// Installs the nftables rules limiting the sandbox uids to `hosts`.
+    ///
+    /// Host names are resolved on every call, so rules follow DNS changes
+    /// from one step to the next.
+    async fn apply_allowlist(&self, hosts: &[String]) -> Result<()> {
+        let allowed = resolve_allowlist(hosts).await?;
+        let resolv_conf = tokio::fs::read_to_string("/etc/resolv.conf")
+            .await
+            .unwrap_or_default();
+        let ruleset = allowlist_ruleset(self.config.uids(), &allowed, &nameservers(&resolv_conf));
+
+        let mut child = Command::new("nft")
+            .args(["-f", "-"])
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(|e| OoreError::BuildExecution(format!("Failed to run nft: {}", e)))?;
+        if let Some(mut stdin) = child.stdin.take() {
+            stdin.write_all(ruleset.as_bytes()).await?;
+        }
+        let output = child.wait_with_output().await?;
+
+        if output.status.success() {
+            Ok(())
+        } else {
+            Err(OoreError::BuildExecution(format!(
+                "Failed to apply the sandbox network allowlist: {}",
+                String::from_utf8_lossy(&output.stderr).trim()
+            )))
+        }
+    }
+}
+
+#[async_trait]
+impl BuildExecutor for SandboxExecutor {
+    async fn clone_repo(
+        &self,
+        clone_url: &str,
+        commit_sha: &str,
+        workspace: &Path,
+        auth_token: Option<&str>,
+        config: &CloneConfig,
+    ) -> Result<()> {
+        self.host
+            .clone_repo(clone_url, commit_sha, workspace, auth_token, config)
+            .await
+    }
+
+    async fn prepare_build(&self, workspace: &Path, _workflow: &Workflow) -> Result<()> {
+        if !self.is_available() {
+            return Err(OoreError::Configuration(
+                "Sandbox builds need Linux with bubblewrap (bwrap) installed or the server running as root"
+                    .to_string(),
+            ));
+        }
+
+        if matches!(self.config.network, NetworkPolicy::Allowlist(_)) && !self.root {
+            return Err(OoreError::Configuration(
+                "A sandbox network allowlist needs the server running as root".to_string(),
+            ));
+        }
+
+        tokio::fs::create_dir_all(home_path(workspace)).await?;
+        Ok(())
+    }
+
+    fn home_dir(&self, workspace: &Path) -> Option<PathBuf> {
+        Some(home_path(workspace))
+    }
+
+    async fn execute_step(
+        &self,
+        workspace: &Path,
+        script: &str,
+        env: &HashMap<String, String>,
+        timeout_secs: u64,
+        log_dir: &Path,
+        step_index: i32,
+        secrets: &SecretMasker,
+        output: Option<&OutputSender>,
+        cancel_rx: &mut watch::Receiver<bool>,
+    ) -> Result<StepResult> {
+        let uid = self.uid(workspace)?;
+        if self.root {
+            tokio::fs::create_dir_all(home_path(workspace)).await?;
+            self.chown(workspace, uid).await?;
+        }
+
+        if let NetworkPolicy::Allowlist(hosts) = &self.config.network {
+            self.apply_allowlist(hosts).await?;
+        }
+
+        // Killing unshare or bwrap ends the step's PID namespace, taking any
+        // background processes with it
+        run_logged(
+            self.command(workspace, uid, script, env),
+            &self.limits,
+            timeout_secs,
+            log_dir,
+            step_index,
+            secrets,
+            output,
+            cancel_rx,
+        )
+        .await
+    }
+
+    async fn cleanup(&self, workspace: &Path) -> Result<()> {
+        let home = home_path(workspace);
+        if home.exists() {
+            tokio::fs::remove_dir_all(&home).await?;
+        }
+
+        let result = self.host.cleanup(workspace).await;
+        // The build's processes died with its last step, so the uid is free
+        self.uids
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .remove(workspace);
+        result
+    }
+}
+
+/// Resolves allowlist entries to addresses and ranges.
+async fn resolve_allowlist(hosts: &[String]) -> Result<Vec<IpNet>> {
+    let mut allowed = Vec::new();
+
+    for host in hosts {
+        if let Ok(net) = host.parse::<IpNet>() {
+            allowed.push(net);
+        } else if let Ok(addr) = host.parse::<IpAddr>() {
+            allowed.push(IpNet::from(addr));
+        } else {
+            let addrs = tokio::net::lookup_host((host.as_str(), 443))
+                .await
+                .map_err(|e| {
+                    OoreError::BuildExecution(format!(
+                        "Failed to resolve allowed host {}: {}",
+                        host, e
+                    ))
+                })?;
+            allowed.extend(addrs.map(|addr| IpNet::from(addr.ip())));
+        }
+    }
+
+    allowed.sort();
+    allowed.dedup();
+    Ok(allowed)
+}
+
+/// Nameserver addresses listed in a resolv.conf.
+fn nameservers(resolv_conf: &str) -> Vec<IpNet> {
+    resolv_conf
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.split_whitespace();
+            if fields.next() != Some("nameserver") {
+                return None;
+            }
+            // IPv6 link-local servers may carry a zone, e.g. fe80::1%eth0
+            let addr = fields.next()?.split('%').next()?;
+            addr.parse::<IpAddr>().ok().map(IpNet::from)
+        })
+        .collect()
+}
+
+/// nftables rules accepting traffic to `nets`, split by address family,
+/// with `matching` after the address match.
+fn accept_rules(nets: &[IpNet], matching: &str) -> Vec<String> {
+    let family = |v4: bool| {
+        nets.iter()
+            .filter(|net| matches!(net, IpNet::V4(_)) == v4)
+            .map(IpNet::to_string)
+            .collect::<Vec<_>>()
+            .join(", ")
+    };
+
+    [("ip", family(true)), ("ip6", family(false))]
+        .into_iter()
+        .filter(|(_, set)| !set.is_empty())
+        .map(|(ip, set)| format!("{} daddr {{ {} }} {}accept", ip, set, matching))
+        .collect()
+}
+
+/// nftables ruleset rejecting outgoing traffic of `uids` except to
+/// `allowed`, loopback, and DNS to the system's `resolvers`.
+///
+/// Replaces any previous version of the table atomically.
+fn allowlist_ruleset(uids: std::ops::Range<u32>, allowed: &[IpNet], resolvers: &[IpNet]) -> String {
+    let mut rules = vec!["oifname \"lo\" accept".to_string()];
+    rules.extend(accept_rules(
+        resolvers,
+        "meta l4proto { tcp, udp } th dport 53 ",
+    ));
+    rules.extend(accept_rules(allowed, ""));
+    rules.push("reject".to_string());
+
+    let mut ruleset = format!(
+        "table inet {table}\n\
+         delete table inet {table}\n\
+         table inet {table} {{\n\
+         \tchain output {{\n\
+         \t\ttype filter hook output priority 0; policy accept;\n\
+         \t\tmeta skuid {first}-{last} jump sandbox\n\
+         \t}}\n\
+         \tchain sandbox {{\n",
+        table = NFT_TABLE,
+        first = uids.start,
+        last = uids.end.saturating_sub(1).max(uids.start),
+    );
+    for rule in rules {
+        ruleset.push_str("\t\t");
+        ruleset.push_str(&rule);
+        ruleset.push('\n');
+    }
+    ruleset.push_str("\t}\n}\n");
+    ruleset
+}
+
+/// Database file of a `sqlite:` URL.
+fn sqlite_path(url: &str) -> Option<PathBuf> {
+    let path = url.strip_prefix("sqlite:")?;
+    let path = path.strip_prefix("//").unwrap_or(path);
+    let path = path.split('?').next().unwrap_or_default();
+    if path.is_empty() || path == ":memory:" {
+        return None;
+    }
+    std::path::absolute(path).ok()
+}
+
+/// Finds an executable on the server's PATH.
+fn find_program(name: &str) -> Option<PathBuf> {
+    let path = std::env::var_os("PATH")?;
+    std::env::split_paths(&path)
+        .map(|dir| dir.join(name))
+        .find(|path| path.is_file())
+}
+
+fn is_root() -> bool {
+    // SAFETY: geteuid() takes no arguments, has no side effects and can't fail.
+    unsafe { libc::geteuid() == 0 }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_network_policy_parse() {
+        assert_eq!(NetworkPolicy::parse("all"), NetworkPolicy::Unrestricted);
+        assert_eq!(NetworkPolicy::parse(""), NetworkPolicy::Unrestricted);
+        assert_eq!(NetworkPolicy::parse("none"), NetworkPolicy::Disabled);
+        assert_eq!(
+            NetworkPolicy::parse("pub.dev, 10.0.0.0/8,,maven.google.com"),
+            NetworkPolicy::Allowlist(vec![
+                "pub.dev".to_string(),
+                "10.0.0.0/8".to_string(),
+                "maven.google.com".to_string(),
+            ])
+        );
+    }
+
+    #[test]
+    fn test_sqlite_path() {
+        assert_eq!(
+            sqlite_path("sqlite:/var/lib/oore/oore.db?mode=rwc"),
+            Some(PathBuf::from("/var/lib/oore/oore.db"))
+        );
+        assert_eq!(
+            sqlite_path("sqlite:///var/lib/oore/oore.db"),
+            Some(PathBuf::from("/var/lib/oore/oore.db"))
+        );
+        assert!(sqlite_path("sqlite:oore.db").unwrap().is_absolute());
+        assert_eq!(sqlite_path("sqlite::memory:"), None);
+        assert_eq!(sqlite_path("postgres://localhost/oore"), None);
+    }
+
+    #[tokio::test]
+    async fn test_allowlist_ruleset() {
+        let allowed = resolve_allowlist(&[
+            "10.0.0.0/8".to_string(),
+            "192.0.2.7".to_string(),
+            "2001:db8::1".to_string(),
+            "localhost".to_string(),
+        ])
+        .await
+        .unwrap();
+        let resolvers = ["192.0.2.53/32".parse().unwrap()];
+        let ruleset = allowlist_ruleset(200_000..201_000, &allowed, &resolvers);
+
+        assert!(ruleset.starts_with("table inet oore_sandbox\ndelete table inet oore_sandbox\n"));
+        assert!(ruleset.contains("meta skuid 200000-200999 jump sandbox"));
+        assert!(ruleset.contains("ip daddr { 10.0.0.0/8, 127.0.0.1/32, 192.0.2.7/32 } accept"));
+        assert!(ruleset.contains("ip6 daddr { "));
+        assert!(ruleset.contains("2001:db8::1/128"));
+        assert!(ruleset.trim_end().ends_with("reject\n\t}\n}"));
+        // DNS only goes to the system's resolvers
+        assert!(ruleset
+            .contains("ip daddr { 192.0.2.53/32 } meta l4proto { tcp, udp } th dport 53 accept"));
+        assert!(!ruleset.contains("\tmeta l4proto"));
+
+        // Without IPv6 entries or resolvers there's no empty set
+        let v4_only = allowlist_ruleset(1000..1001, &["192.0.2.0/24".parse().unwrap()], &[]);
+        assert!(!v4_only.contains("ip6 daddr"));
+        assert!(!v4_only.contains("dport 53"));
+        assert!(v4_only.contains("meta skuid 1000-1000 jump sandbox"));
+    }
+
+    #[test]
+    fn test_nameservers() {
+        let resolv_conf = "# Generated\n\
+            nameserver 127.0.0.53\n\
+            nameserver fe80::1%eth0\n\
+            nameserver not-an-address\n\
+            search example.com\n";
+        assert_eq!(
+            nameservers(resolv_conf),
+            vec![
+                "127.0.0.53/32".parse::<IpNet>().unwrap(),
+                "fe80::1/128".parse().unwrap(),
+            ]
+        );
+        assert!(nameservers("").is_empty());
+    }
+}
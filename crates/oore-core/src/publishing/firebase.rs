@@ -0,0 +1,704 @@
+//! Firebase App Distribution publishing.
+//!
+//! The binary is uploaded to the app, which returns a long-running operation
+//! that resolves to the release once Firebase has processed it. Release notes
+//! are then set on the release and it is distributed to testers and groups.
+
+use std::path::Path;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use serde_json::{json, Value};
+
+use super::service_account::{check_response, fetch_access_token, ServiceAccountKey};
+use super::{
+    file_body, PublishArtifact, PublishContext, PublishOutcome, Publisher, RequiredCredential,
+};
+use crate::db::publishing::FirebaseCredentialRepo;
+use crate::error::{OoreError, Result};
+use crate::models::{FirebaseCredential, FirebaseCredentialId, FirebasePublishing};
+use crate::oauth::{decrypt_with_aad, encrypt_with_aad, EncryptionKey};
+
+/// Key under `publishing:`.
+pub const KEY: &str = "firebase";
+
+/// AAD table name used when encrypting Firebase service account keys.
+pub const FIREBASE_CREDENTIAL_AAD_TABLE: &str = "firebase_credentials";
+
+/// Default Firebase App Distribution API base URL.
+pub const DEFAULT_FIREBASE_API_URL: &str = "https://firebaseappdistribution.googleapis.com";
+
+/// OAuth scope for the App Distribution API.
+const CLOUD_PLATFORM_SCOPE: &str = "https://www.googleapis.com/auth/cloud-platform";
+
+/// Binaries can be hundreds of megabytes, so uploads get a generous timeout.
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(30 * 60);
+
+/// API settings used for publishing.
+#[derive(Debug, Clone)]
+pub struct FirebaseEndpoints {
+    /// App Distribution API base URL (uploads go to `{api_url}/upload/...`).
+    pub api_url: String,
+    /// OAuth token endpoint for the JWT-bearer grant.
+    pub token_url: String,
+    /// Delay between upload operation checks.
+    pub poll_interval: Duration,
+    /// How long to wait for Firebase to process an upload.
+    pub processing_timeout: Duration,
+}
+
+impl Default for FirebaseEndpoints {
+    fn default() -> Self {
+        Self {
+            api_url: DEFAULT_FIREBASE_API_URL.to_string(),
+            token_url: super::google_play::DEFAULT_GOOGLE_TOKEN_URL.to_string(),
+            poll_interval: Duration::from_secs(5),
+            processing_timeout: Duration::from_secs(10 * 60),
+        }
+    }
+}
+
+impl FirebaseEndpoints {
+    /// Loads endpoints from `OORE_FIREBASE_API_URL` and
+    /// `OORE_GOOGLE_TOKEN_URL`, falling back to Google's.
+    pub fn from_env() -> Self {
+        let mut endpoints = Self::default();
+
+        if let Ok(val) = std::env::var("OORE_FIREBASE_API_URL") {
+            endpoints.api_url = val.trim_end_matches('/').to_string();
+        }
+
+        if let Ok(val) = std::env::var("OORE_GOOGLE_TOKEN_URL") {
+            endpoints.token_url = val;
+        }
+
+        endpoints
+    }
+}
+
+/// Encrypts a service account JSON key for storage.
+pub fn encrypt_service_account(
+    key: &EncryptionKey,
+    id: &FirebaseCredentialId,
+    json: &str,
+) -> Result<(Vec<u8>, Vec<u8>)> {
+    encrypt_with_aad(
+        key,
+        json.as_bytes(),
+        FIREBASE_CREDENTIAL_AAD_TABLE,
+        &id.to_string(),
+    )
+}
+
+/// Decrypts and parses a stored service account key.
+pub fn decrypt_service_account(
+    key: &EncryptionKey,
+    credential: &FirebaseCredential,
+) -> Result<ServiceAccountKey> {
+    let bytes = decrypt_with_aad(
+        key,
+        &credential.service_account_encrypted,
+        &credential.service_account_nonce,
+        FIREBASE_CREDENTIAL_AAD_TABLE,
+        &credential.id.to_string(),
+    )?;
+    let json = String::from_utf8(bytes)
+        .map_err(|_| OoreError::Encryption("Service account key is not valid UTF-8".to_string()))?;
+    ServiceAccountKey::from_json(&json)
+}
+
+/// Platform of a Firebase app, from its app ID.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FirebasePlatform {
+    Android,
+    Ios,
+}
+
+/// Parts of a Firebase app ID (`1:<project number>:<platform>:<hash>`).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FirebaseAppId {
+    pub project_number: String,
+    pub platform: FirebasePlatform,
+}
+
+impl FirebaseAppId {
+    /// Parses a Firebase app ID. Web apps cannot use App Distribution.
+    pub fn parse(app_id: &str) -> std::result::Result<Self, String> {
+        let parts: Vec<&str> = app_id.split(':').collect();
+        let [version, project_number, platform, hash] = parts.as_slice() else {
+            return Err(format!(
+                "invalid app_id '{}' (expected 1:<project number>:<android|ios>:<hash>)",
+                app_id
+            ));
+        };
+
+        let valid = !version.is_empty()
+            && version.chars().all(|c| c.is_ascii_digit())
+            && !project_number.is_empty()
+            && project_number.chars().all(|c| c.is_ascii_digit())
+            && !hash.is_empty()
+            && hash.chars().all(|c| c.is_ascii_hexdigit());
+        if !valid {
+            return Err(format!(
+                "invalid app_id '{}' (expected 1:<project number>:<android|ios>:<hash>)",
+                app_id
+            ));
+        }
+
+        let platform = match *platform {
+            "android" => FirebasePlatform::Android,
+            "ios" => FirebasePlatform::Ios,
+            other => {
+                return Err(format!(
+                    "app_id '{}' is a {} app; only android and ios apps are supported",
+                    app_id, other
+                ));
+            }
+        };
+
+        Ok(Self {
+            project_number: project_number.to_string(),
+            platform,
+        })
+    }
+}
+
+/// Picks the artifact to distribute from the collected file names.
+///
+/// Android apps use the APK, or the App Bundle when no APK was collected.
+/// iOS apps use the IPA. Exactly one file of the chosen kind must exist.
+pub fn select_binary(platform: FirebasePlatform, names: &[&str]) -> Result<usize> {
+    let extensions: &[&str] = match platform {
+        FirebasePlatform::Android => &[".apk", ".aab"],
+        FirebasePlatform::Ios => &[".ipa"],
+    };
+
+    for extension in extensions {
+        let matches: Vec<usize> = names
+            .iter()
+            .enumerate()
+            .filter(|(_, name)| name.to_lowercase().ends_with(extension))
+            .map(|(i, _)| i)
+            .collect();
+        match matches.as_slice() {
+            [] => continue,
+            [index] => return Ok(*index),
+            _ => {
+                return Err(OoreError::Publishing(format!(
+                    "Expected one {} artifact, found {}: {}",
+                    extension,
+                    matches.len(),
+                    matches
+                        .iter()
+                        .map(|&i| names[i])
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                )));
+            }
+        }
+    }
+
+    Err(OoreError::Publishing(format!(
+        "No {} artifact was collected",
+        extensions.join(" or ")
+    )))
+}
+
+/// Outcome of a successful distribution.
+#[derive(Debug, Clone)]
+pub struct FirebaseRelease {
+    /// Release resource name (`projects/.../apps/.../releases/...`).
+    pub name: String,
+    pub display_version: Option<String>,
+    pub build_version: Option<String>,
+    /// Link to the release in the Firebase console.
+    pub console_uri: Option<String>,
+}
+
+/// Uploads a binary and distributes the release to testers and groups.
+pub async fn distribute(
+    endpoints: &FirebaseEndpoints,
+    key: &ServiceAccountKey,
+    config: &FirebasePublishing,
+    binary: &Path,
+) -> Result<FirebaseRelease> {
+    let app = FirebaseAppId::parse(&config.app_id).map_err(OoreError::Publishing)?;
+
+    let client = reqwest::Client::builder()
+        .timeout(REQUEST_TIMEOUT)
+        .build()
+        .map_err(|e| OoreError::Publishing(format!("Failed to create HTTP client: {}", e)))?;
+    let token =
+        fetch_access_token(&client, &endpoints.token_url, key, CLOUD_PLATFORM_SCOPE).await?;
+    let api_url = endpoints.api_url.trim_end_matches('/');
+
+    let size = tokio::fs::metadata(binary).await?.len();
+    let file_name = binary
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_else(|| "app".to_string());
+    let response = client
+        .post(format!(
+            "{}/upload/v1/projects/{}/apps/{}/releases:upload",
+            api_url, app.project_number, config.app_id
+        ))
+        .bearer_auth(&token)
+        .header("X-Goog-Upload-Protocol", "raw")
+        .header("X-Goog-Upload-File-Name", file_name)
+        .header(reqwest::header::CONTENT_TYPE, "application/octet-stream")
+        .header(reqwest::header::CONTENT_LENGTH, size)
+        .body(file_body(binary, 0, size).await?)
+        .send()
+        .await
+        .map_err(|e| OoreError::Publishing(format!("Binary upload failed: {}", e)))?;
+    let operation = check_response(response, "Binary upload").await?;
+    let operation_name = operation
+        .get("name")
+        .and_then(Value::as_str)
+        .ok_or_else(|| {
+            OoreError::Publishing("Binary upload response has no operation".to_string())
+        })?
+        .to_string();
+
+    // Wait for Firebase to process the binary into a release
+    let deadline = tokio::time::Instant::now() + endpoints.processing_timeout;
+    let release = loop {
+        let response = client
+            .get(format!("{}/v1/{}", api_url, operation_name))
+            .bearer_auth(&token)
+            .send()
+            .await
+            .map_err(|e| OoreError::Publishing(format!("Upload status request failed: {}", e)))?;
+        let operation = check_response(response, "Upload status").await?;
+
+        if operation.get("done").and_then(Value::as_bool) == Some(true) {
+            if let Some(message) = operation.pointer("/error/message").and_then(Value::as_str) {
+                return Err(OoreError::Publishing(format!(
+                    "Firebase rejected the upload: {}",
+                    message
+                )));
+            }
+            break operation
+                .pointer("/response/release")
+                .cloned()
+                .ok_or_else(|| {
+                    OoreError::Publishing("Upload finished without a release".to_string())
+                })?;
+        }
+
+        if tokio::time::Instant::now() >= deadline {
+            return Err(OoreError::Publishing(
+                "Timed out waiting for Firebase to process the upload".to_string(),
+            ));
+        }
+        tokio::time::sleep(endpoints.poll_interval).await;
+    };
+
+    let field = |name: &str| {
+        release
+            .get(name)
+            .and_then(Value::as_str)
+            .map(str::to_string)
+    };
+    let release_name =
+        field("name").ok_or_else(|| OoreError::Publishing("Release has no name".to_string()))?;
+
+    if let Some(notes) = config
+        .release_notes
+        .as_deref()
+        .filter(|n| !n.trim().is_empty())
+    {
+        let response = client
+            .patch(format!(
+                "{}/v1/{}?updateMask=release_notes.text",
+                api_url, release_name
+            ))
+            .bearer_auth(&token)
+            .json(&json!({
+                "name": release_name,
+                "releaseNotes": { "text": notes },
+            }))
+            .send()
+            .await
+            .map_err(|e| OoreError::Publishing(format!("Release notes update failed: {}", e)))?;
+        check_response(response, "Release notes update").await?;
+    }
+
+    if !config.testers.is_empty() || !config.groups.is_empty() {
+        let response = client
+            .post(format!("{}/v1/{}:distribute", api_url, release_name))
+            .bearer_auth(&token)
+            .json(&json!({
+                "testerEmails": config.testers,
+                "groupAliases": config.groups,
+            }))
+            .send()
+            .await
+            .map_err(|e| OoreError::Publishing(format!("Distribute request failed: {}", e)))?;
+        check_response(response, "Distribute").await?;
+    }
+
+    Ok(FirebaseRelease {
+        name: release_name,
+        display_version: field("displayVersion"),
+        build_version: field("buildVersion"),
+        console_uri: field("firebaseConsoleUri"),
+    })
+}
+
+/// Distributes a binary configured under `publishing.firebase`.
+pub struct FirebasePublisher {
+    config: FirebasePublishing,
+}
+
+impl FirebasePublisher {
+    pub fn new(config: FirebasePublishing) -> Self {
+        Self { config }
+    }
+}
+
+#[async_trait]
+impl Publisher for FirebasePublisher {
+    fn key(&self) -> &'static str {
+        KEY
+    }
+
+    fn display_name(&self) -> &'static str {
+        "Firebase"
+    }
+
+    fn validate_config(&self) -> std::result::Result<(), String> {
+        let config = &self.config;
+        FirebaseAppId::parse(&config.app_id)?;
+
+        if config.groups.iter().any(|group| group.trim().is_empty()) {
+            return Err("groups has an empty group alias".to_string());
+        }
+
+        if let Some(tester) = config
+            .testers
+            .iter()
+            .find(|tester| !tester.contains('@') || tester.trim() != tester.as_str())
+        {
+            return Err(format!("invalid tester email '{}'", tester));
+        }
+
+        Ok(())
+    }
+
+    fn required_credentials(&self) -> Vec<RequiredCredential> {
+        vec![RequiredCredential::FirebaseServiceAccount]
+    }
+
+    async fn publish(
+        &self,
+        ctx: &PublishContext<'_>,
+        artifacts: &[PublishArtifact],
+    ) -> Result<PublishOutcome> {
+        let config = &self.config;
+        let credential = FirebaseCredentialRepo::get_for_repo(ctx.db, ctx.repository_id)
+            .await?
+            .ok_or_else(|| {
+                OoreError::Publishing(
+                    "No Firebase service account is configured for this repository".to_string(),
+                )
+            })?;
+        let service_account = decrypt_service_account(ctx.encryption_key()?, &credential)?;
+
+        let app = FirebaseAppId::parse(&config.app_id).map_err(OoreError::Publishing)?;
+        let names: Vec<&str> = artifacts.iter().map(|a| a.name.as_str()).collect();
+        let binary = &artifacts[select_binary(app.platform, &names)?];
+
+        let mut log = format!(
+            "Uploading {} to {} as {}\n",
+            binary.name, config.app_id, credential.client_email
+        );
+        let release = distribute(
+            &ctx.endpoints.firebase,
+            &service_account,
+            config,
+            &binary.path,
+        )
+        .await?;
+        log.push_str(&format!("Created release {}\n", release.name));
+        if !config.groups.is_empty() || !config.testers.is_empty() {
+            log.push_str(&format!(
+                "Distributed to {} group(s) and {} tester(s)\n",
+                config.groups.len(),
+                config.testers.len()
+            ));
+        }
+        if let Some(uri) = &release.console_uri {
+            log.push_str(&format!("Console: {}\n", uri));
+        }
+
+        let version = match (&release.display_version, &release.build_version) {
+            (Some(display), Some(build)) => format!(" {} ({})", display, build),
+            (Some(display), None) => format!(" {}", display),
+            _ => String::new(),
+        };
+        Ok(PublishOutcome {
+            log,
+            summary: format!("Distributed{} to Firebase", version),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::publishing::service_account::TEST_PRIVATE_KEY;
+    use std::sync::{Arc, Mutex};
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+
+    const APP_ID: &str = "1:1234567890:android:0a1b2c3d4e5f";
+
+    fn service_account() -> ServiceAccountKey {
+        ServiceAccountKey::from_json(
+            &json!({
+                "type": "service_account",
+                "client_email": "distributor@oore-test.iam.gserviceaccount.com",
+                "private_key": TEST_PRIVATE_KEY,
+            })
+            .to_string(),
+        )
+        .unwrap()
+    }
+
+    fn publishing(groups: &[&str], release_notes: Option<&str>) -> FirebasePublishing {
+        FirebasePublishing {
+            app_id: APP_ID.to_string(),
+            groups: groups.iter().map(|g| g.to_string()).collect(),
+            testers: vec!["qa@example.com".to_string()],
+            release_notes: release_notes.map(str::to_string),
+        }
+    }
+
+    /// A recorded request: method, path (with query) and body.
+    type Recorded = Arc<Mutex<Vec<(String, String, String)>>>;
+
+    /// Local stand-in for the token endpoint and App Distribution API.
+    ///
+    /// The upload operation is pending on the first check and done after,
+    /// failing with `upload_error` when set.
+    async fn mock_firebase(upload_error: Option<&'static str>) -> (FirebaseEndpoints, Recorded) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let base = format!("http://{}", listener.local_addr().unwrap());
+        let recorded: Recorded = Arc::default();
+
+        let log = recorded.clone();
+        tokio::spawn(async move {
+            loop {
+                let (mut socket, _) = listener.accept().await.unwrap();
+                let mut buf = Vec::new();
+                let mut chunk = [0u8; 4096];
+                let body_start = loop {
+                    let n = socket.read(&mut chunk).await.unwrap();
+                    buf.extend_from_slice(&chunk[..n]);
+                    if let Some(pos) = buf.windows(4).position(|w| w == b"\r\n\r\n") {
+                        break pos + 4;
+                    }
+                };
+                let head = String::from_utf8_lossy(&buf[..body_start]).to_string();
+                let content_length: usize = head
+                    .to_lowercase()
+                    .lines()
+                    .find_map(|l| l.strip_prefix("content-length:").map(str::to_string))
+                    .and_then(|v| v.trim().parse().ok())
+                    .unwrap_or(0);
+                while buf.len() < body_start + content_length {
+                    let n = socket.read(&mut chunk).await.unwrap();
+                    buf.extend_from_slice(&chunk[..n]);
+                }
+
+                let mut request_line = head.lines().next().unwrap().split(' ');
+                let method = request_line.next().unwrap().to_string();
+                let path = request_line.next().unwrap().to_string();
+                let body = String::from_utf8_lossy(&buf[body_start..]).to_string();
+
+                let checks = log
+                    .lock()
+                    .unwrap()
+                    .iter()
+                    .filter(|(_, p, _)| p.contains("/operations/"))
+                    .count();
+                let release = json!({
+                    "name": "projects/1234567890/apps/1:1234567890:android:0a1b2c3d4e5f/releases/rel-1",
+                    "displayVersion": "1.4.0",
+                    "buildVersion": "42",
+                    "firebaseConsoleUri": "https://console.firebase.google.com/release/rel-1"
+                });
+                let (status, reply) = match (method.as_str(), path.as_str()) {
+                    ("POST", "/token") => ("200 OK", json!({"access_token": "ya29.test"})),
+                    ("POST", p) if p.starts_with("/upload/") => (
+                        "200 OK",
+                        json!({"name": "projects/1234567890/apps/1:1234567890:android:0a1b2c3d4e5f/releases/-/operations/op-1"}),
+                    ),
+                    ("GET", p) if p.contains("/operations/") && checks == 0 => {
+                        ("200 OK", json!({"name": "op-1", "done": false}))
+                    }
+                    ("GET", p) if p.contains("/operations/") => match upload_error {
+                        Some(message) => (
+                            "200 OK",
+                            json!({"name": "op-1", "done": true, "error": {"code": 3, "message": message}}),
+                        ),
+                        None => (
+                            "200 OK",
+                            json!({"name": "op-1", "done": true, "response": {"result": "RELEASE_CREATED", "release": release}}),
+                        ),
+                    },
+                    ("PATCH", _) => ("200 OK", release.clone()),
+                    ("POST", p) if p.ends_with(":distribute") => ("200 OK", json!({})),
+                    _ => (
+                        "404 Not Found",
+                        json!({"error": {"message": "Unknown path"}}),
+                    ),
+                };
+                log.lock().unwrap().push((method, path, body));
+
+                let reply = reply.to_string();
+                let response = format!(
+                    "HTTP/1.1 {}\r\ncontent-type: application/json\r\ncontent-length: {}\r\nconnection: close\r\n\r\n{}",
+                    status,
+                    reply.len(),
+                    reply
+                );
+                socket.write_all(response.as_bytes()).await.unwrap();
+            }
+        });
+
+        let endpoints = FirebaseEndpoints {
+            api_url: base.clone(),
+            token_url: format!("{}/token", base),
+            poll_interval: Duration::from_millis(10),
+            processing_timeout: Duration::from_secs(5),
+        };
+        (endpoints, recorded)
+    }
+
+    fn write_apk(dir: &tempfile::TempDir) -> std::path::PathBuf {
+        let path = dir.path().join("app-release.apk");
+        std::fs::write(&path, b"PK\x03\x04 not really an apk").unwrap();
+        path
+    }
+
+    #[test]
+    fn test_parse_app_id() {
+        let app = FirebaseAppId::parse(APP_ID).unwrap();
+        assert_eq!(app.project_number, "1234567890");
+        assert_eq!(app.platform, FirebasePlatform::Android);
+        assert_eq!(
+            FirebaseAppId::parse("1:42:ios:ff").unwrap().platform,
+            FirebasePlatform::Ios
+        );
+
+        assert!(FirebaseAppId::parse("com.example.app").is_err());
+        assert!(FirebaseAppId::parse("1:abc:android:ff").is_err());
+        assert!(FirebaseAppId::parse("1:42:android:xyz").is_err());
+        let err = FirebaseAppId::parse("1:42:web:ff").unwrap_err();
+        assert!(err.contains("web"), "{}", err);
+    }
+
+    #[test]
+    fn test_select_binary() {
+        let names = ["app-release.aab", "app-release.apk", "mapping.txt"];
+        assert_eq!(select_binary(FirebasePlatform::Android, &names).unwrap(), 1);
+        assert_eq!(
+            select_binary(FirebasePlatform::Android, &["app-release.aab"]).unwrap(),
+            0
+        );
+        assert_eq!(
+            select_binary(FirebasePlatform::Ios, &["Runner.ipa", "app.apk"]).unwrap(),
+            0
+        );
+
+        let err = select_binary(FirebasePlatform::Android, &["a.apk", "b.apk"])
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("a.apk, b.apk"), "{}", err);
+        assert!(select_binary(FirebasePlatform::Ios, &["app.apk"]).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_distribute_uploads_and_distributes() {
+        let (endpoints, recorded) = mock_firebase(None).await;
+        let dir = tempfile::tempdir().unwrap();
+
+        let release = distribute(
+            &endpoints,
+            &service_account(),
+            &publishing(&["qa-team"], Some("Fixes login")),
+            &write_apk(&dir),
+        )
+        .await
+        .unwrap();
+        assert!(release.name.ends_with("/releases/rel-1"));
+        assert_eq!(release.display_version.as_deref(), Some("1.4.0"));
+        assert_eq!(
+            release.console_uri.as_deref(),
+            Some("https://console.firebase.google.com/release/rel-1")
+        );
+
+        let requests = recorded.lock().unwrap();
+        let calls: Vec<(&str, &str)> = requests
+            .iter()
+            .map(|(m, p, _)| (m.as_str(), p.as_str()))
+            .collect();
+        let release_path =
+            "/v1/projects/1234567890/apps/1:1234567890:android:0a1b2c3d4e5f/releases/rel-1";
+        assert_eq!(
+            calls,
+            vec![
+                ("POST", "/token"),
+                (
+                    "POST",
+                    "/upload/v1/projects/1234567890/apps/1:1234567890:android:0a1b2c3d4e5f/releases:upload"
+                ),
+                (
+                    "GET",
+                    "/v1/projects/1234567890/apps/1:1234567890:android:0a1b2c3d4e5f/releases/-/operations/op-1"
+                ),
+                (
+                    "GET",
+                    "/v1/projects/1234567890/apps/1:1234567890:android:0a1b2c3d4e5f/releases/-/operations/op-1"
+                ),
+                (
+                    "PATCH",
+                    &format!("{}?updateMask=release_notes.text", release_path)
+                ),
+                ("POST", &format!("{}:distribute", release_path)),
+            ]
+        );
+
+        assert!(requests[0].2.contains("grant-type%3Ajwt-bearer"));
+        assert!(requests[1].2.contains("not really an apk"));
+        let notes: Value = serde_json::from_str(&requests[4].2).unwrap();
+        assert_eq!(notes["releaseNotes"]["text"], "Fixes login");
+        let distribution: Value = serde_json::from_str(&requests[5].2).unwrap();
+        assert_eq!(distribution["testerEmails"], json!(["qa@example.com"]));
+        assert_eq!(distribution["groupAliases"], json!(["qa-team"]));
+    }
+
+    #[tokio::test]
+    async fn test_distribute_reports_processing_error() {
+        let (endpoints, recorded) = mock_firebase(Some("APK is not signed")).await;
+        let dir = tempfile::tempdir().unwrap();
+
+        let err = distribute(
+            &endpoints,
+            &service_account(),
+            &publishing(&[], None),
+            &write_apk(&dir),
+        )
+        .await
+        .unwrap_err()
+        .to_string();
+        assert!(err.contains("APK is not signed"), "{}", err);
+        assert!(!recorded
+            .lock()
+            .unwrap()
+            .iter()
+            .any(|(_, p, _)| p.ends_with(":distribute")));
+    }
+}
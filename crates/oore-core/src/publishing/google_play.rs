@@ -0,0 +1,612 @@
+//! Google Play publishing through the Android Publisher API.
+//!
+//! App Bundles are uploaded with the edits flow: open an edit, upload each
+//! bundle, assign the resulting version codes to a track release, then commit.
+//! The edit is deleted if any step fails so nothing is left half-published.
+
+use std::path::PathBuf;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use serde_json::{json, Value};
+
+use super::service_account::{check_response, fetch_access_token, ServiceAccountKey};
+use super::{
+    artifacts_with_extension, file_body, PublishArtifact, PublishContext, PublishOutcome,
+    Publisher, RequiredCredential,
+};
+use crate::db::publishing::GooglePlayCredentialRepo;
+use crate::error::{OoreError, Result};
+use crate::models::{GooglePlayCredential, GooglePlayCredentialId, GooglePlayPublishing};
+use crate::oauth::{decrypt_with_aad, encrypt_with_aad, EncryptionKey};
+
+/// Key under `publishing:`.
+pub const KEY: &str = "google_play";
+
+/// AAD table name used when encrypting service account keys.
+pub const GOOGLE_PLAY_CREDENTIAL_AAD_TABLE: &str = "google_play_credentials";
+
+/// Default Android Publisher API base URL.
+pub const DEFAULT_GOOGLE_PLAY_API_URL: &str = "https://androidpublisher.googleapis.com";
+
+/// Default Google OAuth token endpoint.
+pub const DEFAULT_GOOGLE_TOKEN_URL: &str = "https://oauth2.googleapis.com/token";
+
+/// OAuth scope for the Android Publisher API.
+const ANDROID_PUBLISHER_SCOPE: &str = "https://www.googleapis.com/auth/androidpublisher";
+
+/// Bundles can be hundreds of megabytes, so uploads get a generous timeout.
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(30 * 60);
+
+/// API endpoints used for publishing.
+#[derive(Debug, Clone)]
+pub struct GooglePlayEndpoints {
+    /// Android Publisher API base URL (uploads go to `{api_url}/upload/...`).
+    pub api_url: String,
+    /// OAuth token endpoint for the JWT-bearer grant.
+    pub token_url: String,
+}
+
+impl Default for GooglePlayEndpoints {
+    fn default() -> Self {
+        Self {
+            api_url: DEFAULT_GOOGLE_PLAY_API_URL.to_string(),
+            token_url: DEFAULT_GOOGLE_TOKEN_URL.to_string(),
+        }
+    }
+}
+
+impl GooglePlayEndpoints {
+    /// Loads endpoints from `OORE_GOOGLE_PLAY_API_URL` and
+    /// `OORE_GOOGLE_TOKEN_URL`, falling back to Google's.
+    pub fn from_env() -> Self {
+        let mut endpoints = Self::default();
+
+        if let Ok(val) = std::env::var("OORE_GOOGLE_PLAY_API_URL") {
+            endpoints.api_url = val.trim_end_matches('/').to_string();
+        }
+
+        if let Ok(val) = std::env::var("OORE_GOOGLE_TOKEN_URL") {
+            endpoints.token_url = val;
+        }
+
+        endpoints
+    }
+}
+
+/// Encrypts a service account JSON key for storage.
+pub fn encrypt_service_account(
+    key: &EncryptionKey,
+    id: &GooglePlayCredentialId,
+    json: &str,
+) -> Result<(Vec<u8>, Vec<u8>)> {
+    encrypt_with_aad(
+        key,
+        json.as_bytes(),
+        GOOGLE_PLAY_CREDENTIAL_AAD_TABLE,
+        &id.to_string(),
+    )
+}
+
+/// Decrypts and parses a stored service account key.
+pub fn decrypt_service_account(
+    key: &EncryptionKey,
+    credential: &GooglePlayCredential,
+) -> Result<ServiceAccountKey> {
+    let bytes = decrypt_with_aad(
+        key,
+        &credential.service_account_encrypted,
+        &credential.service_account_nonce,
+        GOOGLE_PLAY_CREDENTIAL_AAD_TABLE,
+        &credential.id.to_string(),
+    )?;
+    let json = String::from_utf8(bytes)
+        .map_err(|_| OoreError::Encryption("Service account key is not valid UTF-8".to_string()))?;
+    ServiceAccountKey::from_json(&json)
+}
+
+/// Release status for the track update.
+pub fn release_status(config: &GooglePlayPublishing) -> &'static str {
+    if config.submit_as_draft {
+        "draft"
+    } else if config.rollout_fraction.is_some() {
+        "inProgress"
+    } else {
+        "completed"
+    }
+}
+
+/// Builds the track resource assigning `version_codes` to a new release.
+pub fn track_release(config: &GooglePlayPublishing, version_codes: &[i64]) -> Value {
+    let mut release = json!({
+        "versionCodes": version_codes.iter().map(|v| v.to_string()).collect::<Vec<_>>(),
+        "status": release_status(config),
+    });
+    if let Some(fraction) = config.rollout_fraction {
+        release["userFraction"] = json!(fraction);
+    }
+    if let Some(priority) = config.in_app_update_priority {
+        release["inAppUpdatePriority"] = json!(priority);
+    }
+
+    json!({
+        "track": config.track,
+        "releases": [release],
+    })
+}
+
+/// Outcome of a successful publish.
+#[derive(Debug, Clone)]
+pub struct GooglePlayUpload {
+    /// The committed edit.
+    pub edit_id: String,
+    /// Version codes assigned to the release, in upload order.
+    pub version_codes: Vec<i64>,
+}
+
+/// Uploads App Bundles and releases them to the configured track.
+pub async fn publish_bundles(
+    endpoints: &GooglePlayEndpoints,
+    key: &ServiceAccountKey,
+    config: &GooglePlayPublishing,
+    bundles: &[PathBuf],
+) -> Result<GooglePlayUpload> {
+    if bundles.is_empty() {
+        return Err(OoreError::Publishing(
+            "No App Bundles to upload".to_string(),
+        ));
+    }
+
+    let client = reqwest::Client::builder()
+        .timeout(REQUEST_TIMEOUT)
+        .build()
+        .map_err(|e| OoreError::Publishing(format!("Failed to create HTTP client: {}", e)))?;
+    let token =
+        fetch_access_token(&client, &endpoints.token_url, key, ANDROID_PUBLISHER_SCOPE).await?;
+
+    let api_url = endpoints.api_url.trim_end_matches('/');
+    let app_url = format!(
+        "{}/androidpublisher/v3/applications/{}",
+        api_url, config.package_name
+    );
+
+    let response = client
+        .post(format!("{}/edits", app_url))
+        .bearer_auth(&token)
+        .json(&json!({}))
+        .send()
+        .await
+        .map_err(|e| OoreError::Publishing(format!("Create edit request failed: {}", e)))?;
+    let edit = check_response(response, "Create edit").await?;
+    let edit_id = edit
+        .get("id")
+        .and_then(Value::as_str)
+        .ok_or_else(|| OoreError::Publishing("Create edit response has no id".to_string()))?
+        .to_string();
+
+    let edit_url = format!("{}/edits/{}", app_url, edit_id);
+    let upload_url = format!(
+        "{}/upload/androidpublisher/v3/applications/{}/edits/{}/bundles?uploadType=media",
+        api_url, config.package_name, edit_id
+    );
+
+    let result = async {
+        let mut version_codes = Vec::with_capacity(bundles.len());
+        for bundle in bundles {
+            let size = tokio::fs::metadata(bundle).await?.len();
+            let response = client
+                .post(&upload_url)
+                .bearer_auth(&token)
+                .header(reqwest::header::CONTENT_TYPE, "application/octet-stream")
+                .header(reqwest::header::CONTENT_LENGTH, size)
+                .body(file_body(bundle, 0, size).await?)
+                .send()
+                .await
+                .map_err(|e| OoreError::Publishing(format!("Bundle upload failed: {}", e)))?;
+            let uploaded = check_response(response, "Bundle upload").await?;
+            let version_code = uploaded
+                .get("versionCode")
+                .and_then(Value::as_i64)
+                .ok_or_else(|| {
+                    OoreError::Publishing("Bundle upload response has no versionCode".to_string())
+                })?;
+            version_codes.push(version_code);
+        }
+
+        let response = client
+            .put(format!("{}/tracks/{}", edit_url, config.track))
+            .bearer_auth(&token)
+            .json(&track_release(config, &version_codes))
+            .send()
+            .await
+            .map_err(|e| OoreError::Publishing(format!("Track update failed: {}", e)))?;
+        check_response(response, "Track update").await?;
+
+        let response = client
+            .post(format!("{}:commit", edit_url))
+            .bearer_auth(&token)
+            .send()
+            .await
+            .map_err(|e| OoreError::Publishing(format!("Commit edit failed: {}", e)))?;
+        check_response(response, "Commit edit").await?;
+
+        Ok(version_codes)
+    }
+    .await;
+
+    match result {
+        Ok(version_codes) => Ok(GooglePlayUpload {
+            edit_id,
+            version_codes,
+        }),
+        Err(e) => {
+            // Best effort: an abandoned edit expires on its own anyway
+            if let Err(delete_err) = client.delete(&edit_url).bearer_auth(&token).send().await {
+                tracing::warn!(
+                    "Failed to delete Google Play edit {}: {}",
+                    edit_id,
+                    delete_err
+                );
+            }
+            Err(e)
+        }
+    }
+}
+
+/// Publishes App Bundles configured under `publishing.google_play`.
+pub struct GooglePlayPublisher {
+    config: GooglePlayPublishing,
+}
+
+impl GooglePlayPublisher {
+    pub fn new(config: GooglePlayPublishing) -> Self {
+        Self { config }
+    }
+}
+
+#[async_trait]
+impl Publisher for GooglePlayPublisher {
+    fn key(&self) -> &'static str {
+        KEY
+    }
+
+    fn display_name(&self) -> &'static str {
+        "Google Play"
+    }
+
+    fn validate_config(&self) -> std::result::Result<(), String> {
+        let config = &self.config;
+        if config.credentials.trim().is_empty() {
+            return Err("credentials must not be empty".to_string());
+        }
+
+        let package_name = &config.package_name;
+        let valid_package = package_name.split('.').count() >= 2
+            && package_name.split('.').all(|segment| {
+                segment
+                    .chars()
+                    .next()
+                    .is_some_and(|c| c.is_ascii_alphabetic())
+                    && segment
+                        .chars()
+                        .all(|c| c.is_ascii_alphanumeric() || c == '_')
+            });
+        if !valid_package {
+            return Err(format!("invalid package_name '{}'", package_name));
+        }
+
+        let track = &config.track;
+        if track.is_empty()
+            || !track
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | ':'))
+        {
+            return Err(format!("invalid track '{}'", track));
+        }
+
+        if config.in_app_update_priority.is_some_and(|p| p > 5) {
+            return Err("in_app_update_priority must be between 0 and 5".to_string());
+        }
+
+        if let Some(fraction) = config.rollout_fraction {
+            if !(fraction > 0.0 && fraction < 1.0) {
+                return Err("rollout_fraction must be between 0 and 1 (exclusive)".to_string());
+            }
+            if config.submit_as_draft {
+                return Err("rollout_fraction cannot be combined with submit_as_draft".to_string());
+            }
+        }
+
+        Ok(())
+    }
+
+    fn required_credentials(&self) -> Vec<RequiredCredential> {
+        vec![RequiredCredential::GooglePlayServiceAccount(
+            self.config.credentials.clone(),
+        )]
+    }
+
+    async fn publish(
+        &self,
+        ctx: &PublishContext<'_>,
+        artifacts: &[PublishArtifact],
+    ) -> Result<PublishOutcome> {
+        let config = &self.config;
+        let credential = GooglePlayCredentialRepo::list_active_for_repo(ctx.db, ctx.repository_id)
+            .await?
+            .into_iter()
+            .find(|c| c.name == config.credentials)
+            .ok_or_else(|| {
+                OoreError::Publishing(format!(
+                    "Google Play credentials '{}' not found",
+                    config.credentials
+                ))
+            })?;
+        let service_account = decrypt_service_account(ctx.encryption_key()?, &credential)?;
+
+        let bundles = artifacts_with_extension(artifacts, ".aab");
+        if bundles.is_empty() {
+            return Err(OoreError::Publishing(
+                "No .aab artifacts were collected; add a pattern such as build/**/outputs/**/*.aab to artifacts".to_string(),
+            ));
+        }
+        let paths: Vec<PathBuf> = bundles.iter().map(|a| a.path.clone()).collect();
+
+        let mut log = format!(
+            "Publishing {} to {} ({} track) as {}\n",
+            bundles
+                .iter()
+                .map(|a| a.name.as_str())
+                .collect::<Vec<_>>()
+                .join(", "),
+            config.package_name,
+            config.track,
+            credential.client_email
+        );
+        let upload =
+            publish_bundles(&ctx.endpoints.google_play, &service_account, config, &paths).await?;
+        let version_codes = upload
+            .version_codes
+            .iter()
+            .map(|v| v.to_string())
+            .collect::<Vec<_>>()
+            .join(", ");
+        log.push_str(&format!(
+            "Committed edit {} with version code(s) {}\n",
+            upload.edit_id, version_codes
+        ));
+
+        Ok(PublishOutcome {
+            log,
+            summary: format!(
+                "Released version code(s) {} to the {} track",
+                version_codes, config.track
+            ),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::publishing::service_account::TEST_PRIVATE_KEY;
+    use std::sync::{Arc, Mutex};
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+
+    fn service_account_json() -> String {
+        json!({
+            "type": "service_account",
+            "project_id": "oore-test",
+            "client_email": "publisher@oore-test.iam.gserviceaccount.com",
+            "private_key": TEST_PRIVATE_KEY,
+        })
+        .to_string()
+    }
+
+    fn publishing(rollout_fraction: Option<f64>, submit_as_draft: bool) -> GooglePlayPublishing {
+        GooglePlayPublishing {
+            credentials: "play".to_string(),
+            package_name: "com.example.app".to_string(),
+            track: "beta".to_string(),
+            in_app_update_priority: Some(2),
+            rollout_fraction,
+            submit_as_draft,
+        }
+    }
+
+    /// A recorded request: method, path (with query) and body.
+    type Recorded = Arc<Mutex<Vec<(String, String, String)>>>;
+
+    /// Local stand-in for the token endpoint and Android Publisher API.
+    ///
+    /// Bundle uploads fail with 403 when `fail_upload` is set.
+    async fn mock_google_play(fail_upload: bool) -> (GooglePlayEndpoints, Recorded) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let base = format!("http://{}", listener.local_addr().unwrap());
+        let recorded: Recorded = Arc::default();
+
+        let log = recorded.clone();
+        tokio::spawn(async move {
+            loop {
+                let (mut socket, _) = listener.accept().await.unwrap();
+                let mut buf = Vec::new();
+                let mut chunk = [0u8; 4096];
+                let body_start = loop {
+                    let n = socket.read(&mut chunk).await.unwrap();
+                    buf.extend_from_slice(&chunk[..n]);
+                    if let Some(pos) = buf.windows(4).position(|w| w == b"\r\n\r\n") {
+                        break pos + 4;
+                    }
+                };
+                let head = String::from_utf8_lossy(&buf[..body_start]).to_string();
+                let content_length: usize = head
+                    .to_lowercase()
+                    .lines()
+                    .find_map(|l| l.strip_prefix("content-length:").map(str::to_string))
+                    .and_then(|v| v.trim().parse().ok())
+                    .unwrap_or(0);
+                while buf.len() < body_start + content_length {
+                    let n = socket.read(&mut chunk).await.unwrap();
+                    buf.extend_from_slice(&chunk[..n]);
+                }
+
+                let mut request_line = head.lines().next().unwrap().split(' ');
+                let method = request_line.next().unwrap().to_string();
+                let path = request_line.next().unwrap().to_string();
+                let body = String::from_utf8_lossy(&buf[body_start..]).to_string();
+
+                let (status, reply) = match (method.as_str(), path.as_str()) {
+                    ("POST", "/token") => ("200 OK", r#"{"access_token":"ya29.test"}"#),
+                    ("POST", p) if p.ends_with("/edits") => ("200 OK", r#"{"id":"edit-1"}"#),
+                    ("POST", p) if p.starts_with("/upload/") && fail_upload => (
+                        "403 Forbidden",
+                        r#"{"error":{"code":403,"message":"The caller does not have permission"}}"#,
+                    ),
+                    ("POST", p) if p.starts_with("/upload/") => ("200 OK", r#"{"versionCode":42}"#),
+                    ("PUT", _) => ("200 OK", "{}"),
+                    ("POST", p) if p.ends_with(":commit") => ("200 OK", r#"{"id":"edit-1"}"#),
+                    ("DELETE", _) => ("204 No Content", ""),
+                    _ => ("404 Not Found", "{}"),
+                };
+                log.lock().unwrap().push((method, path, body));
+
+                let response = format!(
+                    "HTTP/1.1 {}\r\ncontent-type: application/json\r\ncontent-length: {}\r\nconnection: close\r\n\r\n{}",
+                    status,
+                    reply.len(),
+                    reply
+                );
+                socket.write_all(response.as_bytes()).await.unwrap();
+            }
+        });
+
+        let endpoints = GooglePlayEndpoints {
+            api_url: base.clone(),
+            token_url: format!("{}/token", base),
+        };
+        (endpoints, recorded)
+    }
+
+    fn write_bundle(dir: &tempfile::TempDir) -> PathBuf {
+        let path = dir.path().join("app-release.aab");
+        std::fs::write(&path, b"PK\x03\x04 not really a bundle").unwrap();
+        path
+    }
+
+    #[test]
+    fn test_service_account_encryption_roundtrip() {
+        let key = EncryptionKey::from_string(&"07".repeat(32)).unwrap();
+        let id = GooglePlayCredentialId::new();
+        let (encrypted, nonce) =
+            encrypt_service_account(&key, &id, &service_account_json()).unwrap();
+        let credential = GooglePlayCredential {
+            id,
+            repository_id: crate::models::RepositoryId::new(),
+            name: "play".to_string(),
+            client_email: "publisher@oore-test.iam.gserviceaccount.com".to_string(),
+            service_account_encrypted: encrypted,
+            service_account_nonce: nonce,
+            is_active: true,
+            created_at: chrono::Utc::now(),
+            updated_at: chrono::Utc::now(),
+        };
+
+        let decrypted = decrypt_service_account(&key, &credential).unwrap();
+        assert_eq!(decrypted.client_email, credential.client_email);
+
+        // Bound to the row ID
+        let moved = GooglePlayCredential {
+            id: GooglePlayCredentialId::new(),
+            ..credential
+        };
+        assert!(decrypt_service_account(&key, &moved).is_err());
+    }
+
+    #[test]
+    fn test_track_release() {
+        let staged = track_release(&publishing(Some(0.1), false), &[41, 42]);
+        assert_eq!(staged["track"], "beta");
+        assert_eq!(staged["releases"][0]["status"], "inProgress");
+        assert_eq!(staged["releases"][0]["versionCodes"], json!(["41", "42"]));
+        assert_eq!(staged["releases"][0]["userFraction"], 0.1);
+        assert_eq!(staged["releases"][0]["inAppUpdatePriority"], 2);
+
+        let draft = track_release(&publishing(None, true), &[42]);
+        assert_eq!(draft["releases"][0]["status"], "draft");
+        assert!(draft["releases"][0].get("userFraction").is_none());
+
+        let full = track_release(&publishing(None, false), &[42]);
+        assert_eq!(full["releases"][0]["status"], "completed");
+    }
+
+    #[tokio::test]
+    async fn test_publish_bundles_runs_edit_flow() {
+        let (endpoints, recorded) = mock_google_play(false).await;
+        let key = ServiceAccountKey::from_json(&service_account_json()).unwrap();
+        let dir = tempfile::tempdir().unwrap();
+        let bundle = write_bundle(&dir);
+
+        let upload = publish_bundles(&endpoints, &key, &publishing(Some(0.5), false), &[bundle])
+            .await
+            .unwrap();
+        assert_eq!(upload.edit_id, "edit-1");
+        assert_eq!(upload.version_codes, vec![42]);
+
+        let requests = recorded.lock().unwrap();
+        let calls: Vec<(&str, &str)> = requests
+            .iter()
+            .map(|(m, p, _)| (m.as_str(), p.as_str()))
+            .collect();
+        assert_eq!(
+            calls,
+            vec![
+                ("POST", "/token"),
+                ("POST", "/androidpublisher/v3/applications/com.example.app/edits"),
+                (
+                    "POST",
+                    "/upload/androidpublisher/v3/applications/com.example.app/edits/edit-1/bundles?uploadType=media"
+                ),
+                (
+                    "PUT",
+                    "/androidpublisher/v3/applications/com.example.app/edits/edit-1/tracks/beta"
+                ),
+                (
+                    "POST",
+                    "/androidpublisher/v3/applications/com.example.app/edits/edit-1:commit"
+                ),
+            ]
+        );
+
+        assert!(requests[0]
+            .2
+            .contains("grant_type=urn%3Aietf%3Aparams%3Aoauth%3Agrant-type%3Ajwt-bearer"));
+        assert!(requests[2].2.contains("not really a bundle"));
+        let track: Value = serde_json::from_str(&requests[3].2).unwrap();
+        assert_eq!(track["releases"][0]["userFraction"], 0.5);
+    }
+
+    #[tokio::test]
+    async fn test_publish_bundles_deletes_edit_on_failure() {
+        let (endpoints, recorded) = mock_google_play(true).await;
+        let key = ServiceAccountKey::from_json(&service_account_json()).unwrap();
+        let dir = tempfile::tempdir().unwrap();
+        let bundle = write_bundle(&dir);
+
+        let err = publish_bundles(&endpoints, &key, &publishing(None, false), &[bundle])
+            .await
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("403"), "{}", err);
+        assert!(err.contains("does not have permission"), "{}", err);
+
+        let requests = recorded.lock().unwrap();
+        let (method, path, _) = requests.last().unwrap();
+        assert_eq!(method, "DELETE");
+        assert_eq!(
+            path,
+            "/androidpublisher/v3/applications/com.example.app/edits/edit-1"
+        );
+        assert!(!requests.iter().any(|(_, p, _)| p.ends_with(":commit")));
+    }
+}
@@ -0,0 +1,325 @@
+//! Publishing built artifacts to app stores.
+//!
+//! Each store implements [`Publisher`]; [`publishers_for`] maps the
+//! `publishing:` keys of a workflow to those implementations. Base URLs are
+//! configurable so the flows can be exercised against a local mock server.
+
+pub mod app_store_connect;
+pub mod firebase;
+pub mod google_play;
+pub mod service_account;
+
+use std::io::SeekFrom;
+use std::path::{Path, PathBuf};
+
+use async_trait::async_trait;
+use tokio::io::{AsyncReadExt, AsyncSeekExt};
+use tokio::sync::watch;
+use tokio_util::io::ReaderStream;
+
+use crate::db::publishing::{FirebaseCredentialRepo, GooglePlayCredentialRepo};
+use crate::db::signing::AppStoreConnectApiKeyRepo;
+use crate::db::DbPool;
+use crate::error::{OoreError, Result};
+use crate::models::{PublishingConfig, RepositoryId};
+use crate::oauth::EncryptionKey;
+
+use app_store_connect::{AppStoreConnectEndpoints, AppStoreConnectPublisher};
+use firebase::{FirebaseEndpoints, FirebasePublisher};
+use google_play::{GooglePlayEndpoints, GooglePlayPublisher};
+
+/// Streams `length` bytes of `path` from `offset` as a request body.
+///
+/// Artifacts can be hundreds of megabytes and several builds may publish at
+/// once, so they're never read into memory whole.
+pub(crate) async fn file_body(path: &Path, offset: u64, length: u64) -> Result<reqwest::Body> {
+    let mut file = tokio::fs::File::open(path).await?;
+    file.seek(SeekFrom::Start(offset)).await?;
+    let stream = ReaderStream::new(file.take(length));
+    Ok(reqwest::Body::wrap_stream(stream))
+}
+
+/// `publishing:` key for user scripts. They run in the build workspace, so
+/// the build processor executes them itself rather than through a [`Publisher`].
+pub const SCRIPTS_KEY: &str = "scripts";
+
+/// API endpoints for every store.
+#[derive(Debug, Clone, Default)]
+pub struct PublishingEndpoints {
+    pub google_play: GooglePlayEndpoints,
+    pub app_store_connect: AppStoreConnectEndpoints,
+    pub firebase: FirebaseEndpoints,
+}
+
+impl PublishingEndpoints {
+    /// Loads each store's endpoints from the environment.
+    pub fn from_env() -> Self {
+        Self {
+            google_play: GooglePlayEndpoints::from_env(),
+            app_store_connect: AppStoreConnectEndpoints::from_env(),
+            firebase: FirebaseEndpoints::from_env(),
+        }
+    }
+}
+
+/// A stored repository credential a publisher needs.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RequiredCredential {
+    /// Google Play service account, by name.
+    GooglePlayServiceAccount(String),
+    /// App Store Connect API key, by name.
+    AppStoreConnectApiKey(String),
+    /// The repository's Firebase service account.
+    FirebaseServiceAccount,
+}
+
+impl std::fmt::Display for RequiredCredential {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::GooglePlayServiceAccount(name) => write!(f, "Google Play credentials '{}'", name),
+            Self::AppStoreConnectApiKey(name) => write!(f, "App Store Connect API key '{}'", name),
+            Self::FirebaseServiceAccount => write!(f, "Firebase service account"),
+        }
+    }
+}
+
+/// A collected build artifact.
+#[derive(Debug, Clone)]
+pub struct PublishArtifact {
+    /// File name, e.g. `app-release.aab`.
+    pub name: String,
+    /// Location in artifact storage.
+    pub path: PathBuf,
+}
+
+/// Server state available to publishers.
+pub struct PublishContext<'a> {
+    pub db: &'a DbPool,
+    pub encryption_key: Option<&'a EncryptionKey>,
+    pub repository_id: &'a RepositoryId,
+    pub endpoints: &'a PublishingEndpoints,
+    /// Fires when the build is cancelled, for publishers that wait on a store.
+    pub cancel_rx: watch::Receiver<bool>,
+}
+
+impl PublishContext<'_> {
+    /// The encryption key, required to decrypt stored credentials.
+    pub fn encryption_key(&self) -> Result<&EncryptionKey> {
+        self.encryption_key.ok_or_else(|| {
+            OoreError::Configuration(
+                "Encryption key required to use publishing credentials".to_string(),
+            )
+        })
+    }
+}
+
+/// Outcome of a successful publish.
+#[derive(Debug, Clone)]
+pub struct PublishOutcome {
+    /// Step log output. Never includes keys or access tokens.
+    pub log: String,
+    /// One-line outcome recorded on the build publication.
+    pub summary: String,
+}
+
+/// A publishing target configured under a workflow's `publishing:` key.
+#[async_trait]
+pub trait Publisher: Send + Sync {
+    /// Key under `publishing:`, e.g. `google_play`.
+    fn key(&self) -> &'static str;
+
+    /// Store name used in step names and errors.
+    fn display_name(&self) -> &'static str;
+
+    /// Checks the pipeline options. Runs at parse time, so it must not do I/O.
+    fn validate_config(&self) -> std::result::Result<(), String>;
+
+    /// Credentials that must be stored for the repository.
+    fn required_credentials(&self) -> Vec<RequiredCredential>;
+
+    /// Publishes the build's collected artifacts.
+    async fn publish(
+        &self,
+        ctx: &PublishContext<'_>,
+        artifacts: &[PublishArtifact],
+    ) -> Result<PublishOutcome>;
+}
+
+/// A publisher registered under a `publishing:` key.
+struct Registration {
+    key: &'static str,
+    from_config: fn(&PublishingConfig) -> Option<Box<dyn Publisher>>,
+}
+
+/// Registered publishers, in the order they run.
+const REGISTRY: &[Registration] = &[
+    Registration {
+        key: google_play::KEY,
+        from_config: |config| {
+            let publisher = GooglePlayPublisher::new(config.google_play.clone()?);
+            Some(Box::new(publisher))
+        },
+    },
+    Registration {
+        key: app_store_connect::KEY,
+        from_config: |config| {
+            let publisher = AppStoreConnectPublisher::new(config.app_store_connect.clone()?);
+            Some(Box::new(publisher))
+        },
+    },
+    Registration {
+        key: firebase::KEY,
+        from_config: |config| {
+            let publisher = FirebasePublisher::new(config.firebase.clone()?);
+            Some(Box::new(publisher))
+        },
+    },
+];
+
+/// Keys accepted under `publishing:`.
+pub fn supported_keys() -> impl Iterator<Item = &'static str> {
+    REGISTRY
+        .iter()
+        .map(|registration| registration.key)
+        .chain(std::iter::once(SCRIPTS_KEY))
+}
+
+/// Publishers configured in a workflow's `publishing:` section.
+pub fn publishers_for(config: &PublishingConfig) -> Vec<Box<dyn Publisher>> {
+    REGISTRY
+        .iter()
+        .filter_map(|registration| (registration.from_config)(config))
+        .collect()
+}
+
+/// Checks that every required credential is stored for the repository.
+pub async fn check_credentials(
+    ctx: &PublishContext<'_>,
+    required: &[RequiredCredential],
+) -> Result<()> {
+    for credential in required {
+        let (name, available): (&str, Vec<String>) = match credential {
+            RequiredCredential::GooglePlayServiceAccount(name) => (
+                name,
+                GooglePlayCredentialRepo::list_active_for_repo(ctx.db, ctx.repository_id)
+                    .await?
+                    .into_iter()
+                    .map(|c| c.name)
+                    .collect(),
+            ),
+            RequiredCredential::AppStoreConnectApiKey(name) => (
+                name,
+                AppStoreConnectApiKeyRepo::list_active_for_repo(ctx.db, ctx.repository_id)
+                    .await?
+                    .into_iter()
+                    .map(|k| k.name)
+                    .collect(),
+            ),
+            RequiredCredential::FirebaseServiceAccount => {
+                if FirebaseCredentialRepo::get_for_repo(ctx.db, ctx.repository_id)
+                    .await?
+                    .is_none()
+                {
+                    return Err(OoreError::Publishing(format!(
+                        "No {} is configured for this repository",
+                        credential
+                    )));
+                }
+                continue;
+            }
+        };
+
+        if !available.iter().any(|a| a == name) {
+            return Err(OoreError::Publishing(format!(
+                "{} not found (available: {})",
+                credential,
+                if available.is_empty() {
+                    "none".to_string()
+                } else {
+                    available.join(", ")
+                }
+            )));
+        }
+    }
+
+    Ok(())
+}
+
+/// Artifacts whose name ends with `extension` (case-insensitive, e.g. `.aab`).
+pub fn artifacts_with_extension<'a>(
+    artifacts: &'a [PublishArtifact],
+    extension: &str,
+) -> Vec<&'a PublishArtifact> {
+    artifacts
+        .iter()
+        .filter(|a| a.name.to_lowercase().ends_with(extension))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{FirebasePublishing, GooglePlayPublishing};
+
+    #[test]
+    fn test_publishers_for_config() {
+        assert!(publishers_for(&PublishingConfig::default()).is_empty());
+
+        let config = PublishingConfig {
+            google_play: Some(GooglePlayPublishing {
+                credentials: "play".to_string(),
+                package_name: "com.example.app".to_string(),
+                track: "internal".to_string(),
+                in_app_update_priority: None,
+                rollout_fraction: None,
+                submit_as_draft: false,
+            }),
+            firebase: Some(FirebasePublishing {
+                app_id: "1:1234567890:android:0a1b2c3d4e5f".to_string(),
+                groups: vec![],
+                testers: vec![],
+                release_notes: None,
+            }),
+            ..Default::default()
+        };
+        let publishers = publishers_for(&config);
+        let keys: Vec<&str> = publishers.iter().map(|p| p.key()).collect();
+        assert_eq!(keys, vec!["google_play", "firebase"]);
+        assert_eq!(
+            publishers[0].required_credentials(),
+            vec![RequiredCredential::GooglePlayServiceAccount(
+                "play".to_string()
+            )]
+        );
+        assert_eq!(
+            publishers[1].required_credentials(),
+            vec![RequiredCredential::FirebaseServiceAccount]
+        );
+    }
+
+    #[test]
+    fn test_supported_keys() {
+        let keys: Vec<&str> = supported_keys().collect();
+        assert_eq!(
+            keys,
+            vec!["google_play", "app_store_connect", "firebase", "scripts"]
+        );
+    }
+
+    #[test]
+    fn test_artifacts_with_extension() {
+        let artifacts = vec![
+            PublishArtifact {
+                name: "app-release.AAB".to_string(),
+                path: PathBuf::from("/artifacts/app-release.AAB"),
+            },
+            PublishArtifact {
+                name: "mapping.txt".to_string(),
+                path: PathBuf::from("/artifacts/mapping.txt"),
+            },
+        ];
+        let bundles = artifacts_with_extension(&artifacts, ".aab");
+        assert_eq!(bundles.len(), 1);
+        assert_eq!(bundles[0].name, "app-release.AAB");
+    }
+}
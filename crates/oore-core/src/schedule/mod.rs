@@ -0,0 +1,258 @@
+//! Scheduled (cron) builds.
+//!
+//! Cron expressions are read in the schedule's timezone and converted to UTC
+//! run times, which the server's scheduler stores and polls.
+
+use std::str::FromStr;
+
+use chrono::{DateTime, FixedOffset, TimeZone, Utc};
+use chrono_tz::Tz;
+use croner::parser::{CronParser, Seconds, Year};
+use croner::Cron;
+use tokio::process::Command;
+
+use crate::error::{OoreError, Result};
+use crate::pipeline::executor::{inject_token_into_url, sanitize_git_error};
+
+/// Parses a five-field cron expression (`minute hour day-of-month month
+/// day-of-week`) or a shorthand such as `@daily`.
+///
+/// As in Vixie cron, when both day-of-month and day-of-week are restricted a
+/// day matching either runs.
+pub fn parse_cron(expression: &str) -> std::result::Result<Cron, String> {
+    CronParser::builder()
+        .seconds(Seconds::Disallowed)
+        .year(Year::Disallowed)
+        .build()
+        .parse(expression)
+        .map_err(|e| format!("Invalid cron expression '{}': {}", expression.trim(), e))
+}
+
+/// A timezone a schedule's cron fields are read in.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ScheduleTimezone {
+    /// A fixed offset from UTC.
+    Offset(FixedOffset),
+    /// An IANA timezone.
+    Zone(Tz),
+}
+
+impl ScheduleTimezone {
+    /// Parses `UTC`, a fixed offset such as `+05:30` or `UTC-03:00`, or an
+    /// IANA name such as `Europe/Berlin`.
+    pub fn parse(name: &str) -> std::result::Result<Self, String> {
+        let name = name.trim();
+        if name.eq_ignore_ascii_case("utc") || name.eq_ignore_ascii_case("z") {
+            return Ok(Self::Zone(Tz::UTC));
+        }
+
+        let offset = name
+            .strip_prefix("UTC")
+            .or_else(|| name.strip_prefix("GMT"))
+            .unwrap_or(name);
+        if offset.starts_with(['+', '-']) {
+            return FixedOffset::from_str(offset)
+                .map(Self::Offset)
+                .map_err(|_| format!("Invalid UTC offset '{}'", name));
+        }
+
+        Tz::from_str(name)
+            .map(Self::Zone)
+            .map_err(|_| format!("Unknown timezone '{}'", name))
+    }
+}
+
+/// Returns the first run strictly after `after`, or `None` if the expression
+/// never matches.
+///
+/// A run in a local time skipped when clocks go forward happens once they
+/// have; one in a repeated local time happens the first time round.
+pub fn next_run_after(
+    cron: &Cron,
+    timezone: &ScheduleTimezone,
+    after: DateTime<Utc>,
+) -> Option<DateTime<Utc>> {
+    match timezone {
+        ScheduleTimezone::Offset(offset) => next_in(cron, offset, after),
+        ScheduleTimezone::Zone(zone) => next_in(cron, zone, after),
+    }
+}
+
+fn next_in<Z: TimeZone>(cron: &Cron, zone: &Z, after: DateTime<Utc>) -> Option<DateTime<Utc>> {
+    cron.find_next_occurrence(&after.with_timezone(zone), false)
+        .ok()
+        .map(|run| run.with_timezone(&Utc))
+}
+
+/// Parses a schedule's cron expression and timezone and returns its first
+/// run after `after`.
+pub fn next_run_for(
+    cron_expression: &str,
+    timezone: &str,
+    after: DateTime<Utc>,
+) -> std::result::Result<Option<DateTime<Utc>>, String> {
+    let cron = parse_cron(cron_expression)?;
+    let timezone = ScheduleTimezone::parse(timezone)?;
+    Ok(next_run_after(&cron, &timezone, after))
+}
+
+/// Looks up the commit `branch` points to on the remote with `git ls-remote`.
+pub async fn resolve_branch_head(
+    clone_url: &str,
+    branch: &str,
+    auth_token: Option<&str>,
+) -> Result<String> {
+    let url = match auth_token {
+        Some(token) => inject_token_into_url(clone_url, token)?,
+        None => clone_url.to_string(),
+    };
+    let reference = format!("refs/heads/{}", branch);
+
+    let output = Command::new("git")
+        .args(["ls-remote", "--", &url, &reference])
+        .env("GIT_TERMINAL_PROMPT", "0")
+        .output()
+        .await?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(OoreError::Provider(format!(
+            "git ls-remote failed: {}",
+            sanitize_git_error(stderr.trim())
+        )));
+    }
+
+    String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .filter_map(|line| line.split_once('\t'))
+        .find(|(_, name)| *name == reference)
+        .map(|(sha, _)| sha.to_string())
+        .ok_or_else(|| OoreError::Provider(format!("Branch '{}' not found", branch)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDateTime;
+
+    fn utc(s: &str) -> DateTime<Utc> {
+        NaiveDateTime::parse_from_str(s, "%Y-%m-%d %H:%M")
+            .unwrap()
+            .and_utc()
+    }
+
+    fn next(expression: &str, timezone: &str, after: &str) -> Option<DateTime<Utc>> {
+        next_run_for(expression, timezone, utc(after)).unwrap()
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert!(parse_cron("0 2 * *").is_err());
+        assert!(parse_cron("0 0 2 * * *").is_err());
+        assert!(parse_cron("60 * * * *").is_err());
+        assert!(parse_cron("* * 0 * *").is_err());
+        assert!(parse_cron("@sometimes").is_err());
+        assert!(parse_cron("0 2 * * MON-FRI").is_ok());
+        assert!(parse_cron("@daily").is_ok());
+
+        assert!(ScheduleTimezone::parse("+25:00").is_err());
+        assert!(ScheduleTimezone::parse("../../etc/passwd").is_err());
+        assert!(ScheduleTimezone::parse("Mars/Olympus_Mons").is_err());
+    }
+
+    #[test]
+    fn test_next_run_after() {
+        assert_eq!(
+            next("0 2 * * *", "UTC", "2026-03-10 02:00"),
+            Some(utc("2026-03-11 02:00"))
+        );
+        assert_eq!(
+            next("*/15 * * * *", "UTC", "2026-03-10 10:07"),
+            Some(utc("2026-03-10 10:15"))
+        );
+        assert_eq!(
+            next("@monthly", "UTC", "2026-12-15 00:00"),
+            Some(utc("2027-01-01 00:00"))
+        );
+        assert_eq!(
+            next("0 2 * * *", "+05:30", "2026-03-10 12:00"),
+            Some(utc("2026-03-10 20:30"))
+        );
+        assert_eq!(
+            next("0 21 * * *", "UTC-03:00", "2026-01-01 12:00"),
+            Some(utc("2026-01-02 00:00"))
+        );
+    }
+
+    #[test]
+    fn test_weekdays() {
+        // 2026-03-13 is a Friday
+        assert_eq!(
+            next("0 2 * * mon-fri", "UTC", "2026-03-13 03:00"),
+            Some(utc("2026-03-16 02:00"))
+        );
+        assert_eq!(
+            next("0 2 * * 7", "UTC", "2026-03-13 03:00"),
+            Some(utc("2026-03-15 02:00"))
+        );
+        // Day-of-month and day-of-week restricted together match either
+        assert_eq!(
+            next("0 0 20 * 1", "UTC", "2026-03-13 03:00"),
+            Some(utc("2026-03-16 00:00"))
+        );
+    }
+
+    #[test]
+    fn test_daylight_saving() {
+        assert_eq!(
+            next("0 12 * * *", "Europe/Berlin", "2026-01-15 00:00"),
+            Some(utc("2026-01-15 11:00"))
+        );
+        assert_eq!(
+            next("0 12 * * *", "Europe/Berlin", "2026-07-15 00:00"),
+            Some(utc("2026-07-15 10:00"))
+        );
+        // Clocks go forward at 02:00 on 2026-03-29; 02:30 doesn't exist
+        assert_eq!(
+            next("30 2 * * *", "Europe/Berlin", "2026-03-28 12:00"),
+            Some(utc("2026-03-29 01:00"))
+        );
+        // Clocks go back at 03:00 on 2026-10-25; 02:30 happens twice
+        assert_eq!(
+            next("30 2 * * *", "Europe/Berlin", "2026-10-24 12:00"),
+            Some(utc("2026-10-25 00:30"))
+        );
+        assert_eq!(
+            next("30 2 * * *", "Europe/Berlin", "2026-10-25 00:30"),
+            Some(utc("2026-10-26 01:30"))
+        );
+    }
+
+    #[test]
+    fn test_never_matches() {
+        assert_eq!(next("0 0 30 2 *", "UTC", "2026-03-01 00:00"), None);
+    }
+
+    #[tokio::test]
+    async fn test_resolve_branch_head() {
+        let dir = tempfile::tempdir().unwrap();
+        let git = |args: &[&str]| {
+            let output = std::process::Command::new("git")
+                .current_dir(dir.path())
+                .args(["-c", "user.name=oore", "-c", "user.email=oore@example.com"])
+                .args(args)
+                .output()
+                .unwrap();
+            assert!(output.status.success(), "git {:?} failed", args);
+            String::from_utf8(output.stdout).unwrap().trim().to_string()
+        };
+
+        git(&["init", "-q", "-b", "main"]);
+        git(&["commit", "-q", "--allow-empty", "-m", "first"]);
+        let head = git(&["rev-parse", "HEAD"]);
+
+        let url = dir.path().to_str().unwrap();
+        assert_eq!(resolve_branch_head(url, "main", None).await.unwrap(), head);
+        assert!(resolve_branch_head(url, "missing", None).await.is_err());
+    }
+}
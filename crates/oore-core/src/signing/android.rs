@@ -3,12 +3,16 @@
 //! This module handles validating Android keystores and generating
 //! the key.properties file needed by Flutter/Gradle for signing.
 
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::process::Stdio;
 use tokio::process::Command;
 
 use crate::error::{OoreError, Result};
-use crate::models::KeystoreType;
+use crate::models::{AndroidKeystore, KeystoreType};
+use crate::oauth::{decrypt_with_aad, EncryptionKey};
+
+/// AAD table name used when encrypting Android keystore material.
+const KEYSTORE_AAD_TABLE: &str = "android_keystore";
 
 /// Information extracted from a keystore.
 #[derive(Debug, Clone)]
@@ -258,9 +262,185 @@ pub async fn cleanup_android_signing(workspace: &Path) -> Result<()> {
     Ok(())
 }
 
+/// Decrypted keystore material for a build.
+///
+/// Intentionally does not implement `Debug` so the passwords can't end up in logs.
+pub struct DecryptedKeystore {
+    /// Raw keystore file contents.
+    pub data: Vec<u8>,
+    /// Keystore (store) password.
+    pub keystore_password: String,
+    /// Private key password.
+    pub key_password: String,
+}
+
+/// Decrypts a stored keystore and its passwords.
+pub fn decrypt_keystore(key: &EncryptionKey, keystore: &AndroidKeystore) -> Result<DecryptedKeystore> {
+    let id = keystore.id.to_string();
+
+    let data = decrypt_with_aad(
+        key,
+        &keystore.keystore_data_encrypted,
+        &keystore.keystore_data_nonce,
+        KEYSTORE_AAD_TABLE,
+        &id,
+    )?;
+    let keystore_password = decrypt_with_aad(
+        key,
+        &keystore.keystore_password_encrypted,
+        &keystore.keystore_password_nonce,
+        KEYSTORE_AAD_TABLE,
+        &id,
+    )?;
+    let key_password = decrypt_with_aad(
+        key,
+        &keystore.key_password_encrypted,
+        &keystore.key_password_nonce,
+        KEYSTORE_AAD_TABLE,
+        &id,
+    )?;
+
+    Ok(DecryptedKeystore {
+        data,
+        keystore_password: String::from_utf8(keystore_password)
+            .map_err(|_| OoreError::Signing("Keystore password is not valid UTF-8".to_string()))?,
+        key_password: String::from_utf8(key_password)
+            .map_err(|_| OoreError::Signing("Key password is not valid UTF-8".to_string()))?,
+    })
+}
+
+/// Files written to the workspace for Android signing.
+#[derive(Debug, Clone)]
+pub struct AndroidSigningFiles {
+    /// Absolute path to the keystore file.
+    pub keystore_path: PathBuf,
+    /// Path to `android/key.properties`, if the workspace has an Android project.
+    pub key_properties_path: Option<PathBuf>,
+}
+
+/// Writes the keystore and, when an `android/` directory exists, key.properties.
+///
+/// Call [`cleanup_android_signing`] once the build is done, regardless of outcome.
+pub async fn prepare_android_signing(
+    workspace: &Path,
+    keystore: &AndroidKeystore,
+    decrypted: &DecryptedKeystore,
+) -> Result<AndroidSigningFiles> {
+    let keystore_path = write_keystore(workspace, &decrypted.data, keystore.keystore_type).await?;
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        tokio::fs::set_permissions(&keystore_path, std::fs::Permissions::from_mode(0o600))
+            .await
+            .map_err(|e| OoreError::Signing(format!("Failed to restrict keystore permissions: {}", e)))?;
+    }
+
+    let key_properties_path = if workspace.join("android").is_dir() {
+        write_key_properties(
+            workspace,
+            &keystore_path,
+            &decrypted.keystore_password,
+            &keystore.key_alias,
+            &decrypted.key_password,
+        )
+        .await?;
+        Some(workspace.join("android/key.properties"))
+    } else {
+        None
+    };
+
+    Ok(AndroidSigningFiles {
+        keystore_path,
+        key_properties_path,
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::models::{AndroidKeystoreId, RepositoryId};
+    use crate::oauth::encrypt_with_aad;
+
+    fn encrypted_keystore(key: &EncryptionKey) -> AndroidKeystore {
+        let id = AndroidKeystoreId::new();
+        let id_str = id.to_string();
+        let (data, data_nonce) = encrypt_with_aad(key, &[0xFE, 0xED, 0xFE, 0xED], KEYSTORE_AAD_TABLE, &id_str).unwrap();
+        let (ks_pass, ks_pass_nonce) = encrypt_with_aad(key, b"storepass", KEYSTORE_AAD_TABLE, &id_str).unwrap();
+        let (key_pass, key_pass_nonce) = encrypt_with_aad(key, b"keypass", KEYSTORE_AAD_TABLE, &id_str).unwrap();
+
+        AndroidKeystore {
+            id,
+            repository_id: RepositoryId::new(),
+            name: "release".to_string(),
+            keystore_data_encrypted: data,
+            keystore_data_nonce: data_nonce,
+            keystore_password_encrypted: ks_pass,
+            keystore_password_nonce: ks_pass_nonce,
+            key_alias: "upload".to_string(),
+            key_password_encrypted: key_pass,
+            key_password_nonce: key_pass_nonce,
+            keystore_type: KeystoreType::Jks,
+            is_active: true,
+            created_at: chrono::Utc::now(),
+            updated_at: chrono::Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_decrypt_keystore() {
+        let key = EncryptionKey::from_string(&"07".repeat(32)).unwrap();
+        let keystore = encrypted_keystore(&key);
+
+        let decrypted = decrypt_keystore(&key, &keystore).unwrap();
+        assert_eq!(decrypted.data, vec![0xFE, 0xED, 0xFE, 0xED]);
+        assert_eq!(decrypted.keystore_password, "storepass");
+        assert_eq!(decrypted.key_password, "keypass");
+    }
+
+    #[test]
+    fn test_decrypt_keystore_wrong_row_fails() {
+        let key = EncryptionKey::from_string(&"07".repeat(32)).unwrap();
+        let mut keystore = encrypted_keystore(&key);
+        keystore.id = AndroidKeystoreId::new();
+
+        assert!(decrypt_keystore(&key, &keystore).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_prepare_and_cleanup_android_signing() {
+        let key = EncryptionKey::from_string(&"07".repeat(32)).unwrap();
+        let keystore = encrypted_keystore(&key);
+        let decrypted = decrypt_keystore(&key, &keystore).unwrap();
+
+        let workspace = tempfile::tempdir().unwrap();
+        std::fs::create_dir(workspace.path().join("android")).unwrap();
+
+        let files = prepare_android_signing(workspace.path(), &keystore, &decrypted)
+            .await
+            .unwrap();
+        assert!(files.keystore_path.ends_with(".oore/signing/keystore.jks"));
+        let properties = std::fs::read_to_string(files.key_properties_path.unwrap()).unwrap();
+        assert!(properties.contains("keyAlias=upload"));
+
+        cleanup_android_signing(workspace.path()).await.unwrap();
+        assert!(!workspace.path().join(".oore/signing").exists());
+        assert!(!workspace.path().join("android/key.properties").exists());
+    }
+
+    #[tokio::test]
+    async fn test_prepare_android_signing_without_android_dir() {
+        let key = EncryptionKey::from_string(&"07".repeat(32)).unwrap();
+        let keystore = encrypted_keystore(&key);
+        let decrypted = decrypt_keystore(&key, &keystore).unwrap();
+
+        let workspace = tempfile::tempdir().unwrap();
+        let files = prepare_android_signing(workspace.path(), &keystore, &decrypted)
+            .await
+            .unwrap();
+        assert!(files.keystore_path.exists());
+        assert!(files.key_properties_path.is_none());
+    }
 
     #[test]
     fn test_detect_keystore_type_jks() {
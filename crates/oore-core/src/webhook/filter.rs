@@ -0,0 +1,129 @@
+//! Filters that keep a webhook event from creating a build.
+
+use crate::models::{ParsedWebhookEvent, Repository, WebhookEventType};
+
+/// Commit message markers that skip the build, matched case-insensitively.
+const SKIP_CI_MARKERS: &[&str] = &[
+    "[skip ci]",
+    "[ci skip]",
+    "[no ci]",
+    "[skip oore]",
+    "[oore skip]",
+];
+
+/// Why the event shouldn't create a build, or `None` if it should.
+pub fn build_skip_reason(event: &ParsedWebhookEvent, repository: &Repository) -> Option<String> {
+    if let Some(message) = &event.commit_message {
+        let message = message.to_lowercase();
+        if let Some(marker) = SKIP_CI_MARKERS.iter().find(|m| message.contains(*m)) {
+            return Some(format!("Skipped: commit message contains {}", marker));
+        }
+    }
+
+    if !matches!(
+        event.event_type,
+        WebhookEventType::PullRequest | WebhookEventType::MergeRequest
+    ) {
+        return None;
+    }
+
+    if event.draft && repository.skip_draft_pull_requests {
+        return Some("Skipped: draft pull request".to_string());
+    }
+
+    if let Some(label) = &repository.required_pull_request_label
+        && !has_label(event, label)
+    {
+        return Some(format!("Skipped: missing label '{}'", label));
+    }
+
+    None
+}
+
+/// Checks whether the PR/MR carries `label`. Labels are case-insensitive.
+fn has_label(event: &ParsedWebhookEvent, label: &str) -> bool {
+    event.labels.iter().any(|l| l.eq_ignore_ascii_case(label))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::GitProvider;
+
+    fn repository() -> Repository {
+        Repository::new(
+            "app".to_string(),
+            GitProvider::GitHub,
+            "owner".to_string(),
+            "app".to_string(),
+            "https://github.com/owner/app.git".to_string(),
+        )
+    }
+
+    fn event(event_type: WebhookEventType) -> ParsedWebhookEvent {
+        ParsedWebhookEvent {
+            event_type,
+            repository_owner: "owner".to_string(),
+            repository_name: "app".to_string(),
+            commit_sha: "0123456789abcdef0123456789abcdef01234567".to_string(),
+            branch: "main".to_string(),
+            github_repository_id: None,
+            github_installation_id: None,
+            gitlab_project_id: None,
+            pull_request_number: None,
+            action: None,
+            changed_files: None,
+            diff_base: None,
+            commit_message: None,
+            draft: false,
+            labels: vec![],
+            added_label: None,
+        }
+    }
+
+    #[test]
+    fn test_skip_ci_markers() {
+        let repo = repository();
+        let mut push = event(WebhookEventType::Push);
+        assert_eq!(build_skip_reason(&push, &repo), None);
+
+        push.commit_message = Some("Update README [CI Skip]\n\nDocs only".to_string());
+        assert_eq!(
+            build_skip_reason(&push, &repo).as_deref(),
+            Some("Skipped: commit message contains [ci skip]")
+        );
+
+        push.commit_message = Some("Skip the flaky test".to_string());
+        assert_eq!(build_skip_reason(&push, &repo), None);
+    }
+
+    #[test]
+    fn test_draft_and_label_filters() {
+        let mut repo = repository();
+        let mut pr = event(WebhookEventType::PullRequest);
+        pr.draft = true;
+        assert_eq!(build_skip_reason(&pr, &repo), None);
+
+        repo.skip_draft_pull_requests = true;
+        assert_eq!(
+            build_skip_reason(&pr, &repo).as_deref(),
+            Some("Skipped: draft pull request")
+        );
+
+        pr.draft = false;
+        repo.required_pull_request_label = Some("build".to_string());
+        assert_eq!(
+            build_skip_reason(&pr, &repo).as_deref(),
+            Some("Skipped: missing label 'build'")
+        );
+
+        pr.labels = vec!["Build".to_string()];
+        assert_eq!(build_skip_reason(&pr, &repo), None);
+
+        // PR filters don't apply to pushes
+        assert_eq!(
+            build_skip_reason(&event(WebhookEventType::Push), &repo),
+            None
+        );
+    }
+}
@@ -1,8 +1,10 @@
-//! Webhook verification and parsing.
+//! Webhook verification, parsing, and build filtering.
 
+pub mod filter;
 pub mod parser;
 pub mod verifier;
 
+pub use filter::build_skip_reason;
 pub use parser::{
     extract_github_repo_info, extract_gitlab_repo_info, is_github_installation_event,
     parse_github_installation_webhook, parse_github_webhook, parse_gitlab_webhook,
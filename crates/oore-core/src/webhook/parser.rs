@@ -1,5 +1,7 @@
 //! Webhook payload parsing for GitHub and GitLab.
 
+use std::collections::BTreeSet;
+
 use serde::Deserialize;
 
 use crate::error::{OoreError, Result};
@@ -37,7 +39,7 @@ pub fn parse_github_installation_webhook(event_type: &str, payload: &[u8]) -> Re
 /// Parses a GitLab webhook payload.
 pub fn parse_gitlab_webhook(event_type: &str, payload: &[u8]) -> Result<ParsedWebhookEvent> {
     match event_type {
-        "Push Hook" => parse_gitlab_push(payload),
+        "Push Hook" | "Tag Push Hook" => parse_gitlab_push(payload),
         "Merge Request Hook" => parse_gitlab_merge_request(payload),
         _ => Err(OoreError::InvalidWebhookPayload(format!(
             "Unsupported GitLab event type: {}",
@@ -46,13 +48,37 @@ pub fn parse_gitlab_webhook(event_type: &str, payload: &[u8]) -> Result<ParsedWe
     }
 }
 
+/// Most commits GitHub lists in a push payload.
+const GITHUB_PUSH_COMMIT_LIMIT: usize = 2048;
+
+/// A pushed commit. Same shape for GitHub and GitLab.
+#[derive(Deserialize)]
+struct PushCommit {
+    #[serde(default)]
+    id: Option<String>,
+    #[serde(default)]
+    message: Option<String>,
+    #[serde(default)]
+    added: Vec<String>,
+    #[serde(default)]
+    modified: Vec<String>,
+    #[serde(default)]
+    removed: Vec<String>,
+}
+
 // GitHub payload structures
 
 #[derive(Deserialize)]
 struct GitHubPushPayload {
     #[serde(rename = "ref")]
     ref_name: String,
+    #[serde(default)]
+    before: Option<String>,
     after: String,
+    #[serde(default)]
+    commits: Vec<PushCommit>,
+    #[serde(default)]
+    head_commit: Option<PushCommit>,
     repository: GitHubRepository,
     installation: Option<GitHubInstallation>,
 }
@@ -61,6 +87,8 @@ struct GitHubPushPayload {
 struct GitHubPullRequestPayload {
     action: String,
     number: i64,
+    /// Label added, for the `labeled` action.
+    label: Option<GitHubLabel>,
     pull_request: GitHubPullRequest,
     repository: GitHubRepository,
     installation: Option<GitHubInstallation>,
@@ -69,6 +97,16 @@ struct GitHubPullRequestPayload {
 #[derive(Deserialize)]
 struct GitHubPullRequest {
     head: GitHubHead,
+    base: Option<GitHubBase>,
+    #[serde(default)]
+    draft: bool,
+    #[serde(default)]
+    labels: Vec<GitHubLabel>,
+}
+
+#[derive(Deserialize)]
+struct GitHubLabel {
+    name: String,
 }
 
 #[derive(Deserialize)]
@@ -78,6 +116,12 @@ struct GitHubHead {
     ref_name: String,
 }
 
+#[derive(Deserialize)]
+struct GitHubBase {
+    #[serde(rename = "ref")]
+    ref_name: String,
+}
+
 #[derive(Deserialize)]
 struct GitHubRepository {
     id: i64,
@@ -149,15 +193,12 @@ fn parse_github_push(payload: &[u8]) -> Result<ParsedWebhookEvent> {
     // Extract owner and repo from full_name (e.g., "owner/repo")
     let (owner, repo_name) = parse_full_name(&data.repository.full_name)?;
 
-    // Extract branch from ref (e.g., "refs/heads/main" -> "main")
-    let branch = data
-        .ref_name
-        .strip_prefix("refs/heads/")
-        .unwrap_or(&data.ref_name)
-        .to_string();
+    let (event_type, branch) = parse_push_ref(&data.ref_name);
+    let diff_base = push_diff_base(data.before);
+    let complete = data.commits.len() < GITHUB_PUSH_COMMIT_LIMIT;
 
     Ok(ParsedWebhookEvent {
-        event_type: WebhookEventType::Push,
+        event_type,
         repository_owner: owner,
         repository_name: repo_name,
         commit_sha: data.after,
@@ -167,6 +208,12 @@ fn parse_github_push(payload: &[u8]) -> Result<ParsedWebhookEvent> {
         gitlab_project_id: None,
         pull_request_number: None,
         action: None,
+        changed_files: push_changed_files(diff_base.as_deref(), &data.commits, complete),
+        diff_base,
+        commit_message: data.head_commit.and_then(|c| c.message),
+        draft: false,
+        labels: vec![],
+        added_label: None,
     })
 }
 
@@ -186,6 +233,20 @@ fn parse_github_pull_request(payload: &[u8]) -> Result<ParsedWebhookEvent> {
         gitlab_project_id: None,
         pull_request_number: Some(data.number),
         action: Some(data.action),
+        changed_files: None,
+        diff_base: data
+            .pull_request
+            .base
+            .map(|base| format!("origin/{}", base.ref_name)),
+        commit_message: None,
+        draft: data.pull_request.draft,
+        labels: data
+            .pull_request
+            .labels
+            .into_iter()
+            .map(|l| l.name)
+            .collect(),
+        added_label: data.label.map(|l| l.name),
     })
 }
 
@@ -195,7 +256,14 @@ fn parse_github_pull_request(payload: &[u8]) -> Result<ParsedWebhookEvent> {
 struct GitLabPushPayload {
     #[serde(rename = "ref")]
     ref_name: String,
+    #[serde(default)]
+    before: Option<String>,
     after: String,
+    /// GitLab lists at most 20 commits; this is the real count.
+    #[serde(default)]
+    total_commits_count: Option<usize>,
+    #[serde(default)]
+    commits: Vec<PushCommit>,
     project: GitLabProject,
 }
 
@@ -205,6 +273,24 @@ struct GitLabMergeRequestPayload {
     object_kind: String,
     object_attributes: GitLabMergeRequestAttributes,
     project: GitLabProject,
+    #[serde(default)]
+    labels: Vec<GitLabLabel>,
+    #[serde(default)]
+    changes: Option<GitLabMergeRequestChanges>,
+}
+
+/// Attributes an `update` changed.
+#[derive(Deserialize)]
+struct GitLabMergeRequestChanges {
+    labels: Option<GitLabLabelChange>,
+}
+
+#[derive(Deserialize)]
+struct GitLabLabelChange {
+    #[serde(default)]
+    previous: Vec<GitLabLabel>,
+    #[serde(default)]
+    current: Vec<GitLabLabel>,
 }
 
 #[derive(Deserialize)]
@@ -213,11 +299,26 @@ struct GitLabMergeRequestAttributes {
     action: Option<String>,
     last_commit: GitLabCommit,
     source_branch: String,
+    target_branch: Option<String>,
+    #[serde(default)]
+    draft: bool,
+    /// Older GitLab versions send this instead of `draft`.
+    #[serde(default)]
+    work_in_progress: bool,
+    /// Previous head, only sent when an `update` pushed commits.
+    #[serde(default)]
+    oldrev: Option<String>,
 }
 
 #[derive(Deserialize)]
 struct GitLabCommit {
     id: String,
+    message: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct GitLabLabel {
+    title: String,
 }
 
 #[derive(Deserialize)]
@@ -231,15 +332,19 @@ fn parse_gitlab_push(payload: &[u8]) -> Result<ParsedWebhookEvent> {
 
     let (owner, repo_name) = parse_full_name(&data.project.path_with_namespace)?;
 
-    // Extract branch from ref (e.g., "refs/heads/main" -> "main")
-    let branch = data
-        .ref_name
-        .strip_prefix("refs/heads/")
-        .unwrap_or(&data.ref_name)
-        .to_string();
+    let (event_type, branch) = parse_push_ref(&data.ref_name);
+    let diff_base = push_diff_base(data.before);
+    let complete = data
+        .total_commits_count
+        .is_none_or(|total| total <= data.commits.len());
+    let commit_message = data
+        .commits
+        .iter()
+        .find(|c| c.id.as_deref() == Some(data.after.as_str()))
+        .and_then(|c| c.message.clone());
 
     Ok(ParsedWebhookEvent {
-        event_type: WebhookEventType::Push,
+        event_type,
         repository_owner: owner,
         repository_name: repo_name,
         commit_sha: data.after,
@@ -249,6 +354,12 @@ fn parse_gitlab_push(payload: &[u8]) -> Result<ParsedWebhookEvent> {
         gitlab_project_id: Some(data.project.id),
         pull_request_number: None,
         action: None,
+        changed_files: push_changed_files(diff_base.as_deref(), &data.commits, complete),
+        diff_base,
+        commit_message,
+        draft: false,
+        labels: vec![],
+        added_label: None,
     })
 }
 
@@ -257,6 +368,26 @@ fn parse_gitlab_merge_request(payload: &[u8]) -> Result<ParsedWebhookEvent> {
 
     let (owner, repo_name) = parse_full_name(&data.project.path_with_namespace)?;
 
+    let added_label = data
+        .changes
+        .and_then(|changes| changes.labels)
+        .and_then(|labels| {
+            labels
+                .current
+                .into_iter()
+                .find(|label| !labels.previous.iter().any(|p| p.title == label.title))
+        })
+        .map(|label| label.title);
+    // GitLab sends `update` for any change; one that only added a label is
+    // handled like GitHub's `labeled`
+    let mut action = data.object_attributes.action;
+    if action.as_deref() == Some("update")
+        && data.object_attributes.oldrev.is_none()
+        && added_label.is_some()
+    {
+        action = Some("labeled".to_string());
+    }
+
     Ok(ParsedWebhookEvent {
         event_type: WebhookEventType::MergeRequest,
         repository_owner: owner,
@@ -267,10 +398,61 @@ fn parse_gitlab_merge_request(payload: &[u8]) -> Result<ParsedWebhookEvent> {
         github_installation_id: None,
         gitlab_project_id: Some(data.project.id),
         pull_request_number: Some(data.object_attributes.iid),
-        action: data.object_attributes.action,
+        action,
+        changed_files: None,
+        diff_base: data
+            .object_attributes
+            .target_branch
+            .map(|branch| format!("origin/{}", branch)),
+        commit_message: data.object_attributes.last_commit.message,
+        draft: data.object_attributes.draft || data.object_attributes.work_in_progress,
+        labels: data.labels.into_iter().map(|l| l.title).collect(),
+        added_label,
     })
 }
 
+/// Splits a pushed ref into the event type and the branch or tag name.
+///
+/// e.g. "refs/heads/main" -> (Push, "main"), "refs/tags/v1.0" -> (Tag, "v1.0")
+fn parse_push_ref(ref_name: &str) -> (WebhookEventType, String) {
+    match ref_name.strip_prefix("refs/tags/") {
+        Some(tag) => (WebhookEventType::Tag, tag.to_string()),
+        None => (
+            WebhookEventType::Push,
+            ref_name
+                .strip_prefix("refs/heads/")
+                .unwrap_or(ref_name)
+                .to_string(),
+        ),
+    }
+}
+
+/// The previous head of a pushed ref, unless the push created it.
+fn push_diff_base(before: Option<String>) -> Option<String> {
+    before.filter(|sha| !sha.is_empty() && !sha.chars().all(|c| c == '0'))
+}
+
+/// Files touched by a push's commits.
+///
+/// `None` when the list may be incomplete: the payload was truncated, the
+/// push created the ref, or it lists no commits (e.g. a force push to an
+/// older commit).
+fn push_changed_files(
+    diff_base: Option<&str>,
+    commits: &[PushCommit],
+    complete: bool,
+) -> Option<Vec<String>> {
+    if diff_base.is_none() || commits.is_empty() || !complete {
+        return None;
+    }
+
+    let files: BTreeSet<&String> = commits
+        .iter()
+        .flat_map(|c| c.added.iter().chain(&c.modified).chain(&c.removed))
+        .collect();
+    Some(files.into_iter().cloned().collect())
+}
+
 /// Parses "owner/repo" format into (owner, repo).
 fn parse_full_name(full_name: &str) -> Result<(String, String)> {
     let parts: Vec<&str> = full_name.splitn(2, '/').collect();
@@ -349,6 +531,23 @@ mod tests {
         assert_eq!(event.github_installation_id, Some(67890));
     }
 
+    #[test]
+    fn test_parse_github_tag_push() {
+        let payload = r#"{
+            "ref": "refs/tags/v1.2.0",
+            "after": "abc123",
+            "repository": {
+                "id": 12345,
+                "full_name": "owner/repo"
+            }
+        }"#;
+
+        let event = parse_github_webhook("push", payload.as_bytes()).unwrap();
+        assert_eq!(event.event_type, WebhookEventType::Tag);
+        assert_eq!(event.branch, "v1.2.0");
+        assert_eq!(event.commit_sha, "abc123");
+    }
+
     #[test]
     fn test_parse_github_pull_request() {
         let payload = r#"{
@@ -392,6 +591,186 @@ mod tests {
         assert_eq!(event.gitlab_project_id, Some(12345));
     }
 
+    #[test]
+    fn test_parse_gitlab_tag_push() {
+        let payload = r#"{
+            "object_kind": "tag_push",
+            "ref": "refs/tags/release/2024.1",
+            "after": "abc123",
+            "project": {
+                "id": 12345,
+                "path_with_namespace": "group/project"
+            }
+        }"#;
+
+        let event = parse_gitlab_webhook("Tag Push Hook", payload.as_bytes()).unwrap();
+        assert_eq!(event.event_type, WebhookEventType::Tag);
+        assert_eq!(event.branch, "release/2024.1");
+        assert_eq!(event.gitlab_project_id, Some(12345));
+    }
+
+    #[test]
+    fn test_parse_push_changed_files() {
+        let payload = r#"{
+            "ref": "refs/heads/main",
+            "before": "1111111111111111111111111111111111111111",
+            "after": "abc123",
+            "commits": [
+                {"added": ["apps/ios/new.swift"], "modified": ["README.md"], "removed": []},
+                {"added": [], "modified": ["README.md"], "removed": ["apps/ios/old.swift"]}
+            ],
+            "repository": {
+                "id": 12345,
+                "full_name": "owner/repo"
+            }
+        }"#;
+
+        let event = parse_github_webhook("push", payload.as_bytes()).unwrap();
+        assert_eq!(
+            event.changed_files,
+            Some(vec![
+                "README.md".to_string(),
+                "apps/ios/new.swift".to_string(),
+                "apps/ios/old.swift".to_string(),
+            ])
+        );
+        assert_eq!(
+            event.diff_base.as_deref(),
+            Some("1111111111111111111111111111111111111111")
+        );
+
+        // New branch: nothing to compare against
+        let payload = payload.replace(
+            "1111111111111111111111111111111111111111",
+            "0000000000000000000000000000000000000000",
+        );
+        let event = parse_github_webhook("push", payload.as_bytes()).unwrap();
+        assert_eq!(event.changed_files, None);
+        assert_eq!(event.diff_base, None);
+
+        // GitLab truncates the commit list to 20
+        let payload = r#"{
+            "ref": "refs/heads/main",
+            "before": "1111111111111111111111111111111111111111",
+            "after": "abc123",
+            "total_commits_count": 25,
+            "commits": [{"added": [], "modified": ["lib/main.dart"], "removed": []}],
+            "project": {
+                "id": 12345,
+                "path_with_namespace": "group/project"
+            }
+        }"#;
+        let event = parse_gitlab_webhook("Push Hook", payload.as_bytes()).unwrap();
+        assert_eq!(event.changed_files, None);
+        assert!(event.diff_base.is_some());
+    }
+
+    #[test]
+    fn test_parse_commit_message_draft_and_labels() {
+        let payload = r#"{
+            "ref": "refs/heads/main",
+            "after": "abc123",
+            "head_commit": {"id": "abc123", "message": "Fix typo [skip ci]"},
+            "repository": {"id": 12345, "full_name": "owner/repo"}
+        }"#;
+        let event = parse_github_webhook("push", payload.as_bytes()).unwrap();
+        assert_eq!(event.commit_message.as_deref(), Some("Fix typo [skip ci]"));
+
+        let payload = r#"{
+            "action": "labeled",
+            "number": 42,
+            "label": {"name": "build"},
+            "pull_request": {
+                "head": {"sha": "def456", "ref": "feature"},
+                "draft": true,
+                "labels": [{"name": "build"}, {"name": "ios"}]
+            },
+            "repository": {"id": 12345, "full_name": "owner/repo"}
+        }"#;
+        let event = parse_github_webhook("pull_request", payload.as_bytes()).unwrap();
+        assert!(event.draft);
+        assert_eq!(event.labels, vec!["build", "ios"]);
+        assert_eq!(event.added_label.as_deref(), Some("build"));
+
+        let payload = r#"{
+            "ref": "refs/heads/main",
+            "after": "bbb",
+            "commits": [
+                {"id": "aaa", "message": "First [skip ci]"},
+                {"id": "bbb", "message": "Second"}
+            ],
+            "project": {"id": 12345, "path_with_namespace": "group/project"}
+        }"#;
+        let event = parse_gitlab_webhook("Push Hook", payload.as_bytes()).unwrap();
+        assert_eq!(event.commit_message.as_deref(), Some("Second"));
+
+        let payload = r#"{
+            "object_kind": "merge_request",
+            "object_attributes": {
+                "iid": 7,
+                "action": "update",
+                "last_commit": {"id": "def456", "message": "WIP [ci skip]"},
+                "source_branch": "feature",
+                "work_in_progress": true
+            },
+            "labels": [{"title": "build"}],
+            "project": {"id": 12345, "path_with_namespace": "group/project"}
+        }"#;
+        let event = parse_gitlab_webhook("Merge Request Hook", payload.as_bytes()).unwrap();
+        assert!(event.draft);
+        assert_eq!(event.labels, vec!["build"]);
+        assert_eq!(event.commit_message.as_deref(), Some("WIP [ci skip]"));
+        assert_eq!(event.action.as_deref(), Some("update"));
+        assert_eq!(event.added_label, None);
+    }
+
+    #[test]
+    fn test_parse_gitlab_merge_request_label_added() {
+        let payload = r#"{
+            "object_kind": "merge_request",
+            "object_attributes": {
+                "iid": 7,
+                "action": "update",
+                "last_commit": {"id": "def456", "message": "Add login"},
+                "source_branch": "feature"
+            },
+            "labels": [{"title": "ios"}, {"title": "build"}],
+            "changes": {
+                "labels": {
+                    "previous": [{"title": "ios"}],
+                    "current": [{"title": "ios"}, {"title": "build"}]
+                }
+            },
+            "project": {"id": 12345, "path_with_namespace": "group/project"}
+        }"#;
+        let event = parse_gitlab_webhook("Merge Request Hook", payload.as_bytes()).unwrap();
+        assert_eq!(event.action.as_deref(), Some("labeled"));
+        assert_eq!(event.added_label.as_deref(), Some("build"));
+
+        // Pushing commits in the same update keeps it an update
+        let payload = payload.replace(
+            r#""source_branch": "feature""#,
+            r#""source_branch": "feature", "oldrev": "abc123""#,
+        );
+        let event = parse_gitlab_webhook("Merge Request Hook", payload.as_bytes()).unwrap();
+        assert_eq!(event.action.as_deref(), Some("update"));
+        assert_eq!(event.added_label.as_deref(), Some("build"));
+    }
+
+    #[test]
+    fn test_parse_github_pull_request_has_no_commit_message() {
+        // GitHub leaves the head commit out of pull request payloads; the
+        // webhook processor looks the message up instead
+        let payload = r#"{
+            "action": "synchronize",
+            "number": 42,
+            "pull_request": {"head": {"sha": "def456", "ref": "feature"}},
+            "repository": {"id": 12345, "full_name": "owner/repo"}
+        }"#;
+        let event = parse_github_webhook("pull_request", payload.as_bytes()).unwrap();
+        assert_eq!(event.commit_message, None);
+    }
+
     #[test]
     fn test_parse_gitlab_merge_request() {
         let payload = r#"{
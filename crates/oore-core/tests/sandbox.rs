@@ -0,0 +1,292 @@
+//! Integration tests for the sandbox executor.
+//!
+//! These run real steps in the sandbox, so they need Linux with `bwrap`
+//! installed or root. Elsewhere they're skipped.
+
+use std::collections::HashMap;
+use std::os::unix::fs::PermissionsExt;
+use std::path::{Path, PathBuf};
+
+use oore_core::pipeline::{
+    BuildExecutor, BuildLimits, SandboxConfig, SandboxExecutor, SecretMasker, ShellExecutor,
+    StepResult,
+};
+use tempfile::TempDir;
+use tokio::sync::watch;
+
+const ENV_FILE: &str = "/etc/oore/oore.env";
+
+/// Creates a sandbox executor, or returns `None` if sandboxing isn't
+/// available here.
+fn executor(limits: BuildLimits) -> Option<SandboxExecutor> {
+    let executor =
+        SandboxExecutor::new(ShellExecutor::new(), SandboxConfig::default()).with_limits(limits);
+    if executor.is_available() {
+        Some(executor)
+    } else {
+        eprintln!("Skipping: sandboxing needs bwrap or root");
+        None
+    }
+}
+
+/// A build directory with an empty workspace.
+struct Build {
+    _dir: TempDir,
+    workspace: PathBuf,
+    logs: PathBuf,
+}
+
+impl Build {
+    fn new() -> Self {
+        let dir = tempfile::tempdir().unwrap();
+        let workspace = dir.path().join("workspaces/workspace");
+        std::fs::create_dir_all(&workspace).unwrap();
+        Self {
+            workspace,
+            logs: dir.path().join("logs"),
+            _dir: dir,
+        }
+    }
+
+    /// Another build's workspace next to this one.
+    fn sibling(&self, name: &str) -> PathBuf {
+        let workspace = self.workspace.with_file_name(name);
+        std::fs::create_dir(&workspace).unwrap();
+        workspace
+    }
+}
+
+async fn run(
+    executor: &SandboxExecutor,
+    build: &Build,
+    script: &str,
+    env: &HashMap<String, String>,
+) -> (StepResult, String, String) {
+    run_in(executor, &build.workspace, &build.logs, script, env).await
+}
+
+async fn run_in(
+    executor: &SandboxExecutor,
+    workspace: &Path,
+    logs: &Path,
+    script: &str,
+    env: &HashMap<String, String>,
+) -> (StepResult, String, String) {
+    let workflow = oore_core::pipeline::create_minimal_pipeline("true")
+        .workflows
+        .remove("default")
+        .unwrap();
+    executor.prepare_build(workspace, &workflow).await.unwrap();
+
+    let (_tx, mut cancel_rx) = watch::channel(false);
+    let result = executor
+        .execute_step(
+            workspace,
+            script,
+            env,
+            60,
+            logs,
+            0,
+            &SecretMasker::new(Vec::<String>::new()),
+            None,
+            &mut cancel_rx,
+        )
+        .await
+        .unwrap();
+    let stdout = std::fs::read_to_string(&result.stdout_path).unwrap();
+    let stderr = std::fs::read_to_string(&result.stderr_path).unwrap();
+    (result, stdout, stderr)
+}
+
+/// Makes sure the server's env file exists, creating it with `marker` if it
+/// doesn't. Removes what it created when dropped.
+struct EnvFile {
+    created_dir: bool,
+    created_file: bool,
+}
+
+impl EnvFile {
+    fn ensure(marker: &str) -> Option<Self> {
+        let path = Path::new(ENV_FILE);
+        let dir = path.parent().unwrap();
+        let created_dir = !dir.exists();
+        let created_file = !path.exists();
+        if created_dir && std::fs::create_dir_all(dir).is_err() {
+            return None;
+        }
+        if created_file {
+            std::fs::write(path, format!("ENCRYPTION_KEY={}\n", marker)).ok()?;
+            std::fs::set_permissions(path, std::fs::Permissions::from_mode(0o644)).ok()?;
+        }
+        Some(Self {
+            created_dir,
+            created_file,
+        })
+    }
+}
+
+impl Drop for EnvFile {
+    fn drop(&mut self) {
+        if self.created_file {
+            let _ = std::fs::remove_file(ENV_FILE);
+        }
+        if self.created_dir {
+            let _ = std::fs::remove_dir(Path::new(ENV_FILE).parent().unwrap());
+        }
+    }
+}
+
+#[tokio::test]
+async fn test_step_cannot_read_server_env_file() {
+    let Some(executor) = executor(BuildLimits::default()) else {
+        return;
+    };
+    let marker = "sandbox-test-marker";
+    let Some(_env_file) = EnvFile::ensure(marker) else {
+        eprintln!("Skipping: can't create {}", ENV_FILE);
+        return;
+    };
+    // Whatever the file holds, the host can read it
+    let contents = std::fs::read_to_string(ENV_FILE).unwrap();
+    let build = Build::new();
+
+    let (result, stdout, stderr) = run(
+        &executor,
+        &build,
+        &format!("cat {}", ENV_FILE),
+        &HashMap::new(),
+    )
+    .await;
+    assert_ne!(result.exit_code, 0);
+    assert!(stdout.is_empty());
+    assert!(stderr.contains("No such file or directory"), "{}", stderr);
+    assert!(!stdout.contains(marker));
+    for line in contents.lines().filter(|line| !line.trim().is_empty()) {
+        assert!(!stdout.contains(line));
+    }
+
+    // The directory is empty, and host processes (whose environment may hold
+    // the file's values) aren't visible
+    let (_, stdout, _) = run(
+        &executor,
+        &build,
+        "ls -A /etc/oore; cat /proc/[0-9]*/environ 2>/dev/null | tr '\\0' '\\n' | grep -c ^CARGO_",
+        &HashMap::new(),
+    )
+    .await;
+    assert_eq!(stdout.trim(), "0");
+
+    executor.cleanup(&build.workspace).await.unwrap();
+}
+
+#[tokio::test]
+async fn test_step_runs_isolated_as_unprivileged_user() {
+    let Some(executor) = executor(BuildLimits::default()) else {
+        return;
+    };
+    let build = Build::new();
+    let env = HashMap::from([("GREETING".to_string(), "hello".to_string())]);
+
+    let (result, stdout, stderr) = run(
+        &executor,
+        &build,
+        "id -u; echo \"$GREETING\"; echo \"$HOME\"; ls /proc | grep -c '^[0-9]'; \
+         touch output.txt && echo written; env",
+        &env,
+    )
+    .await;
+    assert_eq!(result.exit_code, 0, "{}", stderr);
+
+    let lines: Vec<&str> = stdout.lines().collect();
+    let uid: u32 = lines[0].parse().unwrap();
+    // SAFETY: geteuid() has no preconditions.
+    if unsafe { libc::geteuid() } == 0 {
+        assert!(SandboxConfig::default().uids().contains(&uid));
+    }
+    assert_eq!(lines[1], "hello");
+    assert_eq!(
+        Path::new(lines[2]),
+        build.workspace.with_file_name("workspace.home")
+    );
+    // Only the step's own processes are visible
+    let processes: u32 = lines[3].parse().unwrap();
+    assert!(processes <= 5, "{} processes visible", processes);
+    assert_eq!(lines[4], "written");
+    assert!(build.workspace.join("output.txt").exists());
+
+    // Nothing from the server's environment leaks in
+    assert!(!stdout.contains("CARGO_"));
+    assert!(!stdout.contains("OORE_SANDBOX_ENV_"));
+
+    executor.cleanup(&build.workspace).await.unwrap();
+    assert!(!build.workspace.exists());
+}
+
+#[tokio::test]
+async fn test_step_cannot_access_other_builds() {
+    let Some(executor) = executor(BuildLimits::default()) else {
+        return;
+    };
+    let build = Build::new();
+    let other = build.sibling("other");
+    let (result, _, _) = run_in(
+        &executor,
+        &other,
+        &build.logs,
+        "echo other-build-secret > key.properties; id -u",
+        &HashMap::new(),
+    )
+    .await;
+    assert_eq!(result.exit_code, 0);
+    let other_uid = std::fs::read_to_string(&result.stdout_path).unwrap();
+
+    let (result, stdout, _) = run(
+        &executor,
+        &build,
+        "ls -A ..; cat ../other/key.properties; echo changed > ../other/key.properties; id -u",
+        &HashMap::new(),
+    )
+    .await;
+    assert_eq!(result.exit_code, 0);
+    let lines: Vec<&str> = stdout.lines().collect();
+    // Only this build's workspace and home are there
+    assert_eq!(lines[..2], ["workspace", "workspace.home"]);
+    assert!(!stdout.contains("other-build-secret"));
+    assert_eq!(
+        std::fs::read_to_string(other.join("key.properties")).unwrap(),
+        "other-build-secret\n"
+    );
+    // SAFETY: geteuid() has no preconditions.
+    if unsafe { libc::geteuid() } == 0 {
+        assert_ne!(lines[2], other_uid.trim());
+    }
+
+    executor.cleanup(&other).await.unwrap();
+    executor.cleanup(&build.workspace).await.unwrap();
+}
+
+#[tokio::test]
+async fn test_file_size_limit_follows_log_limit() {
+    let Some(executor) = executor(BuildLimits {
+        max_log_size_bytes: 64 * 1024,
+        ..Default::default()
+    }) else {
+        return;
+    };
+    let build = Build::new();
+
+    let (result, stdout, _) = run(
+        &executor,
+        &build,
+        "head -c 32768 /dev/zero > small && echo small; \
+         head -c 131072 /dev/zero > big && echo big; ulimit -f",
+        &HashMap::new(),
+    )
+    .await;
+    assert_eq!(result.exit_code, 0);
+    assert_eq!(stdout.lines().collect::<Vec<_>>(), ["small", "64"]);
+    let big = std::fs::metadata(build.workspace.join("big")).unwrap();
+    assert!(big.len() <= 64 * 1024);
+
+    executor.cleanup(&build.workspace).await.unwrap();
+}
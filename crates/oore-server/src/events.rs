@@ -0,0 +1,187 @@
+//! In-process build event bus.
+//!
+//! The build processor publishes every build and step transition here.
+//! Anything interested in build progress (log streaming, notifications,
+//! metrics) subscribes instead of being wired into the worker.
+//!
+//! Log chunks go out on a channel of their own, so a chatty step can't push
+//! lifecycle events such as `BuildFinished` out of a subscriber's buffer.
+
+use oore_core::models::{BuildArtifact, BuildId, BuildStatus, LogStream, StepStatus};
+use serde::Serialize;
+use tokio::sync::broadcast;
+
+/// Number of events buffered per subscriber before it starts lagging.
+const DEFAULT_CAPACITY: usize = 1024;
+
+/// A build lifecycle event.
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum BuildEvent {
+    /// The build was handed to the build processor.
+    BuildQueued { build_id: BuildId },
+    /// The build started running.
+    BuildStarted { build_id: BuildId },
+    /// A step started running.
+    StepStarted {
+        build_id: BuildId,
+        step_index: i32,
+        name: String,
+    },
+    /// A step reached a terminal status.
+    StepFinished {
+        build_id: BuildId,
+        step_index: i32,
+        status: StepStatus,
+        exit_code: Option<i32>,
+    },
+    /// Output written by a running step, after secret masking.
+    LogChunk {
+        build_id: BuildId,
+        step_index: i32,
+        stream: LogStream,
+        content: String,
+    },
+    /// An artifact was stored.
+    ArtifactCollected {
+        build_id: BuildId,
+        artifact: BuildArtifact,
+    },
+    /// The build reached a terminal status.
+    BuildFinished {
+        build_id: BuildId,
+        status: BuildStatus,
+    },
+}
+
+impl BuildEvent {
+    /// Returns the build this event belongs to.
+    pub fn build_id(&self) -> &BuildId {
+        match self {
+            BuildEvent::BuildQueued { build_id }
+            | BuildEvent::BuildStarted { build_id }
+            | BuildEvent::StepStarted { build_id, .. }
+            | BuildEvent::StepFinished { build_id, .. }
+            | BuildEvent::LogChunk { build_id, .. }
+            | BuildEvent::ArtifactCollected { build_id, .. }
+            | BuildEvent::BuildFinished { build_id, .. } => build_id,
+        }
+    }
+}
+
+/// Broadcast channels for [`BuildEvent`]s.
+///
+/// Publishing never blocks; subscribers that fall more than the channel
+/// capacity behind miss events and see `RecvError::Lagged`.
+#[derive(Clone)]
+pub struct BuildEventBus {
+    tx: broadcast::Sender<BuildEvent>,
+    logs: broadcast::Sender<BuildEvent>,
+}
+
+impl BuildEventBus {
+    /// Creates a bus buffering up to `capacity` events per subscriber and
+    /// channel.
+    pub fn new(capacity: usize) -> Self {
+        let (tx, _) = broadcast::channel(capacity);
+        let (logs, _) = broadcast::channel(capacity);
+        Self { tx, logs }
+    }
+
+    /// Publishes an event to all current subscribers of its channel.
+    pub fn publish(&self, event: BuildEvent) {
+        let tx = match event {
+            BuildEvent::LogChunk { .. } => &self.logs,
+            _ => &self.tx,
+        };
+        // No subscribers is fine; the event is simply dropped
+        let _ = tx.send(event);
+    }
+
+    /// Subscribes to events other than log chunks published from now on.
+    pub fn subscribe(&self) -> broadcast::Receiver<BuildEvent> {
+        self.tx.subscribe()
+    }
+
+    /// Subscribes to [`BuildEvent::LogChunk`]s published from now on.
+    pub fn subscribe_logs(&self) -> broadcast::Receiver<BuildEvent> {
+        self.logs.subscribe()
+    }
+}
+
+impl Default for BuildEventBus {
+    fn default() -> Self {
+        Self::new(DEFAULT_CAPACITY)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_publish_reaches_subscribers() {
+        let bus = BuildEventBus::default();
+        let mut rx = bus.subscribe();
+        let build_id = BuildId::new();
+
+        bus.publish(BuildEvent::BuildStarted {
+            build_id: build_id.clone(),
+        });
+
+        let event = rx.recv().await.unwrap();
+        assert!(matches!(event, BuildEvent::BuildStarted { .. }));
+        assert_eq!(event.build_id(), &build_id);
+    }
+
+    #[tokio::test]
+    async fn test_log_chunks_do_not_crowd_out_lifecycle_events() {
+        let bus = BuildEventBus::new(16);
+        let mut rx = bus.subscribe();
+        let mut logs = bus.subscribe_logs();
+        let build_id = BuildId::new();
+
+        for line in 0..100 {
+            bus.publish(BuildEvent::LogChunk {
+                build_id: build_id.clone(),
+                step_index: 0,
+                stream: LogStream::Stdout,
+                content: format!("line {}\n", line),
+            });
+        }
+        bus.publish(BuildEvent::BuildFinished {
+            build_id: build_id.clone(),
+            status: BuildStatus::Success,
+        });
+
+        let event = rx.recv().await.unwrap();
+        assert!(matches!(event, BuildEvent::BuildFinished { .. }));
+        assert!(matches!(
+            logs.recv().await,
+            Err(broadcast::error::RecvError::Lagged(_))
+        ));
+    }
+
+    #[test]
+    fn test_publish_without_subscribers() {
+        BuildEventBus::default().publish(BuildEvent::BuildQueued {
+            build_id: BuildId::new(),
+        });
+    }
+
+    #[test]
+    fn test_event_serialization() {
+        let build_id = BuildId::new();
+        let event = BuildEvent::StepFinished {
+            build_id: build_id.clone(),
+            step_index: 2,
+            status: StepStatus::Success,
+            exit_code: Some(0),
+        };
+
+        let json = serde_json::to_value(&event).unwrap();
+        assert_eq!(json["type"], "step_finished");
+        assert_eq!(json["build_id"], build_id.to_string());
+        assert_eq!(json["status"], "success");
+    }
+}
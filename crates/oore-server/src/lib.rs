@@ -2,11 +2,13 @@
 //!
 //! This library exposes the server components for use in integration tests.
 
+pub mod events;
 pub mod middleware;
 pub mod routes;
 pub mod state;
 pub mod worker;
 
+pub use events::{BuildEvent, BuildEventBus};
 pub use middleware::{AdminAuthConfig, require_admin};
 pub use state::{AppState, ServerConfig};
 pub use worker::{BuildJob, CancelChannels, WebhookJob};
@@ -16,6 +16,7 @@ use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
 
 mod cli;
 mod commands;
+mod events;
 mod middleware;
 mod routes;
 mod service;
@@ -23,6 +24,7 @@ mod state;
 mod worker;
 
 use cli::{Cli, Commands};
+use events::BuildEventBus;
 use middleware::{AdminAuthConfig, require_admin};
 use oore_core::{
     crypto::MAX_WEBHOOK_SIZE,
@@ -34,7 +36,8 @@ use oore_core::{
 use state::{AppState, ServerConfig};
 use worker::{
     recover_pending_builds, recover_unprocessed_events, start_build_processor,
-    start_webhook_processor, BuildProcessorConfig,
+    start_notification_processor, start_scheduler, start_webhook_processor_with_build_tx,
+    BuildProcessorConfig, NotificationConfig,
 };
 
 #[derive(Serialize)]
@@ -84,7 +87,9 @@ fn api_router(state: AppState) -> Router {
         .route("/builds/{id}/steps", get(routes::builds::get_build_steps))
         .route("/builds/{id}/logs", get(routes::builds::get_build_logs))
         .route("/builds/{id}/logs/content", get(routes::builds::get_build_log_content))
+        .route("/builds/{id}/logs/stream", get(routes::log_stream::stream_build_logs))
         .route("/builds/{id}/artifacts", get(routes::builds::list_build_artifacts))
+        .route("/builds/{id}/publications", get(routes::builds::list_build_publications))
         .route("/builds/{id}/artifacts/{artifact_id}", get(routes::builds::download_artifact))
         // Pipelines
         .route("/pipelines/validate", post(routes::pipelines::validate_pipeline))
@@ -137,6 +142,39 @@ fn admin_router(state: AppState) -> Router {
         .route("/repositories/{id}/signing/android/keystores", get(routes::signing::list_keystores))
         .route("/repositories/{id}/signing/android/keystores", post(routes::signing::upload_keystore))
         .route("/repositories/{id}/signing/android/keystores/{keystore_id}", delete(routes::signing::delete_keystore))
+        // Publishing credentials
+        .route("/repositories/{id}/publishing/google-play/credentials", get(routes::publishing::list_google_play_credentials))
+        .route("/repositories/{id}/publishing/google-play/credentials", post(routes::publishing::upload_google_play_credential))
+        .route("/repositories/{id}/publishing/google-play/credentials/{credential_id}", delete(routes::publishing::delete_google_play_credential))
+        .route("/repositories/{id}/publishing/firebase/credentials", get(routes::publishing::get_firebase_credential))
+        .route("/repositories/{id}/publishing/firebase/credentials", put(routes::publishing::set_firebase_credential))
+        .route("/repositories/{id}/publishing/firebase/credentials", delete(routes::publishing::delete_firebase_credential))
+        // Repository environment variables
+        .route("/repositories/{id}/env", get(routes::env_vars::list_env_vars))
+        .route("/repositories/{id}/env", post(routes::env_vars::create_env_var))
+        .route("/repositories/{id}/env/{var_id}", put(routes::env_vars::update_env_var))
+        .route("/repositories/{id}/env/{var_id}", delete(routes::env_vars::delete_env_var))
+        // Build schedules
+        .route("/repositories/{id}/schedules", get(routes::schedules::list_schedules))
+        .route("/repositories/{id}/schedules", post(routes::schedules::create_schedule))
+        .route("/repositories/{id}/schedules/{schedule_id}", put(routes::schedules::update_schedule))
+        .route("/repositories/{id}/schedules/{schedule_id}", delete(routes::schedules::delete_schedule))
+        // Outbound webhook routes
+        .route("/repositories/{id}/outbound-webhooks", get(routes::outbound_webhooks::list_outbound_webhooks))
+        .route("/repositories/{id}/outbound-webhooks", post(routes::outbound_webhooks::create_outbound_webhook))
+        .route("/repositories/{id}/outbound-webhooks/{webhook_id}", put(routes::outbound_webhooks::update_outbound_webhook))
+        .route("/repositories/{id}/outbound-webhooks/{webhook_id}", delete(routes::outbound_webhooks::delete_outbound_webhook))
+        .route("/repositories/{id}/outbound-webhooks/{webhook_id}/deliveries", get(routes::outbound_webhooks::list_webhook_deliveries))
+        .route("/repositories/{id}/notifications/slack", get(routes::slack::get_slack_config))
+        .route("/repositories/{id}/notifications/slack", put(routes::slack::set_slack_config))
+        .route("/repositories/{id}/notifications/slack", delete(routes::slack::delete_slack_config))
+        .route("/repositories/{id}/notifications/email", get(routes::email::get_email_config))
+        .route("/repositories/{id}/notifications/email", put(routes::email::set_email_config))
+        .route("/repositories/{id}/notifications/email", delete(routes::email::delete_email_config))
+        // Server settings
+        .route("/settings/smtp", get(routes::email::get_smtp_settings))
+        .route("/settings/smtp", put(routes::email::set_smtp_settings))
+        .route("/settings/smtp", delete(routes::email::delete_smtp_settings))
         .layer(axum_mw::from_fn_with_state(admin_config, require_admin))
         .with_state(state)
 }
@@ -373,13 +411,34 @@ async fn run_server() -> Result<()> {
         }
     }
 
-    // Start webhook processor
-    let (webhook_tx, webhook_worker_handle) = start_webhook_processor(db.clone(), encryption_key.clone());
-
     // Start build processor
     let build_config = BuildProcessorConfig::from_env();
+    let notification_config = NotificationConfig {
+        base_url: config.base_url.clone(),
+        logs_dir: build_config.logs_dir.clone(),
+        digest_hour_utc: oore_core::notifications::email::digest_hour_from_env(),
+    };
+    let logs_dir = build_config.logs_dir.clone();
+    let build_events = BuildEventBus::default();
     let (build_tx, build_worker_handle, build_cancel_channels) =
-        start_build_processor(db.clone(), build_config, encryption_key.clone());
+        start_build_processor(db.clone(), build_config, encryption_key.clone(), build_events.clone());
+
+    // Start webhook processor, queueing the builds it creates
+    let (webhook_tx, webhook_worker_handle) = start_webhook_processor_with_build_tx(
+        db.clone(),
+        encryption_key.clone(),
+        Some(build_tx.clone()),
+        build_cancel_channels.clone(),
+        build_events.clone(),
+    );
+
+    // Start outbound notification worker
+    let notification_handle = start_notification_processor(
+        db.clone(),
+        encryption_key.clone(),
+        notification_config,
+        &build_events,
+    );
 
     // Recover any unprocessed events and pending builds from previous runs
     recover_unprocessed_events(&db, &webhook_tx).await;
@@ -388,6 +447,9 @@ async fn run_server() -> Result<()> {
     // Start cleanup task
     let cleanup_handle = start_cleanup_task(db.clone());
 
+    // Start cron scheduler
+    let scheduler_handle = start_scheduler(db.clone(), encryption_key.clone(), build_tx.clone());
+
     // Create application state
     let state = AppState::new(
         db,
@@ -397,6 +459,8 @@ async fn run_server() -> Result<()> {
         webhook_tx,
         build_tx,
         build_cancel_channels,
+        build_events,
+        logs_dir,
         encryption_key,
         admin_auth_config,
         demo_provider,
@@ -451,10 +515,12 @@ async fn run_server() -> Result<()> {
     tracing::info!("HTTP server stopped, shutting down background tasks...");
 
     // Shut down workers and cleanup task gracefully
-    let (webhook_result, build_result, cleanup_result) = tokio::join!(
+    let (webhook_result, build_result, notification_result, cleanup_result, scheduler_result) = tokio::join!(
         webhook_worker_handle.shutdown(),
         build_worker_handle.shutdown(),
-        cleanup_handle.shutdown()
+        notification_handle.shutdown(),
+        cleanup_handle.shutdown(),
+        scheduler_handle.shutdown()
     );
 
     if let Err(e) = webhook_result {
@@ -473,6 +539,14 @@ async fn run_server() -> Result<()> {
         tracing::debug!("Build worker shut down cleanly");
     }
 
+    if let Err(e) = notification_result {
+        if e.is_panic() {
+            tracing::error!("Notification worker panicked during shutdown: {:?}", e);
+        }
+    } else {
+        tracing::debug!("Notification worker shut down cleanly");
+    }
+
     if let Err(e) = cleanup_result {
         if e.is_panic() {
             tracing::error!("Cleanup task panicked during shutdown: {:?}", e);
@@ -481,6 +555,14 @@ async fn run_server() -> Result<()> {
         tracing::debug!("Cleanup task shut down cleanly");
     }
 
+    if let Err(e) = scheduler_result {
+        if e.is_panic() {
+            tracing::error!("Scheduler panicked during shutdown: {:?}", e);
+        }
+    } else {
+        tracing::debug!("Scheduler shut down cleanly");
+    }
+
     // Signal that shutdown is complete (for any waiting tasks)
     let _ = shutdown_complete_tx.send(());
     drop(shutdown_complete_rx);
@@ -12,17 +12,19 @@ use oore_core::{
     db::{
         artifact::BuildArtifactRepo,
         pipeline::{BuildLogRepo, BuildStepRepo},
+        publishing::BuildPublicationRepo,
         repository::{BuildRepo, RepositoryRepo},
     },
     models::{
         Build, BuildArtifactResponse, BuildId, BuildLogContentResponse,
-        BuildLogResponse, BuildResponse, BuildStatus, BuildStepResponse, RepositoryId,
+        BuildLogResponse, BuildPublicationResponse, BuildResponse, BuildStatus, BuildStepResponse, RepositoryId,
         TriggerBuildRequest, TriggerType, sanitize_filename,
     },
 };
 use serde::Deserialize;
 use serde_json::json;
 
+use crate::events::BuildEvent;
 use crate::state::AppState;
 use crate::worker::BuildJob;
 
@@ -199,13 +201,21 @@ pub async fn cancel_build(
         );
     }
 
-    // Send cancel signal to stop any running processes
-    // This is best-effort - the build processor will check the DB status too
-    if build.status == BuildStatus::Running {
-        if let Some(cancel_tx) = state.build_cancel_channels.get(&build_id) {
-            let _ = cancel_tx.send(true);
-            tracing::info!("Sent cancel signal to build {}", build_id);
-        }
+    // Send cancel signal to stop any running processes. The build processor
+    // then finishes the build and publishes the final status itself.
+    let signalled = build.status == BuildStatus::Running
+        && state
+            .build_cancel_channels
+            .get(&build_id)
+            .is_some_and(|cancel_tx| cancel_tx.send(true).is_ok());
+
+    if signalled {
+        tracing::info!("Sent cancel signal to build {}", build_id);
+    } else {
+        state.build_events.publish(BuildEvent::BuildFinished {
+            build_id: build_id.clone(),
+            status: BuildStatus::Cancelled,
+        });
     }
 
     (StatusCode::OK, Json(json!({"status": "cancelled"})))
@@ -605,6 +615,59 @@ pub async fn list_build_artifacts(
     }
 }
 
+/// List publishing outcomes for a build.
+///
+/// GET /api/builds/:id/publications
+pub async fn list_build_publications(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> impl IntoResponse {
+    let build_id = match BuildId::from_string(&id) {
+        Ok(id) => id,
+        Err(_) => {
+            return (
+                StatusCode::BAD_REQUEST,
+                Json(json!({"error": "Invalid build ID"})),
+            );
+        }
+    };
+
+    // Verify build exists
+    match BuildRepo::get_by_id(&state.db, &build_id).await {
+        Ok(None) => {
+            return (
+                StatusCode::NOT_FOUND,
+                Json(json!({"error": "Build not found"})),
+            );
+        }
+        Err(e) => {
+            tracing::error!("Failed to get build: {}", e);
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(json!({"error": "Database error"})),
+            );
+        }
+        Ok(Some(_)) => {}
+    }
+
+    match BuildPublicationRepo::list_for_build(&state.db, &build_id).await {
+        Ok(publications) => {
+            let responses: Vec<BuildPublicationResponse> = publications
+                .into_iter()
+                .map(BuildPublicationResponse::from)
+                .collect();
+            (StatusCode::OK, Json(json!(responses)))
+        }
+        Err(e) => {
+            tracing::error!("Failed to list build publications: {}", e);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(json!({"error": "Database error"})),
+            )
+        }
+    }
+}
+
 /// Download an artifact by ID.
 ///
 /// GET /api/builds/:build_id/artifacts/:artifact_id
@@ -0,0 +1,320 @@
+//! Email notification endpoints.
+
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::IntoResponse,
+    Json,
+};
+use chrono::Utc;
+use oore_core::{
+    db::{
+        email::{encrypt_smtp_password, EmailConfigRepo, SmtpSettingsRepo},
+        repository::RepositoryRepo,
+    },
+    models::{
+        validate_email_address, validate_email_recipients, EmailConfigResponse,
+        EmailNotificationConfig, RepositoryId, SetEmailConfigRequest, SetSmtpSettingsRequest,
+        SmtpSettings, SmtpSettingsResponse,
+    },
+};
+use serde_json::json;
+
+use crate::state::AppState;
+
+/// Get the server-wide SMTP settings.
+///
+/// The password is never returned. Returns 404 when the settings come from
+/// the environment instead.
+///
+/// GET /api/settings/smtp
+pub async fn get_smtp_settings(State(state): State<AppState>) -> impl IntoResponse {
+    match SmtpSettingsRepo::get(&state.db).await {
+        Ok(Some(settings)) => (
+            StatusCode::OK,
+            Json(json!(SmtpSettingsResponse::from(settings))),
+        ),
+        Ok(None) => (
+            StatusCode::NOT_FOUND,
+            Json(json!({"error": "SMTP settings not configured"})),
+        ),
+        Err(e) => {
+            tracing::error!("Failed to get SMTP settings: {}", e);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(json!({"error": "Database error"})),
+            )
+        }
+    }
+}
+
+/// Create or replace the server-wide SMTP settings.
+///
+/// Omit `password` to keep the stored one; send an empty string to clear it.
+///
+/// PUT /api/settings/smtp
+pub async fn set_smtp_settings(
+    State(state): State<AppState>,
+    Json(req): Json<SetSmtpSettingsRequest>,
+) -> impl IntoResponse {
+    let host = req.host.trim();
+    if host.is_empty() || host.chars().any(|c| c.is_whitespace() || c.is_control()) {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(json!({"error": "Invalid SMTP host"})),
+        );
+    }
+    if req.port == Some(0) {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(json!({"error": "Invalid SMTP port"})),
+        );
+    }
+    if let Err(e) = validate_email_address(&req.from_address) {
+        return (StatusCode::BAD_REQUEST, Json(json!({"error": e})));
+    }
+    if let Err(e) = req
+        .digest_recipients
+        .iter()
+        .try_for_each(|r| validate_email_address(r))
+    {
+        return (StatusCode::BAD_REQUEST, Json(json!({"error": e})));
+    }
+
+    let existing = match SmtpSettingsRepo::get(&state.db).await {
+        Ok(existing) => existing,
+        Err(e) => {
+            tracing::error!("Failed to get SMTP settings: {}", e);
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(json!({"error": "Database error"})),
+            );
+        }
+    };
+
+    let (password_encrypted, password_nonce) = match (req.password.as_deref(), &existing) {
+        (Some(""), _) => (None, None),
+        (Some(password), _) => {
+            let Some(encryption_key) = &state.encryption_key else {
+                return (
+                    StatusCode::SERVICE_UNAVAILABLE,
+                    Json(json!({"error": "Encryption not configured"})),
+                );
+            };
+            match encrypt_smtp_password(encryption_key, password) {
+                Ok((ciphertext, nonce)) => (Some(ciphertext), Some(nonce)),
+                Err(e) => {
+                    tracing::error!("Failed to encrypt SMTP password: {}", e);
+                    return (
+                        StatusCode::INTERNAL_SERVER_ERROR,
+                        Json(json!({"error": "Encryption error"})),
+                    );
+                }
+            }
+        }
+        (None, Some(existing)) => (
+            existing.password_encrypted.clone(),
+            existing.password_nonce.clone(),
+        ),
+        (None, None) => (None, None),
+    };
+
+    let now = Utc::now();
+    let created = existing.is_none();
+    let settings = SmtpSettings {
+        host: host.to_string(),
+        port: req.port.unwrap_or_else(|| req.tls.default_port()),
+        tls: req.tls,
+        username: req.username.filter(|u| !u.is_empty()),
+        password_encrypted,
+        password_nonce,
+        from_address: req.from_address,
+        digest_recipients: req.digest_recipients,
+        created_at: existing.map(|s| s.created_at).unwrap_or(now),
+        updated_at: now,
+    };
+
+    if let Err(e) = SmtpSettingsRepo::upsert(&state.db, &settings).await {
+        tracing::error!("Failed to save SMTP settings: {}", e);
+        return (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(json!({"error": "Database error"})),
+        );
+    }
+
+    let status = if created {
+        StatusCode::CREATED
+    } else {
+        StatusCode::OK
+    };
+    (status, Json(json!(SmtpSettingsResponse::from(settings))))
+}
+
+/// Remove the stored SMTP settings, falling back to `OORE_SMTP_*`.
+///
+/// DELETE /api/settings/smtp
+pub async fn delete_smtp_settings(State(state): State<AppState>) -> impl IntoResponse {
+    match SmtpSettingsRepo::delete(&state.db).await {
+        Ok(true) => (StatusCode::NO_CONTENT, Json(json!({}))),
+        Ok(false) => (
+            StatusCode::NOT_FOUND,
+            Json(json!({"error": "SMTP settings not configured"})),
+        ),
+        Err(e) => {
+            tracing::error!("Failed to delete SMTP settings: {}", e);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(json!({"error": "Database error"})),
+            )
+        }
+    }
+}
+
+/// Get the email notification config for a repository.
+///
+/// GET /api/repositories/:repo_id/notifications/email
+pub async fn get_email_config(
+    State(state): State<AppState>,
+    Path(repo_id): Path<String>,
+) -> impl IntoResponse {
+    let repo_id = match RepositoryId::from_string(&repo_id) {
+        Ok(id) => id,
+        Err(_) => {
+            return (
+                StatusCode::BAD_REQUEST,
+                Json(json!({"error": "Invalid repository ID"})),
+            );
+        }
+    };
+
+    match EmailConfigRepo::get_for_repo(&state.db, &repo_id).await {
+        Ok(Some(config)) => (
+            StatusCode::OK,
+            Json(json!(EmailConfigResponse::from(config))),
+        ),
+        Ok(None) => (
+            StatusCode::NOT_FOUND,
+            Json(json!({"error": "Email notifications not configured"})),
+        ),
+        Err(e) => {
+            tracing::error!("Failed to get email config: {}", e);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(json!({"error": "Database error"})),
+            )
+        }
+    }
+}
+
+/// Create or replace the email notification config for a repository.
+///
+/// PUT /api/repositories/:repo_id/notifications/email
+pub async fn set_email_config(
+    State(state): State<AppState>,
+    Path(repo_id): Path<String>,
+    Json(req): Json<SetEmailConfigRequest>,
+) -> impl IntoResponse {
+    let repo_id = match RepositoryId::from_string(&repo_id) {
+        Ok(id) => id,
+        Err(_) => {
+            return (
+                StatusCode::BAD_REQUEST,
+                Json(json!({"error": "Invalid repository ID"})),
+            );
+        }
+    };
+
+    // Verify repository exists
+    match RepositoryRepo::get_by_id(&state.db, &repo_id).await {
+        Ok(Some(_)) => {}
+        Ok(None) => {
+            return (
+                StatusCode::NOT_FOUND,
+                Json(json!({"error": "Repository not found"})),
+            );
+        }
+        Err(e) => {
+            tracing::error!("Failed to get repository: {}", e);
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(json!({"error": "Database error"})),
+            );
+        }
+    }
+
+    if let Err(e) = validate_email_recipients(&req.recipients) {
+        return (StatusCode::BAD_REQUEST, Json(json!({"error": e})));
+    }
+
+    let existing = match EmailConfigRepo::get_for_repo(&state.db, &repo_id).await {
+        Ok(existing) => existing,
+        Err(e) => {
+            tracing::error!("Failed to get email config: {}", e);
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(json!({"error": "Database error"})),
+            );
+        }
+    };
+
+    let now = Utc::now();
+    let created = existing.is_none();
+    let config = EmailNotificationConfig {
+        id: existing.as_ref().map(|c| c.id.clone()).unwrap_or_default(),
+        repository_id: repo_id,
+        recipients: req.recipients,
+        on_failure: req.on_failure,
+        on_recovery: req.on_recovery,
+        is_active: req.is_active,
+        created_at: existing.map(|c| c.created_at).unwrap_or(now),
+        updated_at: now,
+    };
+
+    if let Err(e) = EmailConfigRepo::upsert(&state.db, &config).await {
+        tracing::error!("Failed to save email config: {}", e);
+        return (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(json!({"error": "Database error"})),
+        );
+    }
+
+    let status = if created {
+        StatusCode::CREATED
+    } else {
+        StatusCode::OK
+    };
+    (status, Json(json!(EmailConfigResponse::from(config))))
+}
+
+/// Remove the email notification config for a repository.
+///
+/// DELETE /api/repositories/:repo_id/notifications/email
+pub async fn delete_email_config(
+    State(state): State<AppState>,
+    Path(repo_id): Path<String>,
+) -> impl IntoResponse {
+    let repo_id = match RepositoryId::from_string(&repo_id) {
+        Ok(id) => id,
+        Err(_) => {
+            return (
+                StatusCode::BAD_REQUEST,
+                Json(json!({"error": "Invalid repository ID"})),
+            );
+        }
+    };
+
+    match EmailConfigRepo::delete_for_repo(&state.db, &repo_id).await {
+        Ok(true) => (StatusCode::NO_CONTENT, Json(json!({}))),
+        Ok(false) => (
+            StatusCode::NOT_FOUND,
+            Json(json!({"error": "Email notifications not configured"})),
+        ),
+        Err(e) => {
+            tracing::error!("Failed to delete email config: {}", e);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(json!({"error": "Database error"})),
+            )
+        }
+    }
+}
@@ -0,0 +1,345 @@
+//! Repository environment variable endpoints.
+
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::IntoResponse,
+    Json,
+};
+use oore_core::{
+    db::{
+        env_vars::{decrypt_env_value, encrypt_env_value, EnvVarRepo},
+        repository::RepositoryRepo,
+    },
+    models::{
+        validate_env_group_name, validate_env_var_name, CreateEnvVarRequest, EnvVarId,
+        EnvVarResponse, RepositoryEnvVar, RepositoryId, UpdateEnvVarRequest,
+    },
+};
+use serde_json::json;
+
+use crate::state::AppState;
+
+/// List environment variables for a repository.
+///
+/// Secret values are never returned.
+///
+/// GET /api/repositories/:repo_id/env
+pub async fn list_env_vars(
+    State(state): State<AppState>,
+    Path(repo_id): Path<String>,
+) -> impl IntoResponse {
+    let repo_id = match RepositoryId::from_string(&repo_id) {
+        Ok(id) => id,
+        Err(_) => {
+            return (
+                StatusCode::BAD_REQUEST,
+                Json(json!({"error": "Invalid repository ID"})),
+            );
+        }
+    };
+
+    let Some(encryption_key) = &state.encryption_key else {
+        return (
+            StatusCode::SERVICE_UNAVAILABLE,
+            Json(json!({"error": "Encryption not configured"})),
+        );
+    };
+
+    let vars = match EnvVarRepo::list_for_repo(&state.db, &repo_id).await {
+        Ok(vars) => vars,
+        Err(e) => {
+            tracing::error!("Failed to list environment variables: {}", e);
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(json!({"error": "Database error"})),
+            );
+        }
+    };
+
+    let mut responses = Vec::with_capacity(vars.len());
+    for var in vars {
+        let plain_value = if var.is_secret {
+            None
+        } else {
+            match decrypt_env_value(encryption_key, &var) {
+                Ok(value) => Some(value),
+                Err(e) => {
+                    tracing::error!("Failed to decrypt environment variable {}: {}", var.id, e);
+                    return (
+                        StatusCode::INTERNAL_SERVER_ERROR,
+                        Json(json!({"error": "Decryption error"})),
+                    );
+                }
+            }
+        };
+        responses.push(EnvVarResponse::from_var(var, plain_value));
+    }
+
+    (StatusCode::OK, Json(json!(responses)))
+}
+
+/// Create an environment variable for a repository.
+///
+/// POST /api/repositories/:repo_id/env
+pub async fn create_env_var(
+    State(state): State<AppState>,
+    Path(repo_id): Path<String>,
+    Json(req): Json<CreateEnvVarRequest>,
+) -> impl IntoResponse {
+    let repo_id = match RepositoryId::from_string(&repo_id) {
+        Ok(id) => id,
+        Err(_) => {
+            return (
+                StatusCode::BAD_REQUEST,
+                Json(json!({"error": "Invalid repository ID"})),
+            );
+        }
+    };
+
+    // Verify repository exists
+    match RepositoryRepo::get_by_id(&state.db, &repo_id).await {
+        Ok(Some(_)) => {}
+        Ok(None) => {
+            return (
+                StatusCode::NOT_FOUND,
+                Json(json!({"error": "Repository not found"})),
+            );
+        }
+        Err(e) => {
+            tracing::error!("Failed to get repository: {}", e);
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(json!({"error": "Database error"})),
+            );
+        }
+    }
+
+    let Some(encryption_key) = &state.encryption_key else {
+        return (
+            StatusCode::SERVICE_UNAVAILABLE,
+            Json(json!({"error": "Encryption not configured"})),
+        );
+    };
+
+    if let Err(e) = validate_env_var_name(&req.name) {
+        return (StatusCode::BAD_REQUEST, Json(json!({"error": e})));
+    }
+
+    // Treat an empty group as ungrouped
+    let group_name = req.group.filter(|g| !g.is_empty());
+    if let Some(group) = &group_name
+        && let Err(e) = validate_env_group_name(group)
+    {
+        return (StatusCode::BAD_REQUEST, Json(json!({"error": e})));
+    }
+
+    match EnvVarRepo::get_by_name(&state.db, &repo_id, group_name.as_deref(), &req.name).await {
+        Ok(None) => {}
+        Ok(Some(_)) => {
+            return (
+                StatusCode::CONFLICT,
+                Json(json!({"error": format!("Variable '{}' already exists", req.name)})),
+            );
+        }
+        Err(e) => {
+            tracing::error!("Failed to look up environment variable: {}", e);
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(json!({"error": "Database error"})),
+            );
+        }
+    }
+
+    let var_id = EnvVarId::new();
+    let (value_encrypted, value_nonce) =
+        match encrypt_env_value(encryption_key, &var_id, &req.value) {
+            Ok(result) => result,
+            Err(e) => {
+                tracing::error!("Failed to encrypt environment variable: {}", e);
+                return (
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    Json(json!({"error": "Encryption error"})),
+                );
+            }
+        };
+
+    let var = RepositoryEnvVar {
+        id: var_id,
+        repository_id: repo_id,
+        group_name,
+        name: req.name,
+        value_encrypted,
+        value_nonce,
+        is_secret: req.is_secret,
+        created_at: chrono::Utc::now(),
+        updated_at: chrono::Utc::now(),
+    };
+
+    if let Err(e) = EnvVarRepo::create(&state.db, &var).await {
+        tracing::error!("Failed to create environment variable: {}", e);
+        return (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(json!({"error": "Failed to save environment variable"})),
+        );
+    }
+
+    let response = EnvVarResponse::from_var(var, Some(req.value));
+    (StatusCode::CREATED, Json(json!(response)))
+}
+
+/// Update an environment variable's value or secret flag.
+///
+/// PUT /api/repositories/:repo_id/env/:var_id
+pub async fn update_env_var(
+    State(state): State<AppState>,
+    Path((repo_id, var_id)): Path<(String, String)>,
+    Json(req): Json<UpdateEnvVarRequest>,
+) -> impl IntoResponse {
+    let repo_id = match RepositoryId::from_string(&repo_id) {
+        Ok(id) => id,
+        Err(_) => {
+            return (
+                StatusCode::BAD_REQUEST,
+                Json(json!({"error": "Invalid repository ID"})),
+            );
+        }
+    };
+
+    let var_id = match EnvVarId::from_string(&var_id) {
+        Ok(id) => id,
+        Err(_) => {
+            return (
+                StatusCode::BAD_REQUEST,
+                Json(json!({"error": "Invalid variable ID"})),
+            );
+        }
+    };
+
+    let Some(encryption_key) = &state.encryption_key else {
+        return (
+            StatusCode::SERVICE_UNAVAILABLE,
+            Json(json!({"error": "Encryption not configured"})),
+        );
+    };
+
+    let mut var = match EnvVarRepo::get_by_id(&state.db, &var_id).await {
+        Ok(Some(var)) if var.repository_id == repo_id => var,
+        Ok(_) => {
+            return (
+                StatusCode::NOT_FOUND,
+                Json(json!({"error": "Variable not found"})),
+            );
+        }
+        Err(e) => {
+            tracing::error!("Failed to get environment variable: {}", e);
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(json!({"error": "Database error"})),
+            );
+        }
+    };
+
+    let value = match req.value {
+        Some(value) => value,
+        None => match decrypt_env_value(encryption_key, &var) {
+            Ok(value) => value,
+            Err(e) => {
+                tracing::error!("Failed to decrypt environment variable {}: {}", var.id, e);
+                return (
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    Json(json!({"error": "Decryption error"})),
+                );
+            }
+        },
+    };
+
+    // Re-encrypt on every update so the nonce is never reused
+    let (value_encrypted, value_nonce) = match encrypt_env_value(encryption_key, &var.id, &value)
+    {
+        Ok(result) => result,
+        Err(e) => {
+            tracing::error!("Failed to encrypt environment variable: {}", e);
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(json!({"error": "Encryption error"})),
+            );
+        }
+    };
+
+    let is_secret = req.is_secret.unwrap_or(var.is_secret);
+
+    if let Err(e) =
+        EnvVarRepo::update(&state.db, &var.id, &value_encrypted, &value_nonce, is_secret).await
+    {
+        tracing::error!("Failed to update environment variable: {}", e);
+        return (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(json!({"error": "Failed to update environment variable"})),
+        );
+    }
+
+    var.value_encrypted = value_encrypted;
+    var.value_nonce = value_nonce;
+    var.is_secret = is_secret;
+    var.updated_at = chrono::Utc::now();
+
+    let response = EnvVarResponse::from_var(var, Some(value));
+    (StatusCode::OK, Json(json!(response)))
+}
+
+/// Delete an environment variable.
+///
+/// DELETE /api/repositories/:repo_id/env/:var_id
+pub async fn delete_env_var(
+    State(state): State<AppState>,
+    Path((repo_id, var_id)): Path<(String, String)>,
+) -> impl IntoResponse {
+    let repo_id = match RepositoryId::from_string(&repo_id) {
+        Ok(id) => id,
+        Err(_) => {
+            return (
+                StatusCode::BAD_REQUEST,
+                Json(json!({"error": "Invalid repository ID"})),
+            );
+        }
+    };
+
+    let var_id = match EnvVarId::from_string(&var_id) {
+        Ok(id) => id,
+        Err(_) => {
+            return (
+                StatusCode::BAD_REQUEST,
+                Json(json!({"error": "Invalid variable ID"})),
+            );
+        }
+    };
+
+    // Verify variable belongs to repository
+    match EnvVarRepo::get_by_id(&state.db, &var_id).await {
+        Ok(Some(var)) if var.repository_id == repo_id => {}
+        Ok(_) => {
+            return (
+                StatusCode::NOT_FOUND,
+                Json(json!({"error": "Variable not found"})),
+            );
+        }
+        Err(e) => {
+            tracing::error!("Failed to get environment variable: {}", e);
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(json!({"error": "Database error"})),
+            );
+        }
+    }
+
+    if let Err(e) = EnvVarRepo::delete(&state.db, &var_id).await {
+        tracing::error!("Failed to delete environment variable: {}", e);
+        return (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(json!({"error": "Failed to delete environment variable"})),
+        );
+    }
+
+    (StatusCode::NO_CONTENT, Json(json!({})))
+}
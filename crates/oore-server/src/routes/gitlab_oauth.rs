@@ -1092,6 +1092,10 @@ pub async fn enable_project(
         github_repository_id: None,
         github_installation_id: None,
         gitlab_project_id: Some(project_id),
+        skip_draft_pull_requests: false,
+        required_pull_request_label: None,
+        cancel_previous_builds: false,
+        executor: None,
         created_at: chrono::Utc::now(),
         updated_at: chrono::Utc::now(),
     };
@@ -0,0 +1,417 @@
+//! Live build log streaming over Server-Sent Events.
+//!
+//! Tails each step's log files as the executor writes them and reports step
+//! status transitions, waking up on [`BuildEvent`]s for the build and
+//! polling as a fallback. Every log event carries an ID of the form
+//! `step_index:stdout_offset:stderr_offset`, so a client reconnecting with
+//! `Last-Event-ID` resumes from the exact byte it last received.
+
+use std::collections::HashMap;
+use std::convert::Infallible;
+use std::io::SeekFrom;
+use std::path::{Path as FsPath, PathBuf};
+use std::time::Duration;
+
+use axum::{
+    extract::{Path, State},
+    http::{HeaderMap, StatusCode},
+    response::{
+        sse::{Event, KeepAlive, Sse},
+        IntoResponse, Response,
+    },
+    Json,
+};
+use oore_core::{
+    db::{
+        pipeline::BuildStepRepo,
+        repository::BuildRepo,
+        DbPool,
+    },
+    models::{BuildId, BuildStatus, BuildStepResponse, LogStream, StepStatus},
+};
+use serde_json::json;
+use tokio::io::{AsyncReadExt, AsyncSeekExt};
+use tokio::sync::{broadcast, mpsc};
+use tokio_stream::wrappers::ReceiverStream;
+
+use crate::events::BuildEvent;
+use crate::state::AppState;
+
+/// How often the stream checks for changes when no build events arrive.
+const POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Maximum bytes sent in a single log event.
+const MAX_CHUNK_BYTES: usize = 64 * 1024;
+
+type EventSender = mpsc::Sender<Result<Event, Infallible>>;
+
+/// Position in a build's logs, used as the SSE event ID.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct LogCursor {
+    step_index: i32,
+    stdout_offset: u64,
+    stderr_offset: u64,
+}
+
+impl LogCursor {
+    fn parse(value: &str) -> Option<Self> {
+        let mut parts = value.trim().split(':');
+        let cursor = Self {
+            step_index: parts.next()?.parse().ok()?,
+            stdout_offset: parts.next()?.parse().ok()?,
+            stderr_offset: parts.next()?.parse().ok()?,
+        };
+        parts.next().is_none().then_some(cursor)
+    }
+
+    fn event_id(&self) -> String {
+        format!("{}:{}:{}", self.step_index, self.stdout_offset, self.stderr_offset)
+    }
+}
+
+/// Stream build logs and step status changes.
+///
+/// Emits `step` events (a build step) on status changes, `log` events
+/// (`{step_index, stream, content}`) as output is written, and a final
+/// `done` event (`{status}`) once the build reaches a terminal status.
+///
+/// GET /api/builds/:id/logs/stream
+pub async fn stream_build_logs(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    headers: HeaderMap,
+) -> Response {
+    let build_id = match BuildId::from_string(&id) {
+        Ok(id) => id,
+        Err(_) => {
+            return (
+                StatusCode::BAD_REQUEST,
+                Json(json!({"error": "Invalid build ID"})),
+            )
+                .into_response();
+        }
+    };
+
+    let resume = headers
+        .get("last-event-id")
+        .and_then(|v| v.to_str().ok())
+        .and_then(LogCursor::parse);
+
+    let (tx, rx) = mpsc::channel(32);
+
+    if let Some(demo) = state.demo_provider.clone() {
+        match demo.get_build(&build_id) {
+            Ok(Some(_)) => {}
+            Ok(None) => {
+                return (
+                    StatusCode::NOT_FOUND,
+                    Json(json!({"error": "Build not found"})),
+                )
+                    .into_response();
+            }
+            Err(e) => {
+                tracing::error!("Demo provider error: {}", e);
+                return (
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    Json(json!({"error": "Demo provider error"})),
+                )
+                    .into_response();
+            }
+        }
+
+        // Demo builds are static, so replay them in one go
+        tokio::spawn(async move {
+            let steps = demo.list_build_steps(&build_id).unwrap_or_default();
+            for step in steps {
+                if let Ok(Some((stdout, stderr))) =
+                    demo.get_build_log_content(&build_id, step.step_index)
+                {
+                    for (stream, content) in [(LogStream::Stdout, stdout), (LogStream::Stderr, stderr)] {
+                        if content.is_empty() {
+                            continue;
+                        }
+                        if tx.send(Ok(log_event(step.step_index, stream, &content))).await.is_err() {
+                            return;
+                        }
+                    }
+                }
+                if tx.send(Ok(step_event(step.into()))).await.is_err() {
+                    return;
+                }
+            }
+            if let Ok(Some(build)) = demo.get_build(&build_id)
+                && build.status.is_terminal()
+            {
+                let _ = tx.send(Ok(done_event(build.status))).await;
+            }
+        });
+
+        return Sse::new(ReceiverStream::new(rx))
+            .keep_alive(KeepAlive::default())
+            .into_response();
+    }
+
+    match BuildRepo::get_by_id(&state.db, &build_id).await {
+        Ok(Some(_)) => {}
+        Ok(None) => {
+            return (
+                StatusCode::NOT_FOUND,
+                Json(json!({"error": "Build not found"})),
+            )
+                .into_response();
+        }
+        Err(e) => {
+            tracing::error!("Failed to get build: {}", e);
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(json!({"error": "Database error"})),
+            )
+                .into_response();
+        }
+    }
+
+    let logs_dir = state.logs_dir.join(build_id.to_string());
+    let events = state.build_events.subscribe();
+    let log_events = state.build_events.subscribe_logs();
+    tokio::spawn(tail_build(
+        state.db.clone(),
+        build_id,
+        logs_dir,
+        resume,
+        events,
+        log_events,
+        tx,
+    ));
+
+    Sse::new(ReceiverStream::new(rx))
+        .keep_alive(KeepAlive::default())
+        .into_response()
+}
+
+/// Polls a build until it finishes, forwarding new output and status changes.
+///
+/// Returns early when the client disconnects.
+async fn tail_build(
+    db: DbPool,
+    build_id: BuildId,
+    logs_dir: PathBuf,
+    resume: Option<LogCursor>,
+    mut events: broadcast::Receiver<BuildEvent>,
+    mut log_events: broadcast::Receiver<BuildEvent>,
+    tx: EventSender,
+) {
+    let mut offsets: HashMap<i32, [u64; 2]> = HashMap::new();
+    let mut statuses: HashMap<i32, StepStatus> = HashMap::new();
+
+    loop {
+        let build = match BuildRepo::get_by_id(&db, &build_id).await {
+            Ok(Some(build)) => build,
+            Ok(None) => return,
+            Err(e) => {
+                tracing::error!("Log stream for build {} failed: {}", build_id, e);
+                return;
+            }
+        };
+        let build_finished = build.status.is_terminal();
+
+        let steps = match BuildStepRepo::list_for_build(&db, &build_id).await {
+            Ok(steps) => steps,
+            Err(e) => {
+                tracing::error!("Log stream for build {} failed: {}", build_id, e);
+                return;
+            }
+        };
+
+        for step in steps {
+            let index = step.step_index;
+
+            // Everything before the resume point was already delivered
+            if resume.is_some_and(|cursor| index < cursor.step_index) {
+                continue;
+            }
+
+            let changed = statuses.get(&index) != Some(&step.status);
+            let status = step.status;
+
+            // Announce a running step before its output, a finished one after it
+            if changed && !status.is_terminal() {
+                statuses.insert(index, status);
+                if tx.send(Ok(step_event(step.clone().into()))).await.is_err() {
+                    return;
+                }
+            }
+
+            if status != StepStatus::Pending {
+                let offsets = offsets.entry(index).or_insert_with(|| match resume {
+                    Some(cursor) if cursor.step_index == index => {
+                        [cursor.stdout_offset, cursor.stderr_offset]
+                    }
+                    _ => [0, 0],
+                });
+                let flush = status.is_terminal() || build_finished;
+
+                for (i, stream) in [LogStream::Stdout, LogStream::Stderr].into_iter().enumerate() {
+                    let path = logs_dir.join(format!("step-{}-{}.log", index, stream));
+
+                    while let Some((content, next_offset)) =
+                        read_new_output(&path, offsets[i], flush).await
+                    {
+                        offsets[i] = next_offset;
+                        let cursor = LogCursor {
+                            step_index: index,
+                            stdout_offset: offsets[0],
+                            stderr_offset: offsets[1],
+                        };
+                        let event = log_event(index, stream, &content).id(cursor.event_id());
+                        if tx.send(Ok(event)).await.is_err() {
+                            return;
+                        }
+                    }
+                }
+            }
+
+            if changed && status.is_terminal() {
+                statuses.insert(index, status);
+                if tx.send(Ok(step_event(step.into()))).await.is_err() {
+                    return;
+                }
+            }
+        }
+
+        if build_finished {
+            let _ = tx.send(Ok(done_event(build.status))).await;
+            return;
+        }
+
+        tokio::select! {
+            _ = tokio::time::sleep(POLL_INTERVAL) => {}
+            _ = next_build_event(&mut events, &build_id) => {}
+            _ = next_build_event(&mut log_events, &build_id) => {}
+            _ = tx.closed() => return,
+        }
+    }
+}
+
+/// Waits until an event for the given build is published.
+///
+/// Lagging counts as an event, since something may have been missed. If the
+/// bus is gone this never returns, leaving the stream to polling.
+async fn next_build_event(events: &mut broadcast::Receiver<BuildEvent>, build_id: &BuildId) {
+    loop {
+        match events.recv().await {
+            Ok(event) if event.build_id() == build_id => return,
+            Ok(_) => {}
+            Err(broadcast::error::RecvError::Lagged(_)) => return,
+            Err(broadcast::error::RecvError::Closed) => std::future::pending::<()>().await,
+        }
+    }
+}
+
+/// Reads output written to a log file since `offset`.
+///
+/// Unless `flush` is set, only complete lines are returned so a UTF-8
+/// sequence or line is never split across events. Returns the text and the
+/// offset to read from next, or `None` if there is nothing new.
+async fn read_new_output(path: &FsPath, offset: u64, flush: bool) -> Option<(String, u64)> {
+    let mut file = tokio::fs::File::open(path).await.ok()?;
+    let len = file.metadata().await.ok()?.len();
+    if len <= offset {
+        return None;
+    }
+
+    let available = usize::try_from(len - offset).unwrap_or(usize::MAX);
+    let mut buf = vec![0u8; available.min(MAX_CHUNK_BYTES)];
+    file.seek(SeekFrom::Start(offset)).await.ok()?;
+    file.read_exact(&mut buf).await.ok()?;
+
+    let full_chunk = buf.len() == MAX_CHUNK_BYTES;
+    if !flush && !full_chunk {
+        let end = buf.iter().rposition(|&b| b == b'\n')? + 1;
+        buf.truncate(end);
+    } else if full_chunk {
+        // Don't cut a multi-byte character in half
+        let valid = match std::str::from_utf8(&buf) {
+            Ok(_) => buf.len(),
+            Err(e) if e.error_len().is_none() => e.valid_up_to(),
+            Err(_) => buf.len(),
+        };
+        buf.truncate(valid.max(1));
+    }
+
+    let consumed = buf.len() as u64;
+    Some((String::from_utf8_lossy(&buf).into_owned(), offset + consumed))
+}
+
+fn log_event(step_index: i32, stream: LogStream, content: &str) -> Event {
+    Event::default()
+        .event("log")
+        .json_data(json!({
+            "step_index": step_index,
+            "stream": stream.as_str(),
+            "content": content,
+        }))
+        .unwrap_or_default()
+}
+
+fn done_event(status: BuildStatus) -> Event {
+    Event::default()
+        .event("done")
+        .json_data(json!({"status": status}))
+        .unwrap_or_default()
+}
+
+fn step_event(step: BuildStepResponse) -> Event {
+    Event::default()
+        .event("step")
+        .json_data(step)
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_log_cursor_round_trip() {
+        let cursor = LogCursor {
+            step_index: -1000,
+            stdout_offset: 42,
+            stderr_offset: 7,
+        };
+        assert_eq!(cursor.event_id(), "-1000:42:7");
+        assert_eq!(LogCursor::parse("-1000:42:7"), Some(cursor));
+    }
+
+    #[test]
+    fn test_log_cursor_rejects_malformed_ids() {
+        assert_eq!(LogCursor::parse(""), None);
+        assert_eq!(LogCursor::parse("3:10"), None);
+        assert_eq!(LogCursor::parse("3:10:5:1"), None);
+        assert_eq!(LogCursor::parse("3:-1:5"), None);
+    }
+
+    #[tokio::test]
+    async fn test_read_new_output_returns_complete_lines() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("step-0-stdout.log");
+        tokio::fs::write(&path, "line one\nline tw").await.unwrap();
+
+        let (content, offset) = read_new_output(&path, 0, false).await.unwrap();
+        assert_eq!(content, "line one\n");
+        assert_eq!(offset, 9);
+
+        // Partial line is held back until the step finishes
+        assert!(read_new_output(&path, offset, false).await.is_none());
+        let (content, offset) = read_new_output(&path, offset, true).await.unwrap();
+        assert_eq!(content, "line tw");
+        assert_eq!(offset, 16);
+
+        assert!(read_new_output(&path, offset, true).await.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_read_new_output_missing_file() {
+        let dir = tempfile::tempdir().unwrap();
+        assert!(read_new_output(&dir.path().join("missing.log"), 0, true).await.is_none());
+    }
+}
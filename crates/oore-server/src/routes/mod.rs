@@ -1,11 +1,18 @@
 //! HTTP route handlers.
 
 pub mod builds;
+pub mod email;
+pub mod env_vars;
 pub mod github_oauth;
 pub mod gitlab_oauth;
+pub mod log_stream;
 pub mod oauth_callback;
+pub mod outbound_webhooks;
 pub mod pipelines;
+pub mod publishing;
 pub mod repositories;
+pub mod schedules;
 pub mod setup;
 pub mod signing;
+pub mod slack;
 pub mod webhooks;
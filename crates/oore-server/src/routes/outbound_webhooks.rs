@@ -0,0 +1,421 @@
+//! Outbound webhook endpoints.
+
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    response::IntoResponse,
+    Json,
+};
+use oore_core::{
+    db::{
+        outbound_webhooks::{
+            decrypt_webhook_secret, encrypt_webhook_secret, OutboundDeliveryRepo,
+            OutboundWebhookRepo,
+        },
+        repository::RepositoryRepo,
+    },
+    models::{
+        validate_notification_events, CreateOutboundWebhookRequest, OutboundDeliveryResponse,
+        OutboundWebhook, OutboundWebhookId, OutboundWebhookResponse, RepositoryId,
+        UpdateOutboundWebhookRequest,
+    },
+    oauth::{validate_outbound_url, SsrfConfig},
+};
+use serde::Deserialize;
+use serde_json::json;
+
+use crate::state::AppState;
+
+/// Default number of deliveries returned by the history endpoint.
+const DEFAULT_DELIVERY_LIMIT: i64 = 50;
+
+/// Maximum number of deliveries returned by the history endpoint.
+const MAX_DELIVERY_LIMIT: i64 = 200;
+
+#[derive(Deserialize)]
+pub struct ListDeliveriesQuery {
+    pub limit: Option<i64>,
+}
+
+/// Checks a webhook URL against the SSRF rules, returning the error message on failure.
+fn check_url(url: &str) -> Result<(), String> {
+    validate_outbound_url(url, &SsrfConfig::from_env())
+        .map(|_| ())
+        .map_err(|e| e.to_string())
+}
+
+/// List outbound webhooks for a repository.
+///
+/// Secrets are never returned.
+///
+/// GET /api/repositories/:repo_id/outbound-webhooks
+pub async fn list_outbound_webhooks(
+    State(state): State<AppState>,
+    Path(repo_id): Path<String>,
+) -> impl IntoResponse {
+    let repo_id = match RepositoryId::from_string(&repo_id) {
+        Ok(id) => id,
+        Err(_) => {
+            return (
+                StatusCode::BAD_REQUEST,
+                Json(json!({"error": "Invalid repository ID"})),
+            );
+        }
+    };
+
+    match OutboundWebhookRepo::list_for_repo(&state.db, &repo_id).await {
+        Ok(webhooks) => {
+            let responses: Vec<OutboundWebhookResponse> =
+                webhooks.into_iter().map(OutboundWebhookResponse::from).collect();
+            (StatusCode::OK, Json(json!(responses)))
+        }
+        Err(e) => {
+            tracing::error!("Failed to list outbound webhooks: {}", e);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(json!({"error": "Database error"})),
+            )
+        }
+    }
+}
+
+/// Create an outbound webhook for a repository.
+///
+/// POST /api/repositories/:repo_id/outbound-webhooks
+pub async fn create_outbound_webhook(
+    State(state): State<AppState>,
+    Path(repo_id): Path<String>,
+    Json(req): Json<CreateOutboundWebhookRequest>,
+) -> impl IntoResponse {
+    let repo_id = match RepositoryId::from_string(&repo_id) {
+        Ok(id) => id,
+        Err(_) => {
+            return (
+                StatusCode::BAD_REQUEST,
+                Json(json!({"error": "Invalid repository ID"})),
+            );
+        }
+    };
+
+    // Verify repository exists
+    match RepositoryRepo::get_by_id(&state.db, &repo_id).await {
+        Ok(Some(_)) => {}
+        Ok(None) => {
+            return (
+                StatusCode::NOT_FOUND,
+                Json(json!({"error": "Repository not found"})),
+            );
+        }
+        Err(e) => {
+            tracing::error!("Failed to get repository: {}", e);
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(json!({"error": "Database error"})),
+            );
+        }
+    }
+
+    let Some(encryption_key) = &state.encryption_key else {
+        return (
+            StatusCode::SERVICE_UNAVAILABLE,
+            Json(json!({"error": "Encryption not configured"})),
+        );
+    };
+
+    if let Err(e) = validate_notification_events(&req.events) {
+        return (StatusCode::BAD_REQUEST, Json(json!({"error": e})));
+    }
+
+    if req.secret.is_empty() {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(json!({"error": "Secret must not be empty"})),
+        );
+    }
+
+    if let Err(e) = check_url(&req.url) {
+        return (StatusCode::BAD_REQUEST, Json(json!({"error": e})));
+    }
+
+    let webhook_id = OutboundWebhookId::new();
+    let (secret_encrypted, secret_nonce) =
+        match encrypt_webhook_secret(encryption_key, &webhook_id, &req.secret) {
+            Ok(result) => result,
+            Err(e) => {
+                tracing::error!("Failed to encrypt webhook secret: {}", e);
+                return (
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    Json(json!({"error": "Encryption error"})),
+                );
+            }
+        };
+
+    let webhook = OutboundWebhook {
+        id: webhook_id,
+        repository_id: repo_id,
+        url: req.url,
+        events: req.events,
+        secret_encrypted,
+        secret_nonce,
+        is_active: req.is_active,
+        created_at: chrono::Utc::now(),
+        updated_at: chrono::Utc::now(),
+    };
+
+    if let Err(e) = OutboundWebhookRepo::create(&state.db, &webhook).await {
+        tracing::error!("Failed to create outbound webhook: {}", e);
+        return (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(json!({"error": "Failed to save webhook"})),
+        );
+    }
+
+    let response = OutboundWebhookResponse::from(webhook);
+    (StatusCode::CREATED, Json(json!(response)))
+}
+
+/// Update an outbound webhook.
+///
+/// Omitted fields are left unchanged.
+///
+/// PUT /api/repositories/:repo_id/outbound-webhooks/:webhook_id
+pub async fn update_outbound_webhook(
+    State(state): State<AppState>,
+    Path((repo_id, webhook_id)): Path<(String, String)>,
+    Json(req): Json<UpdateOutboundWebhookRequest>,
+) -> impl IntoResponse {
+    let repo_id = match RepositoryId::from_string(&repo_id) {
+        Ok(id) => id,
+        Err(_) => {
+            return (
+                StatusCode::BAD_REQUEST,
+                Json(json!({"error": "Invalid repository ID"})),
+            );
+        }
+    };
+
+    let webhook_id = match OutboundWebhookId::from_string(&webhook_id) {
+        Ok(id) => id,
+        Err(_) => {
+            return (
+                StatusCode::BAD_REQUEST,
+                Json(json!({"error": "Invalid webhook ID"})),
+            );
+        }
+    };
+
+    let Some(encryption_key) = &state.encryption_key else {
+        return (
+            StatusCode::SERVICE_UNAVAILABLE,
+            Json(json!({"error": "Encryption not configured"})),
+        );
+    };
+
+    let mut webhook = match OutboundWebhookRepo::get_by_id(&state.db, &webhook_id).await {
+        Ok(Some(webhook)) if webhook.repository_id == repo_id => webhook,
+        Ok(_) => {
+            return (
+                StatusCode::NOT_FOUND,
+                Json(json!({"error": "Webhook not found"})),
+            );
+        }
+        Err(e) => {
+            tracing::error!("Failed to get outbound webhook: {}", e);
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(json!({"error": "Database error"})),
+            );
+        }
+    };
+
+    if let Some(url) = req.url {
+        if let Err(e) = check_url(&url) {
+            return (StatusCode::BAD_REQUEST, Json(json!({"error": e})));
+        }
+        webhook.url = url;
+    }
+
+    if let Some(events) = req.events {
+        if let Err(e) = validate_notification_events(&events) {
+            return (StatusCode::BAD_REQUEST, Json(json!({"error": e})));
+        }
+        webhook.events = events;
+    }
+
+    let secret = match req.secret {
+        Some(secret) if secret.is_empty() => {
+            return (
+                StatusCode::BAD_REQUEST,
+                Json(json!({"error": "Secret must not be empty"})),
+            );
+        }
+        Some(secret) => secret,
+        None => match decrypt_webhook_secret(encryption_key, &webhook) {
+            Ok(secret) => secret,
+            Err(e) => {
+                tracing::error!("Failed to decrypt webhook secret {}: {}", webhook.id, e);
+                return (
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    Json(json!({"error": "Decryption error"})),
+                );
+            }
+        },
+    };
+
+    // Re-encrypt on every update so the nonce is never reused
+    match encrypt_webhook_secret(encryption_key, &webhook.id, &secret) {
+        Ok((secret_encrypted, secret_nonce)) => {
+            webhook.secret_encrypted = secret_encrypted;
+            webhook.secret_nonce = secret_nonce;
+        }
+        Err(e) => {
+            tracing::error!("Failed to encrypt webhook secret: {}", e);
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(json!({"error": "Encryption error"})),
+            );
+        }
+    }
+
+    if let Some(is_active) = req.is_active {
+        webhook.is_active = is_active;
+    }
+
+    if let Err(e) = OutboundWebhookRepo::update(&state.db, &webhook).await {
+        tracing::error!("Failed to update outbound webhook: {}", e);
+        return (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(json!({"error": "Failed to update webhook"})),
+        );
+    }
+
+    webhook.updated_at = chrono::Utc::now();
+
+    let response = OutboundWebhookResponse::from(webhook);
+    (StatusCode::OK, Json(json!(response)))
+}
+
+/// Delete an outbound webhook and its delivery history.
+///
+/// DELETE /api/repositories/:repo_id/outbound-webhooks/:webhook_id
+pub async fn delete_outbound_webhook(
+    State(state): State<AppState>,
+    Path((repo_id, webhook_id)): Path<(String, String)>,
+) -> impl IntoResponse {
+    let repo_id = match RepositoryId::from_string(&repo_id) {
+        Ok(id) => id,
+        Err(_) => {
+            return (
+                StatusCode::BAD_REQUEST,
+                Json(json!({"error": "Invalid repository ID"})),
+            );
+        }
+    };
+
+    let webhook_id = match OutboundWebhookId::from_string(&webhook_id) {
+        Ok(id) => id,
+        Err(_) => {
+            return (
+                StatusCode::BAD_REQUEST,
+                Json(json!({"error": "Invalid webhook ID"})),
+            );
+        }
+    };
+
+    // Verify webhook belongs to repository
+    match OutboundWebhookRepo::get_by_id(&state.db, &webhook_id).await {
+        Ok(Some(webhook)) if webhook.repository_id == repo_id => {}
+        Ok(_) => {
+            return (
+                StatusCode::NOT_FOUND,
+                Json(json!({"error": "Webhook not found"})),
+            );
+        }
+        Err(e) => {
+            tracing::error!("Failed to get outbound webhook: {}", e);
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(json!({"error": "Database error"})),
+            );
+        }
+    }
+
+    if let Err(e) = OutboundWebhookRepo::delete(&state.db, &webhook_id).await {
+        tracing::error!("Failed to delete outbound webhook: {}", e);
+        return (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(json!({"error": "Failed to delete webhook"})),
+        );
+    }
+
+    (StatusCode::NO_CONTENT, Json(json!({})))
+}
+
+/// List recent deliveries for an outbound webhook, newest first.
+///
+/// GET /api/repositories/:repo_id/outbound-webhooks/:webhook_id/deliveries
+/// GET /api/repositories/:repo_id/outbound-webhooks/:webhook_id/deliveries?limit=<n>
+pub async fn list_webhook_deliveries(
+    State(state): State<AppState>,
+    Path((repo_id, webhook_id)): Path<(String, String)>,
+    Query(query): Query<ListDeliveriesQuery>,
+) -> impl IntoResponse {
+    let repo_id = match RepositoryId::from_string(&repo_id) {
+        Ok(id) => id,
+        Err(_) => {
+            return (
+                StatusCode::BAD_REQUEST,
+                Json(json!({"error": "Invalid repository ID"})),
+            );
+        }
+    };
+
+    let webhook_id = match OutboundWebhookId::from_string(&webhook_id) {
+        Ok(id) => id,
+        Err(_) => {
+            return (
+                StatusCode::BAD_REQUEST,
+                Json(json!({"error": "Invalid webhook ID"})),
+            );
+        }
+    };
+
+    match OutboundWebhookRepo::get_by_id(&state.db, &webhook_id).await {
+        Ok(Some(webhook)) if webhook.repository_id == repo_id => {}
+        Ok(_) => {
+            return (
+                StatusCode::NOT_FOUND,
+                Json(json!({"error": "Webhook not found"})),
+            );
+        }
+        Err(e) => {
+            tracing::error!("Failed to get outbound webhook: {}", e);
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(json!({"error": "Database error"})),
+            );
+        }
+    }
+
+    let limit = query
+        .limit
+        .unwrap_or(DEFAULT_DELIVERY_LIMIT)
+        .clamp(1, MAX_DELIVERY_LIMIT);
+
+    match OutboundDeliveryRepo::list_for_webhook(&state.db, &webhook_id, limit).await {
+        Ok(deliveries) => {
+            let responses: Vec<OutboundDeliveryResponse> = deliveries
+                .into_iter()
+                .map(OutboundDeliveryResponse::from)
+                .collect();
+            (StatusCode::OK, Json(json!(responses)))
+        }
+        Err(e) => {
+            tracing::error!("Failed to list webhook deliveries: {}", e);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(json!({"error": "Database error"})),
+            )
+        }
+    }
+}
@@ -10,9 +10,9 @@ use oore_core::{
     db::{pipeline::PipelineConfigRepo, repository::RepositoryRepo},
     models::{
         CreatePipelineConfigRequest, PipelineConfig, PipelineConfigResponse, RepositoryId,
-        StoredConfigFormat,
+        StoredConfigFormat, ValidatePipelineRequest, ValidatePipelineResponse,
     },
-    pipeline::{parse_pipeline, parse_pipeline_huml},
+    pipeline::{check_pipeline, parse_pipeline, parse_pipeline_huml, ConfigFormat},
 };
 use serde_json::json;
 
@@ -214,32 +214,37 @@ pub async fn delete_pipeline_config(
 
 /// Validate pipeline configuration without saving.
 ///
+/// Returns every diagnostic found, not just the first error. With
+/// `strict: true`, unknown fields are errors.
+///
 /// POST /api/pipelines/validate
-pub async fn validate_pipeline(Json(req): Json<CreatePipelineConfigRequest>) -> impl IntoResponse {
-    // Parse based on format
-    let parse_result = match req.config_format {
-        StoredConfigFormat::Huml => parse_pipeline_huml(&req.config_content),
-        StoredConfigFormat::Yaml => parse_pipeline(&req.config_content),
+pub async fn validate_pipeline(Json(req): Json<ValidatePipelineRequest>) -> impl IntoResponse {
+    let format = match req.config_format {
+        StoredConfigFormat::Huml => ConfigFormat::Huml,
+        StoredConfigFormat::Yaml => ConfigFormat::Yaml,
+    };
+    let check = check_pipeline(&req.config_content, format, req.strict);
+
+    let valid = check.is_valid();
+    let mut workflows: Vec<String> = check
+        .pipeline
+        .as_ref()
+        .map(|pipeline| pipeline.workflows.keys().cloned().collect())
+        .unwrap_or_default();
+    workflows.sort();
+
+    let response = ValidatePipelineResponse {
+        valid,
+        format: req.config_format,
+        workflows,
+        error: check.first_error().map(|d| d.message.clone()),
+        diagnostics: check.diagnostics,
     };
 
-    match parse_result {
-        Ok(pipeline) => {
-            let workflow_names: Vec<&String> = pipeline.workflows.keys().collect();
-            (
-                StatusCode::OK,
-                Json(json!({
-                    "valid": true,
-                    "workflows": workflow_names,
-                    "format": req.config_format.as_str()
-                })),
-            )
-        }
-        Err(e) => (
-            StatusCode::BAD_REQUEST,
-            Json(json!({
-                "valid": false,
-                "error": e.to_string()
-            })),
-        ),
-    }
+    let status = if valid {
+        StatusCode::OK
+    } else {
+        StatusCode::BAD_REQUEST
+    };
+    (status, Json(json!(response)))
 }
@@ -0,0 +1,404 @@
+//! Store publishing credential endpoints.
+
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::IntoResponse,
+    Json,
+};
+use oore_core::{
+    db::{
+        publishing::{FirebaseCredentialRepo, GooglePlayCredentialRepo},
+        repository::RepositoryRepo,
+    },
+    models::{
+        FirebaseCredential, FirebaseCredentialResponse, GooglePlayCredential,
+        GooglePlayCredentialId, GooglePlayCredentialResponse, RepositoryId,
+        SetFirebaseCredentialRequest, UploadGooglePlayCredentialRequest,
+    },
+    publishing::{
+        firebase, google_play::encrypt_service_account, service_account::ServiceAccountKey,
+    },
+};
+use serde_json::json;
+
+use crate::state::AppState;
+
+// ============================================================================
+// Google Play Credentials
+// ============================================================================
+
+/// List Google Play credentials for a repository.
+///
+/// GET /api/repositories/:repo_id/publishing/google-play/credentials
+pub async fn list_google_play_credentials(
+    State(state): State<AppState>,
+    Path(repo_id): Path<String>,
+) -> impl IntoResponse {
+    let repo_id = match RepositoryId::from_string(&repo_id) {
+        Ok(id) => id,
+        Err(_) => {
+            return (
+                StatusCode::BAD_REQUEST,
+                Json(json!({"error": "Invalid repository ID"})),
+            );
+        }
+    };
+
+    match GooglePlayCredentialRepo::list_all_for_repo(&state.db, &repo_id).await {
+        Ok(credentials) => {
+            let responses: Vec<GooglePlayCredentialResponse> = credentials
+                .into_iter()
+                .map(GooglePlayCredentialResponse::from)
+                .collect();
+            (StatusCode::OK, Json(json!(responses)))
+        }
+        Err(e) => {
+            tracing::error!("Failed to list Google Play credentials: {}", e);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(json!({"error": "Database error"})),
+            )
+        }
+    }
+}
+
+/// Upload a Google Play service account key.
+///
+/// POST /api/repositories/:repo_id/publishing/google-play/credentials
+pub async fn upload_google_play_credential(
+    State(state): State<AppState>,
+    Path(repo_id): Path<String>,
+    Json(req): Json<UploadGooglePlayCredentialRequest>,
+) -> impl IntoResponse {
+    let repo_id = match RepositoryId::from_string(&repo_id) {
+        Ok(id) => id,
+        Err(_) => {
+            return (
+                StatusCode::BAD_REQUEST,
+                Json(json!({"error": "Invalid repository ID"})),
+            );
+        }
+    };
+
+    // Verify repository exists
+    if let Ok(None) = RepositoryRepo::get_by_id(&state.db, &repo_id).await {
+        return (
+            StatusCode::NOT_FOUND,
+            Json(json!({"error": "Repository not found"})),
+        );
+    }
+
+    // Check encryption key
+    let Some(encryption_key) = &state.encryption_key else {
+        return (
+            StatusCode::SERVICE_UNAVAILABLE,
+            Json(json!({"error": "Encryption not configured"})),
+        );
+    };
+
+    let name = req.name.trim().to_string();
+    if name.is_empty() {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(json!({"error": "Name is required"})),
+        );
+    }
+
+    // Validate the key file before storing it
+    let service_account = match ServiceAccountKey::from_json(&req.service_account_json) {
+        Ok(key) => key,
+        Err(e) => {
+            return (
+                StatusCode::BAD_REQUEST,
+                Json(json!({"error": e.to_string()})),
+            );
+        }
+    };
+
+    let credential_id = GooglePlayCredentialId::new();
+    let (encrypted, nonce) =
+        match encrypt_service_account(encryption_key, &credential_id, &req.service_account_json) {
+            Ok(result) => result,
+            Err(e) => {
+                tracing::error!("Failed to encrypt service account key: {}", e);
+                return (
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    Json(json!({"error": "Encryption error"})),
+                );
+            }
+        };
+
+    let credential = GooglePlayCredential {
+        id: credential_id,
+        repository_id: repo_id,
+        name,
+        client_email: service_account.client_email,
+        service_account_encrypted: encrypted,
+        service_account_nonce: nonce,
+        is_active: true,
+        created_at: chrono::Utc::now(),
+        updated_at: chrono::Utc::now(),
+    };
+
+    if let Err(e) = GooglePlayCredentialRepo::create(&state.db, &credential).await {
+        tracing::error!("Failed to create Google Play credential: {}", e);
+        // Check for unique constraint violation
+        if e.to_string().contains("UNIQUE constraint failed") {
+            return (
+                StatusCode::CONFLICT,
+                Json(
+                    json!({"error": "A Google Play credential with this name already exists for this repository"}),
+                ),
+            );
+        }
+        return (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(json!({"error": "Failed to save credential"})),
+        );
+    }
+
+    let response = GooglePlayCredentialResponse::from(credential);
+    (StatusCode::CREATED, Json(json!(response)))
+}
+
+/// Delete a Google Play credential.
+///
+/// DELETE /api/repositories/:repo_id/publishing/google-play/credentials/:credential_id
+pub async fn delete_google_play_credential(
+    State(state): State<AppState>,
+    Path((repo_id, credential_id)): Path<(String, String)>,
+) -> impl IntoResponse {
+    let repo_id = match RepositoryId::from_string(&repo_id) {
+        Ok(id) => id,
+        Err(_) => {
+            return (
+                StatusCode::BAD_REQUEST,
+                Json(json!({"error": "Invalid repository ID"})),
+            );
+        }
+    };
+
+    let credential_id = match GooglePlayCredentialId::from_string(&credential_id) {
+        Ok(id) => id,
+        Err(_) => {
+            return (
+                StatusCode::BAD_REQUEST,
+                Json(json!({"error": "Invalid credential ID"})),
+            );
+        }
+    };
+
+    // Verify credential belongs to repository
+    match GooglePlayCredentialRepo::get_by_id(&state.db, &credential_id).await {
+        Ok(Some(credential)) if credential.repository_id == repo_id => {}
+        Ok(_) => {
+            return (
+                StatusCode::NOT_FOUND,
+                Json(json!({"error": "Credential not found"})),
+            );
+        }
+        Err(e) => {
+            tracing::error!("Failed to get Google Play credential: {}", e);
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(json!({"error": "Database error"})),
+            );
+        }
+    }
+
+    if let Err(e) = GooglePlayCredentialRepo::delete(&state.db, &credential_id).await {
+        tracing::error!("Failed to delete Google Play credential: {}", e);
+        return (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(json!({"error": "Failed to delete credential"})),
+        );
+    }
+
+    (StatusCode::NO_CONTENT, Json(json!({})))
+}
+
+// ============================================================================
+// Firebase Credential
+// ============================================================================
+
+/// Get the Firebase service account for a repository.
+///
+/// The key itself is never returned.
+///
+/// GET /api/repositories/:repo_id/publishing/firebase/credentials
+pub async fn get_firebase_credential(
+    State(state): State<AppState>,
+    Path(repo_id): Path<String>,
+) -> impl IntoResponse {
+    let repo_id = match RepositoryId::from_string(&repo_id) {
+        Ok(id) => id,
+        Err(_) => {
+            return (
+                StatusCode::BAD_REQUEST,
+                Json(json!({"error": "Invalid repository ID"})),
+            );
+        }
+    };
+
+    match FirebaseCredentialRepo::get_for_repo(&state.db, &repo_id).await {
+        Ok(Some(credential)) => (
+            StatusCode::OK,
+            Json(json!(FirebaseCredentialResponse::from(credential))),
+        ),
+        Ok(None) => (
+            StatusCode::NOT_FOUND,
+            Json(json!({"error": "Firebase credential not configured"})),
+        ),
+        Err(e) => {
+            tracing::error!("Failed to get Firebase credential: {}", e);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(json!({"error": "Database error"})),
+            )
+        }
+    }
+}
+
+/// Set or replace the Firebase service account for a repository.
+///
+/// PUT /api/repositories/:repo_id/publishing/firebase/credentials
+pub async fn set_firebase_credential(
+    State(state): State<AppState>,
+    Path(repo_id): Path<String>,
+    Json(req): Json<SetFirebaseCredentialRequest>,
+) -> impl IntoResponse {
+    let repo_id = match RepositoryId::from_string(&repo_id) {
+        Ok(id) => id,
+        Err(_) => {
+            return (
+                StatusCode::BAD_REQUEST,
+                Json(json!({"error": "Invalid repository ID"})),
+            );
+        }
+    };
+
+    // Verify repository exists
+    match RepositoryRepo::get_by_id(&state.db, &repo_id).await {
+        Ok(Some(_)) => {}
+        Ok(None) => {
+            return (
+                StatusCode::NOT_FOUND,
+                Json(json!({"error": "Repository not found"})),
+            );
+        }
+        Err(e) => {
+            tracing::error!("Failed to get repository: {}", e);
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(json!({"error": "Database error"})),
+            );
+        }
+    }
+
+    let Some(encryption_key) = &state.encryption_key else {
+        return (
+            StatusCode::SERVICE_UNAVAILABLE,
+            Json(json!({"error": "Encryption not configured"})),
+        );
+    };
+
+    let service_account = match ServiceAccountKey::from_json(&req.service_account_json) {
+        Ok(key) => key,
+        Err(e) => {
+            return (
+                StatusCode::BAD_REQUEST,
+                Json(json!({"error": e.to_string()})),
+            );
+        }
+    };
+
+    let existing = match FirebaseCredentialRepo::get_for_repo(&state.db, &repo_id).await {
+        Ok(existing) => existing,
+        Err(e) => {
+            tracing::error!("Failed to get Firebase credential: {}", e);
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(json!({"error": "Database error"})),
+            );
+        }
+    };
+
+    // Keep the existing ID: it is bound into the ciphertext as AAD
+    let id = existing.as_ref().map(|c| c.id.clone()).unwrap_or_default();
+
+    let (encrypted, nonce) =
+        match firebase::encrypt_service_account(encryption_key, &id, &req.service_account_json) {
+            Ok(result) => result,
+            Err(e) => {
+                tracing::error!("Failed to encrypt service account key: {}", e);
+                return (
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    Json(json!({"error": "Encryption error"})),
+                );
+            }
+        };
+
+    let now = chrono::Utc::now();
+    let created = existing.is_none();
+    let credential = FirebaseCredential {
+        id,
+        repository_id: repo_id,
+        client_email: service_account.client_email,
+        service_account_encrypted: encrypted,
+        service_account_nonce: nonce,
+        created_at: existing.map(|c| c.created_at).unwrap_or(now),
+        updated_at: now,
+    };
+
+    if let Err(e) = FirebaseCredentialRepo::upsert(&state.db, &credential).await {
+        tracing::error!("Failed to save Firebase credential: {}", e);
+        return (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(json!({"error": "Failed to save credential"})),
+        );
+    }
+
+    let status = if created {
+        StatusCode::CREATED
+    } else {
+        StatusCode::OK
+    };
+    (
+        status,
+        Json(json!(FirebaseCredentialResponse::from(credential))),
+    )
+}
+
+/// Remove the Firebase service account for a repository.
+///
+/// DELETE /api/repositories/:repo_id/publishing/firebase/credentials
+pub async fn delete_firebase_credential(
+    State(state): State<AppState>,
+    Path(repo_id): Path<String>,
+) -> impl IntoResponse {
+    let repo_id = match RepositoryId::from_string(&repo_id) {
+        Ok(id) => id,
+        Err(_) => {
+            return (
+                StatusCode::BAD_REQUEST,
+                Json(json!({"error": "Invalid repository ID"})),
+            );
+        }
+    };
+
+    match FirebaseCredentialRepo::delete_for_repo(&state.db, &repo_id).await {
+        Ok(true) => (StatusCode::NO_CONTENT, Json(json!({}))),
+        Ok(false) => (
+            StatusCode::NOT_FOUND,
+            Json(json!({"error": "Firebase credential not configured"})),
+        ),
+        Err(e) => {
+            tracing::error!("Failed to delete Firebase credential: {}", e);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(json!({"error": "Database error"})),
+            )
+        }
+    }
+}
@@ -244,6 +244,29 @@ pub async fn update_repository(
     if let Some(project_id) = req.gitlab_project_id {
         repo.gitlab_project_id = Some(project_id);
     }
+    if let Some(skip) = req.skip_draft_pull_requests {
+        repo.skip_draft_pull_requests = skip;
+    }
+    if let Some(label) = req.required_pull_request_label {
+        let label = label.trim();
+        repo.required_pull_request_label = (!label.is_empty()).then(|| label.to_string());
+    }
+    if let Some(cancel) = req.cancel_previous_builds {
+        repo.cancel_previous_builds = cancel;
+    }
+    if let Some(executor) = req.executor {
+        let executor = executor.trim();
+        repo.executor = if executor.is_empty() {
+            None
+        } else {
+            match executor.parse() {
+                Ok(kind) => Some(kind),
+                Err(e) => {
+                    return (StatusCode::BAD_REQUEST, Json(json!({"error": e.to_string()})));
+                }
+            }
+        };
+    }
 
     // Update webhook secret if provided
     if let Some(secret) = req.webhook_secret
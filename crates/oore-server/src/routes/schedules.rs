@@ -0,0 +1,299 @@
+//! Build schedule endpoints.
+
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::IntoResponse,
+    Json,
+};
+use chrono::Utc;
+use oore_core::{
+    db::{repository::RepositoryRepo, schedules::BuildScheduleRepo},
+    models::{
+        validate_schedule_branch, BuildSchedule, BuildScheduleId, BuildScheduleResponse,
+        CreateBuildScheduleRequest, RepositoryId, UpdateBuildScheduleRequest,
+    },
+    schedule::next_run_for,
+};
+use serde_json::json;
+
+use crate::state::AppState;
+
+/// List build schedules for a repository.
+///
+/// GET /api/repositories/:repo_id/schedules
+pub async fn list_schedules(
+    State(state): State<AppState>,
+    Path(repo_id): Path<String>,
+) -> impl IntoResponse {
+    let repo_id = match RepositoryId::from_string(&repo_id) {
+        Ok(id) => id,
+        Err(_) => {
+            return (
+                StatusCode::BAD_REQUEST,
+                Json(json!({"error": "Invalid repository ID"})),
+            );
+        }
+    };
+
+    match BuildScheduleRepo::list_for_repo(&state.db, &repo_id).await {
+        Ok(schedules) => {
+            let responses: Vec<BuildScheduleResponse> = schedules
+                .into_iter()
+                .map(BuildScheduleResponse::from)
+                .collect();
+            (StatusCode::OK, Json(json!(responses)))
+        }
+        Err(e) => {
+            tracing::error!("Failed to list schedules: {}", e);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(json!({"error": "Database error"})),
+            )
+        }
+    }
+}
+
+/// Create a build schedule for a repository.
+///
+/// POST /api/repositories/:repo_id/schedules
+pub async fn create_schedule(
+    State(state): State<AppState>,
+    Path(repo_id): Path<String>,
+    Json(req): Json<CreateBuildScheduleRequest>,
+) -> impl IntoResponse {
+    let repo_id = match RepositoryId::from_string(&repo_id) {
+        Ok(id) => id,
+        Err(_) => {
+            return (
+                StatusCode::BAD_REQUEST,
+                Json(json!({"error": "Invalid repository ID"})),
+            );
+        }
+    };
+
+    let repository = match RepositoryRepo::get_by_id(&state.db, &repo_id).await {
+        Ok(Some(repository)) => repository,
+        Ok(None) => {
+            return (
+                StatusCode::NOT_FOUND,
+                Json(json!({"error": "Repository not found"})),
+            );
+        }
+        Err(e) => {
+            tracing::error!("Failed to get repository: {}", e);
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(json!({"error": "Database error"})),
+            );
+        }
+    };
+
+    let now = Utc::now();
+    let mut schedule = BuildSchedule {
+        id: BuildScheduleId::new(),
+        repository_id: repo_id,
+        cron_expression: req.cron_expression.trim().to_string(),
+        branch: req.branch.unwrap_or(repository.default_branch),
+        workflow_name: req.workflow_name,
+        timezone: req.timezone.unwrap_or_else(|| "UTC".to_string()),
+        skip_if_built: req.skip_if_built,
+        is_active: req.is_active.unwrap_or(true),
+        next_run_at: None,
+        last_run_at: None,
+        last_build_id: None,
+        last_error: None,
+        created_at: now,
+        updated_at: now,
+    };
+
+    if let Err(e) = prepare_schedule(&mut schedule) {
+        return (StatusCode::BAD_REQUEST, Json(json!({"error": e})));
+    }
+
+    if let Err(e) = BuildScheduleRepo::create(&state.db, &schedule).await {
+        tracing::error!("Failed to create schedule: {}", e);
+        return (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(json!({"error": "Failed to save schedule"})),
+        );
+    }
+
+    let response = BuildScheduleResponse::from(schedule);
+    (StatusCode::CREATED, Json(json!(response)))
+}
+
+/// Update a build schedule.
+///
+/// Any change recomputes the next run from now.
+///
+/// PUT /api/repositories/:repo_id/schedules/:schedule_id
+pub async fn update_schedule(
+    State(state): State<AppState>,
+    Path((repo_id, schedule_id)): Path<(String, String)>,
+    Json(req): Json<UpdateBuildScheduleRequest>,
+) -> impl IntoResponse {
+    let repo_id = match RepositoryId::from_string(&repo_id) {
+        Ok(id) => id,
+        Err(_) => {
+            return (
+                StatusCode::BAD_REQUEST,
+                Json(json!({"error": "Invalid repository ID"})),
+            );
+        }
+    };
+
+    let schedule_id = match BuildScheduleId::from_string(&schedule_id) {
+        Ok(id) => id,
+        Err(_) => {
+            return (
+                StatusCode::BAD_REQUEST,
+                Json(json!({"error": "Invalid schedule ID"})),
+            );
+        }
+    };
+
+    let mut schedule = match BuildScheduleRepo::get_by_id(&state.db, &schedule_id).await {
+        Ok(Some(schedule)) if schedule.repository_id == repo_id => schedule,
+        Ok(_) => {
+            return (
+                StatusCode::NOT_FOUND,
+                Json(json!({"error": "Schedule not found"})),
+            );
+        }
+        Err(e) => {
+            tracing::error!("Failed to get schedule: {}", e);
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(json!({"error": "Database error"})),
+            );
+        }
+    };
+
+    if let Some(cron_expression) = req.cron_expression {
+        schedule.cron_expression = cron_expression.trim().to_string();
+    }
+    if let Some(branch) = req.branch {
+        schedule.branch = branch;
+    }
+    if let Some(workflow_name) = req.workflow_name {
+        schedule.workflow_name = Some(workflow_name);
+    }
+    if let Some(timezone) = req.timezone {
+        schedule.timezone = timezone;
+    }
+    if let Some(skip_if_built) = req.skip_if_built {
+        schedule.skip_if_built = skip_if_built;
+    }
+    if let Some(is_active) = req.is_active {
+        schedule.is_active = is_active;
+    }
+
+    if let Err(e) = prepare_schedule(&mut schedule) {
+        return (StatusCode::BAD_REQUEST, Json(json!({"error": e})));
+    }
+
+    match BuildScheduleRepo::update(&state.db, &schedule).await {
+        Ok(true) => {}
+        Ok(false) => {
+            return (
+                StatusCode::NOT_FOUND,
+                Json(json!({"error": "Schedule not found"})),
+            );
+        }
+        Err(e) => {
+            tracing::error!("Failed to update schedule: {}", e);
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(json!({"error": "Failed to update schedule"})),
+            );
+        }
+    }
+
+    schedule.updated_at = Utc::now();
+    let response = BuildScheduleResponse::from(schedule);
+    (StatusCode::OK, Json(json!(response)))
+}
+
+/// Delete a build schedule.
+///
+/// DELETE /api/repositories/:repo_id/schedules/:schedule_id
+pub async fn delete_schedule(
+    State(state): State<AppState>,
+    Path((repo_id, schedule_id)): Path<(String, String)>,
+) -> impl IntoResponse {
+    let repo_id = match RepositoryId::from_string(&repo_id) {
+        Ok(id) => id,
+        Err(_) => {
+            return (
+                StatusCode::BAD_REQUEST,
+                Json(json!({"error": "Invalid repository ID"})),
+            );
+        }
+    };
+
+    let schedule_id = match BuildScheduleId::from_string(&schedule_id) {
+        Ok(id) => id,
+        Err(_) => {
+            return (
+                StatusCode::BAD_REQUEST,
+                Json(json!({"error": "Invalid schedule ID"})),
+            );
+        }
+    };
+
+    // Verify schedule belongs to repository
+    match BuildScheduleRepo::get_by_id(&state.db, &schedule_id).await {
+        Ok(Some(schedule)) if schedule.repository_id == repo_id => {}
+        Ok(_) => {
+            return (
+                StatusCode::NOT_FOUND,
+                Json(json!({"error": "Schedule not found"})),
+            );
+        }
+        Err(e) => {
+            tracing::error!("Failed to get schedule: {}", e);
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(json!({"error": "Database error"})),
+            );
+        }
+    }
+
+    if let Err(e) = BuildScheduleRepo::delete(&state.db, &schedule_id).await {
+        tracing::error!("Failed to delete schedule: {}", e);
+        return (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(json!({"error": "Failed to delete schedule"})),
+        );
+    }
+
+    (StatusCode::NO_CONTENT, Json(json!({})))
+}
+
+/// Validates a schedule's settings and computes its next run.
+fn prepare_schedule(schedule: &mut BuildSchedule) -> Result<(), String> {
+    validate_schedule_branch(&schedule.branch)?;
+
+    // An empty workflow name means the pipeline's default workflow
+    schedule.workflow_name = schedule
+        .workflow_name
+        .take()
+        .map(|name| name.trim().to_string())
+        .filter(|name| !name.is_empty());
+
+    let next_run_at = next_run_for(&schedule.cron_expression, &schedule.timezone, Utc::now())?;
+    if next_run_at.is_none() {
+        return Err(format!(
+            "Cron expression '{}' never matches a date",
+            schedule.cron_expression
+        ));
+    }
+    schedule.next_run_at = if schedule.is_active {
+        next_run_at
+    } else {
+        None
+    };
+
+    Ok(())
+}
@@ -0,0 +1,217 @@
+//! Slack notification endpoints.
+
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::IntoResponse,
+    Json,
+};
+use chrono::Utc;
+use oore_core::{
+    db::{
+        repository::RepositoryRepo,
+        slack::{encrypt_slack_url, SlackConfigRepo},
+    },
+    models::{
+        validate_notification_events, RepositoryId, SetSlackConfigRequest, SlackConfig,
+        SlackConfigResponse,
+    },
+    oauth::{validate_outbound_url, SsrfConfig},
+};
+use serde_json::json;
+
+use crate::state::AppState;
+
+/// Get the Slack notification config for a repository.
+///
+/// The webhook URL is never returned.
+///
+/// GET /api/repositories/:repo_id/notifications/slack
+pub async fn get_slack_config(
+    State(state): State<AppState>,
+    Path(repo_id): Path<String>,
+) -> impl IntoResponse {
+    let repo_id = match RepositoryId::from_string(&repo_id) {
+        Ok(id) => id,
+        Err(_) => {
+            return (
+                StatusCode::BAD_REQUEST,
+                Json(json!({"error": "Invalid repository ID"})),
+            );
+        }
+    };
+
+    match SlackConfigRepo::get_for_repo(&state.db, &repo_id).await {
+        Ok(Some(config)) => (
+            StatusCode::OK,
+            Json(json!(SlackConfigResponse::from(config))),
+        ),
+        Ok(None) => (
+            StatusCode::NOT_FOUND,
+            Json(json!({"error": "Slack notifications not configured"})),
+        ),
+        Err(e) => {
+            tracing::error!("Failed to get Slack config: {}", e);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(json!({"error": "Database error"})),
+            )
+        }
+    }
+}
+
+/// Create or replace the Slack notification config for a repository.
+///
+/// `webhook_url` is required the first time; omit it to keep the stored URL.
+///
+/// PUT /api/repositories/:repo_id/notifications/slack
+pub async fn set_slack_config(
+    State(state): State<AppState>,
+    Path(repo_id): Path<String>,
+    Json(req): Json<SetSlackConfigRequest>,
+) -> impl IntoResponse {
+    let repo_id = match RepositoryId::from_string(&repo_id) {
+        Ok(id) => id,
+        Err(_) => {
+            return (
+                StatusCode::BAD_REQUEST,
+                Json(json!({"error": "Invalid repository ID"})),
+            );
+        }
+    };
+
+    // Verify repository exists
+    match RepositoryRepo::get_by_id(&state.db, &repo_id).await {
+        Ok(Some(_)) => {}
+        Ok(None) => {
+            return (
+                StatusCode::NOT_FOUND,
+                Json(json!({"error": "Repository not found"})),
+            );
+        }
+        Err(e) => {
+            tracing::error!("Failed to get repository: {}", e);
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(json!({"error": "Database error"})),
+            );
+        }
+    }
+
+    let Some(encryption_key) = &state.encryption_key else {
+        return (
+            StatusCode::SERVICE_UNAVAILABLE,
+            Json(json!({"error": "Encryption not configured"})),
+        );
+    };
+
+    if let Err(e) = validate_notification_events(&req.events) {
+        return (StatusCode::BAD_REQUEST, Json(json!({"error": e})));
+    }
+
+    let existing = match SlackConfigRepo::get_for_repo(&state.db, &repo_id).await {
+        Ok(existing) => existing,
+        Err(e) => {
+            tracing::error!("Failed to get Slack config: {}", e);
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(json!({"error": "Database error"})),
+            );
+        }
+    };
+
+    // Keep the existing ID: it is bound into the ciphertext as AAD
+    let id = existing.as_ref().map(|c| c.id.clone()).unwrap_or_default();
+
+    let (webhook_url_encrypted, webhook_url_nonce) = match (&req.webhook_url, &existing) {
+        (Some(url), _) => {
+            if let Err(e) = validate_outbound_url(url, &SsrfConfig::from_env()) {
+                return (
+                    StatusCode::BAD_REQUEST,
+                    Json(json!({"error": e.to_string()})),
+                );
+            }
+            match encrypt_slack_url(encryption_key, &id, url) {
+                Ok(encrypted) => encrypted,
+                Err(e) => {
+                    tracing::error!("Failed to encrypt Slack webhook URL: {}", e);
+                    return (
+                        StatusCode::INTERNAL_SERVER_ERROR,
+                        Json(json!({"error": "Encryption error"})),
+                    );
+                }
+            }
+        }
+        (None, Some(existing)) => (
+            existing.webhook_url_encrypted.clone(),
+            existing.webhook_url_nonce.clone(),
+        ),
+        (None, None) => {
+            return (
+                StatusCode::BAD_REQUEST,
+                Json(json!({"error": "webhook_url is required"})),
+            );
+        }
+    };
+
+    let now = Utc::now();
+    let created = existing.is_none();
+    let config = SlackConfig {
+        id,
+        repository_id: repo_id,
+        webhook_url_encrypted,
+        webhook_url_nonce,
+        events: req.events,
+        is_active: req.is_active,
+        created_at: existing.map(|c| c.created_at).unwrap_or(now),
+        updated_at: now,
+    };
+
+    if let Err(e) = SlackConfigRepo::upsert(&state.db, &config).await {
+        tracing::error!("Failed to save Slack config: {}", e);
+        return (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(json!({"error": "Database error"})),
+        );
+    }
+
+    let status = if created {
+        StatusCode::CREATED
+    } else {
+        StatusCode::OK
+    };
+    (status, Json(json!(SlackConfigResponse::from(config))))
+}
+
+/// Remove the Slack notification config for a repository.
+///
+/// DELETE /api/repositories/:repo_id/notifications/slack
+pub async fn delete_slack_config(
+    State(state): State<AppState>,
+    Path(repo_id): Path<String>,
+) -> impl IntoResponse {
+    let repo_id = match RepositoryId::from_string(&repo_id) {
+        Ok(id) => id,
+        Err(_) => {
+            return (
+                StatusCode::BAD_REQUEST,
+                Json(json!({"error": "Invalid repository ID"})),
+            );
+        }
+    };
+
+    match SlackConfigRepo::delete_for_repo(&state.db, &repo_id).await {
+        Ok(true) => (StatusCode::NO_CONTENT, Json(json!({}))),
+        Ok(false) => (
+            StatusCode::NOT_FOUND,
+            Json(json!({"error": "Slack notifications not configured"})),
+        ),
+        Err(e) => {
+            tracing::error!("Failed to delete Slack config: {}", e);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(json!({"error": "Database error"})),
+            )
+        }
+    }
+}
@@ -4,10 +4,12 @@ use oore_core::db::DbPool;
 use oore_core::demo::DemoProvider;
 use oore_core::oauth::EncryptionKey;
 use oore_core::providers::{GitHubAppConfig, GitLabConfig};
+use std::path::PathBuf;
 use std::sync::Arc;
 use tokio::sync::mpsc;
 use url::Url;
 
+use crate::events::BuildEventBus;
 use crate::middleware::AdminAuthConfig;
 use crate::worker::{BuildJob, CancelChannels, WebhookJob};
 
@@ -75,6 +77,10 @@ pub struct AppState {
     pub build_tx: mpsc::Sender<BuildJob>,
     /// Channels for cancelling running builds.
     pub build_cancel_channels: CancelChannels,
+    /// Build lifecycle events published by the build processor.
+    pub build_events: BuildEventBus,
+    /// Directory the build processor writes step logs to.
+    pub logs_dir: PathBuf,
     /// Encryption key for storing credentials.
     pub encryption_key: Option<EncryptionKey>,
     /// Admin authentication configuration.
@@ -94,6 +100,8 @@ impl AppState {
         webhook_tx: mpsc::Sender<WebhookJob>,
         build_tx: mpsc::Sender<BuildJob>,
         build_cancel_channels: CancelChannels,
+        build_events: BuildEventBus,
+        logs_dir: PathBuf,
         encryption_key: Option<EncryptionKey>,
         admin_auth_config: AdminAuthConfig,
         demo_provider: Option<DemoProvider>,
@@ -106,6 +114,8 @@ impl AppState {
             webhook_tx,
             build_tx,
             build_cancel_channels,
+            build_events,
+            logs_dir,
             encryption_key,
             admin_auth_config: Arc::new(admin_auth_config),
             demo_provider: demo_provider.map(Arc::new),
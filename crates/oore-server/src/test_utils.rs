@@ -9,11 +9,13 @@ use axum::{
 };
 use dashmap::DashMap;
 use oore_core::db::{create_pool, run_migrations, DbPool};
+use oore_core::oauth::EncryptionKey;
 use serde::Serialize;
 use std::sync::Arc;
 use tokio::sync::mpsc;
 use url::Url;
 
+use crate::events::BuildEventBus;
 use crate::middleware::{AdminAuthConfig, require_admin};
 use crate::state::{AppState, ServerConfig};
 use crate::worker::{BuildJob, CancelChannels, WebhookJob};
@@ -77,6 +79,8 @@ pub fn api_router(state: AppState) -> Router {
         .route("/builds/{id}/steps", get(routes::builds::get_build_steps))
         .route("/builds/{id}/logs", get(routes::builds::get_build_logs))
         .route("/builds/{id}/logs/content", get(routes::builds::get_build_log_content))
+        .route("/builds/{id}/logs/stream", get(routes::log_stream::stream_build_logs))
+        .route("/builds/{id}/publications", get(routes::builds::list_build_publications))
         // Pipelines
         .route("/pipelines/validate", post(routes::pipelines::validate_pipeline))
         .route("/repositories/{id}/pipeline", get(routes::pipelines::get_pipeline_config))
@@ -115,6 +119,38 @@ pub fn admin_router(state: AppState) -> Router {
         .route("/gitlab/projects/{id}/enabled", delete(routes::gitlab_oauth::disable_project))
         .route("/gitlab/refresh", post(routes::gitlab_oauth::refresh_token))
         .route("/gitlab/apps", post(routes::gitlab_oauth::register_app))
+        // Publishing credentials
+        .route("/repositories/{id}/publishing/google-play/credentials", get(routes::publishing::list_google_play_credentials))
+        .route("/repositories/{id}/publishing/google-play/credentials", post(routes::publishing::upload_google_play_credential))
+        .route("/repositories/{id}/publishing/google-play/credentials/{credential_id}", delete(routes::publishing::delete_google_play_credential))
+        .route("/repositories/{id}/publishing/firebase/credentials", get(routes::publishing::get_firebase_credential))
+        .route("/repositories/{id}/publishing/firebase/credentials", put(routes::publishing::set_firebase_credential))
+        .route("/repositories/{id}/publishing/firebase/credentials", delete(routes::publishing::delete_firebase_credential))
+        // Repository environment variables
+        .route("/repositories/{id}/env", get(routes::env_vars::list_env_vars))
+        .route("/repositories/{id}/env", post(routes::env_vars::create_env_var))
+        .route("/repositories/{id}/env/{var_id}", put(routes::env_vars::update_env_var))
+        .route("/repositories/{id}/env/{var_id}", delete(routes::env_vars::delete_env_var))
+        .route("/repositories/{id}/schedules", get(routes::schedules::list_schedules))
+        .route("/repositories/{id}/schedules", post(routes::schedules::create_schedule))
+        .route("/repositories/{id}/schedules/{schedule_id}", put(routes::schedules::update_schedule))
+        .route("/repositories/{id}/schedules/{schedule_id}", delete(routes::schedules::delete_schedule))
+        // Outbound webhook routes
+        .route("/repositories/{id}/outbound-webhooks", get(routes::outbound_webhooks::list_outbound_webhooks))
+        .route("/repositories/{id}/outbound-webhooks", post(routes::outbound_webhooks::create_outbound_webhook))
+        .route("/repositories/{id}/outbound-webhooks/{webhook_id}", put(routes::outbound_webhooks::update_outbound_webhook))
+        .route("/repositories/{id}/outbound-webhooks/{webhook_id}", delete(routes::outbound_webhooks::delete_outbound_webhook))
+        .route("/repositories/{id}/outbound-webhooks/{webhook_id}/deliveries", get(routes::outbound_webhooks::list_webhook_deliveries))
+        .route("/repositories/{id}/notifications/slack", get(routes::slack::get_slack_config))
+        .route("/repositories/{id}/notifications/slack", put(routes::slack::set_slack_config))
+        .route("/repositories/{id}/notifications/slack", delete(routes::slack::delete_slack_config))
+        .route("/repositories/{id}/notifications/email", get(routes::email::get_email_config))
+        .route("/repositories/{id}/notifications/email", put(routes::email::set_email_config))
+        .route("/repositories/{id}/notifications/email", delete(routes::email::delete_email_config))
+        // Server settings
+        .route("/settings/smtp", get(routes::email::get_smtp_settings))
+        .route("/settings/smtp", put(routes::email::set_smtp_settings))
+        .route("/settings/smtp", delete(routes::email::delete_smtp_settings))
         .layer(axum_mw::from_fn_with_state(admin_config, require_admin))
         .with_state(state)
 }
@@ -161,6 +197,8 @@ pub async fn setup_test_state() -> (AppState, TestConfig) {
         webhook_tx,
         build_tx,
         build_cancel_channels,
+        build_events: BuildEventBus::default(),
+        logs_dir: std::env::temp_dir().join("oore-test-logs"),
         encryption_key: None,
         admin_auth_config: Arc::new(admin_auth_config),
         demo_provider: None,
@@ -189,3 +227,12 @@ pub async fn create_test_app_with_state() -> (Router, TestConfig) {
     let app = create_test_app(state);
     (app, config)
 }
+
+/// Creates a test application with an encryption key configured.
+pub async fn create_test_app_with_encryption() -> (Router, TestConfig) {
+    let (mut state, config) = setup_test_state().await;
+    state.encryption_key =
+        Some(EncryptionKey::from_string(&"07".repeat(32)).expect("valid test key"));
+    let app = create_test_app(state);
+    (app, config)
+}
@@ -3,8 +3,9 @@
 //! Processes pending builds by cloning repos, resolving configs, and executing steps.
 
 use std::collections::HashMap;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
+use std::time::Duration;
 
 use dashmap::DashMap;
 use oore_core::{
@@ -13,25 +14,49 @@ use oore_core::{
         artifact::BuildArtifactRepo,
         credentials::{GitHubAppCredentialsRepo, GitLabOAuthCredentialsRepo},
         pipeline::{BuildLogRepo, BuildStepRepo},
-        repository::{BuildRepo, RepositoryRepo},
+        publishing::BuildPublicationRepo,
+        repository::{BuildRepo, RepositoryRepo, WebhookEventRepo},
         DbPool,
     },
     flutter::{detect_flutter_project, generate_flutter_setup_script, get_flutter_version},
-    models::{Build, BuildArtifact, BuildId, BuildLog, BuildStatus, BuildStep, LogStream, Repository, StepStatus, infer_content_type, compute_sha256},
+    models::{Build, BuildArtifact, BuildId, BuildLog, BuildStatus, BuildStep, BuildPublication, ExecutorKind, GitProvider, PublishStatus, LogStream, Repository, StepStatus, TriggerType, Workflow, infer_content_type, compute_sha256},
     oauth::{github::GitHubClient, gitlab::GitLabClient, EncryptionKey},
-    pipeline::{resolve_config, select_workflow, BuildExecutor, ShellExecutor},
+    pipeline::{
+        cache_key, cache_scope, checkout_config_files, diff_changed_files, fetch_diff_history, find_workflow, resolve_cache_paths, resolve_config, resolve_environment,
+        resolve_signing, select_workflow, BuildExecutor, CachePath, CacheRestore, ContainerConfig, ContainerExecutor, DependencyCache, GitMirrors,
+        OutputSender, SandboxConfig, SandboxExecutor, SecretMasker, ShellExecutor, StepOutput, FLUTTER_CACHE_PATHS,
+    },
+    publishing::{
+        check_credentials, publishers_for, PublishArtifact, PublishContext, PublishOutcome,
+        Publisher, PublishingEndpoints, SCRIPTS_KEY,
+    },
+    signing::{cleanup_android_signing, decrypt_keystore, prepare_android_signing},
+    webhook::{parse_github_webhook, parse_gitlab_webhook},
     OoreError,
 };
 
 use tokio::sync::{mpsc, watch, Semaphore};
 
+use crate::events::{BuildEvent, BuildEventBus};
+
 /// Step indices for system steps.
 /// Using a wider negative range for system pre-steps:
-/// Clone = -1000, Signing = -900, Flutter Setup = -100, User steps = 0+, Cleanup = i32::MAX - 1
+/// Clone = -1000, Signing = -900, Restore Cache = -200, Flutter Setup = -100,
+/// User steps = 0+, Save Cache = i32::MAX - 300,
+/// Publishing scripts = i32::MAX - 200.., Publishers = i32::MAX - 100..,
+/// Cleanup = i32::MAX - 1
 const CLONE_STEP_INDEX: i32 = -1000;
+const SIGNING_STEP_INDEX: i32 = -900;
+const RESTORE_CACHE_STEP_INDEX: i32 = -200;
 const FLUTTER_SETUP_STEP_INDEX: i32 = -100;
+const SAVE_CACHE_STEP_INDEX: i32 = i32::MAX - 300;
+const PUBLISHING_SCRIPTS_STEP_INDEX: i32 = i32::MAX - 200;
+const PUBLISHING_STEP_INDEX: i32 = i32::MAX - 100;
 const CLEANUP_STEP_INDEX: i32 = i32::MAX - 1;
 
+/// How often git mirrors are garbage collected and pruned.
+const MIRROR_MAINTENANCE_INTERVAL: Duration = Duration::from_secs(24 * 60 * 60);
+
 /// A build processing job.
 #[derive(Debug, Clone)]
 pub struct BuildJob {
@@ -64,10 +89,20 @@ pub struct BuildProcessorConfig {
     pub logs_dir: PathBuf,
     /// Base directory for build artifacts.
     pub artifacts_dir: PathBuf,
+    /// Base directory for dependency cache archives.
+    pub cache_dir: PathBuf,
+    /// Total size of cache archives before the least recently used are evicted.
+    pub cache_max_size_bytes: u64,
+    /// Base directory for bare git mirrors that workspaces are cloned from.
+    pub mirrors_dir: PathBuf,
+    /// Executor for repositories that don't choose one.
+    pub executor: ExecutorKind,
     /// Maximum concurrent builds.
     pub max_concurrent_builds: usize,
     /// Base URL for the Oore server (used in status check target_url).
     pub base_url: String,
+    /// Store API endpoints used for publishing.
+    pub publishing: PublishingEndpoints,
 }
 
 impl Default for BuildProcessorConfig {
@@ -76,8 +111,13 @@ impl Default for BuildProcessorConfig {
             workspaces_dir: PathBuf::from("/var/lib/oore/workspaces"),
             logs_dir: PathBuf::from("/var/lib/oore/logs"),
             artifacts_dir: PathBuf::from("/var/lib/oore/artifacts"),
+            cache_dir: PathBuf::from("/var/lib/oore/cache"),
+            cache_max_size_bytes: 10 * 1024 * 1024 * 1024,
+            mirrors_dir: PathBuf::from("/var/lib/oore/mirrors"),
+            executor: ExecutorKind::Shell,
             max_concurrent_builds: 2,
             base_url: "http://localhost:8080".to_string(),
+            publishing: PublishingEndpoints::default(),
         }
     }
 }
@@ -99,6 +139,27 @@ impl BuildProcessorConfig {
             config.artifacts_dir = PathBuf::from(val);
         }
 
+        if let Ok(val) = std::env::var("OORE_CACHE_DIR") {
+            config.cache_dir = PathBuf::from(val);
+        }
+
+        if let Ok(val) = std::env::var("OORE_CACHE_MAX_SIZE_BYTES")
+            && let Ok(v) = val.parse()
+        {
+            config.cache_max_size_bytes = v;
+        }
+
+        if let Ok(val) = std::env::var("OORE_MIRRORS_DIR") {
+            config.mirrors_dir = PathBuf::from(val);
+        }
+
+        if let Ok(val) = std::env::var("OORE_EXECUTOR") {
+            match val.parse() {
+                Ok(kind) => config.executor = kind,
+                Err(e) => tracing::warn!("Ignoring OORE_EXECUTOR: {}", e),
+            }
+        }
+
         if let Ok(val) = std::env::var("OORE_MAX_CONCURRENT_BUILDS") {
             if let Ok(v) = val.parse() {
                 config.max_concurrent_builds = v;
@@ -109,10 +170,30 @@ impl BuildProcessorConfig {
             config.base_url = val;
         }
 
+        config.publishing = PublishingEndpoints::from_env();
+
         config
     }
 }
 
+/// The executors builds can run with.
+#[derive(Clone)]
+struct Executors {
+    shell: Arc<dyn BuildExecutor>,
+    container: Arc<dyn BuildExecutor>,
+    sandbox: Arc<dyn BuildExecutor>,
+}
+
+impl Executors {
+    fn get(&self, kind: ExecutorKind) -> &Arc<dyn BuildExecutor> {
+        match kind {
+            ExecutorKind::Shell => &self.shell,
+            ExecutorKind::Container => &self.container,
+            ExecutorKind::Sandbox => &self.sandbox,
+        }
+    }
+}
+
 /// Shared state for cancellation tracking.
 pub type CancelChannels = Arc<DashMap<BuildId, watch::Sender<bool>>>;
 
@@ -124,6 +205,7 @@ pub fn start_build_processor(
     db: DbPool,
     config: BuildProcessorConfig,
     encryption_key: Option<EncryptionKey>,
+    events: BuildEventBus,
 ) -> (mpsc::Sender<BuildJob>, BuildWorkerHandle, CancelChannels) {
     let (tx, rx) = mpsc::channel::<BuildJob>(100);
     let (shutdown_tx, shutdown_rx) = watch::channel(false);
@@ -131,10 +213,30 @@ pub fn start_build_processor(
     let cancel_channels: CancelChannels = Arc::new(DashMap::new());
     let cancel_channels_clone = cancel_channels.clone();
 
-    let executor: Arc<dyn BuildExecutor> = Arc::new(ShellExecutor::new());
+    let mirrors = GitMirrors::new(&config.mirrors_dir);
+    // Sandboxed steps can't see other builds or the server's data
+    let mut sandbox_config = SandboxConfig::from_env();
+    sandbox_config.hidden_paths.extend([
+        config.workspaces_dir.clone(),
+        config.logs_dir.clone(),
+        config.artifacts_dir.clone(),
+        config.cache_dir.clone(),
+        config.mirrors_dir.clone(),
+    ]);
+    let executors = Executors {
+        shell: Arc::new(ShellExecutor::new().with_mirrors(mirrors.clone())),
+        container: Arc::new(ContainerExecutor::new(
+            ShellExecutor::new().with_mirrors(mirrors.clone()),
+            ContainerConfig::from_env(),
+        )),
+        sandbox: Arc::new(SandboxExecutor::new(
+            ShellExecutor::new().with_mirrors(mirrors.clone()),
+            sandbox_config,
+        )),
+    };
 
     let handle = tokio::spawn(async move {
-        run_build_processor(db, config, encryption_key, executor, rx, shutdown_rx, cancel_channels_clone).await;
+        run_build_processor(db, config, encryption_key, events, executors, mirrors, rx, shutdown_rx, cancel_channels_clone).await;
     });
 
     let worker_handle = BuildWorkerHandle {
@@ -145,6 +247,42 @@ pub fn start_build_processor(
     (tx, worker_handle, cancel_channels)
 }
 
+/// Cancels older pending and running builds of the branch or PR `build` was
+/// created for.
+///
+/// Pending builds are finished here; running ones get a cancel signal and
+/// finish through their own cleanup. Tag, manual and scheduled builds never
+/// supersede anything.
+pub async fn cancel_superseded_builds(
+    db: &DbPool,
+    events: &BuildEventBus,
+    cancel_channels: &CancelChannels,
+    build: &Build,
+) -> oore_core::Result<()> {
+    if !matches!(
+        build.trigger_type,
+        TriggerType::Push | TriggerType::PullRequest | TriggerType::MergeRequest
+    ) {
+        return Ok(());
+    }
+
+    for previous in BuildRepo::cancel_superseded(db, build).await? {
+        tracing::info!("Build {} superseded by build {}", previous.id, build.id);
+
+        match cancel_channels.get(&previous.id) {
+            Some(cancel_tx) => {
+                let _ = cancel_tx.send(true);
+            }
+            None => events.publish(BuildEvent::BuildFinished {
+                build_id: previous.id.clone(),
+                status: BuildStatus::Cancelled,
+            }),
+        }
+    }
+
+    Ok(())
+}
+
 /// Recovers pending builds on startup.
 ///
 /// - Re-enqueues builds with status = 'pending'
@@ -190,11 +328,14 @@ pub async fn recover_pending_builds(db: &DbPool, tx: &mpsc::Sender<BuildJob>) {
 }
 
 /// Main processor loop.
+#[allow(clippy::too_many_arguments)]
 async fn run_build_processor(
     db: DbPool,
     config: BuildProcessorConfig,
     encryption_key: Option<EncryptionKey>,
-    executor: Arc<dyn BuildExecutor>,
+    events: BuildEventBus,
+    executors: Executors,
+    mirrors: GitMirrors,
     mut rx: mpsc::Receiver<BuildJob>,
     mut shutdown_rx: watch::Receiver<bool>,
     cancel_channels: CancelChannels,
@@ -216,6 +357,12 @@ async fn run_build_processor(
     let semaphore = Arc::new(Semaphore::new(config.max_concurrent_builds));
     let config = Arc::new(config);
 
+    // Git mirror maintenance runs daily, starting a day after startup
+    let mut mirror_maintenance = tokio::time::interval_at(
+        tokio::time::Instant::now() + MIRROR_MAINTENANCE_INTERVAL,
+        MIRROR_MAINTENANCE_INTERVAL,
+    );
+
     loop {
         tokio::select! {
             _ = shutdown_rx.changed() => {
@@ -224,11 +371,29 @@ async fn run_build_processor(
                     break;
                 }
             }
+            _ = mirror_maintenance.tick() => {
+                let mirrors = mirrors.clone();
+                tokio::spawn(async move {
+                    match mirrors.maintain().await {
+                        Ok(stats) => tracing::info!(
+                            "Git mirror maintenance done: {} mirrors using {}",
+                            stats.mirrors,
+                            format_size(stats.total_bytes)
+                        ),
+                        Err(e) => tracing::error!("Git mirror maintenance failed: {}", e),
+                    }
+                });
+            }
             job = rx.recv() => {
                 match job {
                     Some(job) => {
+                        events.publish(BuildEvent::BuildQueued {
+                            build_id: job.build_id.clone(),
+                        });
+
                         let db = db.clone();
-                        let executor = executor.clone();
+                        let events = events.clone();
+                        let executors = executors.clone();
                         let config = config.clone();
                         let encryption_key = encryption_key.clone();
                         let cancel_channels = cancel_channels.clone();
@@ -252,9 +417,11 @@ async fn run_build_processor(
                             // Process the build
                             let result = process_build(
                                 &db,
-                                &executor,
+                                &executors,
                                 &config,
                                 encryption_key.as_ref(),
+                                &events,
+                                &cancel_channels,
                                 &job,
                                 cancel_rx,
                             )
@@ -263,6 +430,13 @@ async fn run_build_processor(
                             // Remove cancellation channel
                             cancel_channels.remove(&job.build_id);
 
+                            // Signing material must never outlive the build, even if
+                            // process_build bailed out before reaching its own cleanup.
+                            let workspace = config.workspaces_dir.join(job.build_id.to_string());
+                            if let Err(e) = cleanup_android_signing(&workspace).await {
+                                tracing::warn!("Failed to remove signing files for build {}: {}", job.build_id, e);
+                            }
+
                             if let Err(e) = result {
                                 tracing::error!("Build {} failed: {}", job.build_id, e);
                             }
@@ -281,11 +455,14 @@ async fn run_build_processor(
 }
 
 /// Processes a single build.
+#[allow(clippy::too_many_arguments)]
 async fn process_build(
     db: &DbPool,
-    executor: &Arc<dyn BuildExecutor>,
+    executors: &Executors,
     config: &BuildProcessorConfig,
     encryption_key: Option<&EncryptionKey>,
+    events: &BuildEventBus,
+    cancel_channels: &CancelChannels,
     job: &BuildJob,
     mut cancel_rx: watch::Receiver<bool>,
 ) -> oore_core::Result<()> {
@@ -294,15 +471,15 @@ async fn process_build(
         .await?
         .ok_or_else(|| OoreError::BuildNotFound(job.build_id.to_string()))?;
 
-    // Verify build is still pending
-    if build.status != BuildStatus::Pending {
-        tracing::debug!(
-            "Build {} is no longer pending (status: {}), skipping",
-            build.id,
-            build.status
-        );
+    // Claim the build. The status check in the update keeps a build that was
+    // cancelled or superseded after being queued from running anyway.
+    if !BuildRepo::start(db, &build.id).await? {
+        tracing::debug!("Build {} is no longer pending, skipping", build.id);
         return Ok(());
     }
+    events.publish(BuildEvent::BuildStarted {
+        build_id: build.id.clone(),
+    });
 
     tracing::info!(
         "Processing build {} for commit {} on branch {}",
@@ -311,9 +488,6 @@ async fn process_build(
         build.branch
     );
 
-    // Update status to running
-    BuildRepo::update_status(db, &build.id, BuildStatus::Running).await?;
-
     // Set up paths
     let workspace = config.workspaces_dir.join(build.id.to_string());
     let build_logs_dir = config.logs_dir.join(build.id.to_string());
@@ -321,24 +495,11 @@ async fn process_build(
     // Create log directory
     tokio::fs::create_dir_all(&build_logs_dir).await?;
 
-    // Create Clone step in database (system step, step_index = -2)
-    let clone_step = BuildStep::new(
-        build.id.clone(),
-        CLONE_STEP_INDEX,
-        "Clone Repository".to_string(),
-        None, // System step - no user script
-        Some(300), // 5 minute timeout for clone
-        false,
-    );
-    BuildStepRepo::create(db, &clone_step).await?;
-
-    // Mark Clone step as running
-    BuildStepRepo::update_status(db, &clone_step.id, StepStatus::Running, None).await?;
-
     // Load repository
     let repository = RepositoryRepo::get_by_id(db, &build.repository_id)
         .await?
         .ok_or_else(|| OoreError::RepositoryNotFound(build.repository_id.to_string()))?;
+    let executor = executors.get(repository.executor.unwrap_or(config.executor));
 
     // Get auth token if available (for private repos)
     let auth_token: Option<String> = if let Some(key) = encryption_key {
@@ -352,7 +513,7 @@ async fn process_build(
                     msg
                 );
                 BuildRepo::set_error(db, &build.id, &format!("Configuration error: {}", msg)).await?;
-                BuildRepo::update_status(db, &build.id, BuildStatus::Failure).await?;
+                set_build_status(db, events, &build.id, BuildStatus::Failure).await?;
                 return Err(OoreError::Configuration(msg));
             }
             Err(e) => {
@@ -370,19 +531,108 @@ async fn process_build(
         None
     };
 
-    // Clone the repository
-    let clone_result = executor
-        .clone_repo(
-            &repository.clone_url,
-            &build.commit_sha,
-            &workspace,
-            auth_token.as_deref(),
-        )
-        .await;
-
     // Post initial commit status (pending)
     post_build_status(config, db, encryption_key, &repository, &build, "pending", "Build started").await;
 
+    // The pipeline is read from a checkout of just the config files, so the
+    // workflow's clone options apply to the clone itself
+    if let Err(e) = checkout_config_files(&repository.clone_url, &build.commit_sha, &workspace, auth_token.as_deref()).await {
+        let message = format!("Failed to check out pipeline config: {}", e);
+        cleanup_and_fail(db, events, executor, &workspace, &build_logs_dir, &build, &message).await?;
+        post_build_status(config, db, encryption_key, &repository, &build, "failure", "Checkout failed").await;
+        return Err(e);
+    }
+
+    // Resolve pipeline configuration
+    let resolved = match resolve_config(db, &build.repository_id, Some(&workspace)).await {
+        Ok(r) => r,
+        Err(e) => {
+            cleanup_and_fail(db, events, executor, &workspace, &build_logs_dir, &build, &e.to_string()).await?;
+            return Err(e);
+        }
+    };
+
+    // Select workflow; scheduled builds may name theirs up front
+    let selected = match &build.workflow_name {
+        Some(name) => find_workflow(&resolved.pipeline, name),
+        None => {
+            // Only look up the changed files if a workflow filters on them
+            let has_changeset = resolved
+                .pipeline
+                .workflows
+                .values()
+                .any(|wf| wf.when.as_ref().is_some_and(|when| when.changeset.is_some()));
+            let changed_files = if has_changeset {
+                triggering_changes(db, &build, &workspace).await
+            } else {
+                None
+            };
+
+            select_workflow(
+                &resolved.pipeline,
+                build.trigger_type,
+                &build.branch,
+                changed_files.as_deref(),
+            )
+        }
+    };
+    let (workflow_name, workflow) = match selected {
+        Ok(w) => w,
+        Err(OoreError::NoRelevantChanges) => {
+            let message = OoreError::NoRelevantChanges.to_string();
+            tracing::info!("Build {} skipped: no workflow matches the changed files", build.id);
+            cleanup_and_finish(db, events, executor, &workspace, &build_logs_dir, &build, BuildStatus::Cancelled, &message).await?;
+            post_build_status(config, db, encryption_key, &repository, &build, "success", &message).await;
+            return Ok(());
+        }
+        Err(e) => {
+            cleanup_and_fail(db, events, executor, &workspace, &build_logs_dir, &build, &e.to_string()).await?;
+            return Err(e);
+        }
+    };
+
+    // Update build with workflow info
+    BuildRepo::update_workflow_info(db, &build.id, &workflow_name, resolved.source).await?;
+
+    // The repository setting was applied when the webhook created this build;
+    // a workflow can still opt in on its own.
+    if !repository.cancel_previous_builds
+        && workflow.triggering.as_ref().is_some_and(|t| t.cancel_previous_builds)
+    {
+        cancel_superseded_builds(db, events, cancel_channels, &build).await?;
+    }
+
+    // Create Clone step in database (system step, step_index = -2)
+    let clone_step = BuildStep::new(
+        build.id.clone(),
+        CLONE_STEP_INDEX,
+        "Clone Repository".to_string(),
+        None, // System step - no user script
+        Some(300), // 5 minute timeout for clone
+        false,
+    );
+    BuildStepRepo::create(db, &clone_step).await?;
+
+    // Mark Clone step as running
+    set_step_status(db, events, &clone_step, StepStatus::Running, None).await?;
+
+    // Clone the repository into a fresh workspace
+    let clone_config = workflow.clone.clone().unwrap_or_default();
+    let clone_result = match tokio::fs::remove_dir_all(&workspace).await {
+        Ok(()) => {
+            executor
+                .clone_repo(
+                    &repository.clone_url,
+                    &build.commit_sha,
+                    &workspace,
+                    auth_token.as_deref(),
+                    &clone_config,
+                )
+                .await
+        }
+        Err(e) => Err(e.into()),
+    };
+
     // Create log files for clone step
     let clone_stdout_path = build_logs_dir.join(format!("step-{}-stdout.log", CLONE_STEP_INDEX));
     let clone_stderr_path = build_logs_dir.join(format!("step-{}-stderr.log", CLONE_STEP_INDEX));
@@ -412,11 +662,11 @@ async fn process_build(
         BuildLogRepo::update_line_count(db, &stderr_log.id, 1).await?;
 
         // Mark Clone step as failed
-        BuildStepRepo::update_status(db, &clone_step.id, StepStatus::Failure, Some(-1)).await?;
+        set_step_status(db, events, &clone_step, StepStatus::Failure, Some(-1)).await?;
 
         // Clone failed - mark build as failed
         BuildRepo::set_error(db, &build.id, &e.to_string()).await?;
-        BuildRepo::update_status(db, &build.id, BuildStatus::Failure).await?;
+        set_build_status(db, events, &build.id, BuildStatus::Failure).await?;
         return Err(clone_result.unwrap_err());
     }
 
@@ -449,39 +699,51 @@ async fn process_build(
     BuildLogRepo::update_line_count(db, &stderr_log.id, 0).await?;
 
     // Mark Clone step as success
-    BuildStepRepo::update_status(db, &clone_step.id, StepStatus::Success, Some(0)).await?;
+    set_step_status(db, events, &clone_step, StepStatus::Success, Some(0)).await?;
 
     // Check for cancellation
     if *cancel_rx.borrow() {
-        cleanup_and_fail(db, executor, &workspace, &build_logs_dir, &build, "Build cancelled").await?;
+        cleanup_cancelled(db, events, executor, &workspace, &build_logs_dir, &build).await?;
         return Err(OoreError::BuildCancelled);
     }
 
-    // Resolve pipeline configuration
-    let resolved = match resolve_config(db, &build.repository_id, Some(&workspace)).await {
-        Ok(r) => r,
+    if let Err(e) = executor.prepare_build(&workspace, workflow).await {
+        cleanup_and_fail(db, events, executor, &workspace, &build_logs_dir, &build, &e.to_string()).await?;
+        post_build_status(config, db, encryption_key, &repository, &build, "failure", "Build environment failed to start").await;
+        return Err(e);
+    }
+
+    // Resolve signing credentials selected by the workflow
+    let signing = match resolve_signing(db, &build.repository_id, &workflow.environment).await {
+        Ok(s) => s,
         Err(e) => {
-            cleanup_and_fail(db, executor, &workspace, &build_logs_dir, &build, &e.to_string()).await?;
+            cleanup_and_fail(db, events, executor, &workspace, &build_logs_dir, &build, &e.to_string()).await?;
+            post_build_status(config, db, encryption_key, &repository, &build, "failure", "Signing configuration invalid").await;
             return Err(e);
         }
     };
-
-    // Select workflow
-    let (workflow_name, workflow) = match select_workflow(
-        &resolved.pipeline,
-        build.trigger_type,
-        &build.branch,
-    ) {
-        Ok(w) => w,
+    // A selected keystore must not silently give an unsigned build
+    if let Some(keystore) = &signing.android_keystore
+        && encryption_key.is_none()
+    {
+        let e = OoreError::Configuration(format!(
+            "Android keystore '{}' can't be decrypted: ENCRYPTION_KEY is not set",
+            keystore.name
+        ));
+        cleanup_and_fail(db, events, executor, &workspace, &build_logs_dir, &build, &e.to_string()).await?;
+        post_build_status(config, db, encryption_key, &repository, &build, "failure", "Signing configuration invalid").await;
+        return Err(e);
+    }
+    // Resolve repository environment variables and groups
+    let environment = match resolve_environment(db, encryption_key, &build.repository_id, &workflow.environment).await {
+        Ok(env) => env,
         Err(e) => {
-            cleanup_and_fail(db, executor, &workspace, &build_logs_dir, &build, &e.to_string()).await?;
+            cleanup_and_fail(db, events, executor, &workspace, &build_logs_dir, &build, &e.to_string()).await?;
+            post_build_status(config, db, encryption_key, &repository, &build, "failure", "Environment configuration invalid").await;
             return Err(e);
         }
     };
 
-    // Update build with workflow info
-    BuildRepo::update_workflow_info(db, &build.id, &workflow_name, resolved.source).await?;
-
     tracing::info!(
         "Build {} using workflow '{}' from {:?}",
         build.id,
@@ -502,17 +764,138 @@ async fn process_build(
         BuildStepRepo::create(db, &build_step).await?;
     }
 
-    // Build environment variables
-    let mut env: HashMap<String, String> = workflow.environment.vars.clone();
+    // Build environment variables. Repository and workflow vars come first;
+    // signing exports and system vars below take precedence over them.
+    let mut env: HashMap<String, String> = environment.vars;
     env.insert("CI".to_string(), "true".to_string());
     env.insert("OORE".to_string(), "true".to_string());
     env.insert("OORE_BUILD_ID".to_string(), build.id.to_string());
     env.insert("OORE_COMMIT_SHA".to_string(), build.commit_sha.clone());
-    env.insert("OORE_BRANCH".to_string(), build.branch.clone());
+    // Tag builds store the tag name in `branch`
+    if build.trigger_type == TriggerType::Tag {
+        env.insert("OORE_TAG".to_string(), build.branch.clone());
+    } else {
+        env.insert("OORE_BRANCH".to_string(), build.branch.clone());
+    }
     env.insert("OORE_REPOSITORY_ID".to_string(), build.repository_id.to_string());
+    if let Some(home) = executor.home_dir(&workspace) {
+        env.insert("HOME".to_string(), home.to_string_lossy().into_owned());
+    }
+
+    // Secret values that must never appear in step logs
+    let mut secret_values: Vec<String> = auth_token.iter().cloned().collect();
+    secret_values.extend(environment.secrets);
+
+    // Android signing step (if the workflow resolved a keystore)
+    if let (Some(key), Some(keystore)) = (encryption_key, signing.android_keystore) {
+        tracing::info!("Build {} using Android keystore '{}'", build.id, keystore.name);
+
+        let signing_step = BuildStep::new(
+            build.id.clone(),
+            SIGNING_STEP_INDEX,
+            "Signing".to_string(),
+            None, // System step
+            Some(60),
+            false,
+        );
+        BuildStepRepo::create(db, &signing_step).await?;
+        set_step_status(db, events, &signing_step, StepStatus::Running, None).await?;
+
+        let signing_stdout_path = build_logs_dir.join(format!("step-{}-stdout.log", SIGNING_STEP_INDEX));
+        let signing_stderr_path = build_logs_dir.join(format!("step-{}-stderr.log", SIGNING_STEP_INDEX));
+
+        let prepared = match decrypt_keystore(key, &keystore) {
+            Ok(decrypted) => prepare_android_signing(&workspace, &keystore, &decrypted)
+                .await
+                .map(|files| (files, decrypted)),
+            Err(e) => Err(e),
+        };
+
+        // Log lines never include passwords or keystore contents
+        let (step_status, stdout_msg, stderr_msg) = match &prepared {
+            Ok((files, _)) => {
+                let mut msg = format!(
+                    "Using Android keystore '{}' (alias: {}, type: {})\n",
+                    keystore.name, keystore.key_alias, keystore.keystore_type
+                );
+                msg.push_str(&format!("Wrote keystore to {}\n", files.keystore_path.display()));
+                match &files.key_properties_path {
+                    Some(path) => msg.push_str(&format!("Wrote {}\n", path.display())),
+                    None => msg.push_str("No android/ directory found, skipped key.properties\n"),
+                }
+                msg.push_str("Exported KEYSTORE_PATH, KEYSTORE_PASSWORD, KEY_ALIAS, KEY_PASSWORD\n");
+                (StepStatus::Success, msg, String::new())
+            }
+            Err(e) => (
+                StepStatus::Failure,
+                String::new(),
+                format!("Failed to prepare Android keystore '{}': {}\n", keystore.name, e),
+            ),
+        };
+
+        let _ = tokio::fs::write(&signing_stdout_path, &stdout_msg).await;
+        let _ = tokio::fs::write(&signing_stderr_path, &stderr_msg).await;
+
+        let stdout_log = BuildLog::new(
+            build.id.clone(),
+            SIGNING_STEP_INDEX,
+            LogStream::Stdout,
+            format!("{}/step-{}-stdout.log", build.id, SIGNING_STEP_INDEX),
+        );
+        BuildLogRepo::create(db, &stdout_log).await?;
+        BuildLogRepo::update_line_count(db, &stdout_log.id, stdout_msg.lines().count() as i32).await?;
+
+        let stderr_log = BuildLog::new(
+            build.id.clone(),
+            SIGNING_STEP_INDEX,
+            LogStream::Stderr,
+            format!("{}/step-{}-stderr.log", build.id, SIGNING_STEP_INDEX),
+        );
+        BuildLogRepo::create(db, &stderr_log).await?;
+        BuildLogRepo::update_line_count(db, &stderr_log.id, stderr_msg.lines().count() as i32).await?;
+
+        match prepared {
+            Ok((files, decrypted)) => {
+                set_step_status(db, events, &signing_step, step_status, Some(0)).await?;
+
+                secret_values.push(decrypted.keystore_password.clone());
+                secret_values.push(decrypted.key_password.clone());
+
+                env.insert("KEYSTORE_PATH".to_string(), files.keystore_path.display().to_string());
+                env.insert("KEYSTORE_PASSWORD".to_string(), decrypted.keystore_password);
+                env.insert("KEY_ALIAS".to_string(), keystore.key_alias.clone());
+                env.insert("KEY_PASSWORD".to_string(), decrypted.key_password);
+            }
+            Err(e) => {
+                set_step_status(db, events, &signing_step, step_status, Some(1)).await?;
+                cleanup_and_fail(
+                    db,
+                    events,
+                    executor,
+                    &workspace,
+                    &build_logs_dir,
+                    &build,
+                    &format!("Android signing setup failed: {}", e),
+                )
+                .await?;
+                post_build_status(config, db, encryption_key, &repository, &build, "failure", "Signing setup failed").await;
+                return Err(e);
+            }
+        }
+    }
+
+    let secrets = SecretMasker::new(&secret_values);
 
-    // Flutter setup step (if this is a Flutter project)
     let is_flutter_project = detect_flutter_project(&workspace).await;
+
+    // Restore dependency cache before Flutter setup fetches packages
+    let home = executor.home_dir(&workspace).or_else(|| std::env::var_os("HOME").map(PathBuf::from));
+    let mut cache_plan = plan_cache(config, &build, &workflow_name, workflow, &workspace, home.as_deref(), &env, is_flutter_project).await;
+    if let Some(plan) = &mut cache_plan {
+        restore_cache(db, events, &build, &build_logs_dir, plan).await?;
+    }
+
+    // Flutter setup step (if this is a Flutter project)
     if is_flutter_project {
         tracing::info!("Build {} detected Flutter project, running setup", build.id);
 
@@ -526,7 +909,7 @@ async fn process_build(
             false,
         );
         BuildStepRepo::create(db, &flutter_step).await?;
-        BuildStepRepo::update_status(db, &flutter_step.id, StepStatus::Running, None).await?;
+        set_step_status(db, events, &flutter_step, StepStatus::Running, None).await?;
 
         // Get Flutter version from project
         let flutter_version = get_flutter_version(&workspace).await;
@@ -541,6 +924,8 @@ async fn process_build(
                 300,
                 &build_logs_dir,
                 FLUTTER_SETUP_STEP_INDEX,
+                &secrets,
+                Some(&forward_step_output(events, &build.id, FLUTTER_SETUP_STEP_INDEX)),
                 &mut cancel_rx,
             )
             .await;
@@ -548,21 +933,23 @@ async fn process_build(
         match result {
             Ok(step_result) => {
                 // Create log records
-                let stdout_log = BuildLog::new(
+                let mut stdout_log = BuildLog::new(
                     build.id.clone(),
                     FLUTTER_SETUP_STEP_INDEX,
                     LogStream::Stdout,
                     format!("{}/step-{}-stdout.log", build.id, FLUTTER_SETUP_STEP_INDEX),
                 );
+                stdout_log.masked_count = step_result.stdout_masked;
                 BuildLogRepo::create(db, &stdout_log).await?;
                 BuildLogRepo::update_line_count(db, &stdout_log.id, step_result.stdout_lines).await?;
 
-                let stderr_log = BuildLog::new(
+                let mut stderr_log = BuildLog::new(
                     build.id.clone(),
                     FLUTTER_SETUP_STEP_INDEX,
                     LogStream::Stderr,
                     format!("{}/step-{}-stderr.log", build.id, FLUTTER_SETUP_STEP_INDEX),
                 );
+                stderr_log.masked_count = step_result.stderr_masked;
                 BuildLogRepo::create(db, &stderr_log).await?;
                 BuildLogRepo::update_line_count(db, &stderr_log.id, step_result.stderr_lines).await?;
 
@@ -572,9 +959,10 @@ async fn process_build(
                     StepStatus::Failure
                 };
 
-                BuildStepRepo::update_status(
+                set_step_status(
                     db,
-                    &flutter_step.id,
+                    events,
+                    &flutter_step,
                     step_status.clone(),
                     Some(step_result.exit_code),
                 )
@@ -583,6 +971,7 @@ async fn process_build(
                 if step_status == StepStatus::Failure {
                     cleanup_and_fail(
                         db,
+                        events,
                         executor,
                         &workspace,
                         &build_logs_dir,
@@ -595,9 +984,9 @@ async fn process_build(
                 }
             }
             Err(e) => {
-                BuildStepRepo::update_status(db, &flutter_step.id, StepStatus::Failure, Some(-1))
+                set_step_status(db, events, &flutter_step, StepStatus::Failure, Some(-1))
                     .await?;
-                cleanup_and_fail(db, executor, &workspace, &build_logs_dir, &build, &e.to_string()).await?;
+                cleanup_and_fail(db, events, executor, &workspace, &build_logs_dir, &build, &e.to_string()).await?;
                 post_build_status(config, db, encryption_key, &repository, &build, "failure", "Flutter setup failed").await;
                 return Err(e);
             }
@@ -612,7 +1001,7 @@ async fn process_build(
         if *cancel_rx.borrow() {
             // Cancel remaining steps
             BuildStepRepo::cancel_pending_for_build(db, &build.id).await?;
-            cleanup_and_fail(db, executor, &workspace, &build_logs_dir, &build, "Build cancelled").await?;
+            cleanup_cancelled(db, events, executor, &workspace, &build_logs_dir, &build).await?;
             return Err(OoreError::BuildCancelled);
         }
 
@@ -627,7 +1016,7 @@ async fn process_build(
             .ok_or_else(|| OoreError::BuildStepNotFound(format!("step {}", i)))?;
 
         // Mark step as running
-        BuildStepRepo::update_status(db, &step_record.id, StepStatus::Running, None).await?;
+        set_step_status(db, events, step_record, StepStatus::Running, None).await?;
 
         // Execute the step
         let result = executor
@@ -638,6 +1027,8 @@ async fn process_build(
                 step.timeout as u64,
                 &build_logs_dir,
                 i as i32,
+                &secrets,
+                Some(&forward_step_output(events, &build.id, i as i32)),
                 &mut cancel_rx,
             )
             .await;
@@ -645,21 +1036,23 @@ async fn process_build(
         match result {
             Ok(step_result) => {
                 // Create log records
-                let stdout_log = BuildLog::new(
+                let mut stdout_log = BuildLog::new(
                     build.id.clone(),
                     i as i32,
                     LogStream::Stdout,
                     format!("{}/step-{}-stdout.log", build.id, i),
                 );
+                stdout_log.masked_count = step_result.stdout_masked;
                 BuildLogRepo::create(db, &stdout_log).await?;
                 BuildLogRepo::update_line_count(db, &stdout_log.id, step_result.stdout_lines).await?;
 
-                let stderr_log = BuildLog::new(
+                let mut stderr_log = BuildLog::new(
                     build.id.clone(),
                     i as i32,
                     LogStream::Stderr,
                     format!("{}/step-{}-stderr.log", build.id, i),
                 );
+                stderr_log.masked_count = step_result.stderr_masked;
                 BuildLogRepo::create(db, &stderr_log).await?;
                 BuildLogRepo::update_line_count(db, &stderr_log.id, step_result.stderr_lines).await?;
 
@@ -685,9 +1078,10 @@ async fn process_build(
                     StepStatus::Failure
                 };
 
-                BuildStepRepo::update_status(
+                set_step_status(
                     db,
-                    &step_record.id,
+                    events,
+                    step_record,
                     step_status,
                     Some(step_result.exit_code),
                 )
@@ -707,45 +1101,45 @@ async fn process_build(
                     for remaining in steps.iter().filter(|s| {
                         s.step_index > current_step_index && s.step_index < CLEANUP_STEP_INDEX
                     }) {
-                        BuildStepRepo::update_status(
-                            db,
-                            &remaining.id,
-                            StepStatus::Skipped,
-                            None,
-                        )
-                        .await?;
+                        set_step_status(db, events, remaining, StepStatus::Skipped, None).await?;
                     }
                     break;
                 }
             }
             Err(OoreError::BuildCancelled) => {
-                BuildStepRepo::update_status(db, &step_record.id, StepStatus::Cancelled, None)
+                set_step_status(db, events, step_record, StepStatus::Cancelled, None)
                     .await?;
                 BuildStepRepo::cancel_pending_for_build(db, &build.id).await?;
-                cleanup_and_fail(db, executor, &workspace, &build_logs_dir, &build, "Build cancelled").await?;
+                cleanup_cancelled(db, events, executor, &workspace, &build_logs_dir, &build).await?;
                 return Err(OoreError::BuildCancelled);
             }
             Err(OoreError::BuildTimeout(msg)) => {
-                BuildStepRepo::update_status(db, &step_record.id, StepStatus::Failure, Some(-1))
+                set_step_status(db, events, step_record, StepStatus::Failure, Some(-1))
                     .await?;
-                cleanup_and_fail(db, executor, &workspace, &build_logs_dir, &build, &msg).await?;
+                cleanup_and_fail(db, events, executor, &workspace, &build_logs_dir, &build, &msg).await?;
                 return Err(OoreError::BuildTimeout(msg));
             }
             Err(e) => {
-                BuildStepRepo::update_status(db, &step_record.id, StepStatus::Failure, Some(-1))
+                set_step_status(db, events, step_record, StepStatus::Failure, Some(-1))
                     .await?;
-                cleanup_and_fail(db, executor, &workspace, &build_logs_dir, &build, &e.to_string()).await?;
+                cleanup_and_fail(db, events, executor, &workspace, &build_logs_dir, &build, &e.to_string()).await?;
                 return Err(e);
             }
         }
     }
 
+    // Save dependency cache for the next build
+    if build_success && let Some(plan) = &cache_plan {
+        save_cache(db, events, &build, &build_logs_dir, plan).await?;
+    }
+
     // Collect artifacts (before cleanup removes the workspace)
     let artifact_patterns = workflow.artifacts.clone();
     let collected_artifacts = if build_success && !artifact_patterns.is_empty() {
         tracing::info!("Build {} collecting artifacts with patterns: {:?}", build.id, artifact_patterns);
         collect_artifacts(
             db,
+            events,
             config,
             &build,
             &workspace,
@@ -759,6 +1153,163 @@ async fn process_build(
         tracing::info!("Build {} collected {} artifacts", build.id, collected_artifacts.len());
     }
 
+    // Publish (only after a successful build). Publishing failures are
+    // recorded on the build's publications and leave the build status alone.
+    let mut publishing_failed = false;
+    if build_success {
+        let artifacts: Vec<PublishArtifact> = collected_artifacts
+            .iter()
+            .map(|a| PublishArtifact {
+                name: a.name.clone(),
+                path: config.artifacts_dir.join(&a.storage_path),
+            })
+            .collect();
+
+        // Publishing scripts run in the workspace like user steps
+        let script_steps: Vec<BuildStep> = workflow
+            .publishing
+            .scripts
+            .iter()
+            .enumerate()
+            .map(|(i, step)| {
+                BuildStep::new(
+                    build.id.clone(),
+                    PUBLISHING_SCRIPTS_STEP_INDEX + i as i32,
+                    step.name.clone().unwrap_or_else(|| format!("Publishing script {}", i + 1)),
+                    Some(step.script.clone()),
+                    Some(step.timeout as i32),
+                    step.ignore_failure,
+                )
+            })
+            .collect();
+        for step in &script_steps {
+            BuildStepRepo::create(db, step).await?;
+        }
+
+        let mut script_env = env.clone();
+        script_env.insert(
+            "OORE_ARTIFACT_PATHS".to_string(),
+            artifacts.iter().map(|a| a.path.display().to_string()).collect::<Vec<_>>().join("\n"),
+        );
+
+        let mut scripts_failed = false;
+        for (step, step_record) in workflow.publishing.scripts.iter().zip(&script_steps) {
+            if scripts_failed {
+                set_step_status(db, events, step_record, StepStatus::Skipped, None).await?;
+                continue;
+            }
+            if *cancel_rx.borrow() {
+                BuildStepRepo::cancel_pending_for_build(db, &build.id).await?;
+                cleanup_cancelled(db, events, executor, &workspace, &build_logs_dir, &build).await?;
+                return Err(OoreError::BuildCancelled);
+            }
+
+            let step_index = step_record.step_index;
+            set_step_status(db, events, step_record, StepStatus::Running, None).await?;
+            let publication = BuildPublication::new(build.id.clone(), SCRIPTS_KEY, step_index);
+            BuildPublicationRepo::create(db, &publication).await?;
+
+            let result = executor
+                .execute_step(
+                    &workspace,
+                    &step.script,
+                    &script_env,
+                    step.timeout as u64,
+                    &build_logs_dir,
+                    step_index,
+                    &secrets,
+                    Some(&forward_step_output(events, &build.id, step_index)),
+                    &mut cancel_rx,
+                )
+                .await;
+
+            let step_result = match result {
+                Ok(step_result) => step_result,
+                Err(OoreError::BuildCancelled) => {
+                    set_step_status(db, events, step_record, StepStatus::Cancelled, None).await?;
+                    BuildPublicationRepo::finish(db, &publication.id, PublishStatus::Failure, None, Some("Build cancelled")).await?;
+                    BuildStepRepo::cancel_pending_for_build(db, &build.id).await?;
+                    cleanup_cancelled(db, events, executor, &workspace, &build_logs_dir, &build).await?;
+                    return Err(OoreError::BuildCancelled);
+                }
+                Err(e) => {
+                    tracing::error!("Build {} publishing script '{}' failed: {}", build.id, step_record.name, e);
+                    set_step_status(db, events, step_record, StepStatus::Failure, Some(-1)).await?;
+                    BuildPublicationRepo::finish(db, &publication.id, PublishStatus::Failure, None, Some(&e.to_string())).await?;
+                    scripts_failed = !step.ignore_failure;
+                    publishing_failed |= scripts_failed;
+                    continue;
+                }
+            };
+
+            let mut stdout_log = BuildLog::new(
+                build.id.clone(),
+                step_index,
+                LogStream::Stdout,
+                format!("{}/step-{}-stdout.log", build.id, step_index),
+            );
+            stdout_log.masked_count = step_result.stdout_masked;
+            BuildLogRepo::create(db, &stdout_log).await?;
+            BuildLogRepo::update_line_count(db, &stdout_log.id, step_result.stdout_lines).await?;
+
+            let mut stderr_log = BuildLog::new(
+                build.id.clone(),
+                step_index,
+                LogStream::Stderr,
+                format!("{}/step-{}-stderr.log", build.id, step_index),
+            );
+            stderr_log.masked_count = step_result.stderr_masked;
+            BuildLogRepo::create(db, &stderr_log).await?;
+            BuildLogRepo::update_line_count(db, &stderr_log.id, step_result.stderr_lines).await?;
+
+            if step_result.exit_code == 0 {
+                set_step_status(db, events, step_record, StepStatus::Success, Some(0)).await?;
+                BuildPublicationRepo::finish(db, &publication.id, PublishStatus::Success, Some(&format!("'{}' succeeded", step_record.name)), None).await?;
+            } else {
+                let error = format!("'{}' failed with exit code {}", step_record.name, step_result.exit_code);
+                tracing::warn!("Build {} publishing script {}", build.id, error);
+                set_step_status(db, events, step_record, StepStatus::Failure, Some(step_result.exit_code)).await?;
+                BuildPublicationRepo::finish(db, &publication.id, PublishStatus::Failure, None, Some(&error)).await?;
+                scripts_failed = !step.ignore_failure;
+                publishing_failed |= scripts_failed;
+            }
+        }
+
+        let ctx = PublishContext {
+            db,
+            encryption_key,
+            repository_id: &build.repository_id,
+            endpoints: &config.publishing,
+            cancel_rx: cancel_rx.clone(),
+        };
+        for (i, publisher) in publishers_for(&workflow.publishing).iter().enumerate() {
+            let published = match run_publisher(
+                db,
+                events,
+                &build,
+                &build_logs_dir,
+                PUBLISHING_STEP_INDEX + i as i32,
+                publisher.as_ref(),
+                &ctx,
+                &artifacts,
+                &mut cancel_rx,
+            )
+            .await
+            {
+                Ok(published) => published,
+                Err(OoreError::BuildCancelled) => {
+                    BuildStepRepo::cancel_pending_for_build(db, &build.id).await?;
+                    cleanup_cancelled(db, events, executor, &workspace, &build_logs_dir, &build).await?;
+                    return Err(OoreError::BuildCancelled);
+                }
+                Err(e) => return Err(e),
+            };
+            if !published {
+                publishing_failed = true;
+            }
+        }
+    }
+
     // Create Cleanup step in database (system step)
     let cleanup_step = BuildStep::new(
         build.id.clone(),
@@ -769,9 +1320,12 @@ async fn process_build(
         true, // Ignore failure - cleanup shouldn't fail the build
     );
     BuildStepRepo::create(db, &cleanup_step).await?;
-    BuildStepRepo::update_status(db, &cleanup_step.id, StepStatus::Running, None).await?;
+    set_step_status(db, events, &cleanup_step, StepStatus::Running, None).await?;
 
     // Cleanup workspace (keep logs)
+    if let Err(e) = cleanup_android_signing(&workspace).await {
+        tracing::warn!("Failed to remove signing files for build {}: {}", build.id, e);
+    }
     let cleanup_result = executor.cleanup(&workspace).await;
 
     // Create log files for cleanup step
@@ -807,7 +1361,7 @@ async fn process_build(
 
     // Mark cleanup step status
     let exit_code = if cleanup_status == StepStatus::Success { 0 } else { 1 };
-    BuildStepRepo::update_status(db, &cleanup_step.id, cleanup_status, Some(exit_code)).await?;
+    set_step_status(db, events, &cleanup_step, cleanup_status, Some(exit_code)).await?;
 
     // Update final build status (cleanup failure doesn't affect build status)
     let final_status = if build_success {
@@ -815,10 +1369,12 @@ async fn process_build(
     } else {
         BuildStatus::Failure
     };
-    BuildRepo::update_status(db, &build.id, final_status).await?;
+    set_build_status(db, events, &build.id, final_status).await?;
 
     // Post final commit status
-    let (status_state, status_desc) = if build_success {
+    let (status_state, status_desc) = if build_success && publishing_failed {
+        ("success", "Build succeeded, publishing failed")
+    } else if build_success {
         ("success", "Build succeeded")
     } else {
         ("failure", "Build failed")
@@ -834,17 +1390,487 @@ async fn process_build(
     Ok(())
 }
 
+/// Dependency cache paths and key resolved for a build.
+struct CachePlan {
+    cache: DependencyCache,
+    key: String,
+    paths: Vec<CachePath>,
+    lockfiles: Vec<&'static str>,
+    /// Cache paths that couldn't be resolved.
+    errors: Vec<String>,
+    /// Whether an archive for `key` was restored.
+    restored: bool,
+}
+
+/// Resolves the cache paths for a build: Flutter defaults for Flutter
+/// projects plus the workflow's `cache.cache_paths`.
+///
+/// Returns `None` if nothing is cached.
+#[allow(clippy::too_many_arguments)]
+async fn plan_cache(
+    config: &BuildProcessorConfig,
+    build: &Build,
+    workflow_name: &str,
+    workflow: &Workflow,
+    workspace: &Path,
+    home: Option<&Path>,
+    env: &HashMap<String, String>,
+    is_flutter_project: bool,
+) -> Option<CachePlan> {
+    let mut specs: Vec<String> = Vec::new();
+    if is_flutter_project {
+        specs.extend(FLUTTER_CACHE_PATHS.iter().map(|p| p.to_string()));
+    }
+    if let Some(cache) = &workflow.cache {
+        specs.extend(cache.cache_paths.iter().cloned());
+    }
+    if specs.is_empty() {
+        return None;
+    }
+
+    let (paths, errors) = resolve_cache_paths(&specs, workspace, home, env);
+    for error in &errors {
+        tracing::warn!("Build {}: {}", build.id, error);
+    }
+    let scope = cache_scope(build.trigger_type, &build.branch);
+    let (key, lockfiles) = cache_key(workspace, &scope, workflow_name, &paths).await;
+
+    Some(CachePlan {
+        cache: DependencyCache::new(&config.cache_dir, config.cache_max_size_bytes),
+        key,
+        paths,
+        lockfiles,
+        errors,
+        restored: false,
+    })
+}
+
+/// Runs the "Restore cache" system step. Cache failures never fail the build.
+async fn restore_cache(
+    db: &DbPool,
+    events: &BuildEventBus,
+    build: &Build,
+    logs_dir: &Path,
+    plan: &mut CachePlan,
+) -> oore_core::Result<()> {
+    let step = BuildStep::new(
+        build.id.clone(),
+        RESTORE_CACHE_STEP_INDEX,
+        "Restore cache".to_string(),
+        None, // System step
+        Some(600),
+        true,
+    );
+    BuildStepRepo::create(db, &step).await?;
+    set_step_status(db, events, &step, StepStatus::Running, None).await?;
+
+    let mut stdout = format!("Cache key: {}\n", plan.key);
+    if plan.lockfiles.is_empty() {
+        stdout.push_str("Lockfiles: none found\n");
+    } else {
+        stdout.push_str(&format!("Lockfiles: {}\n", plan.lockfiles.join(", ")));
+    }
+    stdout.push_str("Paths:\n");
+    for path in &plan.paths {
+        stdout.push_str(&format!("  {} ({})\n", path.spec, path.path.display()));
+    }
+    let mut stderr: String = plan.errors.iter().map(|e| format!("Skipped {}\n", e)).collect();
+
+    let status = match plan.cache.restore(&build.repository_id, &plan.key, &plan.paths).await {
+        Ok(CacheRestore::Hit { archive_bytes, entries }) => {
+            plan.restored = true;
+            tracing::info!("Build {} restored dependency cache {}", build.id, plan.key);
+            stdout.push_str(&format!(
+                "Cache hit: restored {} entries ({})\n",
+                entries,
+                format_size(archive_bytes)
+            ));
+            StepStatus::Success
+        }
+        Ok(CacheRestore::Miss) => {
+            stdout.push_str("Cache miss: the cache is saved if this build succeeds\n");
+            StepStatus::Success
+        }
+        Err(e) => {
+            tracing::warn!("Build {} failed to restore dependency cache: {}", build.id, e);
+            stderr.push_str(&format!("Failed to restore cache: {}\n", e));
+            StepStatus::Failure
+        }
+    };
+
+    finish_system_step(db, events, build, logs_dir, &step, status, &stdout, &stderr).await
+}
+
+/// Runs the "Save cache" system step unless the cache was restored for the
+/// same key. Cache failures never fail the build.
+async fn save_cache(
+    db: &DbPool,
+    events: &BuildEventBus,
+    build: &Build,
+    logs_dir: &Path,
+    plan: &CachePlan,
+) -> oore_core::Result<()> {
+    let step = BuildStep::new(
+        build.id.clone(),
+        SAVE_CACHE_STEP_INDEX,
+        "Save cache".to_string(),
+        None, // System step
+        Some(600),
+        true,
+    );
+    BuildStepRepo::create(db, &step).await?;
+    set_step_status(db, events, &step, StepStatus::Running, None).await?;
+
+    let mut stdout = format!("Cache key: {}\n", plan.key);
+    let mut stderr = String::new();
+
+    // Another build may have saved this key since the restore
+    let status = if plan.restored || plan.cache.contains(&build.repository_id, &plan.key).await {
+        stdout.push_str("Cache is up to date, nothing to save\n");
+        StepStatus::Skipped
+    } else {
+        match plan.cache.save(&build.repository_id, &plan.key, &plan.paths).await {
+            Ok(Some(saved)) => {
+                tracing::info!("Build {} saved dependency cache {}", build.id, plan.key);
+                stdout.push_str(&format!(
+                    "Saved {} ({})\n",
+                    saved.saved_paths.join(", "),
+                    format_size(saved.archive_bytes)
+                ));
+                if saved.evicted > 0 {
+                    stdout.push_str(&format!(
+                        "Evicted {} least recently used archive(s) to stay under {}\n",
+                        saved.evicted,
+                        format_size(plan.cache.max_size_bytes())
+                    ));
+                }
+                StepStatus::Success
+            }
+            Ok(None) => {
+                stdout.push_str("None of the cache paths exist, nothing to save\n");
+                StepStatus::Skipped
+            }
+            Err(e) => {
+                tracing::warn!("Build {} failed to save dependency cache: {}", build.id, e);
+                stderr.push_str(&format!("Failed to save cache: {}\n", e));
+                StepStatus::Failure
+            }
+        }
+    };
+
+    finish_system_step(db, events, build, logs_dir, &step, status, &stdout, &stderr).await
+}
+
+/// Writes a system step's log output and records its final status.
+#[allow(clippy::too_many_arguments)]
+async fn finish_system_step(
+    db: &DbPool,
+    events: &BuildEventBus,
+    build: &Build,
+    logs_dir: &Path,
+    step: &BuildStep,
+    status: StepStatus,
+    stdout: &str,
+    stderr: &str,
+) -> oore_core::Result<()> {
+    let index = step.step_index;
+    let _ = tokio::fs::write(logs_dir.join(format!("step-{}-stdout.log", index)), stdout).await;
+    let _ = tokio::fs::write(logs_dir.join(format!("step-{}-stderr.log", index)), stderr).await;
+
+    for (stream, output) in [(LogStream::Stdout, stdout), (LogStream::Stderr, stderr)] {
+        let log = BuildLog::new(
+            build.id.clone(),
+            index,
+            stream,
+            format!("{}/step-{}-{}.log", build.id, index, stream),
+        );
+        BuildLogRepo::create(db, &log).await?;
+        BuildLogRepo::update_line_count(db, &log.id, output.lines().count() as i32).await?;
+    }
+
+    let exit_code = if status == StepStatus::Failure { 1 } else { 0 };
+    set_step_status(db, events, step, status, Some(exit_code)).await
+}
+
+/// Formats a byte count for step logs.
+fn format_size(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["KB", "MB", "GB", "TB"];
+    if bytes < 1024 {
+        return format!("{} B", bytes);
+    }
+    let mut size = bytes as f64 / 1024.0;
+    let mut unit = 0;
+    while size >= 1024.0 && unit < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+    format!("{:.1} {}", size, UNITS[unit])
+}
+
+/// Runs a publisher as a system step, recording its outcome in the step
+/// logs and the build's publications.
+///
+/// Returns `false` if publishing failed. The build itself is not failed.
+#[allow(clippy::too_many_arguments)]
+async fn run_publisher(
+    db: &DbPool,
+    events: &BuildEventBus,
+    build: &Build,
+    build_logs_dir: &std::path::Path,
+    step_index: i32,
+    publisher: &dyn Publisher,
+    ctx: &PublishContext<'_>,
+    artifacts: &[PublishArtifact],
+    cancel_rx: &mut watch::Receiver<bool>,
+) -> oore_core::Result<bool> {
+    let step = BuildStep::new(
+        build.id.clone(),
+        step_index,
+        format!("Publish to {}", publisher.display_name()),
+        None, // System step
+        None,
+        true, // Publishing failures don't fail the build
+    );
+    BuildStepRepo::create(db, &step).await?;
+    set_step_status(db, events, &step, StepStatus::Running, None).await?;
+
+    let publication = BuildPublication::new(build.id.clone(), publisher.key(), step_index);
+    BuildPublicationRepo::create(db, &publication).await?;
+
+    let publish = async {
+        check_credentials(ctx, &publisher.required_credentials()).await?;
+        publisher.publish(ctx, artifacts).await
+    };
+    // Stop uploading as soon as the build is cancelled
+    let result: oore_core::Result<PublishOutcome> = tokio::select! {
+        result = publish => result,
+        Ok(_) = cancel_rx.wait_for(|cancelled| *cancelled) => Err(OoreError::BuildCancelled),
+    };
+
+    // Log lines never include keys or access tokens
+    let (stdout_msg, stderr_msg) = match &result {
+        Ok(outcome) => (outcome.log.clone(), String::new()),
+        Err(e) => (String::new(), format!("{}\n", e)),
+    };
+    let _ = tokio::fs::write(
+        build_logs_dir.join(format!("step-{}-stdout.log", step_index)),
+        &stdout_msg,
+    )
+    .await;
+    let _ = tokio::fs::write(
+        build_logs_dir.join(format!("step-{}-stderr.log", step_index)),
+        &stderr_msg,
+    )
+    .await;
+
+    let stdout_log = BuildLog::new(
+        build.id.clone(),
+        step_index,
+        LogStream::Stdout,
+        format!("{}/step-{}-stdout.log", build.id, step_index),
+    );
+    BuildLogRepo::create(db, &stdout_log).await?;
+    BuildLogRepo::update_line_count(db, &stdout_log.id, stdout_msg.lines().count() as i32).await?;
+
+    let stderr_log = BuildLog::new(
+        build.id.clone(),
+        step_index,
+        LogStream::Stderr,
+        format!("{}/step-{}-stderr.log", build.id, step_index),
+    );
+    BuildLogRepo::create(db, &stderr_log).await?;
+    BuildLogRepo::update_line_count(db, &stderr_log.id, stderr_msg.lines().count() as i32).await?;
+
+    match result {
+        Ok(outcome) => {
+            BuildPublicationRepo::finish(db, &publication.id, PublishStatus::Success, Some(&outcome.summary), None).await?;
+            set_step_status(db, events, &step, StepStatus::Success, Some(0)).await?;
+            Ok(true)
+        }
+        Err(OoreError::BuildCancelled) => {
+            BuildPublicationRepo::finish(db, &publication.id, PublishStatus::Failure, None, Some("Build cancelled")).await?;
+            set_step_status(db, events, &step, StepStatus::Cancelled, None).await?;
+            Err(OoreError::BuildCancelled)
+        }
+        Err(e) => {
+            tracing::error!("Build {} failed to publish to {}: {}", build.id, publisher.display_name(), e);
+            BuildPublicationRepo::finish(db, &publication.id, PublishStatus::Failure, None, Some(&e.to_string())).await?;
+            set_step_status(db, events, &step, StepStatus::Failure, Some(1)).await?;
+            Ok(false)
+        }
+    }
+}
+
+/// Updates a build's status and publishes the transition.
+async fn set_build_status(
+    db: &DbPool,
+    events: &BuildEventBus,
+    build_id: &BuildId,
+    status: BuildStatus,
+) -> oore_core::Result<()> {
+    BuildRepo::update_status(db, build_id, status).await?;
+
+    match status {
+        BuildStatus::Running => events.publish(BuildEvent::BuildStarted {
+            build_id: build_id.clone(),
+        }),
+        status if status.is_terminal() => events.publish(BuildEvent::BuildFinished {
+            build_id: build_id.clone(),
+            status,
+        }),
+        _ => {}
+    }
+
+    Ok(())
+}
+
+/// Updates a build step's status and publishes the transition.
+async fn set_step_status(
+    db: &DbPool,
+    events: &BuildEventBus,
+    step: &BuildStep,
+    status: StepStatus,
+    exit_code: Option<i32>,
+) -> oore_core::Result<()> {
+    BuildStepRepo::update_status(db, &step.id, status, exit_code).await?;
+
+    match status {
+        StepStatus::Running => events.publish(BuildEvent::StepStarted {
+            build_id: step.build_id.clone(),
+            step_index: step.step_index,
+            name: step.name.clone(),
+        }),
+        status if status.is_terminal() => events.publish(BuildEvent::StepFinished {
+            build_id: step.build_id.clone(),
+            step_index: step.step_index,
+            status,
+            exit_code,
+        }),
+        _ => {}
+    }
+
+    Ok(())
+}
+
+/// Publishes a step's live output as [`BuildEvent::LogChunk`]s.
+///
+/// The returned sender is handed to the executor; forwarding stops once
+/// every clone of it has been dropped.
+fn forward_step_output(events: &BuildEventBus, build_id: &BuildId, step_index: i32) -> OutputSender {
+    let (tx, mut rx) = mpsc::unbounded_channel::<StepOutput>();
+    let events = events.clone();
+    let build_id = build_id.clone();
+
+    tokio::spawn(async move {
+        while let Some(output) = rx.recv().await {
+            events.publish(BuildEvent::LogChunk {
+                build_id: build_id.clone(),
+                step_index,
+                stream: output.stream,
+                content: String::from_utf8_lossy(&output.data).into_owned(),
+            });
+        }
+    });
+
+    tx
+}
+
+/// Files changed by the webhook event that triggered the build.
+///
+/// Taken from the payload when it lists them, otherwise diffed in the
+/// workspace. `None` for manual builds or when the changes can't be
+/// determined, in which case changeset conditions don't filter workflows.
+async fn triggering_changes(db: &DbPool, build: &Build, workspace: &Path) -> Option<Vec<String>> {
+    let event_id = build.webhook_event_id.as_ref()?;
+    let event = match WebhookEventRepo::get_by_id(db, event_id).await {
+        Ok(event) => event?,
+        Err(e) => {
+            tracing::warn!("Failed to load webhook event {} for build {}: {}", event_id, build.id, e);
+            return None;
+        }
+    };
+
+    let parsed = match event.provider {
+        GitProvider::GitHub => parse_github_webhook(&event.event_type, &event.payload),
+        GitProvider::GitLab => parse_gitlab_webhook(&event.event_type, &event.payload),
+    };
+    let parsed = match parsed {
+        Ok(parsed) => parsed,
+        Err(e) => {
+            tracing::warn!("Failed to parse webhook event {} for build {}: {}", event_id, build.id, e);
+            return None;
+        }
+    };
+
+    if parsed.changed_files.is_some() {
+        return parsed.changed_files;
+    }
+
+    let base = parsed.diff_base?;
+    // The config checkout has no history of its own
+    if let Err(e) = fetch_diff_history(workspace, &base, &build.commit_sha).await {
+        tracing::warn!("Failed to fetch history for build {} against {}: {}", build.id, base, e);
+        return None;
+    }
+    match diff_changed_files(workspace, &base, &build.commit_sha).await {
+        Ok(files) => Some(files),
+        Err(e) => {
+            tracing::warn!("Failed to list changed files for build {} against {}: {}", build.id, base, e);
+            None
+        }
+    }
+}
+
 /// Cleans up workspace and marks build as failed.
 async fn cleanup_and_fail(
     db: &DbPool,
+    events: &BuildEventBus,
     executor: &Arc<dyn BuildExecutor>,
     workspace: &PathBuf,
     logs_dir: &PathBuf,
     build: &Build,
     error_message: &str,
+) -> oore_core::Result<()> {
+    cleanup_and_finish(db, events, executor, workspace, logs_dir, build, BuildStatus::Failure, error_message).await
+}
+
+/// Cleans up after a build's cancel signal fired.
+///
+/// The build finishes as cancelled, pointing at the newer build if it was
+/// superseded by a newer commit.
+async fn cleanup_cancelled(
+    db: &DbPool,
+    events: &BuildEventBus,
+    executor: &Arc<dyn BuildExecutor>,
+    workspace: &Path,
+    logs_dir: &Path,
+    build: &Build,
+) -> oore_core::Result<()> {
+    let superseded_by = BuildRepo::get_by_id(db, &build.id)
+        .await?
+        .and_then(|b| b.superseded_by);
+
+    let message = match superseded_by {
+        Some(newer) => format!("Superseded by build {}", newer),
+        None => "Build cancelled".to_string(),
+    };
+    cleanup_and_finish(db, events, executor, workspace, logs_dir, build, BuildStatus::Cancelled, &message).await
+}
+
+/// Cleans up workspace and finishes the build with `status`.
+#[allow(clippy::too_many_arguments)]
+async fn cleanup_and_finish(
+    db: &DbPool,
+    events: &BuildEventBus,
+    executor: &Arc<dyn BuildExecutor>,
+    workspace: &Path,
+    logs_dir: &Path,
+    build: &Build,
+    status: BuildStatus,
+    error_message: &str,
 ) -> oore_core::Result<()> {
     BuildRepo::set_error(db, &build.id, error_message).await?;
-    BuildRepo::update_status(db, &build.id, BuildStatus::Failure).await?;
+    set_build_status(db, events, &build.id, status).await?;
 
     // Create Cleanup step for failed builds too
     let cleanup_step = BuildStep::new(
@@ -856,8 +1882,11 @@ async fn cleanup_and_fail(
         true,
     );
     BuildStepRepo::create(db, &cleanup_step).await?;
-    BuildStepRepo::update_status(db, &cleanup_step.id, StepStatus::Running, None).await?;
+    set_step_status(db, events, &cleanup_step, StepStatus::Running, None).await?;
 
+    if let Err(e) = cleanup_android_signing(workspace).await {
+        tracing::warn!("Failed to remove signing files for build {}: {}", build.id, e);
+    }
     let cleanup_result = executor.cleanup(workspace).await;
 
     // Create log files
@@ -892,7 +1921,7 @@ async fn cleanup_and_fail(
     BuildLogRepo::update_line_count(db, &stderr_log.id, 0).await?;
 
     let exit_code = if cleanup_status == StepStatus::Success { 0 } else { 1 };
-    BuildStepRepo::update_status(db, &cleanup_step.id, cleanup_status, Some(exit_code)).await?;
+    set_step_status(db, events, &cleanup_step, cleanup_status, Some(exit_code)).await?;
 
     Ok(())
 }
@@ -1091,6 +2120,7 @@ fn validate_artifact_pattern(pattern: &str) -> Result<(), &'static str> {
 /// Collects build artifacts based on glob patterns.
 async fn collect_artifacts(
     db: &DbPool,
+    events: &BuildEventBus,
     config: &BuildProcessorConfig,
     build: &Build,
     workspace: &PathBuf,
@@ -1218,6 +2248,10 @@ async fn collect_artifacts(
                                 file_name,
                                 file_size
                             );
+                            events.publish(BuildEvent::ArtifactCollected {
+                                build_id: build.id.clone(),
+                                artifact: artifact.clone(),
+                            });
                             collected.push(artifact);
                         }
                         Err(e) => {
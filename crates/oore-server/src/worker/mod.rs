@@ -1,7 +1,11 @@
-//! Background workers for processing webhooks and builds.
+//! Background workers for processing webhooks, builds and schedules.
 
 pub mod build_processor;
+pub mod notification_processor;
+pub mod scheduler;
 pub mod webhook_processor;
 
 pub use build_processor::*;
+pub use notification_processor::*;
+pub use scheduler::*;
 pub use webhook_processor::*;
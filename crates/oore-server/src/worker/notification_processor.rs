@@ -0,0 +1,1181 @@
+//! Outbound notification worker.
+//!
+//! Listens on the build event bus and queues a delivery for every outbound
+//! webhook subscribed to the event. Deliveries live in the database, so
+//! pending retries survive restarts; a dispatcher loop sends whatever is
+//! due and reschedules failures with exponential backoff.
+//!
+//! Slack messages and failure/recovery emails are sent directly from the
+//! listener, one task per message, and are not retried. A third task sends
+//! the daily email digest.
+
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+
+use chrono::{DateTime, Duration as ChronoDuration, TimeZone, Utc};
+use oore_core::{
+    db::{
+        email::{decrypt_smtp_password, EmailConfigRepo, SmtpSettingsRepo},
+        outbound_webhooks::{decrypt_webhook_secret, OutboundDeliveryRepo, OutboundWebhookRepo},
+        pipeline::{BuildLogRepo, BuildStepRepo},
+        repository::{BuildRepo, RepositoryRepo},
+        slack::{decrypt_slack_url, SlackConfigRepo},
+        DbPool,
+    },
+    models::{
+        BuildId, BuildStatus, DeliveryStatus, LogStream, NotificationEvent, OutboundDelivery,
+        SlackConfig, StepStatus,
+    },
+    notifications::{
+        email::{
+            render_build_email, render_digest, summarize_builds, BuildEmailKind, BuildEmailSummary,
+            Email, EmailSettings, EmailTransport,
+        },
+        slack::{build_slack_message, send_slack_message, SlackBuildSummary},
+        smtp::{SmtpConfig, SmtpTransport},
+        tail_lines,
+        webhook::{build_payload, next_retry_at, send_delivery},
+        FailedStep, LOG_TAIL_LINES,
+    },
+    oauth::{EncryptionKey, SsrfConfig},
+};
+use tokio::sync::{broadcast, watch, Notify};
+
+use crate::events::{BuildEvent, BuildEventBus};
+
+/// How often the dispatcher checks for due retries when idle.
+const DISPATCH_INTERVAL: Duration = Duration::from_secs(15);
+
+/// Maximum deliveries sent per dispatcher pass.
+const DISPATCH_BATCH_SIZE: i64 = 50;
+
+/// Settings used to render notifications.
+#[derive(Debug, Clone)]
+pub struct NotificationConfig {
+    /// Base URL of the Oore server, used for build links.
+    pub base_url: String,
+    /// Base directory for build logs, read for failure excerpts.
+    pub logs_dir: PathBuf,
+    /// Hour of day (UTC) the email digest is sent.
+    pub digest_hour_utc: u32,
+}
+
+/// Handle for managing the notification worker.
+pub struct NotificationWorkerHandle {
+    listener_handle: tokio::task::JoinHandle<()>,
+    dispatcher_handle: tokio::task::JoinHandle<()>,
+    digest_handle: tokio::task::JoinHandle<()>,
+    shutdown_tx: watch::Sender<bool>,
+}
+
+impl NotificationWorkerHandle {
+    /// Signals the worker to shut down and waits for its tasks to finish.
+    pub async fn shutdown(self) -> Result<(), tokio::task::JoinError> {
+        let _ = self.shutdown_tx.send(true);
+        self.listener_handle.await?;
+        self.dispatcher_handle.await?;
+        self.digest_handle.await
+    }
+}
+
+/// Starts the notification worker.
+pub fn start_notification_processor(
+    db: DbPool,
+    encryption_key: Option<EncryptionKey>,
+    config: NotificationConfig,
+    events: &BuildEventBus,
+) -> NotificationWorkerHandle {
+    let (shutdown_tx, shutdown_rx) = watch::channel(false);
+    let wake = Arc::new(Notify::new());
+    let ssrf = SsrfConfig::from_env();
+
+    let listener_handle = tokio::spawn(run_listener(
+        db.clone(),
+        encryption_key.clone(),
+        ssrf.clone(),
+        config.clone(),
+        events.subscribe(),
+        wake.clone(),
+        shutdown_rx.clone(),
+    ));
+    let digest_handle = tokio::spawn(run_digest_scheduler(
+        db.clone(),
+        encryption_key.clone(),
+        config,
+        shutdown_rx.clone(),
+    ));
+    let dispatcher_handle =
+        tokio::spawn(run_dispatcher(db, encryption_key, ssrf, wake, shutdown_rx));
+
+    NotificationWorkerHandle {
+        listener_handle,
+        dispatcher_handle,
+        digest_handle,
+        shutdown_tx,
+    }
+}
+
+/// Turns build events into queued deliveries, Slack messages and emails.
+async fn run_listener(
+    db: DbPool,
+    encryption_key: Option<EncryptionKey>,
+    ssrf: SsrfConfig,
+    config: NotificationConfig,
+    mut events: broadcast::Receiver<BuildEvent>,
+    wake: Arc<Notify>,
+    mut shutdown_rx: watch::Receiver<bool>,
+) {
+    loop {
+        let event = tokio::select! {
+            _ = shutdown_rx.changed() => {
+                if *shutdown_rx.borrow() {
+                    break;
+                }
+                continue;
+            }
+            event = events.recv() => event,
+        };
+
+        let (build_id, notification) = match event {
+            Ok(BuildEvent::BuildStarted { build_id }) => {
+                (build_id, NotificationEvent::BuildStarted)
+            }
+            Ok(BuildEvent::BuildFinished { build_id, status }) => {
+                match NotificationEvent::for_status(status) {
+                    Some(notification) => (build_id, notification),
+                    None => continue,
+                }
+            }
+            Ok(_) => continue,
+            Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                tracing::warn!(
+                    "Notification worker lagged behind the event bus, {} events dropped",
+                    skipped
+                );
+                continue;
+            }
+            Err(broadcast::error::RecvError::Closed) => break,
+        };
+
+        spawn_slack_notification(
+            db.clone(),
+            encryption_key.clone(),
+            ssrf.clone(),
+            config.clone(),
+            build_id.clone(),
+            notification,
+        );
+        if matches!(
+            notification,
+            NotificationEvent::BuildFailed | NotificationEvent::BuildSucceeded
+        ) {
+            spawn_email_notification(
+                db.clone(),
+                encryption_key.clone(),
+                config.clone(),
+                build_id.clone(),
+            );
+        }
+
+        match enqueue_deliveries(&db, &config.base_url, &build_id, notification).await {
+            Ok(0) => {}
+            Ok(_) => wake.notify_one(),
+            Err(e) => {
+                tracing::error!(
+                    "Failed to queue {} notifications for build {}: {}",
+                    notification,
+                    build_id,
+                    e
+                );
+            }
+        }
+    }
+
+    tracing::debug!("Notification listener stopped");
+}
+
+/// Queues a delivery for each webhook subscribed to the event.
+///
+/// Returns the number of deliveries queued.
+async fn enqueue_deliveries(
+    db: &DbPool,
+    base_url: &str,
+    build_id: &BuildId,
+    event: NotificationEvent,
+) -> oore_core::Result<usize> {
+    let Some(build) = BuildRepo::get_by_id(db, build_id).await? else {
+        return Ok(0);
+    };
+
+    let webhooks: Vec<_> = OutboundWebhookRepo::list_for_repo(db, &build.repository_id)
+        .await?
+        .into_iter()
+        .filter(|w| w.subscribes_to(event))
+        .collect();
+    if webhooks.is_empty() {
+        return Ok(0);
+    }
+
+    let Some(repository) = RepositoryRepo::get_by_id(db, &build.repository_id).await? else {
+        return Ok(0);
+    };
+
+    let payload = build_payload(event, &build, &repository, base_url).to_string();
+    for webhook in &webhooks {
+        let delivery = OutboundDelivery::new(
+            webhook.id.clone(),
+            Some(build.id.clone()),
+            event,
+            payload.clone(),
+        );
+        OutboundDeliveryRepo::create(db, &delivery).await?;
+    }
+
+    Ok(webhooks.len())
+}
+
+/// Posts a Slack message for the event in the background, if the build's
+/// repository has Slack configured for it.
+fn spawn_slack_notification(
+    db: DbPool,
+    encryption_key: Option<EncryptionKey>,
+    ssrf: SsrfConfig,
+    config: NotificationConfig,
+    build_id: BuildId,
+    event: NotificationEvent,
+) {
+    tokio::spawn(async move {
+        let prepared = match prepare_slack_message(&db, &config, &build_id, event).await {
+            Ok(Some(prepared)) => prepared,
+            Ok(None) => return,
+            Err(e) => {
+                tracing::error!(
+                    "Failed to prepare Slack message for build {}: {}",
+                    build_id,
+                    e
+                );
+                return;
+            }
+        };
+        let (slack_config, message) = prepared;
+
+        let Some(key) = encryption_key else {
+            tracing::warn!("Skipping Slack notification: encryption is not configured");
+            return;
+        };
+
+        let url = match decrypt_slack_url(&key, &slack_config) {
+            Ok(url) => url,
+            Err(e) => {
+                tracing::error!(
+                    "Failed to decrypt Slack webhook URL for repository {}: {}",
+                    slack_config.repository_id,
+                    e
+                );
+                return;
+            }
+        };
+
+        match send_slack_message(&ssrf, &url, &message).await {
+            Ok(()) => tracing::debug!("Posted {} for build {} to Slack", event, build_id),
+            Err(e) => tracing::warn!(
+                "Failed to post {} for build {} to Slack: {}",
+                event,
+                build_id,
+                e
+            ),
+        }
+    });
+}
+
+/// Renders the Slack message for an event.
+///
+/// Returns `None` when the repository has no active Slack config
+/// subscribed to the event.
+async fn prepare_slack_message(
+    db: &DbPool,
+    config: &NotificationConfig,
+    build_id: &BuildId,
+    event: NotificationEvent,
+) -> oore_core::Result<Option<(SlackConfig, serde_json::Value)>> {
+    let Some(build) = BuildRepo::get_by_id(db, build_id).await? else {
+        return Ok(None);
+    };
+
+    let slack_config = match SlackConfigRepo::get_for_repo(db, &build.repository_id).await? {
+        Some(c) if c.subscribes_to(event) => c,
+        _ => return Ok(None),
+    };
+
+    let Some(repository) = RepositoryRepo::get_by_id(db, &build.repository_id).await? else {
+        return Ok(None);
+    };
+
+    let failed_step = if event == NotificationEvent::BuildFailed {
+        find_failed_step(db, &config.logs_dir, build_id).await?
+    } else {
+        None
+    };
+
+    let message = build_slack_message(&SlackBuildSummary {
+        event,
+        build: &build,
+        repository: &repository,
+        failed_step,
+        base_url: &config.base_url,
+    });
+
+    Ok(Some((slack_config, message)))
+}
+
+/// Emails the repository's recipients if the build failed or recovered.
+fn spawn_email_notification(
+    db: DbPool,
+    encryption_key: Option<EncryptionKey>,
+    config: NotificationConfig,
+    build_id: BuildId,
+) {
+    tokio::spawn(async move {
+        let (recipients, content) = match prepare_build_email(&db, &config, &build_id).await {
+            Ok(Some(prepared)) => prepared,
+            Ok(None) => return,
+            Err(e) => {
+                tracing::error!("Failed to prepare email for build {}: {}", build_id, e);
+                return;
+            }
+        };
+
+        let settings = match resolve_email_settings(&db, encryption_key.as_ref()).await {
+            Ok(Some(settings)) => settings,
+            Ok(None) => {
+                tracing::warn!("Skipping email notification: SMTP is not configured");
+                return;
+            }
+            Err(e) => {
+                tracing::error!("Failed to load SMTP settings: {}", e);
+                return;
+            }
+        };
+
+        let email = content.into_email(&settings.from, &recipients);
+        let transport = SmtpTransport::new(settings.smtp);
+        match transport.send(&email).await {
+            Ok(()) => tracing::debug!(
+                "Emailed {} recipients for build {}",
+                email.to.len(),
+                build_id
+            ),
+            Err(e) => tracing::warn!("Failed to email build {}: {}", build_id, e),
+        }
+    });
+}
+
+/// Renders the failure or recovery email for a finished build.
+///
+/// Returns the recipients and content, or `None` when the repository has no
+/// active email config or the build is neither a failure nor a recovery.
+async fn prepare_build_email(
+    db: &DbPool,
+    config: &NotificationConfig,
+    build_id: &BuildId,
+) -> oore_core::Result<Option<(Vec<String>, oore_core::notifications::email::EmailContent)>> {
+    let Some(build) = BuildRepo::get_by_id(db, build_id).await? else {
+        return Ok(None);
+    };
+
+    let email_config = match EmailConfigRepo::get_for_repo(db, &build.repository_id).await? {
+        Some(c) if c.is_active && !c.recipients.is_empty() => c,
+        _ => return Ok(None),
+    };
+
+    let kind = match build.status {
+        BuildStatus::Failure if email_config.on_failure => BuildEmailKind::Failure,
+        BuildStatus::Success if email_config.on_recovery => {
+            // Only the first green build after a red one counts
+            match BuildRepo::get_previous_finished(db, &build).await? {
+                Some(previous) if previous.status == BuildStatus::Failure => {
+                    BuildEmailKind::Recovery
+                }
+                _ => return Ok(None),
+            }
+        }
+        _ => return Ok(None),
+    };
+
+    let Some(repository) = RepositoryRepo::get_by_id(db, &build.repository_id).await? else {
+        return Ok(None);
+    };
+
+    let failed_step = if kind == BuildEmailKind::Failure {
+        find_failed_step(db, &config.logs_dir, build_id).await?
+    } else {
+        None
+    };
+
+    let content = render_build_email(&BuildEmailSummary {
+        kind,
+        build: &build,
+        repository: &repository,
+        failed_step,
+        base_url: &config.base_url,
+    });
+
+    Ok(Some((email_config.recipients, content)))
+}
+
+/// Resolves the SMTP settings: the database row if present, otherwise
+/// `OORE_SMTP_*` from the environment.
+async fn resolve_email_settings(
+    db: &DbPool,
+    encryption_key: Option<&EncryptionKey>,
+) -> oore_core::Result<Option<EmailSettings>> {
+    let Some(stored) = SmtpSettingsRepo::get(db).await? else {
+        return EmailSettings::from_env();
+    };
+
+    let password = match (encryption_key, stored.password_encrypted.is_some()) {
+        (_, false) => None,
+        (Some(key), true) => decrypt_smtp_password(key, &stored)?,
+        (None, true) => {
+            return Err(oore_core::OoreError::Configuration(
+                "SMTP password is stored but encryption is not configured".to_string(),
+            ));
+        }
+    };
+
+    Ok(Some(EmailSettings {
+        smtp: SmtpConfig {
+            host: stored.host,
+            port: stored.port,
+            tls: stored.tls,
+            username: stored.username,
+            password,
+        },
+        from: stored.from_address,
+        digest_recipients: stored.digest_recipients,
+    }))
+}
+
+/// Sends the email digest once a day at the configured hour.
+async fn run_digest_scheduler(
+    db: DbPool,
+    encryption_key: Option<EncryptionKey>,
+    config: NotificationConfig,
+    mut shutdown_rx: watch::Receiver<bool>,
+) {
+    loop {
+        let now = Utc::now();
+        let next = next_digest_at(now, config.digest_hour_utc);
+        let wait = (next - now).to_std().unwrap_or_default();
+
+        tokio::select! {
+            _ = shutdown_rx.changed() => {
+                if *shutdown_rx.borrow() {
+                    break;
+                }
+                continue;
+            }
+            _ = tokio::time::sleep(wait) => {}
+        }
+
+        let settings = match resolve_email_settings(&db, encryption_key.as_ref()).await {
+            Ok(Some(settings)) if !settings.digest_recipients.is_empty() => settings,
+            Ok(_) => continue,
+            Err(e) => {
+                tracing::error!("Failed to load SMTP settings for digest: {}", e);
+                continue;
+            }
+        };
+
+        let transport = SmtpTransport::new(settings.smtp.clone());
+        match send_digest(&db, &transport, &settings, &config.base_url, next).await {
+            Ok(true) => tracing::info!(
+                "Sent build digest to {} recipients",
+                settings.digest_recipients.len()
+            ),
+            Ok(false) => tracing::debug!("No builds finished today, digest skipped"),
+            Err(e) => tracing::error!("Failed to send build digest: {}", e),
+        }
+    }
+
+    tracing::debug!("Digest scheduler stopped");
+}
+
+/// Returns the next time at or after `now` when the digest is due.
+fn next_digest_at(now: DateTime<Utc>, hour: u32) -> DateTime<Utc> {
+    let today = now
+        .date_naive()
+        .and_hms_opt(hour.min(23), 0, 0)
+        .unwrap_or_default();
+    let today = Utc.from_utc_datetime(&today);
+    if today > now {
+        today
+    } else {
+        today + ChronoDuration::days(1)
+    }
+}
+
+/// Sends a digest of the builds that finished in the 24 hours before `until`.
+///
+/// Returns `false` without sending when no builds finished.
+async fn send_digest(
+    db: &DbPool,
+    transport: &dyn EmailTransport,
+    settings: &EmailSettings,
+    base_url: &str,
+    until: DateTime<Utc>,
+) -> oore_core::Result<bool> {
+    let since = until - ChronoDuration::days(1);
+    let builds = BuildRepo::list_finished_between(db, since, until).await?;
+    if builds.is_empty() {
+        return Ok(false);
+    }
+
+    let repositories = RepositoryRepo::list(db).await?;
+    let digests = summarize_builds(&builds, &repositories);
+    let email: Email = render_digest(&digests, since, until, base_url)
+        .into_email(&settings.from, &settings.digest_recipients);
+    transport.send(&email).await?;
+    Ok(true)
+}
+
+/// Finds the step that failed the build and the tail of its output.
+///
+/// Steps with `ignore_failure` don't fail the build, so they are skipped.
+/// Stderr is preferred; stdout is used when stderr is empty.
+async fn find_failed_step(
+    db: &DbPool,
+    logs_dir: &Path,
+    build_id: &BuildId,
+) -> oore_core::Result<Option<FailedStep>> {
+    let steps = BuildStepRepo::list_for_build(db, build_id).await?;
+    let Some(step) = steps
+        .into_iter()
+        .find(|s| s.status == StepStatus::Failure && !s.ignore_failure)
+    else {
+        return Ok(None);
+    };
+
+    let logs = BuildLogRepo::list_for_step(db, build_id, step.step_index).await?;
+    let mut log_tail = String::new();
+    for stream in [LogStream::Stderr, LogStream::Stdout] {
+        let Some(log) = logs.iter().find(|l| l.stream == stream) else {
+            continue;
+        };
+        // Logs are already masked when written, so they are safe to share
+        if let Ok(content) = tokio::fs::read_to_string(logs_dir.join(&log.log_file_path)).await {
+            log_tail = tail_lines(&content, LOG_TAIL_LINES);
+            if !log_tail.trim().is_empty() {
+                break;
+            }
+        }
+    }
+
+    Ok(Some(FailedStep {
+        name: step.name,
+        log_tail,
+    }))
+}
+
+/// Sends due deliveries whenever woken or on a fixed interval.
+async fn run_dispatcher(
+    db: DbPool,
+    encryption_key: Option<EncryptionKey>,
+    ssrf: SsrfConfig,
+    wake: Arc<Notify>,
+    mut shutdown_rx: watch::Receiver<bool>,
+) {
+    let mut interval = tokio::time::interval(DISPATCH_INTERVAL);
+
+    loop {
+        tokio::select! {
+            _ = shutdown_rx.changed() => {
+                if *shutdown_rx.borrow() {
+                    break;
+                }
+            }
+            _ = wake.notified() => {}
+            _ = interval.tick() => {}
+        }
+
+        if *shutdown_rx.borrow() {
+            break;
+        }
+
+        if let Err(e) = dispatch_due(&db, encryption_key.as_ref(), &ssrf).await {
+            tracing::error!("Failed to dispatch webhook deliveries: {}", e);
+        }
+    }
+
+    tracing::debug!("Notification dispatcher stopped");
+}
+
+/// Attempts every pending delivery whose retry time has passed.
+async fn dispatch_due(
+    db: &DbPool,
+    encryption_key: Option<&EncryptionKey>,
+    ssrf: &SsrfConfig,
+) -> oore_core::Result<()> {
+    let due = OutboundDeliveryRepo::list_due(db, Utc::now(), DISPATCH_BATCH_SIZE).await?;
+    if due.is_empty() {
+        return Ok(());
+    }
+
+    let Some(key) = encryption_key else {
+        // Leave them queued; they go out once a key is configured
+        tracing::warn!(
+            "{} webhook deliveries pending but encryption is not configured",
+            due.len()
+        );
+        return Ok(());
+    };
+
+    for delivery in due {
+        let Some(webhook) = OutboundWebhookRepo::get_by_id(db, &delivery.webhook_id).await? else {
+            continue;
+        };
+
+        if !webhook.is_active {
+            OutboundDeliveryRepo::record_attempt(
+                db,
+                &delivery.id,
+                DeliveryStatus::Failed,
+                delivery.attempts,
+                None,
+                None,
+                Some("Webhook is disabled"),
+            )
+            .await?;
+            continue;
+        }
+
+        let secret = match decrypt_webhook_secret(key, &webhook) {
+            Ok(secret) => secret,
+            Err(e) => {
+                tracing::error!("Failed to decrypt secret for webhook {}: {}", webhook.id, e);
+                OutboundDeliveryRepo::record_attempt(
+                    db,
+                    &delivery.id,
+                    DeliveryStatus::Failed,
+                    delivery.attempts,
+                    None,
+                    None,
+                    Some("Failed to decrypt webhook secret"),
+                )
+                .await?;
+                continue;
+            }
+        };
+
+        let attempts = delivery.attempts + 1;
+        let attempt = send_delivery(ssrf, &webhook.url, &secret, &delivery).await;
+        let response_status = attempt.response_status.map(i32::from);
+
+        if attempt.is_success() {
+            tracing::debug!(
+                "Delivered {} to webhook {} (attempt {})",
+                delivery.event,
+                webhook.id,
+                attempts
+            );
+            OutboundDeliveryRepo::record_attempt(
+                db,
+                &delivery.id,
+                DeliveryStatus::Success,
+                attempts,
+                None,
+                response_status,
+                None,
+            )
+            .await?;
+            continue;
+        }
+
+        let error = attempt.error.as_deref().unwrap_or("Delivery failed");
+        let next_attempt_at = next_retry_at(attempts, Utc::now());
+        let status = if next_attempt_at.is_some() {
+            DeliveryStatus::Pending
+        } else {
+            DeliveryStatus::Failed
+        };
+
+        tracing::warn!(
+            "Delivery {} to webhook {} failed (attempt {}): {}",
+            delivery.id,
+            webhook.id,
+            attempts,
+            error
+        );
+        OutboundDeliveryRepo::record_attempt(
+            db,
+            &delivery.id,
+            status,
+            attempts,
+            next_attempt_at,
+            response_status,
+            Some(error),
+        )
+        .await?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use oore_core::db::{
+        create_pool, outbound_webhooks::encrypt_webhook_secret, run_migrations,
+        slack::encrypt_slack_url,
+    };
+    use oore_core::models::{
+        Build, BuildLog, BuildStatus, BuildStep, GitProvider, OutboundWebhook, OutboundWebhookId,
+        Repository, SlackConfigId, TriggerType,
+    };
+
+    async fn setup_test_db() -> DbPool {
+        let pool = create_pool("sqlite::memory:").await.unwrap();
+        run_migrations(&pool).await.unwrap();
+        pool
+    }
+
+    async fn create_webhook(
+        db: &DbPool,
+        key: &EncryptionKey,
+        events: Vec<NotificationEvent>,
+    ) -> (Build, OutboundWebhook) {
+        let repository = Repository::new(
+            "app".to_string(),
+            GitProvider::GitHub,
+            "acme".to_string(),
+            "app".to_string(),
+            "https://github.com/acme/app.git".to_string(),
+        );
+        RepositoryRepo::create(db, &repository).await.unwrap();
+
+        let build = Build::new(
+            repository.id.clone(),
+            None,
+            "a".repeat(40),
+            "main".to_string(),
+            TriggerType::Push,
+        );
+        BuildRepo::create(db, &build).await.unwrap();
+
+        let id = OutboundWebhookId::new();
+        let (secret_encrypted, secret_nonce) = encrypt_webhook_secret(key, &id, "secret").unwrap();
+        let webhook = OutboundWebhook {
+            id,
+            repository_id: repository.id.clone(),
+            // Rejected by the SSRF rules, so every attempt fails without network access
+            url: "https://127.0.0.1/hook".to_string(),
+            events,
+            secret_encrypted,
+            secret_nonce,
+            is_active: true,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        };
+        OutboundWebhookRepo::create(db, &webhook).await.unwrap();
+
+        (build, webhook)
+    }
+
+    #[tokio::test]
+    async fn test_enqueue_respects_event_filter() {
+        let db = setup_test_db().await;
+        let key = EncryptionKey::from_string(&"07".repeat(32)).unwrap();
+        let (build, webhook) =
+            create_webhook(&db, &key, vec![NotificationEvent::BuildFailed]).await;
+
+        let queued = enqueue_deliveries(
+            &db,
+            "https://ci.example.com",
+            &build.id,
+            NotificationEvent::BuildStarted,
+        )
+        .await
+        .unwrap();
+        assert_eq!(queued, 0);
+
+        let queued = enqueue_deliveries(
+            &db,
+            "https://ci.example.com",
+            &build.id,
+            NotificationEvent::BuildFailed,
+        )
+        .await
+        .unwrap();
+        assert_eq!(queued, 1);
+
+        let deliveries = OutboundDeliveryRepo::list_for_webhook(&db, &webhook.id, 10)
+            .await
+            .unwrap();
+        assert_eq!(deliveries.len(), 1);
+        assert_eq!(deliveries[0].event, NotificationEvent::BuildFailed);
+        assert_eq!(deliveries[0].status, DeliveryStatus::Pending);
+
+        let payload: serde_json::Value = serde_json::from_str(&deliveries[0].payload).unwrap();
+        assert_eq!(payload["event"], "build.failed");
+        assert_eq!(payload["build"]["id"], build.id.to_string());
+    }
+
+    #[tokio::test]
+    async fn test_failed_attempt_is_rescheduled() {
+        let db = setup_test_db().await;
+        let key = EncryptionKey::from_string(&"07".repeat(32)).unwrap();
+        let (build, webhook) =
+            create_webhook(&db, &key, vec![NotificationEvent::BuildStarted]).await;
+
+        enqueue_deliveries(
+            &db,
+            "https://ci.example.com",
+            &build.id,
+            NotificationEvent::BuildStarted,
+        )
+        .await
+        .unwrap();
+        dispatch_due(&db, Some(&key), &SsrfConfig::default())
+            .await
+            .unwrap();
+
+        let delivery = OutboundDeliveryRepo::list_for_webhook(&db, &webhook.id, 10)
+            .await
+            .unwrap()
+            .remove(0);
+        assert_eq!(delivery.status, DeliveryStatus::Pending);
+        assert_eq!(delivery.attempts, 1);
+        assert!(delivery.next_attempt_at.unwrap() > Utc::now());
+        assert!(delivery.error_message.unwrap().contains("private"));
+
+        // Not due yet, so a second pass leaves it alone
+        dispatch_due(&db, Some(&key), &SsrfConfig::default())
+            .await
+            .unwrap();
+        let delivery = OutboundDeliveryRepo::list_for_webhook(&db, &webhook.id, 10)
+            .await
+            .unwrap()
+            .remove(0);
+        assert_eq!(delivery.attempts, 1);
+    }
+
+    #[tokio::test]
+    async fn test_deliveries_wait_for_encryption_key() {
+        let db = setup_test_db().await;
+        let key = EncryptionKey::from_string(&"07".repeat(32)).unwrap();
+        let (build, webhook) =
+            create_webhook(&db, &key, vec![NotificationEvent::BuildStarted]).await;
+
+        enqueue_deliveries(
+            &db,
+            "https://ci.example.com",
+            &build.id,
+            NotificationEvent::BuildStarted,
+        )
+        .await
+        .unwrap();
+        dispatch_due(&db, None, &SsrfConfig::default())
+            .await
+            .unwrap();
+
+        let delivery = OutboundDeliveryRepo::list_for_webhook(&db, &webhook.id, 10)
+            .await
+            .unwrap()
+            .remove(0);
+        assert_eq!(delivery.status, DeliveryStatus::Pending);
+        assert_eq!(delivery.attempts, 0);
+    }
+
+    async fn create_slack_config(
+        db: &DbPool,
+        key: &EncryptionKey,
+        build: &Build,
+        events: Vec<NotificationEvent>,
+    ) {
+        let id = SlackConfigId::new();
+        let (webhook_url_encrypted, webhook_url_nonce) =
+            encrypt_slack_url(key, &id, "https://hooks.slack.com/services/T0/B0/X").unwrap();
+        let config = SlackConfig {
+            id,
+            repository_id: build.repository_id.clone(),
+            webhook_url_encrypted,
+            webhook_url_nonce,
+            events,
+            is_active: true,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        };
+        SlackConfigRepo::upsert(db, &config).await.unwrap();
+    }
+
+    async fn create_step(
+        db: &DbPool,
+        build: &Build,
+        step_index: i32,
+        name: &str,
+        ignore_failure: bool,
+    ) -> BuildStep {
+        let step = BuildStep::new(
+            build.id.clone(),
+            step_index,
+            name.to_string(),
+            None,
+            None,
+            ignore_failure,
+        );
+        BuildStepRepo::create(db, &step).await.unwrap();
+        BuildStepRepo::update_status(db, &step.id, StepStatus::Failure, Some(1))
+            .await
+            .unwrap();
+        step
+    }
+
+    #[tokio::test]
+    async fn test_slack_message_includes_failed_step_tail() {
+        let db = setup_test_db().await;
+        let key = EncryptionKey::from_string(&"07".repeat(32)).unwrap();
+        let (build, _) = create_webhook(&db, &key, vec![NotificationEvent::BuildFailed]).await;
+        BuildRepo::update_status(&db, &build.id, BuildStatus::Failure)
+            .await
+            .unwrap();
+        create_slack_config(&db, &key, &build, vec![NotificationEvent::BuildFailed]).await;
+
+        // An ignored failure earlier in the build must not be reported
+        create_step(&db, &build, 0, "Lint", true).await;
+        create_step(&db, &build, 1, "Run tests", false).await;
+
+        let logs_dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir_all(logs_dir.path().join(build.id.to_string())).unwrap();
+        let log_lines: Vec<String> = (1..=30).map(|i| format!("line {}", i)).collect();
+        for (stream, content) in [
+            (LogStream::Stdout, "compiling\n".to_string()),
+            (LogStream::Stderr, log_lines.join("\n")),
+        ] {
+            let suffix = if stream == LogStream::Stderr {
+                "stderr"
+            } else {
+                "stdout"
+            };
+            let path = format!("{}/step-1-{}.log", build.id, suffix);
+            std::fs::write(logs_dir.path().join(&path), content).unwrap();
+            BuildLogRepo::create(&db, &BuildLog::new(build.id.clone(), 1, stream, path))
+                .await
+                .unwrap();
+        }
+
+        let config = NotificationConfig {
+            base_url: "https://ci.example.com".to_string(),
+            logs_dir: logs_dir.path().to_path_buf(),
+            digest_hour_utc: 8,
+        };
+        let (_, message) =
+            prepare_slack_message(&db, &config, &build.id, NotificationEvent::BuildFailed)
+                .await
+                .unwrap()
+                .unwrap();
+
+        let text = message.to_string();
+        assert!(text.contains("*Failed step:* Run tests"));
+        assert!(!text.contains("Lint"));
+        assert!(text.contains("line 30"));
+        assert!(text.contains("line 11"));
+        assert!(!text.contains("line 10"));
+        assert!(text.contains(&format!("https://ci.example.com/builds/{}", build.id)));
+    }
+
+    #[tokio::test]
+    async fn test_slack_message_respects_event_filter() {
+        let db = setup_test_db().await;
+        let key = EncryptionKey::from_string(&"07".repeat(32)).unwrap();
+        let (build, _) = create_webhook(&db, &key, vec![NotificationEvent::BuildFailed]).await;
+        let config = NotificationConfig {
+            base_url: "https://ci.example.com".to_string(),
+            logs_dir: PathBuf::from("/nonexistent"),
+            digest_hour_utc: 8,
+        };
+
+        // No Slack config at all
+        let prepared =
+            prepare_slack_message(&db, &config, &build.id, NotificationEvent::BuildFailed)
+                .await
+                .unwrap();
+        assert!(prepared.is_none());
+
+        create_slack_config(&db, &key, &build, vec![NotificationEvent::BuildFailed]).await;
+        let prepared =
+            prepare_slack_message(&db, &config, &build.id, NotificationEvent::BuildStarted)
+                .await
+                .unwrap();
+        assert!(prepared.is_none());
+
+        let prepared =
+            prepare_slack_message(&db, &config, &build.id, NotificationEvent::BuildFailed)
+                .await
+                .unwrap();
+        assert!(prepared.is_some());
+    }
+
+    async fn create_email_config(db: &DbPool, build: &Build) {
+        let config = oore_core::models::EmailNotificationConfig {
+            id: oore_core::models::EmailConfigId::new(),
+            repository_id: build.repository_id.clone(),
+            recipients: vec!["dev@example.com".to_string()],
+            on_failure: true,
+            on_recovery: true,
+            is_active: true,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        };
+        EmailConfigRepo::upsert(db, &config).await.unwrap();
+    }
+
+    async fn create_finished_build(db: &DbPool, previous: &Build, status: BuildStatus) -> Build {
+        let build = Build::new(
+            previous.repository_id.clone(),
+            None,
+            "b".repeat(40),
+            previous.branch.clone(),
+            TriggerType::Push,
+        );
+        BuildRepo::create(db, &build).await.unwrap();
+        BuildRepo::update_status(db, &build.id, status)
+            .await
+            .unwrap();
+        build
+    }
+
+    #[tokio::test]
+    async fn test_build_email_only_on_failure_or_recovery() {
+        let db = setup_test_db().await;
+        let key = EncryptionKey::from_string(&"07".repeat(32)).unwrap();
+        let (first, _) = create_webhook(&db, &key, vec![]).await;
+        BuildRepo::update_status(&db, &first.id, BuildStatus::Success)
+            .await
+            .unwrap();
+        create_email_config(&db, &first).await;
+        let config = NotificationConfig {
+            base_url: "https://ci.example.com".to_string(),
+            logs_dir: PathBuf::from("/nonexistent"),
+            digest_hour_utc: 8,
+        };
+
+        // Green after nothing red: no email
+        assert!(prepare_build_email(&db, &config, &first.id)
+            .await
+            .unwrap()
+            .is_none());
+
+        let failed = create_finished_build(&db, &first, BuildStatus::Failure).await;
+        let (recipients, content) = prepare_build_email(&db, &config, &failed.id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(recipients, vec!["dev@example.com"]);
+        assert!(content.subject.contains("Build failed"));
+
+        let fixed = create_finished_build(&db, &failed, BuildStatus::Success).await;
+        let (_, content) = prepare_build_email(&db, &config, &fixed.id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(content.subject.contains("Build fixed"));
+
+        // Still green: no second recovery email
+        let green = create_finished_build(&db, &fixed, BuildStatus::Success).await;
+        assert!(prepare_build_email(&db, &config, &green.id)
+            .await
+            .unwrap()
+            .is_none());
+    }
+
+    #[test]
+    fn test_next_digest_at() {
+        let at = |s: &str| DateTime::parse_from_rfc3339(s).unwrap().with_timezone(&Utc);
+
+        assert_eq!(
+            next_digest_at(at("2026-03-01T06:30:00Z"), 8),
+            at("2026-03-01T08:00:00Z")
+        );
+        assert_eq!(
+            next_digest_at(at("2026-03-01T08:00:00Z"), 8),
+            at("2026-03-02T08:00:00Z")
+        );
+        assert_eq!(
+            next_digest_at(at("2026-03-01T23:59:00Z"), 8),
+            at("2026-03-02T08:00:00Z")
+        );
+    }
+
+    #[derive(Default)]
+    struct RecordingTransport {
+        sent: std::sync::Mutex<Vec<Email>>,
+    }
+
+    #[async_trait::async_trait]
+    impl EmailTransport for RecordingTransport {
+        async fn send(&self, email: &Email) -> oore_core::Result<()> {
+            self.sent.lock().unwrap().push(email.clone());
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_send_digest_covers_last_day() {
+        let db = setup_test_db().await;
+        let key = EncryptionKey::from_string(&"07".repeat(32)).unwrap();
+        let settings = EmailSettings {
+            smtp: SmtpConfig {
+                host: "localhost".to_string(),
+                port: 25,
+                tls: oore_core::models::SmtpTls::None,
+                username: None,
+                password: None,
+            },
+            from: "oore@example.com".to_string(),
+            digest_recipients: vec!["team@example.com".to_string()],
+        };
+        let transport = RecordingTransport::default();
+
+        // Nothing finished yet
+        let sent = send_digest(
+            &db,
+            &transport,
+            &settings,
+            "https://ci.example.com",
+            Utc::now(),
+        )
+        .await
+        .unwrap();
+        assert!(!sent);
+
+        let (build, _) = create_webhook(&db, &key, vec![]).await;
+        BuildRepo::update_status(&db, &build.id, BuildStatus::Failure)
+            .await
+            .unwrap();
+        let until = Utc::now() + ChronoDuration::seconds(1);
+        let sent = send_digest(&db, &transport, &settings, "https://ci.example.com", until)
+            .await
+            .unwrap();
+        assert!(sent);
+
+        {
+            let sent = transport.sent.lock().unwrap();
+            assert_eq!(sent.len(), 1);
+            assert_eq!(sent[0].to, vec!["team@example.com"]);
+            assert!(sent[0].subject.contains("1 build, 1 failed"));
+        }
+
+        // A day later the build is outside the window
+        let sent = send_digest(
+            &db,
+            &transport,
+            &settings,
+            "https://ci.example.com",
+            until + ChronoDuration::days(1),
+        )
+        .await
+        .unwrap();
+        assert!(!sent);
+    }
+}
@@ -0,0 +1,338 @@
+//! Cron scheduler.
+//!
+//! Polls for build schedules that are due, resolves the branch head and
+//! queues a scheduled build. Each schedule's next run is stored before the
+//! build is created, so a failing run doesn't repeat on every poll, and runs
+//! missed while the server was down collapse into one.
+
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use oore_core::{
+    auth::get_repository_auth_token,
+    db::{
+        repository::{BuildRepo, RepositoryRepo},
+        schedules::BuildScheduleRepo,
+        DbPool,
+    },
+    models::{Build, BuildSchedule, TriggerType},
+    oauth::EncryptionKey,
+    schedule::{next_run_for, resolve_branch_head},
+};
+use tokio::sync::{mpsc, watch};
+
+use super::BuildJob;
+
+/// How often due schedules are checked.
+const SCHEDULER_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Handle for managing the scheduler task.
+pub struct SchedulerHandle {
+    task_handle: tokio::task::JoinHandle<()>,
+    shutdown_tx: watch::Sender<bool>,
+}
+
+impl SchedulerHandle {
+    /// Signals the scheduler to stop and waits for it to finish.
+    pub async fn shutdown(self) -> Result<(), tokio::task::JoinError> {
+        let _ = self.shutdown_tx.send(true);
+        self.task_handle.await
+    }
+}
+
+/// Starts the scheduler task.
+pub fn start_scheduler(
+    db: DbPool,
+    encryption_key: Option<EncryptionKey>,
+    build_tx: mpsc::Sender<BuildJob>,
+) -> SchedulerHandle {
+    let (shutdown_tx, mut shutdown_rx) = watch::channel(false);
+
+    let task_handle = tokio::spawn(async move {
+        let mut interval = tokio::time::interval(SCHEDULER_INTERVAL);
+
+        loop {
+            tokio::select! {
+                _ = shutdown_rx.changed() => {
+                    if *shutdown_rx.borrow() {
+                        tracing::debug!("Scheduler received shutdown signal");
+                        break;
+                    }
+                }
+                _ = interval.tick() => {
+                    run_due_schedules(&db, encryption_key.as_ref(), &build_tx).await;
+                }
+            }
+        }
+
+        tracing::debug!("Scheduler stopped");
+    });
+
+    SchedulerHandle {
+        task_handle,
+        shutdown_tx,
+    }
+}
+
+/// Outcome of a schedule run.
+enum ScheduledRun {
+    Created(Box<Build>),
+    Skipped(String),
+}
+
+/// Runs every schedule that is due.
+async fn run_due_schedules(
+    db: &DbPool,
+    encryption_key: Option<&EncryptionKey>,
+    build_tx: &mpsc::Sender<BuildJob>,
+) {
+    let now = Utc::now();
+    let due = match BuildScheduleRepo::list_due(db, now).await {
+        Ok(due) => due,
+        Err(e) => {
+            tracing::error!("Failed to load due schedules: {}", e);
+            return;
+        }
+    };
+
+    for schedule in due {
+        if let Err(e) = run_schedule(db, encryption_key, build_tx, &schedule, now).await {
+            tracing::error!("Failed to run schedule {}: {}", schedule.id, e);
+        }
+    }
+}
+
+/// Moves a due schedule to its next run, then creates and queues its build.
+async fn run_schedule(
+    db: &DbPool,
+    encryption_key: Option<&EncryptionKey>,
+    build_tx: &mpsc::Sender<BuildJob>,
+    schedule: &BuildSchedule,
+    now: DateTime<Utc>,
+) -> oore_core::Result<()> {
+    let Some(due_at) = schedule.next_run_at else {
+        return Ok(());
+    };
+
+    let next_run_at = next_run_for(&schedule.cron_expression, &schedule.timezone, now)
+        .unwrap_or_else(|e| {
+            tracing::warn!(
+                "Schedule {} can't be evaluated, pausing it: {}",
+                schedule.id,
+                e
+            );
+            None
+        });
+    if !BuildScheduleRepo::claim_run(db, &schedule.id, due_at, next_run_at).await? {
+        // Edited since it was loaded
+        return Ok(());
+    }
+
+    match create_scheduled_build(db, encryption_key, schedule).await {
+        Ok(ScheduledRun::Created(build)) => {
+            BuildScheduleRepo::record_run(db, &schedule.id, Some(&build.id), None).await?;
+            tracing::info!(
+                "Schedule {} created build {} for {} ({})",
+                schedule.id,
+                build.id,
+                schedule.branch,
+                &build.commit_sha[..7.min(build.commit_sha.len())]
+            );
+
+            let job = BuildJob {
+                build_id: build.id.clone(),
+            };
+            if let Err(e) = build_tx.try_send(job) {
+                // The build stays pending and is recovered on restart
+                tracing::error!("Failed to queue scheduled build {}: {}", build.id, e);
+            }
+        }
+        Ok(ScheduledRun::Skipped(reason)) => {
+            tracing::info!(
+                "Schedule {} did not create a build: {}",
+                schedule.id,
+                reason
+            );
+            BuildScheduleRepo::record_run(db, &schedule.id, None, Some(&reason)).await?;
+        }
+        Err(e) => {
+            tracing::warn!("Schedule {} failed: {}", schedule.id, e);
+            BuildScheduleRepo::record_run(db, &schedule.id, None, Some(&e.to_string())).await?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Resolves the schedule's branch head and creates a pending build for it.
+async fn create_scheduled_build(
+    db: &DbPool,
+    encryption_key: Option<&EncryptionKey>,
+    schedule: &BuildSchedule,
+) -> oore_core::Result<ScheduledRun> {
+    let repository = RepositoryRepo::get_by_id(db, &schedule.repository_id)
+        .await?
+        .ok_or_else(|| {
+            oore_core::OoreError::RepositoryNotFound(schedule.repository_id.to_string())
+        })?;
+    if !repository.is_active {
+        return Ok(ScheduledRun::Skipped(
+            "Skipped: repository is inactive".to_string(),
+        ));
+    }
+
+    let auth_token = match encryption_key {
+        Some(key) => get_repository_auth_token(db, key, &repository).await?,
+        None => None,
+    };
+    let commit_sha = resolve_branch_head(
+        &repository.clone_url,
+        &schedule.branch,
+        auth_token.as_deref(),
+    )
+    .await?;
+
+    if schedule.skip_if_built
+        && BuildRepo::has_successful_build(
+            db,
+            &repository.id,
+            &commit_sha,
+            schedule.workflow_name.as_deref(),
+        )
+        .await?
+    {
+        return Ok(ScheduledRun::Skipped(format!(
+            "Skipped: commit {} already built successfully",
+            &commit_sha[..7.min(commit_sha.len())]
+        )));
+    }
+
+    let mut build = Build::new(
+        repository.id.clone(),
+        None,
+        commit_sha,
+        schedule.branch.clone(),
+        TriggerType::Scheduled,
+    );
+    // The build processor runs a preset workflow instead of selecting one
+    build.workflow_name = schedule.workflow_name.clone();
+    BuildRepo::create(db, &build).await?;
+
+    Ok(ScheduledRun::Created(Box::new(build)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration as ChronoDuration;
+    use oore_core::db::{create_pool, run_migrations};
+    use oore_core::models::{BuildScheduleId, BuildStatus, GitProvider, Repository};
+
+    async fn setup_test_db() -> DbPool {
+        let pool = create_pool("sqlite::memory:").await.unwrap();
+        run_migrations(&pool).await.unwrap();
+        pool
+    }
+
+    /// Creates a local git repository with one commit on `main`.
+    fn init_remote(dir: &std::path::Path) -> String {
+        let git = |args: &[&str]| {
+            let output = std::process::Command::new("git")
+                .current_dir(dir)
+                .args(["-c", "user.name=oore", "-c", "user.email=oore@example.com"])
+                .args(args)
+                .output()
+                .unwrap();
+            assert!(output.status.success(), "git {:?} failed", args);
+            String::from_utf8(output.stdout).unwrap().trim().to_string()
+        };
+        git(&["init", "-q", "-b", "main"]);
+        git(&["commit", "-q", "--allow-empty", "-m", "first"]);
+        git(&["rev-parse", "HEAD"])
+    }
+
+    #[tokio::test]
+    async fn test_run_due_schedule() {
+        let db = setup_test_db().await;
+        let remote = tempfile::tempdir().unwrap();
+        let head = init_remote(remote.path());
+
+        let repository = Repository::new(
+            "app".to_string(),
+            GitProvider::GitHub,
+            "acme".to_string(),
+            "app".to_string(),
+            remote.path().to_str().unwrap().to_string(),
+        );
+        RepositoryRepo::create(&db, &repository).await.unwrap();
+
+        let due_at = Utc::now() - ChronoDuration::minutes(1);
+        let schedule = BuildSchedule {
+            id: BuildScheduleId::new(),
+            repository_id: repository.id.clone(),
+            cron_expression: "0 2 * * *".to_string(),
+            branch: "main".to_string(),
+            workflow_name: Some("nightly".to_string()),
+            timezone: "UTC".to_string(),
+            skip_if_built: true,
+            is_active: true,
+            next_run_at: Some(due_at),
+            last_run_at: None,
+            last_build_id: None,
+            last_error: None,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        };
+        BuildScheduleRepo::create(&db, &schedule).await.unwrap();
+
+        let (build_tx, mut build_rx) = mpsc::channel(10);
+        run_due_schedules(&db, None, &build_tx).await;
+
+        let job = build_rx.try_recv().unwrap();
+        let build = BuildRepo::get_by_id(&db, &job.build_id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(build.trigger_type, TriggerType::Scheduled);
+        assert_eq!(build.commit_sha, head);
+        assert_eq!(build.branch, "main");
+        assert_eq!(build.workflow_name.as_deref(), Some("nightly"));
+
+        let updated = BuildScheduleRepo::get_by_id(&db, &schedule.id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(updated.last_build_id.as_ref(), Some(&build.id));
+        assert!(updated.next_run_at.unwrap() > Utc::now());
+        assert!(updated.last_run_at.is_some());
+
+        // Not due any more
+        run_due_schedules(&db, None, &build_tx).await;
+        assert!(build_rx.try_recv().is_err());
+
+        // Due again, but the head already built successfully
+        BuildRepo::update_status(&db, &build.id, BuildStatus::Success)
+            .await
+            .unwrap();
+        let due = BuildSchedule {
+            next_run_at: Some(due_at),
+            ..updated
+        };
+        BuildScheduleRepo::update(&db, &due).await.unwrap();
+        run_due_schedules(&db, None, &build_tx).await;
+        assert!(build_rx.try_recv().is_err());
+
+        let skipped = BuildScheduleRepo::get_by_id(&db, &schedule.id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(skipped.last_build_id.as_ref(), Some(&build.id));
+        assert_eq!(
+            skipped.last_error,
+            Some(format!(
+                "Skipped: commit {} already built successfully",
+                &head[..7]
+            ))
+        );
+    }
+}
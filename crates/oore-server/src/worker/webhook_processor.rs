@@ -6,13 +6,14 @@ use oore_core::{
         repository::{BuildRepo, RepositoryRepo, WebhookEventRepo},
         DbPool,
     },
-    models::{Build, GitProvider, TriggerType, WebhookEventId, WebhookEventType},
+    models::{Build, GitProvider, ParsedWebhookEvent, TriggerType, WebhookEventId, WebhookEventType},
     oauth::{github::GitHubClient, EncryptionKey},
-    webhook::{is_github_installation_event, parse_github_installation_webhook, parse_github_webhook, parse_gitlab_webhook},
+    webhook::{build_skip_reason, is_github_installation_event, parse_github_installation_webhook, parse_github_webhook, parse_gitlab_webhook},
 };
 use tokio::sync::{mpsc, watch};
 
-use super::BuildJob;
+use super::{cancel_superseded_builds, BuildJob, CancelChannels};
+use crate::events::BuildEventBus;
 
 /// A webhook processing job.
 #[derive(Debug, Clone)]
@@ -71,30 +72,23 @@ impl WebhookWorkerHandle {
     }
 }
 
-/// Starts the webhook processor worker.
-///
-/// Returns a sender for submitting jobs and a handle for managing the worker.
-pub fn start_webhook_processor(
-    db: DbPool,
-    encryption_key: Option<EncryptionKey>,
-) -> (mpsc::Sender<WebhookJob>, WebhookWorkerHandle) {
-    start_webhook_processor_with_build_tx(db, encryption_key, None)
-}
-
 /// Starts the webhook processor worker with optional build job sender.
 ///
 /// When build_tx is provided, builds are automatically queued for execution
-/// after being created.
+/// after being created. `cancel_channels` and `events` are the build
+/// processor's, used to stop builds superseded by a newer commit.
 pub fn start_webhook_processor_with_build_tx(
     db: DbPool,
     encryption_key: Option<EncryptionKey>,
     build_tx: Option<mpsc::Sender<BuildJob>>,
+    cancel_channels: CancelChannels,
+    events: BuildEventBus,
 ) -> (mpsc::Sender<WebhookJob>, WebhookWorkerHandle) {
     let (tx, rx) = mpsc::channel::<WebhookJob>(1000);
     let (shutdown_tx, shutdown_rx) = watch::channel(false);
 
     let handle = tokio::spawn(async move {
-        run_webhook_processor(db, encryption_key, build_tx, rx, shutdown_rx).await;
+        run_webhook_processor(db, encryption_key, build_tx, cancel_channels, events, rx, shutdown_rx).await;
     });
 
     let worker_handle = WebhookWorkerHandle {
@@ -219,6 +213,8 @@ async fn run_webhook_processor(
     db: DbPool,
     encryption_key: Option<EncryptionKey>,
     build_tx: Option<mpsc::Sender<BuildJob>>,
+    cancel_channels: CancelChannels,
+    events: BuildEventBus,
     mut rx: mpsc::Receiver<WebhookJob>,
     mut shutdown_rx: watch::Receiver<bool>,
 ) {
@@ -244,7 +240,7 @@ async fn run_webhook_processor(
                             job.event_type
                         );
 
-                        if let Err(e) = process_webhook_job(&db, &encryption_key, &build_tx, &job).await {
+                        if let Err(e) = process_webhook_job(&db, &encryption_key, &build_tx, &cancel_channels, &events, &job).await {
                             tracing::error!("Failed to process webhook {}: {}", job.event_id, e);
                             // Store error message on the event
                             if let Err(e2) = WebhookEventRepo::set_error(&db, &job.event_id, &e.to_string()).await {
@@ -275,6 +271,8 @@ async fn process_webhook_job(
     db: &DbPool,
     encryption_key: &Option<EncryptionKey>,
     build_tx: &Option<mpsc::Sender<BuildJob>>,
+    cancel_channels: &CancelChannels,
+    events: &BuildEventBus,
     job: &WebhookJob,
 ) -> oore_core::Result<()> {
     // Check if this is a GitHub installation event
@@ -288,7 +286,7 @@ async fn process_webhook_job(
         .ok_or_else(|| oore_core::OoreError::WebhookEventNotFound(job.event_id.to_string()))?;
 
     // Parse the webhook payload
-    let parsed = match job.provider {
+    let mut parsed = match job.provider {
         GitProvider::GitHub => parse_github_webhook(&job.event_type, &event.payload)?,
         GitProvider::GitLab => parse_gitlab_webhook(&job.event_type, &event.payload)?,
     };
@@ -341,15 +339,29 @@ async fn process_webhook_job(
         }
     };
 
+    let Some(repository) = RepositoryRepo::get_by_id(db, &repository_id).await? else {
+        tracing::warn!("Repository {} not found for webhook {}", repository_id, job.event_id);
+        return Ok(());
+    };
+
     // Determine if we should create a build
     let should_build = match parsed.event_type {
-        WebhookEventType::Push => true,
+        // Deleting a branch or tag sends a push whose new SHA is all zeros
+        WebhookEventType::Push | WebhookEventType::Tag => {
+            !parsed.commit_sha.chars().all(|c| c == '0')
+        }
         WebhookEventType::PullRequest | WebhookEventType::MergeRequest => {
-            // Only build on opened or synchronize
-            matches!(
-                parsed.action.as_deref(),
-                Some("opened") | Some("synchronize") | Some("open") | Some("update")
-            )
+            match parsed.action.as_deref() {
+                Some("opened") | Some("synchronize") | Some("open") | Some("update") => true,
+                // A draft skipped earlier is ready to build now
+                Some("ready_for_review") => repository.skip_draft_pull_requests,
+                // The required label was just added
+                Some("labeled") => match (&repository.required_pull_request_label, &parsed.added_label) {
+                    (Some(required), Some(added)) => added.eq_ignore_ascii_case(required),
+                    _ => false,
+                },
+                _ => false,
+            }
         }
         // Installation events are handled separately above
         WebhookEventType::Installation | WebhookEventType::InstallationRepositories => false,
@@ -364,9 +376,21 @@ async fn process_webhook_job(
         return Ok(());
     }
 
+    // GitHub's pull request payloads leave out the head commit's message
+    if parsed.commit_message.is_none() && parsed.event_type == WebhookEventType::PullRequest {
+        parsed.commit_message = github_commit_message(db, encryption_key, &parsed).await;
+    }
+
+    if let Some(reason) = build_skip_reason(&parsed, &repository) {
+        tracing::info!("Webhook {} did not create a build: {}", job.event_id, reason);
+        WebhookEventRepo::set_error(db, &job.event_id, &reason).await?;
+        return Ok(());
+    }
+
     // Create build record
     let trigger_type = match parsed.event_type {
         WebhookEventType::Push => TriggerType::Push,
+        WebhookEventType::Tag => TriggerType::Tag,
         WebhookEventType::PullRequest => TriggerType::PullRequest,
         WebhookEventType::MergeRequest => TriggerType::MergeRequest,
         // These are handled above, but we need to satisfy the match
@@ -406,6 +430,11 @@ async fn process_webhook_job(
         format_commit_sha(&parsed.commit_sha)
     );
 
+    // A workflow can also opt in once the build selects it
+    if repository.cancel_previous_builds {
+        cancel_superseded_builds(db, events, cancel_channels, &build).await?;
+    }
+
     // Queue the build for execution
     if let Some(tx) = build_tx {
         let build_job = BuildJob {
@@ -437,6 +466,54 @@ async fn process_webhook_job(
     Ok(())
 }
 
+/// Looks up the head commit's message through the GitHub App.
+///
+/// Returns `None`, so `[skip ci]` can't apply, if the App isn't configured or
+/// GitHub can't be reached.
+async fn github_commit_message(
+    db: &DbPool,
+    encryption_key: &Option<EncryptionKey>,
+    parsed: &ParsedWebhookEvent,
+) -> Option<String> {
+    let key = encryption_key.as_ref()?;
+    let installation_id = parsed.github_installation_id?;
+    let creds = match GitHubAppCredentialsRepo::get_active(db).await {
+        Ok(creds) => creds?,
+        Err(e) => {
+            tracing::warn!("Failed to load GitHub App credentials: {}", e);
+            return None;
+        }
+    };
+
+    let result = match GitHubClient::new(key.clone()) {
+        Ok(client) => {
+            client
+                .get_commit_message(
+                    &creds,
+                    installation_id,
+                    &parsed.repository_owner,
+                    &parsed.repository_name,
+                    &parsed.commit_sha,
+                )
+                .await
+        }
+        Err(e) => Err(e),
+    };
+    match result {
+        Ok(message) => Some(message),
+        Err(e) => {
+            tracing::warn!(
+                "Failed to fetch message of commit {} in {}/{}: {}",
+                format_commit_sha(&parsed.commit_sha),
+                parsed.repository_owner,
+                parsed.repository_name,
+                e
+            );
+            None
+        }
+    }
+}
+
 /// Processes a GitHub installation event (sync installations and repos).
 async fn process_github_installation_event(
     db: &DbPool,
@@ -582,3 +659,213 @@ async fn process_github_installation_event(
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use oore_core::db::{create_pool, run_migrations};
+    use oore_core::models::{BuildStatus, Repository, WebhookEvent};
+
+    async fn setup_test_db() -> DbPool {
+        let pool = create_pool("sqlite::memory:").await.unwrap();
+        run_migrations(&pool).await.unwrap();
+        pool
+    }
+
+    async fn create_build(db: &DbPool, repository: &Repository, branch: &str, status: BuildStatus) -> Build {
+        let build = Build::new(
+            repository.id.clone(),
+            None,
+            "a".repeat(40),
+            branch.to_string(),
+            TriggerType::Push,
+        );
+        BuildRepo::create(db, &build).await.unwrap();
+        BuildRepo::update_status(db, &build.id, status).await.unwrap();
+        build
+    }
+
+    async fn push_job(db: &DbPool, repository: &Repository, branch: &str) -> WebhookJob {
+        let payload = format!(
+            r#"{{"ref": "refs/heads/{}", "after": "{}", "repository": {{"id": 1, "full_name": "acme/app"}}}}"#,
+            branch,
+            "b".repeat(40)
+        );
+        webhook_job(db, repository, "push", payload).await
+    }
+
+    async fn webhook_job(db: &DbPool, repository: &Repository, event_type: &str, payload: String) -> WebhookJob {
+        let event = WebhookEvent {
+            id: WebhookEventId::new(),
+            repository_id: Some(repository.id.clone()),
+            provider: repository.provider,
+            event_type: event_type.to_string(),
+            delivery_id: ulid::Ulid::new().to_string(),
+            payload: payload.into_bytes(),
+            processed: false,
+            error_message: None,
+            received_at: chrono::Utc::now(),
+        };
+        WebhookEventRepo::create(db, &event).await.unwrap();
+
+        WebhookJob {
+            event_id: event.id,
+            provider: repository.provider,
+            event_type: event_type.to_string(),
+        }
+    }
+
+    /// Processes `job` and returns the build it created, if any.
+    async fn process(db: &DbPool, repository: &Repository, job: &WebhookJob) -> Option<Build> {
+        process_webhook_job(db, &None, &None, &CancelChannels::default(), &BuildEventBus::default(), job)
+            .await
+            .unwrap();
+        BuildRepo::list(db, Some(&repository.id))
+            .await
+            .unwrap()
+            .into_iter()
+            .find(|b| b.webhook_event_id.as_ref() == Some(&job.event_id))
+    }
+
+    #[tokio::test]
+    async fn test_gitlab_label_added_starts_build() {
+        let db = setup_test_db().await;
+        let mut repository = Repository::new(
+            "app".to_string(),
+            GitProvider::GitLab,
+            "acme".to_string(),
+            "app".to_string(),
+            "https://gitlab.com/acme/app.git".to_string(),
+        );
+        repository.required_pull_request_label = Some("build".to_string());
+        RepositoryRepo::create(&db, &repository).await.unwrap();
+
+        let payload = |added: &str| {
+            format!(
+                r#"{{
+                    "object_kind": "merge_request",
+                    "object_attributes": {{
+                        "iid": 7,
+                        "action": "update",
+                        "last_commit": {{"id": "{}"}},
+                        "source_branch": "feature"
+                    }},
+                    "labels": [{{"title": "{added}"}}],
+                    "changes": {{"labels": {{"previous": [], "current": [{{"title": "{added}"}}]}}}},
+                    "project": {{"id": 1, "path_with_namespace": "acme/app"}}
+                }}"#,
+                "c".repeat(40)
+            )
+        };
+
+        let job = webhook_job(&db, &repository, "Merge Request Hook", payload("docs")).await;
+        assert!(process(&db, &repository, &job).await.is_none());
+
+        let job = webhook_job(&db, &repository, "Merge Request Hook", payload("Build")).await;
+        let build = process(&db, &repository, &job).await.unwrap();
+        assert_eq!(build.trigger_type, TriggerType::MergeRequest);
+    }
+
+    #[tokio::test]
+    async fn test_github_pull_request_builds_without_commit_message() {
+        let db = setup_test_db().await;
+        let repository = Repository::new(
+            "app".to_string(),
+            GitProvider::GitHub,
+            "acme".to_string(),
+            "app".to_string(),
+            "https://github.com/acme/app.git".to_string(),
+        );
+        RepositoryRepo::create(&db, &repository).await.unwrap();
+
+        // Without a GitHub App the message can't be looked up, so the build
+        // goes ahead even if the commit says [skip ci]
+        let payload = format!(
+            r#"{{
+                "action": "synchronize",
+                "number": 42,
+                "pull_request": {{"head": {{"sha": "{}", "ref": "feature"}}}},
+                "repository": {{"id": 1, "full_name": "acme/app"}},
+                "installation": {{"id": 99}}
+            }}"#,
+            "d".repeat(40)
+        );
+        let job = webhook_job(&db, &repository, "pull_request", payload).await;
+        let build = process(&db, &repository, &job).await.unwrap();
+        assert_eq!(build.trigger_type, TriggerType::PullRequest);
+    }
+
+    #[tokio::test]
+    async fn test_push_cancels_previous_builds() {
+        let db = setup_test_db().await;
+        let mut repository = Repository::new(
+            "app".to_string(),
+            GitProvider::GitHub,
+            "acme".to_string(),
+            "app".to_string(),
+            "https://github.com/acme/app.git".to_string(),
+        );
+        repository.cancel_previous_builds = true;
+        RepositoryRepo::create(&db, &repository).await.unwrap();
+
+        let pending = create_build(&db, &repository, "main", BuildStatus::Pending).await;
+        let running = create_build(&db, &repository, "main", BuildStatus::Running).await;
+        let finished = create_build(&db, &repository, "main", BuildStatus::Success).await;
+        let other_branch = create_build(&db, &repository, "develop", BuildStatus::Pending).await;
+
+        let cancel_channels = CancelChannels::default();
+        let (cancel_tx, cancel_rx) = watch::channel(false);
+        cancel_channels.insert(running.id.clone(), cancel_tx);
+
+        let job = push_job(&db, &repository, "main").await;
+        process_webhook_job(&db, &None, &None, &cancel_channels, &BuildEventBus::default(), &job)
+            .await
+            .unwrap();
+
+        let builds = BuildRepo::list(&db, Some(&repository.id)).await.unwrap();
+        let newest = builds
+            .iter()
+            .find(|b| b.webhook_event_id.as_ref() == Some(&job.event_id))
+            .unwrap();
+        assert_eq!(newest.status, BuildStatus::Pending);
+
+        for superseded in [&pending, &running] {
+            let build = BuildRepo::get_by_id(&db, &superseded.id).await.unwrap().unwrap();
+            assert_eq!(build.status, BuildStatus::Cancelled);
+            assert_eq!(build.superseded_by.as_ref(), Some(&newest.id));
+            assert_eq!(
+                build.error_message,
+                Some(format!("Superseded by build {}", newest.id))
+            );
+        }
+        assert!(*cancel_rx.borrow());
+
+        let finished = BuildRepo::get_by_id(&db, &finished.id).await.unwrap().unwrap();
+        assert_eq!(finished.status, BuildStatus::Success);
+        let other_branch = BuildRepo::get_by_id(&db, &other_branch.id).await.unwrap().unwrap();
+        assert_eq!(other_branch.status, BuildStatus::Pending);
+    }
+
+    #[tokio::test]
+    async fn test_push_keeps_previous_builds_by_default() {
+        let db = setup_test_db().await;
+        let repository = Repository::new(
+            "app".to_string(),
+            GitProvider::GitHub,
+            "acme".to_string(),
+            "app".to_string(),
+            "https://github.com/acme/app.git".to_string(),
+        );
+        RepositoryRepo::create(&db, &repository).await.unwrap();
+        let pending = create_build(&db, &repository, "main", BuildStatus::Pending).await;
+
+        let job = push_job(&db, &repository, "main").await;
+        process_webhook_job(&db, &None, &None, &CancelChannels::default(), &BuildEventBus::default(), &job)
+            .await
+            .unwrap();
+
+        let pending = BuildRepo::get_by_id(&db, &pending.id).await.unwrap().unwrap();
+        assert_eq!(pending.status, BuildStatus::Pending);
+        assert!(pending.superseded_by.is_none());
+    }
+}
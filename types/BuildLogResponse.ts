@@ -3,4 +3,4 @@
 /**
  * API response DTO for build log metadata.
  */
-export type BuildLogResponse = { id: string, build_id: string, step_index: number, stream: string, log_file_path: string, line_count: number, created_at: string, };
+export type BuildLogResponse = { id: string, build_id: string, step_index: number, stream: string, log_file_path: string, line_count: number, masked_count: number, created_at: string, };
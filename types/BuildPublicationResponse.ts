@@ -0,0 +1,7 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { PublishStatus } from "./PublishStatus";
+
+/**
+ * API response for a build publication.
+ */
+export type BuildPublicationResponse = { id: string, build_id: string, publisher: string, step_index: number, status: PublishStatus, summary: string | null, error: string | null, created_at: string, finished_at: string | null, };
@@ -3,4 +3,4 @@
 /**
  * API response DTO for build.
  */
-export type BuildResponse = { id: string, repository_id: string, webhook_event_id: string | null, commit_sha: string, branch: string, trigger_type: string, status: string, started_at: string | null, finished_at: string | null, created_at: string, workflow_name: string | null, config_source: string | null, error_message: string | null, };
+export type BuildResponse = { id: string, repository_id: string, webhook_event_id: string | null, commit_sha: string, branch: string, trigger_type: string, status: string, started_at: string | null, finished_at: string | null, created_at: string, workflow_name: string | null, config_source: string | null, error_message: string | null, superseded_by: string | null, };
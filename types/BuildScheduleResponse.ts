@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * API response for a build schedule.
+ */
+export type BuildScheduleResponse = { id: string, repository_id: string, cron_expression: string, branch: string, workflow_name: string | null, timezone: string, skip_if_built: boolean, is_active: boolean, next_run_at: string | null, last_run_at: string | null, last_build_id: string | null, last_error: string | null, created_at: string, updated_at: string, };
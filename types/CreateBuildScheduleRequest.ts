@@ -0,0 +1,14 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Request to create a build schedule.
+ */
+export type CreateBuildScheduleRequest = { cron_expression: string, 
+/**
+ * Defaults to the repository's default branch.
+ */
+branch?: string, workflow_name?: string, 
+/**
+ * Defaults to `UTC`.
+ */
+timezone?: string, skip_if_built: boolean, is_active?: boolean, };
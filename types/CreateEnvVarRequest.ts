@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Request to create a repository environment variable.
+ */
+export type CreateEnvVarRequest = { name: string, value: string, is_secret: boolean, group: string | null, };
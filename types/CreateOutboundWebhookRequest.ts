@@ -0,0 +1,7 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { NotificationEvent } from "./NotificationEvent";
+
+/**
+ * Request to create an outbound webhook.
+ */
+export type CreateOutboundWebhookRequest = { url: string, events: Array<NotificationEvent>, secret: string, is_active: boolean, };
@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Delivery status of an outbound webhook request.
+ */
+export type DeliveryStatus = "pending" | "success" | "failed";
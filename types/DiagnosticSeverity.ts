@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Severity of a pipeline diagnostic.
+ */
+export type DiagnosticSeverity = "error" | "warning";
@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Response for an email config.
+ */
+export type EmailConfigResponse = { id: string, repository_id: string, recipients: Array<string>, on_failure: boolean, on_recovery: boolean, is_active: boolean, created_at: string, updated_at: string, };
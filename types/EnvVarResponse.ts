@@ -0,0 +1,8 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Response for a repository environment variable.
+ *
+ * `value` is only populated for plain variables.
+ */
+export type EnvVarResponse = { id: string, repository_id: string, group: string | null, name: string, value: string | null, is_secret: boolean, created_at: string, updated_at: string, };
@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Response for the Firebase credential (no secrets).
+ */
+export type FirebaseCredentialResponse = { id: string, repository_id: string, client_email: string, created_at: string, updated_at: string, };
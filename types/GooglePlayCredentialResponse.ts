@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Response for a Google Play credential (no secrets).
+ */
+export type GooglePlayCredentialResponse = { id: string, repository_id: string, name: string, client_email: string, is_active: boolean, created_at: string, };
@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * A build lifecycle event that notifiers can subscribe to.
+ */
+export type NotificationEvent = "build.started" | "build.succeeded" | "build.failed" | "build.cancelled";
@@ -0,0 +1,8 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { DeliveryStatus } from "./DeliveryStatus";
+import type { NotificationEvent } from "./NotificationEvent";
+
+/**
+ * Response for an outbound webhook delivery.
+ */
+export type OutboundDeliveryResponse = { id: string, webhook_id: string, build_id: string | null, event: NotificationEvent, status: DeliveryStatus, attempts: number, next_attempt_at: string | null, response_status: number | null, error_message: string | null, created_at: string, delivered_at: string | null, };
@@ -0,0 +1,7 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { NotificationEvent } from "./NotificationEvent";
+
+/**
+ * Response for an outbound webhook. The secret is never returned.
+ */
+export type OutboundWebhookResponse = { id: string, repository_id: string, url: string, events: Array<NotificationEvent>, is_active: boolean, created_at: string, updated_at: string, };
@@ -0,0 +1,24 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { DiagnosticSeverity } from "./DiagnosticSeverity";
+
+/**
+ * A problem found while validating a pipeline config.
+ */
+export type PipelineDiagnostic = { severity: DiagnosticSeverity, 
+/**
+ * Location in the config, e.g. `workflows.ios.scripts[0].script`.
+ * Empty for problems with the document as a whole.
+ */
+path: string, message: string, 
+/**
+ * 1-based line in the source, when it can be determined.
+ */
+line: number | null, 
+/**
+ * 1-based column in the source, when it can be determined.
+ */
+column: number | null, 
+/**
+ * Suggested fix, e.g. "did you mean `branch_patterns`?".
+ */
+suggestion: string | null, };
@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Status of publishing a build to one target.
+ */
+export type PublishStatus = "running" | "success" | "failure";
@@ -3,4 +3,4 @@
 /**
  * API response DTO for repository (excludes secrets).
  */
-export type RepositoryResponse = { id: string, name: string, provider: string, owner: string, repo_name: string, clone_url: string, default_branch: string, is_active: boolean, github_repository_id: number | null, github_installation_id: number | null, gitlab_project_id: number | null, created_at: string, updated_at: string, };
+export type RepositoryResponse = { id: string, name: string, provider: string, owner: string, repo_name: string, clone_url: string, default_branch: string, is_active: boolean, github_repository_id: number | null, github_installation_id: number | null, gitlab_project_id: number | null, skip_draft_pull_requests: boolean, required_pull_request_label: string | null, cancel_previous_builds: boolean, executor: string | null, created_at: string, updated_at: string, };
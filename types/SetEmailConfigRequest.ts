@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Request to create or replace a repository's email config.
+ */
+export type SetEmailConfigRequest = { recipients: Array<string>, on_failure: boolean, on_recovery: boolean, is_active: boolean, };
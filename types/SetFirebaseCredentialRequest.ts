@@ -0,0 +1,10 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Request to set the Firebase service account for a repository.
+ */
+export type SetFirebaseCredentialRequest = { 
+/**
+ * Service account JSON key, as downloaded from Google Cloud.
+ */
+service_account_json: string, };
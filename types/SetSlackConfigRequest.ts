@@ -0,0 +1,9 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { NotificationEvent } from "./NotificationEvent";
+
+/**
+ * Request to create or replace a repository's Slack config.
+ *
+ * `webhook_url` may be omitted when updating an existing config.
+ */
+export type SetSlackConfigRequest = { webhook_url?: string, events: Array<NotificationEvent>, is_active: boolean, };
@@ -0,0 +1,10 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { SmtpTls } from "./SmtpTls";
+
+/**
+ * Request to set the server-wide SMTP settings.
+ *
+ * `password` may be omitted to keep the stored one; send an empty string
+ * to clear it.
+ */
+export type SetSmtpSettingsRequest = { host: string, port?: number, tls: SmtpTls, username?: string, password?: string, from_address: string, digest_recipients: Array<string>, };
@@ -0,0 +1,7 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { NotificationEvent } from "./NotificationEvent";
+
+/**
+ * Response for a Slack config. The webhook URL is never returned.
+ */
+export type SlackConfigResponse = { id: string, repository_id: string, events: Array<NotificationEvent>, is_active: boolean, created_at: string, updated_at: string, };
@@ -0,0 +1,7 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { SmtpTls } from "./SmtpTls";
+
+/**
+ * Response for the SMTP settings. The password is never returned.
+ */
+export type SmtpSettingsResponse = { host: string, port: number, tls: SmtpTls, username: string | null, has_password: boolean, from_address: string, digest_recipients: Array<string>, updated_at: string, };
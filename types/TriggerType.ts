@@ -3,4 +3,4 @@
 /**
  * Build trigger type.
  */
-export type TriggerType = "push" | "pull_request" | "merge_request" | "manual";
+export type TriggerType = "push" | "tag" | "pull_request" | "merge_request" | "manual" | "scheduled";
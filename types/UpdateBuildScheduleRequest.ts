@@ -0,0 +1,10 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Request to update a build schedule.
+ */
+export type UpdateBuildScheduleRequest = { cron_expression?: string, branch?: string, 
+/**
+ * Empty to use the default workflow.
+ */
+workflow_name?: string, timezone?: string, skip_if_built?: boolean, is_active?: boolean, };
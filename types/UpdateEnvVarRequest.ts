@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Request to update a repository environment variable.
+ */
+export type UpdateEnvVarRequest = { value: string | null, is_secret: boolean | null, };
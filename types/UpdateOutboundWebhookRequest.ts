@@ -0,0 +1,7 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { NotificationEvent } from "./NotificationEvent";
+
+/**
+ * Request to update an outbound webhook.
+ */
+export type UpdateOutboundWebhookRequest = { url: string | null, events: Array<NotificationEvent> | null, secret: string | null, is_active: boolean | null, };
@@ -7,4 +7,13 @@ export type UpdateRepositoryRequest = { name: string | null, default_branch: str
 /**
  * New webhook secret (will be hashed before storage).
  */
-webhook_secret: string | null, github_installation_id: number | null, gitlab_project_id: number | null, };
+webhook_secret: string | null, github_installation_id: number | null, gitlab_project_id: number | null, skip_draft_pull_requests: boolean | null, 
+/**
+ * Label pull/merge requests need to be built. Empty to remove.
+ */
+required_pull_request_label: string | null, cancel_previous_builds: boolean | null, 
+/**
+ * Executor to run builds with (`shell`, `container` or `sandbox`). Empty
+ * to use the server default.
+ */
+executor: string | null, };
@@ -0,0 +1,10 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Request to upload a Google Play service account.
+ */
+export type UploadGooglePlayCredentialRequest = { name: string, 
+/**
+ * Service account JSON key, as downloaded from Google Cloud.
+ */
+service_account_json: string, };
@@ -0,0 +1,11 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { StoredConfigFormat } from "./StoredConfigFormat";
+
+/**
+ * Request to validate a pipeline config without saving it.
+ */
+export type ValidatePipelineRequest = { config_content: string, config_format: StoredConfigFormat, 
+/**
+ * Report unknown fields as errors instead of warnings.
+ */
+strict: boolean, };
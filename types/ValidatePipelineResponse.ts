@@ -0,0 +1,16 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { PipelineDiagnostic } from "./PipelineDiagnostic";
+import type { StoredConfigFormat } from "./StoredConfigFormat";
+
+/**
+ * API response for pipeline validation.
+ */
+export type ValidatePipelineResponse = { valid: boolean, format: StoredConfigFormat, 
+/**
+ * Workflow names, when the config could be parsed.
+ */
+workflows: Array<string>, diagnostics: Array<PipelineDiagnostic>, 
+/**
+ * Message of the first error, if any.
+ */
+error: string | null, };
@@ -8,14 +8,17 @@ export * from './BuildArtifactResponse';
 export * from './BuildLogContentResponse';
 export * from './BuildLogResponse';
 export * from './BuildResponse';
+export * from './BuildScheduleResponse';
 export * from './BuildStatus';
 export * from './BuildStepResponse';
 export * from './CertificateType';
 export * from './ConfigSource';
 export * from './ConnectResponse';
+export * from './CreateBuildScheduleRequest';
 export * from './CreatePipelineConfigRequest';
 export * from './CreateRepositoryRequest';
 export * from './DefaultPermissions';
+export * from './DiagnosticSeverity';
 export * from './GitHubAppManifest';
 export * from './GitHubAppStatus';
 export * from './GitHubSetupStatusResponse';
@@ -35,6 +38,7 @@ export * from './KeystoreType';
 export * from './LogStream';
 export * from './ManifestResponse';
 export * from './PipelineConfigResponse';
+export * from './PipelineDiagnostic';
 export * from './ProfileType';
 export * from './RepositoryResponse';
 export * from './SetupResponse';
@@ -45,9 +49,12 @@ export * from './StoredConfigFormat';
 export * from './SyncResponse';
 export * from './TriggerBuildRequest';
 export * from './TriggerType';
+export * from './UpdateBuildScheduleRequest';
 export * from './UpdateRepositoryRequest';
 export * from './UploadApiKeyRequest';
 export * from './UploadCertificateRequest';
 export * from './UploadKeystoreRequest';
 export * from './UploadProfileRequest';
+export * from './ValidatePipelineRequest';
+export * from './ValidatePipelineResponse';
 export * from './WebhookEventResponse';
@@ -116,11 +116,15 @@ export function PipelineConfigCard({ repositoryId }: PipelineConfigCardProps) {
     setValidating(true)
     try {
       const result = await validatePipelineConfig({
-        name: null,
         config_content: content,
         config_format: format,
+        strict: false,
+      })
+      setValidationResult({
+        valid: result.valid,
+        error: result.error ?? undefined,
+        workflows: result.workflows,
       })
-      setValidationResult(result)
     } catch (err) {
       setValidationResult({
         valid: false,
@@ -1,6 +1,11 @@
 import useSWR, { mutate } from 'swr'
 import { apiFetch, fetcher } from './client'
-import type { PipelineConfig, CreatePipelineConfigRequest, ValidatePipelineResponse } from './types'
+import type {
+  PipelineConfig,
+  CreatePipelineConfigRequest,
+  ValidatePipelineRequest,
+  ValidatePipelineResponse,
+} from './types'
 
 export function usePipelineConfig(repositoryId: string | null) {
   return useSWR<PipelineConfig>(
@@ -33,7 +38,7 @@ export async function deletePipelineConfig(repositoryId: string): Promise<void>
 }
 
 export async function validatePipelineConfig(
-  data: CreatePipelineConfigRequest
+  data: ValidatePipelineRequest
 ): Promise<ValidatePipelineResponse> {
   return apiFetch<ValidatePipelineResponse>('/api/pipelines/validate', {
     method: 'POST',
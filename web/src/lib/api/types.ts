@@ -41,6 +41,10 @@ export type {
   // Pipeline
   PipelineConfigResponse as PipelineConfig,
   CreatePipelineConfigRequest,
+  ValidatePipelineRequest,
+  ValidatePipelineResponse,
+  PipelineDiagnostic,
+  DiagnosticSeverity,
   StoredConfigFormat as ConfigFormat,
 
   // Webhooks
@@ -191,13 +195,6 @@ export interface GitLabEnableProjectResponse {
   webhook_secret: string
 }
 
-// Validation response (not in Rust yet)
-export interface ValidatePipelineResponse {
-  valid: boolean
-  error?: string
-  workflows?: string[]
-}
-
 // Error response
 export interface ErrorResponse {
   error: {